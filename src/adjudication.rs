@@ -0,0 +1,289 @@
+//! Endgame adjudication
+//!
+//! When zones of players can no longer meet, players can only place crosses into their own
+//! empty cells, so the result of the game depends only on the number of such cells and the
+//! order of turns. This module adds the optional rule to [`Game`]: [`Game::adjudication`]
+//! finds the provable result of such position and [`Game::adjudicate`] ends the game with it.
+//! Call the latter after moves to end games early.
+//!
+//! Region of the player is the set of cells, that they can reach if others don't interfere,
+//! see [`Game::adjudication`]. Players don't interact, if their regions consist only of empty
+//! cells, no cell of a region is active for other players and no cell of a region is adjacent
+//! to filled cells of other players, which could be revived. The check is conservative:
+//! positions, where players can't really interact, may be left undecided.
+//!
+//! Servers can also bound length of games: [`Game::enforce_move_limit`] ends the game, that
+//! has reached [`MoveLimit`], with [`EndReason::MoveLimit`]. The result is decided by
+//! [`LimitScoring`] or the game is drawn.
+//!
+//! [`EndReason::MoveLimit`]: crate::summary::EndReason::MoveLimit
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::engine::MoveError;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! let mut game = Game::from_fen("x2.#./3.#o 2 x 1/1 - 2", [Cell::new(); 28], [0; 28], [None; 2])
+//!     .unwrap();
+//! assert_eq!(game.adjudication(&mut [0; 28]), Some(GameOver::Win(0)));
+//! assert_eq!(game.state(), GameState::Ongoing);
+//! assert_eq!(game.adjudicate(), Some(GameOver::Win(0)));
+//! assert_eq!(game.play(game.board().index(1, 0)), Err(MoveError::GameOver));
+//!
+//! let board = Board::new(5, 2, 2, [Cell::new(); 28], [0; 28]);
+//! let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+//! assert_eq!(game.adjudication(&mut [0; 28]), None);
+//! ```
+use core::ops::IndexMut;
+
+use crate::base::{CellKind, GameBoard};
+use crate::board::{Board, CellStorage, Walk, MAX_PLAYERS};
+use crate::counters::{Counter, PlayerCounters};
+use crate::engine::{ChangeLog, Data, Engine};
+use crate::game::Game;
+use crate::player_manager::{GameOver, GameState, LoseData, PlayerManager, PlayerStatusSource};
+use crate::summary::EndReason;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How games, that have reached [`MoveLimit`], are decided.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum LimitScoring {
+    /// The game is drawn
+    Draw,
+    /// The player with the most crosses and filled cells wins
+    Material,
+    /// The player with the most crosses, filled cells and empty cells of their region, which
+    /// aren't active for other players, wins, see [`Game::adjudication`] for regions
+    Territory,
+}
+
+/// Hard limit of number of moves of the game, see [`Game::enforce_move_limit`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct MoveLimit {
+    /// Number of moves of all players
+    pub moves: usize,
+    /// Scoring of the game, that has reached the limit
+    pub scoring: LimitScoring,
+}
+
+impl<C, B, S, L> Game<Board<C, B>, S, L>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    /// Returns the result of the game, if players don't interact anymore (see module docs).
+    /// Region of the player is their zone of reach and empty cells connected to it through
+    /// empty cells and filled cells of the player. Every player will make a move into
+    /// every cell of their region, so the result is found by playing out the turns with
+    /// these numbers of moves. Returns `None` if the game has ended or players may interact.
+    /// Regions are traversed with `buffer`, it should have at least [`Board::storage_len`]
+    /// elements.
+    /// # Panics
+    /// Panics if `buffer` is too short.
+    pub fn adjudication(&self, buffer: &mut [usize]) -> Option<GameOver> {
+        adjudication(&mut self.board().walk_in(buffer), self.player_manager())
+    }
+    /// Ends the game with the result of [`Game::adjudication`], if any, and returns it.
+    /// Like other endings, it's reverted by [`Game::undo`] of the last move.
+    pub fn adjudicate(&mut self) -> Option<GameOver> {
+        let (board, manager) = self.parts_mut();
+        let result = adjudication(&mut board.walk(), manager)?;
+        manager.game_state = GameState::Ended(result);
+        Some(result)
+    }
+    /// Returns the result of the game by the scoring: the player, who hasn't lost and has
+    /// the greatest score, wins, or it's a draw, if there are several such players.
+    /// Regions of [`LimitScoring::Territory`] are traversed with `buffer`, it should have
+    /// at least [`Board::storage_len`] elements.
+    /// # Panics
+    /// Panics if `buffer` is too short.
+    pub fn limit_result(&self, scoring: LimitScoring, buffer: &mut [usize]) -> GameOver {
+        limit_result(
+            &mut self.board().walk_in(buffer),
+            self.player_manager(),
+            scoring,
+        )
+    }
+    /// Ends the game with the result of [`Game::limit_result`] and [`EndReason::MoveLimit`],
+    /// if it's ongoing and has reached the limit, and returns the result. Call it after moves.
+    /// Like other endings, it's reverted by [`Game::undo`] of the last move.
+    ///
+    /// [`EndReason::MoveLimit`]: crate::summary::EndReason::MoveLimit
+    /// # Example
+    /// ```
+    /// # use crosses_utils::adjudication::*;
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::summary::EndReason;
+    /// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// let mut game = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+    /// let limit = MoveLimit { moves: 3, scoring: LimitScoring::Material };
+    /// for (x, y) in [(0, 0), (1, 1), (2, 2)] {
+    ///     assert_eq!(game.enforce_move_limit(limit), None);
+    ///     game.play(game.board().index(x, y)).unwrap();
+    /// }
+    /// assert_eq!(game.enforce_move_limit(limit), Some(GameOver::Win(0)));
+    /// assert_eq!(game.end_reason(), Some(EndReason::MoveLimit));
+    /// ```
+    pub fn enforce_move_limit(&mut self, limit: MoveLimit) -> Option<GameOver> {
+        if self.state() != GameState::Ongoing || self.player_manager().current_move < limit.moves {
+            return None;
+        }
+        let (board, manager) = self.parts_mut();
+        let result = limit_result(&mut board.walk(), manager, limit.scoring);
+        // The game is ongoing.
+        let _ = self.end(result, EndReason::MoveLimit);
+        Some(result)
+    }
+}
+
+/// Returns the result of [`Game::adjudication`] of the position of the walk.
+fn adjudication<C, B, S>(walk: &mut impl Walk<C, B>, manager: &PlayerManager<S>) -> Option<GameOver>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+{
+    if manager.game_state != GameState::Ongoing {
+        return None;
+    }
+    let players = manager.max_players;
+    let mut active = [false; MAX_PLAYERS];
+    let mut losers = [None; MAX_PLAYERS];
+    for player in 0..players {
+        losers[player] = manager.losers[player];
+        active[player] = manager.is_playing(player);
+    }
+    let mut left = [0; MAX_PLAYERS];
+    for player in (0..players).filter(|player| active[*player]) {
+        let mut independent = true;
+        walk.explore(player, |board, index| {
+            let cell = board.get(index);
+            let touches_others = board.adjacent(index).into_iter().any(|i| {
+                let other = board.get(i);
+                matches!(other.kind(), CellKind::Cross | CellKind::Filled)
+                    && other.player() != player
+                    && active[other.player()]
+            });
+            let contested =
+                (0..players).any(|other| other != player && active[other] && cell.is_active(other));
+            if cell.kind() != CellKind::Empty || touches_others || contested {
+                independent = false;
+            }
+            left[player] += 1;
+        });
+        if !independent {
+            return None;
+        }
+    }
+    let counters = walk.board().counters();
+    let mut crosses = [0; MAX_PLAYERS];
+    for (player, crosses) in crosses.iter_mut().enumerate().take(players) {
+        *crosses = counters.get(Counter::Crosses, player);
+    }
+    let mut outcome = PlayerManager {
+        remaining_moves: manager.remaining_moves,
+        max_moves: manager.max_moves,
+        current_player: manager.current_player,
+        max_players: players,
+        current_move: manager.current_move,
+        game_state: manager.game_state,
+        losers,
+        current_turn: manager.current_turn,
+        schedule: manager.schedule,
+        stalemate: manager.stalemate,
+        neutral: manager.neutral,
+    };
+    loop {
+        match outcome.game_state {
+            GameState::Ended(result) => return Some(result),
+            GameState::Ongoing => {}
+        }
+        let player = outcome.current_player;
+        if left[player] == 0 {
+            return None;
+        }
+        left[player] -= 1;
+        crosses[player] += 1;
+        outcome.advance(&Playout { left, crosses });
+    }
+}
+
+/// Returns the result of [`Game::limit_result`] of the position of the walk.
+fn limit_result<C, B, S>(
+    walk: &mut impl Walk<C, B>,
+    manager: &PlayerManager<S>,
+    scoring: LimitScoring,
+) -> GameOver
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+{
+    if scoring == LimitScoring::Draw {
+        return GameOver::Draw;
+    }
+    let players = manager.max_players;
+    let mut active = [false; MAX_PLAYERS];
+    for (player, active) in active.iter_mut().enumerate().take(players) {
+        *active = manager.is_playing(player);
+    }
+    let mut scores = Scores([0; MAX_PLAYERS]);
+    let board = walk.board();
+    for packed in 0..board.width() * board.height() {
+        let cell = board.get(board.unpack_index(packed));
+        if matches!(cell.kind(), CellKind::Cross | CellKind::Filled) && cell.player() < players {
+            scores.0[cell.player()] += 1;
+        }
+    }
+    if scoring == LimitScoring::Territory {
+        for player in (0..players).filter(|player| active[*player]) {
+            walk.explore(player, |board, index| {
+                let cell = board.get(index);
+                let contested = (0..players)
+                    .any(|other| other != player && active[other] && cell.is_active(other));
+                if cell.kind() == CellKind::Empty && !contested {
+                    scores.0[player] += 1;
+                }
+            });
+        }
+    }
+    manager.result_by_score(&scores)
+}
+
+/// Status of players in the playout of [`Game::adjudication`]: numbers of left moves and crosses.
+struct Playout {
+    left: [usize; MAX_PLAYERS],
+    crosses: [usize; MAX_PLAYERS],
+}
+impl PlayerStatusSource for Playout {
+    fn out_of_moves(&self, player: usize) -> bool {
+        self.left[player] == 0
+    }
+    fn out_of_crosses(&self, player: usize) -> bool {
+        self.crosses[player] == 0 && self.left[player] == 0
+    }
+    fn score(&self, player: usize) -> usize {
+        self.crosses[player]
+    }
+}
+
+/// Scores of players for [`PlayerManager::result_by_score`].
+struct Scores([usize; MAX_PLAYERS]);
+impl PlayerStatusSource for Scores {
+    fn out_of_moves(&self, _player: usize) -> bool {
+        false
+    }
+    fn out_of_crosses(&self, _player: usize) -> bool {
+        false
+    }
+    fn score(&self, player: usize) -> usize {
+        self.0[player]
+    }
+}
@@ -0,0 +1,812 @@
+//! Analysis of positions
+//!
+//! This module computes properties of positions of [`Game`] with [`Board`], that are useful
+//! for evaluation functions and overlays of GUIs. Functions don't allocate: results for
+//! every cell are written into slices provided by the caller, indexed by packed indices
+//! of cells (see [`Board::pack_index`]), so they should have `width * height` elements.
+use core::fmt::Display;
+use core::ops::IndexMut;
+
+use crate::base::{CellKind, GameBoard};
+use crate::board::{Board, Cell, CellStorage, Walk, MAX_PLAYERS};
+use crate::engine::{is_movable, ChangeLog, Data, Engine};
+use crate::game::Game;
+use crate::ibts::IbtsBoard;
+use crate::player_manager::{GameState, LoseData};
+use crate::search::{DefaultEvaluator, Searcher, MAX_DEPTH};
+
+/// Influence of a player on cells, where they can move now.
+/// It halves with every step away from them.
+pub const MAX_INFLUENCE: u16 = 256;
+
+/// Influence of players on a cell, see [`influence_map`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Influence([u16; MAX_PLAYERS]);
+impl Influence {
+    /// Returns influence of the player.
+    pub fn get(&self, player: usize) -> u16 {
+        self.0[player]
+    }
+    /// Returns the player with the greatest influence or `None`
+    /// if nobody reaches the cell or several players have the same influence.
+    pub fn owner(&self) -> Option<usize> {
+        let max = *self.0.iter().max().unwrap_or(&0);
+        let mut owners = (0..MAX_PLAYERS).filter(|player| self.0[*player] == max);
+        match (max, owners.next(), owners.next()) {
+            (0, _, _) => None,
+            (_, owner, None) => owner,
+            _ => None,
+        }
+    }
+}
+
+/// Estimates, which player controls every cell. Influence of the player is computed with
+/// breadth-first search from the cells, where the player can move now: they get
+/// [`MAX_INFLUENCE`] and it halves with every step through cells, where the player
+/// could move. Cells, where nobody can move, and players, who have lost, get no influence.
+/// # Panics
+/// Panics if `out` has less than `width * height` elements.
+/// # Example
+/// ```
+/// # use crosses_utils::analysis::*;
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// let board = Board::new(5, 1, 2, [Cell::new(); 21], [0; 21]);
+/// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+/// game.play(game.board().index(0, 0)).unwrap();
+/// let mut map = [Influence::default(); 5];
+/// influence_map(&game, &mut map);
+/// assert_eq!((map[0].get(0), map[1].get(0), map[4].get(0)), (0, 256, 32));
+/// assert_eq!((map[3].get(1), map[4].get(1)), (128, 256));
+/// let owners = map.map(|influence| influence.owner());
+/// assert_eq!(owners, [Some(1), Some(0), Some(0), Some(1), Some(1)]);
+/// ```
+pub fn influence_map<C, B, S, L>(game: &Game<Board<C, B>, S, L>, out: &mut [Influence])
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    let board = game.board();
+    let cells = board.width() * board.height();
+    let out = &mut out[..cells];
+    out.fill(Influence::default());
+    let manager = game.player_manager();
+    for player in (0..manager.max_players).filter(|player| manager.losers[*player].is_none()) {
+        for (packed, influence) in out.iter_mut().enumerate() {
+            let cell = board.get(board.unpack_index(packed));
+            if is_movable(cell, player) && cell.is_active(player) {
+                influence.0[player] = MAX_INFLUENCE;
+            }
+        }
+        let mut level = MAX_INFLUENCE;
+        while level > 1 {
+            for packed in 0..cells {
+                if out[packed].0[player] != level {
+                    continue;
+                }
+                for i in board.reach(board.unpack_index(packed)) {
+                    let cell = board.get(i);
+                    if !is_movable(cell, player) {
+                        continue;
+                    }
+                    let influence = &mut out[board.pack_index(i)].0[player];
+                    if *influence == 0 {
+                        *influence = level / 2;
+                    }
+                }
+            }
+            level /= 2;
+        }
+    }
+}
+
+/// Finds cells, where the player can make a move within given number of turns, if other
+/// players don't interfere. Writes the turn, counting from one, when the cell can be reached
+/// first, `None` for other cells, and returns number of reachable cells. The player makes
+/// a move into every cell of the path to the cell, filling crosses of other players on
+/// the way, and revives own filled chains next to placed crosses. If the player makes
+/// the move now, the first turn has the remaining moves of the turn, all other turns
+/// have `max_moves` moves.
+/// # Panics
+/// Panics if `out` has less than `width * height` elements.
+/// # Example
+/// ```
+/// # use crosses_utils::analysis::*;
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// let board = Board::new(6, 1, 2, [Cell::new(); 24], [0; 24]);
+/// let mut game = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+/// game.play(game.board().index(0, 0)).unwrap();
+/// let mut turns = [None; 6];
+/// assert_eq!(reachability(&game, 0, 2, &mut turns), 3);
+/// assert_eq!(turns, [None, Some(1), Some(2), Some(2), None, None]);
+/// assert_eq!(reachability(&game, 1, 1, &mut turns), 2);
+/// assert_eq!(turns, [None, None, None, None, Some(1), Some(1)]);
+/// ```
+pub fn reachability<C, B, S, L>(
+    game: &Game<Board<C, B>, S, L>,
+    player: usize,
+    turns: usize,
+    out: &mut [Option<usize>],
+) -> usize
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    let board = game.board();
+    let cells = board.width() * board.height();
+    let out = &mut out[..cells];
+    out.fill(None);
+    let manager = game.player_manager();
+    if game.state() != GameState::Ongoing || manager.losers[player].is_some() || turns == 0 {
+        return 0;
+    }
+    let first = if manager.current_player == player {
+        manager.remaining_moves
+    } else {
+        manager.max_moves
+    };
+    let budget = first + (turns - 1) * manager.max_moves;
+    let own_filled = |cell: Cell| cell.kind() == CellKind::Filled && cell.player() == player;
+    for (packed, distance) in out.iter_mut().enumerate() {
+        let cell = board.get(board.unpack_index(packed));
+        if is_movable(cell, player) && cell.is_active(player) {
+            *distance = Some(1);
+        }
+    }
+    // Distance of the cell is the number of moves needed to make a move there.
+    // Revived filled cells have the distance of the cross next to them.
+    for moves in 1..budget {
+        let mut found = false;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for packed in 0..cells {
+                if out[packed] != Some(moves) {
+                    continue;
+                }
+                found = true;
+                let index = board.unpack_index(packed);
+                let reach = board.reach(index).map(|i| (i, true));
+                for (i, placement) in reach.chain(board.adjacent(index).map(|i| (i, false))) {
+                    let cell = board.get(i);
+                    if !(is_movable(cell, player) && placement || own_filled(cell) && !placement) {
+                        continue;
+                    }
+                    let distance = &mut out[board.pack_index(i)];
+                    if distance.is_some() {
+                        continue;
+                    }
+                    if is_movable(cell, player) {
+                        *distance = Some(moves + 1);
+                    } else {
+                        *distance = Some(moves);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        if !found {
+            break;
+        }
+    }
+    let mut count = 0;
+    for (packed, distance) in out.iter_mut().enumerate() {
+        let cell = board.get(board.unpack_index(packed));
+        *distance = match *distance {
+            Some(moves) if is_movable(cell, player) && moves <= first => Some(1),
+            Some(moves) if is_movable(cell, player) => {
+                Some(1 + (moves - first + manager.max_moves - 1) / manager.max_moves)
+            }
+            _ => None,
+        };
+        count += distance.is_some() as usize;
+    }
+    count
+}
+
+/// Checks if cells at given indices are connected through the network of the player:
+/// their crosses and alive filled cells. Both cells should belong to the network.
+/// The network is traversed with `buffer`, it should have at least [`Board::storage_len`]
+/// elements.
+/// # Panics
+/// Panics if `buffer` is too short.
+/// # Example
+/// ```
+/// # use crosses_utils::analysis::*;
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// let fen = "x2.o/.x.o/3.o/x2.o 2 x 1/1 - 8";
+/// let mut game = Game::from_fen(fen, [Cell::new(); 36], [0; 36], [None; 2]).unwrap();
+/// let (board, buffer) = (game.board(), &mut [0; 36]);
+/// let (a, b, c) = (board.index(0, 0), board.index(1, 1), board.index(0, 3));
+/// assert!(connected(board, a, b, 0, buffer));
+/// assert!(!connected(board, a, c, 0, buffer));
+/// assert!(!connected(board, a, b, 1, buffer));
+/// let (d, e) = (board.index(3, 0), board.index(3, 3));
+/// assert!(connected(board, d, e, 1, buffer));
+/// ```
+pub fn connected<C, B>(
+    board: &Board<C, B>,
+    a: usize,
+    b: usize,
+    player: usize,
+    buffer: &mut [usize],
+) -> bool
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let in_network = |board: &Board<C, B>, index: usize| in_network(board, index, player);
+    in_network(board, b)
+        && board
+            .walk_in(buffer)
+            .traverse(|_, index| index == a, in_network, |_, index| index == b)
+}
+
+/// Iterator over the frontier of the player, see [`Board::frontier`].
+#[derive(Clone, Debug)]
+pub struct FrontierCells<'a, C, B> {
+    board: &'a Board<C, B>,
+    player: usize,
+    touches: usize,
+    packed: usize,
+}
+impl<'a, C, B> Iterator for FrontierCells<'a, C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    type Item = (usize, usize);
+    fn next(&mut self) -> Option<Self::Item> {
+        let board = self.board;
+        let cells = board.width() * board.height();
+        while self.touches > 0 {
+            while self.packed < cells {
+                let index = board.unpack_index(self.packed);
+                self.packed += 1;
+                let cell = board.get(index);
+                if cell.kind() != CellKind::Empty || !cell.is_active(self.player) {
+                    continue;
+                }
+                let touches = board
+                    .adjacent(index)
+                    .into_iter()
+                    .filter(|i| in_network(board, *i, self.player))
+                    .count();
+                if touches == self.touches {
+                    return Some((index, touches));
+                }
+            }
+            self.touches -= 1;
+            self.packed = 0;
+        }
+        None
+    }
+}
+
+impl<C, B> Board<C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    /// Returns iterator over the frontier of the player: empty cells, that are active
+    /// for them, with numbers of their crosses and alive filled cells around. Cells touched
+    /// by more of them go first, cells with the same number go row by row.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// let game = Game::from_fen("x.x/3./3. 2 o 1/1 - 2", [Cell::new(); 25], [0; 25], [None; 2])
+    ///     .unwrap();
+    /// let board = game.board();
+    /// let mut frontier = board.frontier(0);
+    /// assert_eq!(frontier.next(), Some((board.index(1, 0), 2)));
+    /// assert_eq!(frontier.next(), Some((board.index(1, 1), 2)));
+    /// assert_eq!(frontier.next(), Some((board.index(0, 1), 1)));
+    /// assert_eq!(frontier.count(), 1);
+    /// assert_eq!(board.frontier(1).next(), None);
+    /// ```
+    pub fn frontier(&self, player: usize) -> FrontierCells<'_, C, B> {
+        FrontierCells {
+            board: self,
+            player,
+            touches: self.adjacency().offsets().len(),
+            packed: 0,
+        }
+    }
+}
+
+/// Finds dead areas: empty cells, that no player can ever reach. Players can only expand
+/// through empty cells, crosses and their own filled cells, so areas walled off by borders
+/// and filled cells of others are dead, if players, who haven't lost, aren't there yet.
+/// They can be excluded from counts of territory. Writes `true` for cells of dead areas
+/// and `false` for other cells and returns number of dead cells. Zones of players are
+/// traversed with `buffer`, it should have at least [`Board::storage_len`] elements.
+/// # Panics
+/// Panics if `out` has less than `width * height` elements or `buffer` is too short.
+/// # Example
+/// ```
+/// # use crosses_utils::analysis::*;
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// let fen = "x.2#/2.#./2.2#/o3. 2 x 1/1 - 2";
+/// let game = Game::from_fen(fen, [Cell::new(); 36], [0; 36], [None; 2]).unwrap();
+/// let (mut dead, buffer) = ([false; 16], &mut [0; 36]);
+/// assert_eq!(dead_areas(&game, &mut dead, buffer), 1);
+/// assert!(dead[game.board().pack_index(game.board().index(3, 1))]);
+/// // Filled cells of `o` stop `x` and filled cells of `x` stop `o`.
+/// let fen = "xXO./.X2O/.3X/o3. 2 x 1/1 - 12";
+/// let game = Game::from_fen(fen, [Cell::new(); 36], [0; 36], [None; 2]).unwrap();
+/// assert_eq!(dead_areas(&game, &mut dead, buffer), 1);
+/// assert!(dead[game.board().pack_index(game.board().index(3, 0))]);
+/// ```
+pub fn dead_areas<C, B, S, L>(
+    game: &Game<Board<C, B>, S, L>,
+    out: &mut [bool],
+    buffer: &mut [usize],
+) -> usize
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    let board = game.board();
+    let cells = board.width() * board.height();
+    let out = &mut out[..cells];
+    for (packed, dead) in out.iter_mut().enumerate() {
+        *dead = board.get(board.unpack_index(packed)).kind() == CellKind::Empty;
+    }
+    let manager = game.player_manager();
+    let mut active = [false; MAX_PLAYERS];
+    for (player, active) in active.iter_mut().enumerate().take(manager.max_players) {
+        *active = manager.losers[player].is_none();
+    }
+    let mut walk = board.walk_in(buffer);
+    for player in (0..MAX_PLAYERS).filter(|player| active[*player]) {
+        walk.traverse(
+            |board, index| board.rules().check_move(board.get(index), player).is_ok(),
+            |board, index| {
+                let cell = board.get(index);
+                match cell.kind() {
+                    CellKind::Empty | CellKind::Cross => true,
+                    CellKind::Filled => cell.player() == player,
+                    CellKind::Border => false,
+                }
+            },
+            |board, index| {
+                out[board.pack_index(index)] = false;
+                false
+            },
+        );
+    }
+    out.iter().filter(|dead| **dead).count()
+}
+
+/// Cross of other player, that can be filled, see [`captures`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Capture {
+    owner: usize,
+    group: usize,
+}
+impl Capture {
+    /// Returns the player, whose cross would be filled.
+    pub fn owner(&self) -> usize {
+        self.owner
+    }
+    /// Returns the group of the cross: packed index of the first cell row by row
+    /// of the part of the network of the owner, that contains the cross.
+    pub fn group(&self) -> usize {
+        self.group
+    }
+}
+
+/// Finds crosses of other players, that the player can fill now. Crosses are grouped by
+/// connected parts of networks of their owners (crosses and alive filled cells), which
+/// would be affected by the captures. Writes [`Capture`] for such crosses and `None` for
+/// other cells and returns number of captures. Networks are traversed with `buffer`,
+/// it should have at least [`Board::storage_len`] elements.
+/// # Panics
+/// Panics if `out` has less than `width * height` elements or `buffer` is too short.
+/// # Example
+/// ```
+/// # use crosses_utils::analysis::*;
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// let fen = "xo2./.o2./x3./xxxo 2 x 1/1 - 8";
+/// let game = Game::from_fen(fen, [Cell::new(); 36], [0; 36], [None; 2]).unwrap();
+/// let (board, mut out, buffer) = (game.board(), [None; 16], &mut [0; 36]);
+/// assert_eq!(captures(board, 0, &mut out, buffer), 3);
+/// let group = |x, y| out[board.pack_index(board.index(x, y))].map(|capture| capture.group());
+/// assert_eq!((group(1, 0), group(1, 1), group(3, 3)), (Some(1), Some(1), Some(15)));
+/// assert_eq!(captures(board, 1, &mut out, buffer), 3);
+/// ```
+pub fn captures<C, B>(
+    board: &Board<C, B>,
+    player: usize,
+    out: &mut [Option<Capture>],
+    buffer: &mut [usize],
+) -> usize
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let out = &mut out[..board.width() * board.height()];
+    out.fill(None);
+    let mut count = 0;
+    board.walk_in(buffer).components(
+        |board, seed, index| {
+            let owner = board.get(seed).player();
+            owner != player && in_network(board, index, owner)
+        },
+        |board, seed, index| {
+            let cell = board.get(index);
+            if cell.kind() == CellKind::Cross && board.rules().check_move(cell, player).is_ok() {
+                out[board.pack_index(index)] = Some(Capture {
+                    owner: cell.player(),
+                    group: board.pack_index(seed),
+                });
+                count += 1;
+            }
+        },
+    );
+    count
+}
+
+/// Phase of the game, see [`phase`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Phase {
+    /// Networks of players haven't met yet
+    Opening,
+    /// Networks of players have met
+    Contact,
+    /// Little free territory remains or the game has ended
+    Endgame,
+}
+impl Display for Phase {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Phase::Opening => write!(f, "opening"),
+            Phase::Contact => write!(f, "contact"),
+            Phase::Endgame => write!(f, "endgame"),
+        }
+    }
+}
+
+/// Classifies the phase of the game. It's [`Phase::Endgame`], if the game has ended or
+/// at most a quarter of cells is empty. Otherwise it's [`Phase::Contact`], if networks
+/// of players, who haven't lost, have met: one of them can move into a cross of another
+/// or into a cell, where another can move too. Otherwise it's [`Phase::Opening`].
+/// # Example
+/// ```
+/// # use crosses_utils::analysis::*;
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// let game = Game::from_fen("x3./4./4./3.o 2 x 1/1 - 2", [Cell::new(); 36], [0; 36], [None; 2])
+///     .unwrap();
+/// assert_eq!(phase(&game), Phase::Opening);
+/// let game = Game::from_fen("x.o./4./4./4. 2 x 1/1 - 2", [Cell::new(); 36], [0; 36], [None; 2])
+///     .unwrap();
+/// assert_eq!(phase(&game), Phase::Contact);
+/// let fen = "xXo./xXoO/oxX./xoxo 2 x 1/1 - 14";
+/// let game = Game::from_fen(fen, [Cell::new(); 36], [0; 36], [None; 2]).unwrap();
+/// assert_eq!(phase(&game), Phase::Endgame);
+/// ```
+pub fn phase<C, B, S, L>(game: &Game<Board<C, B>, S, L>) -> Phase
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    let board = game.board();
+    let cells = board.width() * board.height();
+    let empty = (0..cells)
+        .filter(|packed| board.get(board.unpack_index(*packed)).kind() == CellKind::Empty)
+        .count();
+    if game.state() != GameState::Ongoing || empty * 4 <= cells {
+        return Phase::Endgame;
+    }
+    let manager = game.player_manager();
+    let players = manager.max_players.min(MAX_PLAYERS);
+    let active = |player: usize| manager.losers[player].is_none();
+    let rules = board.rules();
+    let met = (0..cells).any(|packed| {
+        let cell = board.get(board.unpack_index(packed));
+        let mut movable = (0..players)
+            .filter(|player| active(*player) && rules.check_move(cell, *player).is_ok());
+        match cell.kind() {
+            CellKind::Cross => movable.next().is_some(),
+            _ => movable.nth(1).is_some(),
+        }
+    });
+    if met {
+        Phase::Contact
+    } else {
+        Phase::Opening
+    }
+}
+
+/// Cell of the network of the player, see [`cut_cells`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct CutCell {
+    order: usize,
+    low: usize,
+    parent: Option<usize>,
+    next: u8,
+    parts: u8,
+}
+impl CutCell {
+    /// Checks if the cell belongs to the network.
+    pub fn in_network(&self) -> bool {
+        self.order != 0
+    }
+    /// Checks if the loss of the cell splits its part of the network.
+    pub fn is_cut(&self) -> bool {
+        self.parts >= 2
+    }
+    /// Returns number of parts, that the part of the network is split into without the cell,
+    /// or `0` if the cell doesn't split it.
+    pub fn parts(&self) -> usize {
+        if self.is_cut() {
+            self.parts as usize
+        } else {
+            0
+        }
+    }
+}
+
+/// Finds cut cells of the network of the player: crosses and alive filled cells, whose
+/// loss splits their connected part of the network. If a split part has no crosses,
+/// its filled cells die, so these cells are worth defending. Uses the iterative variant
+/// of Tarjan's algorithm and returns number of cut cells.
+/// # Panics
+/// Panics if `out` has less than `width * height` elements.
+/// # Example
+/// ```
+/// # use crosses_utils::analysis::*;
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// let fen = "xXx./3.X/2.xX/2.x. 2 x 1/1 - 9";
+/// let game = Game::from_fen(fen, [Cell::new(); 36], [0; 36], [None; 2]).unwrap();
+/// let board = game.board();
+/// let mut cells = [CutCell::default(); 16];
+/// assert_eq!(cut_cells(board, 0, &mut cells), 3);
+/// let cut = |x, y| cells[board.pack_index(board.index(x, y))];
+/// assert!(cut(1, 0).is_cut() && cut(2, 0).is_cut() && cut(3, 1).is_cut());
+/// assert_eq!((cut(1, 0).parts(), cut(0, 0).parts()), (2, 0));
+/// assert!(!cut(3, 3).in_network());
+/// ```
+pub fn cut_cells<C, B>(board: &Board<C, B>, player: usize, out: &mut [CutCell]) -> usize
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let cells = board.width() * board.height();
+    let out = &mut out[..cells];
+    out.fill(CutCell::default());
+    let in_network = |index: usize| in_network(board, index, player);
+    let mut order = 0;
+    for root in 0..cells {
+        if out[root].in_network() || !in_network(board.unpack_index(root)) {
+            continue;
+        }
+        order += 1;
+        out[root].order = order;
+        out[root].low = order;
+        let mut current = root;
+        loop {
+            let next = out[current].next as usize;
+            let adjacent = board.adjacent(board.unpack_index(current));
+            if next < adjacent.len() {
+                out[current].next += 1;
+                let i = adjacent.as_slice()[next];
+                if !in_network(i) {
+                    continue;
+                }
+                let packed = board.pack_index(i);
+                if !out[packed].in_network() {
+                    order += 1;
+                    out[packed].order = order;
+                    out[packed].low = order;
+                    out[packed].parent = Some(current);
+                    current = packed;
+                } else if out[current].parent != Some(packed) {
+                    out[current].low = out[current].low.min(out[packed].order);
+                }
+                continue;
+            }
+            let parent = match out[current].parent {
+                Some(parent) => parent,
+                None => break,
+            };
+            out[parent].low = out[parent].low.min(out[current].low);
+            if out[current].low >= out[parent].order {
+                out[parent].parts += 1;
+            }
+            current = parent;
+        }
+    }
+    let mut count = 0;
+    for cell in out.iter_mut() {
+        // Besides the split children, other cells stay connected through the parent.
+        if cell.parent.is_some() && cell.parts > 0 {
+            cell.parts += 1;
+        }
+        count += cell.is_cut() as usize;
+    }
+    count
+}
+
+/// Move, that lets filled cells of the mover die for a better position, see [`sacrifices`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Sacrifice {
+    lost: usize,
+    gain: i32,
+}
+impl Sacrifice {
+    /// Returns how many alive filled cells the mover has less after the move.
+    pub fn lost(&self) -> usize {
+        self.lost
+    }
+    /// Returns how much better the move is than the best move, that loses nothing,
+    /// in units of [`DefaultEvaluator`].
+    pub fn gain(&self) -> i32 {
+        self.gain
+    }
+}
+
+/// Finds sacrifices of the current player: moves, after which they have less alive filled
+/// cells, but which are still better than every move, that doesn't lose cells. Moves are
+/// compared by the search of given depth with [`DefaultEvaluator`], alive cells are counted
+/// at the end of the principal variation. Time of the search grows exponentially with
+/// the depth, so searching until the next turn of the player is only feasible on small
+/// boards. Writes the sacrifice for every move, `None` for other cells, and returns number
+/// of sacrifices. If every move loses cells, they are forced, not sacrifices. The game is
+/// returned to its initial state.
+/// # Panics
+/// Panics if `out` has less than `width * height` elements or `depth` is zero or greater
+/// than [`MAX_DEPTH`].
+/// # Example
+/// ```
+/// # use crosses_utils::analysis::*;
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// let fen = "2xo./2.xo/3.X/3.o 2 x 1/1 - 8";
+/// let mut game = Game::from_fen(fen, [Cell::new(); 36], [0; 36], [None; 2]).unwrap();
+/// let mut found = [None; 16];
+/// assert_eq!(sacrifices(&mut game, 2, &mut found), 1);
+/// let sacrifice = found[game.board().pack_index(game.board().index(1, 2))].unwrap();
+/// assert_eq!((sacrifice.lost(), sacrifice.gain()), (1, 10));
+///
+/// let board = Board::new(4, 4, 2, [Cell::new(); 36], [0; 36]);
+/// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+/// assert_eq!(sacrifices(&mut game, 2, &mut found), 0);
+/// ```
+pub fn sacrifices<C, B, S, L>(
+    game: &mut Game<Board<C, B>, S, L>,
+    depth: usize,
+    out: &mut [Option<Sacrifice>],
+) -> usize
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    assert!(depth != 0 && depth <= MAX_DEPTH, "depth is out of range");
+    let board = game.board();
+    let cells = board.width() * board.height();
+    let out = &mut out[..cells];
+    out.fill(None);
+    if game.state() != GameState::Ongoing {
+        return 0;
+    }
+    let player = game.current_player();
+    let alive_before = alive_filled(game.board(), player);
+    let mut best_safe = None;
+    let mut searcher = Searcher::new(DefaultEvaluator::default());
+    searcher.score_lines(game, depth, |game, index, score, line| {
+        let mut records = [None; MAX_DEPTH];
+        for (record, index) in records.iter_mut().zip(line.moves()) {
+            *record = game.play(*index).ok();
+        }
+        let lost = alive_before.saturating_sub(alive_filled(game.board(), player));
+        for record in records.iter().rev().flatten() {
+            game.undo(*record).expect("the move was just made");
+        }
+        if lost == 0 {
+            best_safe = best_safe.max(Some(score));
+        }
+        out[game.board().pack_index(index)] = Some(Sacrifice { lost, gain: score });
+    });
+    let mut count = 0;
+    for sacrifice in out.iter_mut() {
+        *sacrifice = match (*sacrifice, best_safe) {
+            (Some(Sacrifice { lost, gain }), Some(safe)) if lost > 0 && gain > safe => {
+                count += 1;
+                Some(Sacrifice {
+                    lost,
+                    gain: gain - safe,
+                })
+            }
+            _ => None,
+        };
+    }
+    count
+}
+
+/// Returns how the move of the current player at given index changes mobility: gain
+/// of mobility of the player minus total gain of mobility of other players, or `None`
+/// if the move is illegal. The move is made and cancelled, so the game is left unchanged.
+/// # Example
+/// ```
+/// # use crosses_utils::analysis::*;
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// let mut game = Game::from_fen("x.o./4./4./4. 2 x 1/1 - 2", [Cell::new(); 36], [0; 36], [None; 2])
+///     .unwrap();
+/// let initial = game.clone();
+/// let board = game.board();
+/// let (toward, away, illegal) = (board.index(1, 1), board.index(0, 1), board.index(3, 3));
+/// // `x` reaches 4 more cells including the cross of `o`, `o` can fill the new cross.
+/// assert_eq!(mobility_swing(&mut game, toward), Some(4));
+/// assert_eq!(mobility_swing(&mut game, away), Some(1));
+/// assert_eq!(mobility_swing(&mut game, illegal), None);
+/// assert_eq!(game, initial);
+/// ```
+pub fn mobility_swing<C, B, S, L>(game: &mut Game<Board<C, B>, S, L>, index: usize) -> Option<i32>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    let player = game.current_player();
+    let swing = |game: &Game<Board<C, B>, S, L>| {
+        (0..game.player_manager().max_players)
+            .map(|other| match other == player {
+                true => game.mobility(other) as i32,
+                false => -(game.mobility(other) as i32),
+            })
+            .sum::<i32>()
+    };
+    let before = swing(game);
+    let record = game.play(index).ok()?;
+    let after = swing(game);
+    game.undo(record).expect("the move was just made");
+    Some(after - before)
+}
+
+/// Checks if the cell is a cross or an alive filled cell of the player.
+fn in_network<C, B>(board: &Board<C, B>, index: usize, player: usize) -> bool
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let cell = board.get(index);
+    cell.player() == player
+        && match cell.kind() {
+            CellKind::Cross => true,
+            CellKind::Filled => board.is_alive(index),
+            _ => false,
+        }
+}
+
+fn alive_filled<C: CellStorage, B: AsMut<[usize]>>(board: &Board<C, B>, player: usize) -> usize {
+    (0..board.width() * board.height())
+        .map(|packed| board.unpack_index(packed))
+        .filter(|index| {
+            let cell = board.get(*index);
+            cell.kind() == CellKind::Filled && cell.player() == player && board.is_alive(*index)
+        })
+        .count()
+}
@@ -17,7 +17,7 @@ pub trait GameBoard {
     type Player: Copy + PartialEq;
 
     /// Returns indices of adjacent cells for some `index`
-    fn adjacent(&mut self, index: Self::Index) -> Self::Adjacent;
+    fn adjacent(&self, index: Self::Index) -> Self::Adjacent;
     /// Returns the type of cell
     fn kind(&self, index: Self::Index) -> CellKind;
     /// Returns the player of cell
@@ -0,0 +1,287 @@
+//! Binary form of boards
+//!
+//! This module defines compact binary form of [`Board`] for sending positions over
+//! slow links. It has a header of 5 bytes: width and height as little-endian `u16`
+//! and number of players. Then the cells follow row by row, each cell takes
+//! [`bits_per_cell`] bits (3 bits for 2 players, 4 bits for up to 4 players).
+//! Neither encoding nor decoding allocates, bytes are written into a buffer of the caller.
+//!
+//! Like the text form, binary form stores only the content of the cells,
+//! so the rest of the board is recomputed with [`Board::init`] on decoding.
+//! Bytes can be turned into printable strings with [`encode_hex`] and [`encode_base64`].
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::engine::*;
+//! let mut board = Board::new(10, 10, 2, [Cell::new(); 144], [0; 144]);
+//! let corner = board.index(0, 0);
+//! make_move(&mut board, corner, 0).unwrap();
+//! let mut bytes = [0; 64];
+//! let len = board.to_bytes(&mut bytes).unwrap();
+//! assert_eq!(len, board.encoded_len());
+//! assert_eq!(len, 5 + 38);
+//! let decoded = Board::from_bytes(&bytes[..len], [Cell::new(); 144], [0; 144]).unwrap();
+//! assert_eq!(decoded, board);
+//! ```
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::base::CellKind;
+use crate::board::{Board, CellStorage, MAX_PLAYERS};
+use crate::counters::PlayerCounters;
+use crate::engine::{Data, Engine};
+
+/// Size of the header in bytes.
+pub const HEADER_LEN: usize = 5;
+
+/// Returns number of bits used for one cell of the board with given number of players.
+pub const fn bits_per_cell(players: usize) -> usize {
+    let codes = 2 + 2 * players;
+    (usize::BITS - (codes - 1).leading_zeros()) as usize
+}
+
+/// Errors of [`Board::to_bytes`] and [`Board::from_bytes`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum BinaryError {
+    /// The buffer is smaller than [`Board::encoded_len`]
+    BufferTooSmall,
+    /// The board is too large to be encoded
+    TooLarge,
+    /// The bytes don't contain a board
+    Malformed,
+    /// Given storages are smaller than [`Board::storage_len`]
+    StorageTooSmall,
+}
+impl Display for BinaryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            BinaryError::BufferTooSmall => write!(f, "buffer is too small"),
+            BinaryError::TooLarge => write!(f, "board is too large"),
+            BinaryError::Malformed => write!(f, "bytes are malformed"),
+            BinaryError::StorageTooSmall => write!(f, "storage is too small"),
+        }
+    }
+}
+
+impl<C, B> Board<C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    /// Returns number of bytes in binary form of the board.
+    pub fn encoded_len(&self) -> usize {
+        let bits = self.width() * self.height() * bits_per_cell(self.counters().players());
+        HEADER_LEN + (bits + 7) / 8
+    }
+    /// Writes binary form of the board into `out` and returns number of written bytes.
+    /// # Errors
+    /// Returns [`BinaryError`] if `out` is too small or the board is wider or higher
+    /// than `u16::MAX`.
+    pub fn to_bytes(&self, out: &mut [u8]) -> Result<usize, BinaryError> {
+        let len = self.encoded_len();
+        if out.len() < len {
+            return Err(BinaryError::BufferTooSmall);
+        }
+        let (width, height) = (self.width(), self.height());
+        if width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err(BinaryError::TooLarge);
+        }
+        let players = self.counters().players();
+        out[0..2].copy_from_slice(&(width as u16).to_le_bytes());
+        out[2..4].copy_from_slice(&(height as u16).to_le_bytes());
+        out[4] = players as u8;
+        out[HEADER_LEN..len].fill(0);
+        let bits = bits_per_cell(players);
+        for y in 0..height {
+            for x in 0..width {
+                let cell = self.get(self.index(x, y));
+                let code = match cell.kind() {
+                    CellKind::Empty => 0,
+                    CellKind::Border => 1,
+                    CellKind::Cross => 2 + 2 * cell.player(),
+                    CellKind::Filled => 3 + 2 * cell.player(),
+                };
+                let position = (y * width + x) * bits;
+                for bit in 0..bits {
+                    let i = position + bit;
+                    out[HEADER_LEN + i / 8] |= ((code >> bit) as u8 & 1) << (i % 8);
+                }
+            }
+        }
+        Ok(len)
+    }
+    /// Reads the board from its binary form into given storages.
+    /// Starting cells are the same as in [`Board::new`] and previous owners
+    /// of filled cells are set to the next players.
+    /// # Errors
+    /// Returns [`BinaryError`] if the bytes are malformed or storages are too small.
+    pub fn from_bytes(bytes: &[u8], cells: C, mut buffer: B) -> Result<Self, BinaryError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(BinaryError::Malformed);
+        }
+        let width = u16::from_le_bytes([bytes[0], bytes[1]]) as usize;
+        let height = u16::from_le_bytes([bytes[2], bytes[3]]) as usize;
+        let players = bytes[4] as usize;
+        if width == 0 || height == 0 || players == 0 || players > MAX_PLAYERS {
+            return Err(BinaryError::Malformed);
+        }
+        let bits = bits_per_cell(players);
+        if bytes.len() < HEADER_LEN + (width * height * bits + 7) / 8 {
+            return Err(BinaryError::Malformed);
+        }
+        let code = |position: usize| {
+            (0..bits).fold(0, |code, bit| {
+                let i = position * bits + bit;
+                code | ((bytes[HEADER_LEN + i / 8] >> (i % 8)) as usize & 1) << bit
+            })
+        };
+        if (0..width * height).any(|position| code(position) >= 2 + 2 * players) {
+            return Err(BinaryError::Malformed);
+        }
+        let len = Self::storage_len(width, height);
+        if cells.len() < len || buffer.as_mut().len() < len {
+            return Err(BinaryError::StorageTooSmall);
+        }
+        let mut board = Board::new(width, height, players, cells, buffer);
+        for position in 0..width * height {
+            let (kind, player) = match code(position) {
+                0 => (CellKind::Empty, 0),
+                1 => (CellKind::Border, 0),
+                code if code % 2 == 0 => (CellKind::Cross, (code - 2) / 2),
+                code => (CellKind::Filled, (code - 3) / 2),
+            };
+            let index = board.index(position % width, position / width);
+            board.set_up(index, kind, player);
+        }
+        board.init();
+        Ok(board)
+    }
+}
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Returns length of hex string of given number of bytes.
+pub const fn hex_len(len: usize) -> usize {
+    len * 2
+}
+/// Writes bytes as lowercase hex into `out` and returns the written string,
+/// e.g. to paste binary form of the board into chat.
+/// # Errors
+/// Returns [`BinaryError::BufferTooSmall`] if `out` is shorter than [`hex_len`].
+/// # Example
+/// ```
+/// # use crosses_utils::binary::*;
+/// # use crosses_utils::board::*;
+/// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+/// let mut bytes = [0; 16];
+/// let len = board.to_bytes(&mut bytes).unwrap();
+/// let mut text = [0; 32];
+/// let hex = encode_hex(&bytes[..len], &mut text).unwrap();
+/// assert_eq!(hex, "030003000200000000");
+/// let len = decode_hex(hex, &mut bytes).unwrap();
+/// let decoded = Board::from_bytes(&bytes[..len], [Cell::new(); 25], [0; 25]).unwrap();
+/// assert_eq!(decoded, board);
+/// ```
+pub fn encode_hex<'a>(bytes: &[u8], out: &'a mut [u8]) -> Result<&'a str, BinaryError> {
+    let len = hex_len(bytes.len());
+    if out.len() < len {
+        return Err(BinaryError::BufferTooSmall);
+    }
+    for (byte, pair) in bytes.iter().zip(out.chunks_mut(2)) {
+        pair[0] = HEX[(byte >> 4) as usize];
+        pair[1] = HEX[(byte & 0xf) as usize];
+    }
+    Ok(ascii(&out[..len]))
+}
+/// Reads bytes from hex string into `out` and returns number of read bytes.
+/// Both cases are accepted.
+/// # Errors
+/// Returns [`BinaryError::Malformed`] if the string isn't hex and
+/// [`BinaryError::BufferTooSmall`] if `out` is too small.
+pub fn decode_hex(text: &str, out: &mut [u8]) -> Result<usize, BinaryError> {
+    let text = text.as_bytes();
+    if text.len() % 2 != 0 {
+        return Err(BinaryError::Malformed);
+    }
+    let len = text.len() / 2;
+    if out.len() < len {
+        return Err(BinaryError::BufferTooSmall);
+    }
+    for (pair, byte) in text.chunks(2).zip(out.iter_mut()) {
+        let digit = |c: u8| (c as char).to_digit(16).ok_or(BinaryError::Malformed);
+        *byte = (digit(pair[0])? << 4 | digit(pair[1])?) as u8;
+    }
+    Ok(len)
+}
+
+/// Returns length of base64 string of given number of bytes.
+pub const fn base64_len(len: usize) -> usize {
+    (len * 4 + 2) / 3
+}
+/// Writes bytes as URL-safe base64 without padding into `out` and returns
+/// the written string, e.g. to pass binary form of the board in URL.
+/// # Errors
+/// Returns [`BinaryError::BufferTooSmall`] if `out` is shorter than [`base64_len`].
+/// # Example
+/// ```
+/// # use crosses_utils::binary::*;
+/// # use crosses_utils::board::*;
+/// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+/// let mut bytes = [0; 16];
+/// let len = board.to_bytes(&mut bytes).unwrap();
+/// let mut text = [0; 16];
+/// let base64 = encode_base64(&bytes[..len], &mut text).unwrap();
+/// assert_eq!(base64, "AwADAAIAAAAA");
+/// let len = decode_base64(base64, &mut bytes).unwrap();
+/// let decoded = Board::from_bytes(&bytes[..len], [Cell::new(); 25], [0; 25]).unwrap();
+/// assert_eq!(decoded, board);
+/// ```
+pub fn encode_base64<'a>(bytes: &[u8], out: &'a mut [u8]) -> Result<&'a str, BinaryError> {
+    let len = base64_len(bytes.len());
+    if out.len() < len {
+        return Err(BinaryError::BufferTooSmall);
+    }
+    for (chunk, quad) in bytes.chunks(3).zip(out.chunks_mut(4)) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |group, (i, byte)| group | (*byte as u32) << (16 - 8 * i));
+        for (i, c) in quad.iter_mut().take(chunk.len() + 1).enumerate() {
+            *c = BASE64[(group >> (18 - 6 * i) & 0x3f) as usize];
+        }
+    }
+    Ok(ascii(&out[..len]))
+}
+/// Reads bytes from URL-safe base64 string into `out` and returns number
+/// of read bytes. Trailing padding is accepted.
+/// # Errors
+/// Returns [`BinaryError::Malformed`] if the string isn't base64 and
+/// [`BinaryError::BufferTooSmall`] if `out` is too small.
+pub fn decode_base64(text: &str, out: &mut [u8]) -> Result<usize, BinaryError> {
+    let text = text.trim_end_matches('=').as_bytes();
+    if text.len() % 4 == 1 {
+        return Err(BinaryError::Malformed);
+    }
+    let len = text.len() * 3 / 4;
+    if out.len() < len {
+        return Err(BinaryError::BufferTooSmall);
+    }
+    for (quad, chunk) in text.chunks(4).zip(out.chunks_mut(3)) {
+        let mut group = 0;
+        for (i, c) in quad.iter().enumerate() {
+            let value = BASE64
+                .iter()
+                .position(|b| b == c)
+                .ok_or(BinaryError::Malformed)?;
+            group |= (value as u32) << (18 - 6 * i);
+        }
+        for (i, byte) in chunk.iter_mut().take(quad.len() - 1).enumerate() {
+            *byte = (group >> (16 - 8 * i)) as u8;
+        }
+    }
+    Ok(len)
+}
+
+fn ascii(bytes: &[u8]) -> &str {
+    core::str::from_utf8(bytes).expect("encoded bytes are ASCII")
+}
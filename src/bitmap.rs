@@ -0,0 +1,235 @@
+//! Bitmap rendering
+//!
+//! This module defines [`Rasterizer`], which draws boards into pixel buffers provided by
+//! the user, e.g. framebuffers of embedded displays. Every cell is a square of
+//! [`Rasterizer::cell_size`] pixels with a grid line on its top and left sides, colors
+//! are taken from [`Palette`]. Pixels are stored row by row in [`PixelFormat`], so the
+//! buffer of the board with size `width × height` should have
+//! `width * height * cell_size² * bytes_per_pixel` bytes.
+//! # Example
+//! ```
+//! # use crosses_utils::bitmap::*;
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::engine::*;
+//! let mut board = Board::new(2, 1, 2, [Cell::new(); 12], [0; 12]);
+//! let corner = board.index(0, 0);
+//! make_move(&mut board, corner, 0).unwrap();
+//! let rasterizer = Rasterizer::new(4, PixelFormat::Rgba8888, Palette::default());
+//! assert_eq!(rasterizer.size(&board), (8, 4));
+//! let mut pixels = [0; 8 * 4 * 4];
+//! rasterizer.draw(&board, &mut pixels).unwrap();
+//! let pixel = |x: usize, y: usize| &pixels[(y * 8 + x) * 4..][..4];
+//! let palette = Palette::default();
+//! assert_eq!(pixel(0, 0), palette.grid.to_rgba());
+//! assert_eq!(pixel(1, 1), palette.players[0].to_rgba());
+//! assert_eq!(pixel(6, 2), palette.empty.to_rgba());
+//! assert_eq!(Rgb::new(0xff, 0, 0).to_rgb565(), 0xf800);
+//! let mut small = [0; 8 * 4 * 2 - 1];
+//! let rasterizer = Rasterizer::new(4, PixelFormat::Rgb565, palette);
+//! assert_eq!(rasterizer.draw(&board, &mut small), Err(BufferTooSmall { needed: 64 }));
+//! ```
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::base::CellKind;
+use crate::board::{Board, CellStorage, MAX_PLAYERS};
+use crate::engine::{Data, Engine};
+use crate::ibts::IbtsBoard;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Color with 8 bits per channel.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Rgb {
+    /// Red channel
+    pub r: u8,
+    /// Green channel
+    pub g: u8,
+    /// Blue channel
+    pub b: u8,
+}
+impl Rgb {
+    /// Creates new color.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+    /// Returns the color packed into 16 bits: 5 bits of red, 6 of green and 5 of blue.
+    pub fn to_rgb565(self) -> u16 {
+        (self.r as u16 >> 3) << 11 | (self.g as u16 >> 2) << 5 | self.b as u16 >> 3
+    }
+    /// Returns bytes of the opaque color.
+    pub fn to_rgba(self) -> [u8; 4] {
+        [self.r, self.g, self.b, u8::MAX]
+    }
+    /// Returns the color halfway to other color.
+    pub fn mix(self, other: Rgb) -> Rgb {
+        let half = |a: u8, b: u8| ((a as u16 + b as u16) / 2) as u8;
+        Rgb::new(
+            half(self.r, other.r),
+            half(self.g, other.g),
+            half(self.b, other.b),
+        )
+    }
+}
+
+/// Colors used by [`Rasterizer`]. Dead filled cells are drawn with colors of players
+/// mixed with the color of empty cells.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Palette {
+    /// Color of empty cells and the background of crosses
+    pub empty: Rgb,
+    /// Color of obstacles
+    pub obstacle: Rgb,
+    /// Color of grid lines
+    pub grid: Rgb,
+    /// Colors of players
+    pub players: [Rgb; MAX_PLAYERS],
+}
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            empty: Rgb::new(0xff, 0xff, 0xff),
+            obstacle: Rgb::new(0x40, 0x40, 0x40),
+            grid: Rgb::new(0xa0, 0xa0, 0xa0),
+            players: [
+                Rgb::new(0xe0, 0x20, 0x20),
+                Rgb::new(0x20, 0x40, 0xe0),
+                Rgb::new(0x20, 0xa0, 0x20),
+                Rgb::new(0xe0, 0xc0, 0x00),
+                Rgb::new(0xa0, 0x20, 0xc0),
+                Rgb::new(0x00, 0xb0, 0xc0),
+                Rgb::new(0xe0, 0x70, 0x00),
+                Rgb::new(0x00, 0x00, 0x00),
+            ],
+        }
+    }
+}
+
+/// Layout of pixels in the buffer.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PixelFormat {
+    /// 2 bytes per pixel: little-endian [`Rgb::to_rgb565`]
+    Rgb565,
+    /// 4 bytes per pixel: [`Rgb::to_rgba`]
+    Rgba8888,
+}
+impl PixelFormat {
+    /// Returns number of bytes of one pixel.
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Rgb565 => 2,
+            PixelFormat::Rgba8888 => 4,
+        }
+    }
+    fn write(self, pixel: &mut [u8], color: Rgb) {
+        match self {
+            PixelFormat::Rgb565 => pixel.copy_from_slice(&color.to_rgb565().to_le_bytes()),
+            PixelFormat::Rgba8888 => pixel.copy_from_slice(&color.to_rgba()),
+        }
+    }
+}
+
+/// Error of [`Rasterizer::draw`]: the buffer has less bytes than needed.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BufferTooSmall {
+    /// Number of needed bytes
+    pub needed: usize,
+}
+impl Display for BufferTooSmall {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "buffer is too small, {} bytes are needed", self.needed)
+    }
+}
+
+/// Draws boards into pixel buffers, see module docs.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rasterizer {
+    cell_size: usize,
+    format: PixelFormat,
+    palette: Palette,
+}
+impl Rasterizer {
+    /// Creates new rasterizer. Cells smaller than 3 pixels are drawn as 3 pixels.
+    pub fn new(cell_size: usize, format: PixelFormat, palette: Palette) -> Self {
+        Self {
+            cell_size: cell_size.max(3),
+            format,
+            palette,
+        }
+    }
+    /// Returns size of cells in pixels.
+    pub fn cell_size(&self) -> usize {
+        self.cell_size
+    }
+    /// Returns format of pixels.
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+    /// Returns the palette.
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+    /// Returns width and height of the image of the board in pixels.
+    pub fn size<C, B>(&self, board: &Board<C, B>) -> (usize, usize)
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        (
+            board.width() * self.cell_size,
+            board.height() * self.cell_size,
+        )
+    }
+    /// Draws the board into the beginning of the buffer.
+    /// # Errors
+    /// Returns [`BufferTooSmall`] if the image doesn't fit into the buffer.
+    /// In this case nothing is drawn.
+    pub fn draw<C, B>(&self, board: &Board<C, B>, buffer: &mut [u8]) -> Result<(), BufferTooSmall>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        let (width, height) = self.size(board);
+        let bytes = self.format.bytes_per_pixel();
+        let needed = width * height * bytes;
+        if buffer.len() < needed {
+            return Err(BufferTooSmall { needed });
+        }
+        let size = self.cell_size;
+        let thickness = 1 + size / 8;
+        for (number, pixel) in buffer[..needed].chunks_exact_mut(bytes).enumerate() {
+            let (x, y) = (number % width, number / width);
+            let index = board.index(x / size, y / size);
+            // Coordinates inside the cell, grid lines are at zeros.
+            let (dx, dy) = (x % size, y % size);
+            let color = if dx == 0 || dy == 0 {
+                self.palette.grid
+            } else {
+                let cell = board.get(index);
+                let player = self.palette.players[cell.player()];
+                match cell.kind() {
+                    CellKind::Empty => self.palette.empty,
+                    CellKind::Border => self.palette.obstacle,
+                    CellKind::Filled if board.is_alive(index) => player,
+                    CellKind::Filled => player.mix(self.palette.empty),
+                    CellKind::Cross => {
+                        let (dx, dy) = (dx as isize, dy as isize);
+                        let side = size as isize;
+                        if (dx - dy).abs() < thickness as isize
+                            || (dx + dy - side).abs() < thickness as isize
+                        {
+                            player
+                        } else {
+                            self.palette.empty
+                        }
+                    }
+                }
+            };
+            self.format.write(pixel, color);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1396 @@
+//! Rectangular board
+//!
+//! This module defines [`Board`] struct, which is a ready-to-use implementation of [`Engine`]
+//! (and therefore [`IbtsBoard`] and [`GameBoard`]) for rectangular boards, where every cell
+//! has 8 adjacent cells by default, other [`Adjacency`] can be chosen. Board is surrounded
+//! by a ring of [`CellKind::Border`] cells, so indices of cells are indices in the storage
+//! of width `width + 2`. Use [`Board::index`] and [`Board::coords`] to convert coordinates
+//! to indices and back.
+//!
+//! Board doesn't allocate. Storage for cells and buffer for traversals of filled chains
+//! are provided by the user, both of them should have [`Board::storage_len`] elements.
+//! Any [`CellStorage`] can be used for cells, e.g. Vec, simple array or copy-on-write
+//! chunks from `persistent` module (requires `alloc` feature).
+use crate::base::{CellKind, GameBoard};
+use crate::counters::{Counter, Counters, PlayerCounters};
+use crate::engine::{
+    activate, activate_around, deactivate, deactivate_around, is_movable, Data, Engine,
+};
+use crate::ibts::{IbtsBoard, IbtsCellFlags, SearchResult};
+use crate::rules::RuleSet;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Maximum number of players on [`Board`].
+pub const MAX_PLAYERS: usize = 8;
+
+/// A cell of [`Board`]. Activations of each player are stored in 4 bits.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Cell {
+    kind: CellKind,
+    player: u8,
+    previous_player: u8,
+    flags: IbtsCellFlags,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    visited: bool,
+    activity: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    protected: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    stronghold: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    damage: u8,
+}
+impl Cell {
+    /// Creates new empty cell without activations.
+    pub const fn new() -> Self {
+        Self {
+            kind: CellKind::Empty,
+            player: 0,
+            previous_player: 0,
+            flags: IbtsCellFlags::new(),
+            visited: false,
+            activity: 0,
+            protected: false,
+            stronghold: false,
+            damage: 0,
+        }
+    }
+    /// Returns number of activations for the player.
+    pub fn activity(&self, player: usize) -> u8 {
+        (self.activity >> (player * 4)) as u8 & 0xF
+    }
+    /// Returns IBTS flags of the cell.
+    pub fn flags(&self) -> IbtsCellFlags {
+        self.flags
+    }
+}
+impl Default for Cell {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Data for Cell {
+    fn kind(&self) -> CellKind {
+        self.kind
+    }
+    fn player(&self) -> usize {
+        self.player as usize
+    }
+    fn set_content(&mut self, kind: CellKind, player: usize) {
+        self.kind = kind;
+        self.player = player as u8;
+    }
+    fn previous_player(&self) -> usize {
+        self.previous_player as usize
+    }
+    fn set_previous_player(&mut self, player: usize) {
+        self.previous_player = player as u8;
+    }
+    fn is_active(&self, player: usize) -> bool {
+        self.activity(player) != 0
+    }
+    fn activate(&mut self, player: usize) {
+        debug_assert!(self.activity(player) < 0xF);
+        self.activity += 1 << (player * 4)
+    }
+    fn deactivate(&mut self, player: usize) {
+        debug_assert!(self.activity(player) > 0);
+        self.activity -= 1 << (player * 4)
+    }
+    fn is_protected(&self) -> bool {
+        self.protected
+    }
+    fn is_stronghold(&self) -> bool {
+        self.stronghold
+    }
+    fn damage(&self) -> u8 {
+        self.damage
+    }
+    fn set_damage(&mut self, damage: u8) {
+        self.damage = damage;
+    }
+}
+
+/// Storage of cells of [`Board`]. It is implemented for
+/// everything that can be viewed as a slice of cells.
+pub trait CellStorage {
+    /// Returns number of cells in the storage.
+    fn len(&self) -> usize;
+    /// Checks if the storage has no cells.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Returns the cell at given index.
+    fn cell(&self, index: usize) -> &Cell;
+    /// Returns mutable cell at given index.
+    fn cell_mut(&mut self, index: usize) -> &mut Cell;
+}
+impl<T: AsRef<[Cell]> + AsMut<[Cell]>> CellStorage for T {
+    fn len(&self) -> usize {
+        self.as_ref().len()
+    }
+    fn cell(&self, index: usize) -> &Cell {
+        &self.as_ref()[index]
+    }
+    fn cell_mut(&mut self, index: usize) -> &mut Cell {
+        &mut self.as_mut()[index]
+    }
+}
+
+/// Storage, that can be created with given number of elements.
+/// It is needed to deserialize [`Board`], because serialized board has no traversal buffer.
+pub trait FromLen: Sized {
+    /// Creates new storage with at least `len` elements or returns `None` if it's impossible.
+    fn from_len(len: usize) -> Option<Self>;
+}
+impl<T: Copy + Default, const N: usize> FromLen for [T; N] {
+    fn from_len(len: usize) -> Option<Self> {
+        if len <= N {
+            Some([T::default(); N])
+        } else {
+            None
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: Clone + Default> FromLen for Vec<T> {
+    fn from_len(len: usize) -> Option<Self> {
+        Some(alloc::vec![T::default(); len])
+    }
+}
+
+/// Index of a cell of [`Board`] in stored history of moves, e.g. in [`GameMove`].
+/// `usize` is the index of the cell itself, `u8`, `u16` and `u32` are packed indices
+/// (see [`Board::pack_index`]), so history of boards up to 16×16 fits `u8`.
+/// Records, replays and other tools accept history with any of them.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// # use crosses_utils::simulate::*;
+/// let board = Board::new(16, 16, 2, [Cell::new(); 324], [0; 324]);
+/// let initial = Game::new(board, PlayerManager::new(3, 2, [None; 2]));
+/// let mut game = initial.clone();
+/// let mut history = [None::<GameMove<u8>>; 64];
+/// for ((index, _), slot) in RandomGame::new(initial.clone(), 3).zip(&mut history) {
+///     let played = game.play(index).unwrap();
+///     *slot = Some(played.map_index(|i| u8::from_index(game.board(), i).unwrap()));
+/// }
+/// assert_eq!(u8::from_index(game.board(), game.board().index(15, 15)), Some(255));
+/// let mut replayed = initial.clone();
+/// for played in history.iter().flatten() {
+///     replayed.play(played.record.index.to_index(replayed.board())).unwrap();
+/// }
+/// assert_eq!(replayed, game);
+/// for played in history.iter().flatten().rev() {
+///     let board = replayed.board();
+///     replayed.undo(played.map_index(|i| i.to_index(board))).unwrap();
+/// }
+/// assert_eq!(replayed, initial);
+/// ```
+///
+/// [`GameMove`]: crate::game::GameMove
+pub trait HistoryIndex: Copy {
+    /// Converts index of the cell on `board` or returns `None`, if it doesn't fit.
+    fn from_index<C, B>(board: &Board<C, B>, index: usize) -> Option<Self>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>;
+    /// Converts back to index of the cell on `board`.
+    fn to_index<C, B>(self, board: &Board<C, B>) -> usize
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>;
+}
+impl HistoryIndex for usize {
+    fn from_index<C, B>(_: &Board<C, B>, index: usize) -> Option<Self>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        Some(index)
+    }
+    fn to_index<C, B>(self, _: &Board<C, B>) -> usize
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        self
+    }
+}
+impl HistoryIndex for u8 {
+    fn from_index<C, B>(board: &Board<C, B>, index: usize) -> Option<Self>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        Self::try_from(board.pack_index(index)).ok()
+    }
+    fn to_index<C, B>(self, board: &Board<C, B>) -> usize
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        board.unpack_index(self as usize)
+    }
+}
+impl HistoryIndex for u16 {
+    fn from_index<C, B>(board: &Board<C, B>, index: usize) -> Option<Self>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        Self::try_from(board.pack_index(index)).ok()
+    }
+    fn to_index<C, B>(self, board: &Board<C, B>) -> usize
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        board.unpack_index(self as usize)
+    }
+}
+impl HistoryIndex for u32 {
+    fn from_index<C, B>(board: &Board<C, B>, index: usize) -> Option<Self>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        Self::try_from(board.pack_index(index)).ok()
+    }
+    fn to_index<C, B>(self, board: &Board<C, B>) -> usize
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        board.unpack_index(self as usize)
+    }
+}
+
+/// Maximum number of adjacent cells of a cell of [`Board`].
+pub const MAX_ADJACENT: usize = 12;
+
+/// Adjacency of cells of [`Board`]: offsets `(dx, dy)` from a cell to its adjacent cells.
+/// Offsets are symmetric: if `(dx, dy)` is an offset, `(-dx, -dy)` is an offset too.
+/// Cells of the border are adjacent too, offsets, that lead outside of it, are skipped.
+/// # Example
+/// ```
+/// # use crosses_utils::base::*;
+/// # use crosses_utils::board::*;
+/// let board = Board::with_adjacency(3, 3, 2, Adjacency::VON_NEUMANN, [Cell::new(); 25], [0; 25]);
+/// let (center, corner) = (board.index(1, 1), board.index(0, 0));
+/// assert!(!board.adjacent(center).any(|i| i == corner));
+/// assert_eq!(board.adjacent(center).count(), 4);
+/// let board = Board::with_adjacency(3, 3, 2, Adjacency::KNIGHT, [Cell::new(); 25], [0; 25]);
+/// let inside = board.adjacent(corner).filter(|i| board.kind(*i) != CellKind::Border);
+/// assert!(inside.eq([board.index(2, 1), board.index(1, 2)]));
+/// let diagonal = Adjacency::custom(&[(-1, -1), (1, 1), (-1, 1), (1, -1)]);
+/// assert_eq!(diagonal.offsets().len(), 4);
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "AdjacencyData"))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Adjacency {
+    offsets: [(i8, i8); MAX_ADJACENT],
+    len: u8,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    radius: u8,
+}
+impl Adjacency {
+    /// 8 adjacent cells: orthogonal and diagonal neighbours. It's the default.
+    pub const MOORE: Adjacency = Adjacency::from_array([
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+        (-1, 0),
+        (1, 0),
+        (-1, 1),
+        (0, 1),
+        (1, 1),
+    ]);
+    /// 4 adjacent cells: orthogonal neighbours only.
+    pub const VON_NEUMANN: Adjacency = Adjacency::from_array([(0, -1), (-1, 0), (1, 0), (0, 1)]);
+    /// 8 cells at a knight's move.
+    pub const KNIGHT: Adjacency = Adjacency::from_array([
+        (-1, -2),
+        (1, -2),
+        (-2, -1),
+        (2, -1),
+        (-2, 1),
+        (2, 1),
+        (-1, 2),
+        (1, 2),
+    ]);
+
+    /// Creates adjacency with given offsets.
+    /// # Panics
+    /// Panics if there are more than [`MAX_ADJACENT`] offsets, offsets repeat,
+    /// contain `(0, 0)` or aren't symmetric.
+    pub fn custom(offsets: &[(i8, i8)]) -> Self {
+        assert!(offsets.len() <= MAX_ADJACENT, "too many offsets");
+        let mut array = [(0, 0); MAX_ADJACENT];
+        array[..offsets.len()].copy_from_slice(offsets);
+        match Adjacency::from_parts(array, offsets.len() as u8) {
+            Ok(adjacency) => adjacency,
+            Err(error) => panic!("{}", error),
+        }
+    }
+    /// Returns offsets of adjacent cells.
+    pub fn offsets(&self) -> &[(i8, i8)] {
+        &self.offsets[..self.len as usize]
+    }
+    const fn from_array<const N: usize>(array: [(i8, i8); N]) -> Self {
+        let mut offsets = [(0, 0); MAX_ADJACENT];
+        let mut radius = 0;
+        let mut i = 0;
+        while i < N {
+            offsets[i] = array[i];
+            let (dx, dy) = (array[i].0.unsigned_abs(), array[i].1.unsigned_abs());
+            radius = max(radius, max(dx, dy));
+            i += 1;
+        }
+        Adjacency {
+            offsets,
+            len: N as u8,
+            radius,
+        }
+    }
+    fn from_parts(offsets: [(i8, i8); MAX_ADJACENT], len: u8) -> Result<Self, &'static str> {
+        if len as usize > MAX_ADJACENT {
+            return Err("too many offsets");
+        }
+        let mut adjacency = Self::from_array(offsets);
+        adjacency.len = len;
+        adjacency.radius = adjacency
+            .offsets()
+            .iter()
+            .map(|(dx, dy)| dx.unsigned_abs().max(dy.unsigned_abs()))
+            .max()
+            .unwrap_or(0);
+        adjacency.check().map(|_| adjacency)
+    }
+    fn check(&self) -> Result<(), &'static str> {
+        let offsets = self.offsets();
+        for (i, &(dx, dy)) in offsets.iter().enumerate() {
+            if (dx, dy) == (0, 0) || dx == i8::MIN || dy == i8::MIN {
+                return Err("invalid offset");
+            }
+            if offsets[..i].contains(&(dx, dy)) {
+                return Err("offsets repeat");
+            }
+            if !offsets.contains(&(-dx, -dy)) {
+                return Err("offsets aren't symmetric");
+            }
+        }
+        Ok(())
+    }
+}
+impl Default for Adjacency {
+    fn default() -> Self {
+        Self::MOORE
+    }
+}
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct AdjacencyData {
+    offsets: [(i8, i8); MAX_ADJACENT],
+    len: u8,
+}
+#[cfg(feature = "serde")]
+impl TryFrom<AdjacencyData> for Adjacency {
+    type Error = &'static str;
+    fn try_from(data: AdjacencyData) -> Result<Self, Self::Error> {
+        Adjacency::from_parts(data.offsets, data.len)
+    }
+}
+
+const fn max(a: u8, b: u8) -> u8 {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+/// Iterator over indices of adjacent cells of a cell of [`Board`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct AdjacentCells {
+    cells: [usize; MAX_ADJACENT],
+    len: u8,
+    next: u8,
+}
+impl AdjacentCells {
+    /// Returns indices of remaining cells.
+    pub fn as_slice(&self) -> &[usize] {
+        &self.cells[self.next as usize..self.len as usize]
+    }
+}
+impl Iterator for AdjacentCells {
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        if self.next == self.len {
+            return None;
+        }
+        self.next += 1;
+        Some(self.cells[self.next as usize - 1])
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.len - self.next) as usize;
+        (len, Some(len))
+    }
+}
+impl ExactSizeIterator for AdjacentCells {}
+
+/// Rectangular board.
+/// `C` - is type of cells storage, `B` - is type of traversal buffer.
+/// They can be Vec or simple array.
+///
+/// With `serde` feature board is serialized with its size, number of players, starting
+/// cells and cells. Deserialization checks the content of cells and recomputes activations,
+/// IBTS flags and counters with [`Board::init`], so they can't be out of sync.
+/// Traversal buffer is created with [`FromLen`].
+#[derive(Clone, Debug)]
+pub struct Board<C, B> {
+    width: usize,
+    height: usize,
+    adjacency: Adjacency,
+    placement: Adjacency,
+    rules: RuleSet,
+    counters: Counters<MAX_PLAYERS>,
+    starts: [Option<usize>; MAX_PLAYERS],
+    cells: C,
+    buffer: B,
+}
+impl<C, B> Board<C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    /// Creates new empty [`Board`]. Both `cells` and `buffer` should have at least
+    /// [`Board::storage_len`] elements, their content is overwritten.
+    /// Starting cells of the first four players are the corners of the board:
+    /// top-left, bottom-right, top-right and bottom-left. They are activated for
+    /// their players, so first cross can be placed there. Starting cells
+    /// of other players, if any, should be set with [`Board::set_start`].
+    /// # Panics
+    /// Panics if there are more than [`MAX_PLAYERS`] players or storages are too small.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::counters::*;
+    /// # use crosses_utils::engine::Engine;
+    /// let board = Board::new(10, 10, 2, [Cell::new(); 144], [0; 144]);
+    /// assert_eq!(Board::<[Cell; 0], [usize; 0]>::storage_len(10, 10), 144);
+    /// assert_eq!(board.counters().get(Counter::Moves, 0), 1);
+    /// ```
+    pub fn new(width: usize, height: usize, players: usize, cells: C, buffer: B) -> Self {
+        Self::with_adjacency(width, height, players, Adjacency::MOORE, cells, buffer)
+    }
+    /// Creates new empty [`Board`] with given adjacency of cells, see [`Board::new`].
+    /// # Panics
+    /// Panics if there are more than [`MAX_PLAYERS`] players or storages are too small.
+    pub fn with_adjacency(
+        width: usize,
+        height: usize,
+        players: usize,
+        adjacency: Adjacency,
+        mut cells: C,
+        buffer: B,
+    ) -> Self {
+        assert!(players <= MAX_PLAYERS, "too many players");
+        let len = Self::storage_len(width, height);
+        let full_width = width + 2;
+        assert!(cells.len() >= len, "cells storage is too small");
+        for i in 0..len {
+            let (x, y) = (i % full_width, i / full_width);
+            let cell = cells.cell_mut(i);
+            *cell = Cell::new();
+            if x == 0 || y == 0 || x == width + 1 || y == height + 1 {
+                cell.kind = CellKind::Border
+            }
+        }
+        let mut board = Self {
+            width,
+            height,
+            adjacency,
+            placement: adjacency,
+            rules: RuleSet::STANDARD,
+            counters: Counters::new(players),
+            starts: [None; MAX_PLAYERS],
+            cells,
+            buffer,
+        };
+        assert!(board.buffer.as_mut().len() >= len, "buffer is too small");
+        for player in 0..players {
+            board.set_start(player, board.default_start(player));
+        }
+        board
+    }
+    /// Returns the starting cell of the player on a new board: players start
+    /// at the corners, the first two at opposite ones.
+    pub(crate) fn default_start(&self, player: usize) -> Option<usize> {
+        let (width, height) = (self.width, self.height);
+        let corners = [
+            (0, 0),
+            (width - 1, height - 1),
+            (width - 1, 0),
+            (0, height - 1),
+        ];
+        let (x, y) = *corners.get(player)?;
+        Some(self.index(x, y))
+    }
+    /// Returns number of cells (including border) of the board with given size.
+    pub const fn storage_len(width: usize, height: usize) -> usize {
+        (width + 2) * (height + 2)
+    }
+    /// Returns width of the board.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    /// Returns height of the board.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    /// Returns adjacency of cells of the board.
+    pub fn adjacency(&self) -> Adjacency {
+        self.adjacency
+    }
+    /// Changes adjacency of cells for both chains and moves and recomputes the rest
+    /// of the board with [`Board::init`]. Positions in text formats don't store
+    /// adjacency, so it can be changed after parsing.
+    pub fn set_adjacency(&mut self, adjacency: Adjacency) {
+        self.adjacency = adjacency;
+        self.placement = adjacency;
+        self.init();
+    }
+    /// Returns adjacency of cells for moves, see [`Board::set_placement_adjacency`].
+    pub fn placement_adjacency(&self) -> Adjacency {
+        self.placement
+    }
+    /// Changes adjacency of cells for moves only and recomputes the rest of the board with
+    /// [`Board::init`]. Crosses and alive filled cells activate cells with this adjacency,
+    /// see [`Engine::reach`], while chains are still connected with [`Board::adjacency`].
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::engine::*;
+    /// let mut board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// board.set_placement_adjacency(Adjacency::VON_NEUMANN);
+    /// let (corner, center, side) = (board.index(0, 0), board.index(1, 1), board.index(1, 0));
+    /// make_move(&mut board, corner, 0).unwrap();
+    /// assert_eq!(make_move(&mut board, center, 0), Err(MoveError::Unreachable));
+    /// make_move(&mut board, side, 0).unwrap();
+    /// assert_eq!(board.reach(center).count(), 4);
+    /// assert_eq!(board.adjacency(), Adjacency::MOORE);
+    /// ```
+    pub fn set_placement_adjacency(&mut self, adjacency: Adjacency) {
+        self.placement = adjacency;
+        self.init();
+    }
+    /// Changes rules of moves and recomputes the rest of the board with [`Board::init`].
+    /// By default rules are [`RuleSet::STANDARD`], positions in text formats don't
+    /// store rules either.
+    pub fn set_rules(&mut self, rules: RuleSet) {
+        self.rules = rules;
+        self.init();
+    }
+    /// Checks if the cell at given index is protected, see [`Board::set_protected`].
+    pub fn is_protected(&self, index: usize) -> bool {
+        self.cells.cell(index).protected
+    }
+    /// Protects the cell at given index or removes protection and recomputes
+    /// the rest of the board with [`Board::init`]. Crosses can be placed on protected
+    /// cells, but can't be filled, e.g. in variants with inviolable bases.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::counters::*;
+    /// # use crosses_utils::engine::*;
+    /// let mut board = Board::new(2, 2, 2, [Cell::new(); 16], [0; 16]);
+    /// let (start, base) = (board.index(0, 0), board.index(1, 1));
+    /// make_move(&mut board, start, 0).unwrap();
+    /// make_move(&mut board, base, 1).unwrap();
+    /// assert_eq!(board.counters().get(Counter::Moves, 0), 3);
+    /// board.set_protected(base, true);
+    /// assert_eq!(board.counters().get(Counter::Moves, 0), 2);
+    /// assert_eq!(make_move(&mut board, base, 0), Err(MoveError::ProtectedCell));
+    /// let side = board.index(1, 0);
+    /// board.set_protected(side, true);
+    /// make_move(&mut board, side, 1).unwrap();
+    /// assert_eq!(board.counters().get(Counter::Moves, 0), 1);
+    /// ```
+    pub fn set_protected(&mut self, index: usize, protected: bool) {
+        self.cells.cell_mut(index).protected = protected;
+        self.init();
+    }
+    /// Places a wall of the neutral player at given index and recomputes the rest of
+    /// the board with [`Board::init`]. The wall is a protected cross of the player, so
+    /// it's shown like other crosses, but nobody can move there. The player should be
+    /// neutral in the game, see [`PlayerManager::with_neutral`].
+    ///
+    /// [`PlayerManager::with_neutral`]: crate::player_manager::PlayerManager::with_neutral
+    /// # Example
+    /// ```
+    /// # use crosses_utils::base::*;
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::engine::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// let mut board = Board::new(3, 1, 3, [Cell::new(); 15], [0; 15]);
+    /// let (first, wall) = (board.index(0, 0), board.index(1, 0));
+    /// board.set_neutral(wall, 2);
+    /// assert_eq!((board.kind(wall), board.player(wall)), (CellKind::Cross, 2));
+    /// let manager = PlayerManager::new(1, 3, [None; 3]).with_neutral(2);
+    /// let mut game = Game::new(board, manager);
+    /// game.play(first).unwrap();
+    /// assert_eq!(game.play(wall), Err(MoveError::ProtectedCell));
+    /// ```
+    pub fn set_neutral(&mut self, index: usize, player: usize) {
+        self.set_up(index, CellKind::Cross, player);
+        self.cells.cell_mut(index).protected = true;
+        self.init();
+    }
+    /// Checks if the cell at given index is a stronghold, see [`Board::set_stronghold`].
+    pub fn is_stronghold(&self, index: usize) -> bool {
+        self.cells.cell(index).stronghold
+    }
+    /// Makes the cell at given index a stronghold or an ordinary cell. A cross on
+    /// a stronghold is filled only by the second attack, the first one damages it,
+    /// see [`RuleSet::strong_crosses`]. Counters don't depend on it, so the rest
+    /// of the board isn't recomputed.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::base::*;
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::engine::*;
+    /// let mut board = Board::new(2, 2, 2, [Cell::new(); 16], [0; 16]);
+    /// let (start, fort) = (board.index(0, 0), board.index(1, 1));
+    /// make_move(&mut board, start, 0).unwrap();
+    /// make_move(&mut board, fort, 1).unwrap();
+    /// board.set_stronghold(fort, true);
+    /// let attack = make_move(&mut board, fort, 0).unwrap();
+    /// assert_eq!((board.kind(fort), board.get(fort).damage()), (CellKind::Cross, 1));
+    /// let fill = make_move(&mut board, fort, 0).unwrap();
+    /// assert_eq!(board.kind(fort), CellKind::Filled);
+    /// cancel_move(&mut board, fill).unwrap();
+    /// assert_eq!(board.get(fort).damage(), 1);
+    /// cancel_move(&mut board, attack).unwrap();
+    /// assert_eq!((board.kind(fort), board.get(fort).damage()), (CellKind::Cross, 0));
+    /// ```
+    pub fn set_stronghold(&mut self, index: usize, stronghold: bool) {
+        self.cells.cell_mut(index).stronghold = stronghold;
+    }
+    /// Converts coordinates of the cell to its index.
+    pub fn index(&self, x: usize, y: usize) -> usize {
+        debug_assert!(x < self.width && y < self.height);
+        (y + 1) * (self.width + 2) + x + 1
+    }
+    /// Converts coordinates of the cell to its index or returns `None`,
+    /// if they are out of the board, e.g. for coordinates sent by players.
+    pub fn checked_index(&self, x: usize, y: usize) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| self.index(x, y))
+    }
+    /// Converts index of the cell to its coordinates.
+    pub fn coords(&self, index: usize) -> (usize, usize) {
+        (index % (self.width + 2) - 1, index / (self.width + 2) - 1)
+    }
+    /// Converts index of the cell to its index without border: `y * width + x`.
+    /// Packed indices of the board are in `0..width * height`, so they can be
+    /// stored in smaller types, e.g. `u8` for boards up to 16×16, see [`HistoryIndex`].
+    pub fn pack_index(&self, index: usize) -> usize {
+        let (x, y) = self.coords(index);
+        y * self.width + x
+    }
+    /// Converts packed index of the cell back to its index.
+    pub fn unpack_index(&self, packed: usize) -> usize {
+        self.index(packed % self.width, packed / self.width)
+    }
+    /// Returns indices of the cells, where the player can make a move, row by row.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::engine::*;
+    /// let mut board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// let corner = board.index(0, 0);
+    /// assert!(board.legal_moves(0).eq([corner]));
+    /// make_move(&mut board, corner, 0).unwrap();
+    /// assert_eq!(board.legal_moves(0).count(), 3);
+    /// ```
+    pub fn legal_moves(&self, player: usize) -> impl Iterator<Item = usize> + Clone + '_ {
+        (0..self.width * self.height)
+            .map(move |packed| self.unpack_index(packed))
+            .filter(move |index| self.rules.check_move(self.get(*index), player).is_ok())
+    }
+    /// Returns the storage of cells.
+    pub fn storage(&self) -> &C {
+        &self.cells
+    }
+    /// Returns index of the starting cell of the player.
+    pub fn start(&self, player: usize) -> Option<usize> {
+        self.starts[player]
+    }
+    /// Changes the starting cell of the player. Starting cell has one
+    /// additional activation for its player, so the first cross can be placed there.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::engine::*;
+    /// let mut board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// let (corner, center) = (board.index(0, 0), board.index(1, 1));
+    /// board.set_start(0, Some(center));
+    /// assert_eq!(board.start(0), Some(center));
+    /// assert_eq!(make_move(&mut board, corner, 0), Err(MoveError::Unreachable));
+    /// assert!(make_move(&mut board, center, 0).is_ok());
+    /// ```
+    pub fn set_start(&mut self, player: usize, index: Option<usize>) {
+        debug_assert!(player < self.counters.players());
+        if let Some(old) = self.starts[player] {
+            deactivate(self, old, player);
+        }
+        if let Some(new) = index {
+            activate(self, new, player);
+        }
+        self.starts[player] = index;
+    }
+    /// Changes the content of the cell without updating the rest of the board, so
+    /// [`Board::init`] should be called after all cells are set up. Previous owner of filled
+    /// cell is set to the next player, because positions usually don't store it.
+    pub(crate) fn set_up(&mut self, index: usize, kind: CellKind, player: usize) {
+        let players = self.counters.players();
+        let cell = self.cells.cell_mut(index);
+        cell.set_content(kind, player);
+        if kind == CellKind::Filled {
+            cell.set_previous_player((player + 1) % players);
+        }
+    }
+    /// Recomputes activations, IBTS flags and counters from the content of the cells.
+    /// It should be called after the content was changed directly, e.g. with
+    /// [`Engine::with_cell`] and [`Data::set_content`], to set up a position.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::base::*;
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::counters::*;
+    /// # use crosses_utils::engine::*;
+    /// # use crosses_utils::ibts::*;
+    /// let mut board = Board::new(3, 1, 2, [Cell::new(); 15], [0; 15]);
+    /// let (a, b, c) = (board.index(0, 0), board.index(1, 0), board.index(2, 0));
+    /// board.with_cell(a, |cell| cell.set_content(CellKind::Cross, 0));
+    /// board.with_cell(b, |cell| cell.set_content(CellKind::Filled, 0));
+    /// board.init();
+    /// assert!(board.is_alive(b));
+    /// assert!(board.is_important(a) && board.is_important(b));
+    /// assert_eq!(board.counters().get(Counter::Crosses, 0), 1);
+    /// assert_eq!(board.counters().get(Counter::Moves, 0), 1);
+    /// assert_eq!(board.counters().get(Counter::Moves, 1), 1);
+    /// assert!(make_move(&mut board, c, 0).is_ok());
+    /// ```
+    pub fn init(&mut self) {
+        let len = Self::storage_len(self.width, self.height);
+        self.counters = Counters::new(self.counters.players());
+        for i in 0..len {
+            let cell = self.cells.cell_mut(i);
+            cell.flags.reset();
+            cell.visited = false;
+            cell.activity = 0;
+        }
+        for player in 0..self.counters.players() {
+            if let Some(start) = self.starts[player] {
+                activate(self, start, player);
+            }
+        }
+        for i in 0..len {
+            let cell = *self.cells.cell(i);
+            if cell.kind == CellKind::Cross {
+                self.counters.increment(Counter::Crosses, cell.player());
+                activate_around(self, i, cell.player());
+                // Otherwise fills are counted with activations.
+                if !self.rules.fill_requires_reach {
+                    let (rules, owner) = (self.rules, cell.player());
+                    for player in (0..self.counters.players())
+                        .filter(|p| rules.can_fill(cell, owner, *p))
+                    {
+                        self.counters.increment(Counter::Moves, player);
+                    }
+                }
+            }
+        }
+        for i in 0..len {
+            let cell = *self.cells.cell(i);
+            if cell.kind != CellKind::Cross {
+                continue;
+            }
+            for j in self.adjacent(i) {
+                let other = *self.cells.cell(j);
+                if other.kind == CellKind::Filled
+                    && other.player() == cell.player()
+                    && !other.flags.is_alive()
+                {
+                    self.spread(j, true);
+                    self.cells.cell_mut(i).flags.set_important(true);
+                    self.cells.cell_mut(j).flags.set_important(true);
+                }
+            }
+        }
+    }
+
+    /// Returns [`Walk`], that marks visited cells in the board itself.
+    pub(crate) fn walk(&mut self) -> Flags<'_, C, B> {
+        Flags {
+            board: self,
+            head: 0,
+            len: 0,
+        }
+    }
+    /// Returns [`Walk`], that marks visited cells in the buffer of the caller, so the board
+    /// is borrowed immutably. The buffer should have at least [`Board::storage_len`] elements.
+    pub(crate) fn walk_in<'a>(&'a self, buffer: &'a mut [usize]) -> Links<'a, C, B> {
+        let next = &mut buffer[..Self::storage_len(self.width, self.height)];
+        next.fill(UNSEEN);
+        Links {
+            board: self,
+            next,
+            first: UNSEEN,
+            head: UNSEEN,
+            tail: UNSEEN,
+        }
+    }
+
+    /// Changes aliveness of the chain, that contains cell at given index.
+    fn spread(&mut self, index: usize, alive: bool) {
+        let player = self.cells.cell(index).player();
+        let mut len = 0;
+        self.flip(index, alive, player, &mut len);
+        let mut head = 0;
+        while head < len {
+            let current = self.buffer.as_mut()[head];
+            head += 1;
+            for i in self.adjacent(current) {
+                let cell = *self.cells.cell(i);
+                if cell.kind == CellKind::Filled
+                    && cell.player() == player
+                    && cell.flags.is_alive() != alive
+                {
+                    self.flip(i, alive, player, &mut len);
+                }
+            }
+        }
+    }
+    fn flip(&mut self, index: usize, alive: bool, player: usize, len: &mut usize) {
+        self.cells.cell_mut(index).flags.set_alive(alive);
+        if alive {
+            activate_around(self, index, player);
+        } else {
+            deactivate_around(self, index, player);
+        }
+        self.buffer.as_mut()[*len] = index;
+        *len += 1;
+    }
+}
+
+/// Mark of cells, that [`Links`] hasn't visited.
+const UNSEEN: usize = usize::MAX;
+
+/// Traversals of [`Board`], that don't change it. Implementations keep the queue
+/// of cells waiting for a visit and marks of visited cells.
+pub(crate) trait Walk<C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    /// Returns the board.
+    fn board(&self) -> &Board<C, B>;
+    /// Checks if the cell was visited.
+    fn is_visited(&self, index: usize) -> bool;
+    /// Marks the cell as visited and adds it to the queue.
+    fn push(&mut self, index: usize);
+    /// Removes the next cell from the queue.
+    fn pop(&mut self) -> Option<usize>;
+    /// Removes marks of all visited cells.
+    fn reset(&mut self);
+
+    /// Visits cells, that the player can reach with any number of moves, if other players
+    /// don't touch their cells: the zone of reach and empty cells connected to it through
+    /// empty cells and filled cells of the player, because dead chains are revived by
+    /// adjacent crosses. Calls `f` with the board and index of every such cell. Crosses of
+    /// other players are visited, but the traversal doesn't continue from them.
+    fn explore(&mut self, player: usize, mut f: impl FnMut(&Board<C, B>, usize)) {
+        let board = self.board();
+        for packed in 0..board.width * board.height {
+            let board = self.board();
+            let index = board.unpack_index(packed);
+            let cell = *board.cells.cell(index);
+            if board.rules.check_move(cell, player).is_ok() {
+                f(board, index);
+                self.push(index);
+            }
+        }
+        while let Some(current) = self.pop() {
+            let board = self.board();
+            if board.cells.cell(current).kind == CellKind::Cross {
+                continue;
+            }
+            // Moves are made in reach of cells, own chains are connected through adjacent cells.
+            let reach = board.reach(current).map(|i| (i, true));
+            for (i, placement) in reach.chain(board.adjacent(current).map(|i| (i, false))) {
+                let cell = *self.board().cells.cell(i);
+                let own_filled = cell.kind == CellKind::Filled && cell.player() == player;
+                let movable = is_movable(cell, player);
+                if self.is_visited(i) || !(own_filled && !placement || movable && placement) {
+                    continue;
+                }
+                self.push(i);
+                if movable {
+                    f(self.board(), i);
+                }
+            }
+        }
+        self.reset();
+    }
+    /// Visits cells connected to starting cells, that satisfy `start`, through cells,
+    /// that satisfy `pass`, breadth-first. Calls `f` with the board and index of every
+    /// such cell, including starting ones, and stops if it returns `true`. Returns whether
+    /// the traversal was stopped. Starting cells, that don't satisfy `pass`, are skipped.
+    fn traverse(
+        &mut self,
+        mut start: impl FnMut(&Board<C, B>, usize) -> bool,
+        mut pass: impl FnMut(&Board<C, B>, usize) -> bool,
+        mut f: impl FnMut(&Board<C, B>, usize) -> bool,
+    ) -> bool {
+        let board = self.board();
+        for packed in 0..board.width * board.height {
+            let board = self.board();
+            let index = board.unpack_index(packed);
+            if start(board, index) && pass(board, index) {
+                self.push(index);
+            }
+        }
+        let mut stopped = false;
+        while let Some(current) = self.pop() {
+            if f(self.board(), current) {
+                stopped = true;
+                break;
+            }
+            for i in self.board().adjacent(current) {
+                if !self.is_visited(i) && pass(self.board(), i) {
+                    self.push(i);
+                }
+            }
+        }
+        self.reset();
+        stopped
+    }
+    /// Visits connected components of cells breadth-first, one after another. Component
+    /// starts at its first cell row by row, its seed, and `pass` checks with the board,
+    /// the seed and index of a cell, if the cell belongs to the component of the seed.
+    /// Cells, for which `pass` with the cell as the seed returns `false`, are skipped.
+    /// Calls `f` with the board, the seed and index of every cell of components.
+    fn components(
+        &mut self,
+        mut pass: impl FnMut(&Board<C, B>, usize, usize) -> bool,
+        mut f: impl FnMut(&Board<C, B>, usize, usize),
+    ) {
+        let board = self.board();
+        for packed in 0..board.width * board.height {
+            let seed = self.board().unpack_index(packed);
+            if self.is_visited(seed) || !pass(self.board(), seed, seed) {
+                continue;
+            }
+            self.push(seed);
+            while let Some(current) = self.pop() {
+                f(self.board(), seed, current);
+                for i in self.board().adjacent(current) {
+                    if !self.is_visited(i) && pass(self.board(), seed, i) {
+                        self.push(i);
+                    }
+                }
+            }
+        }
+        self.reset();
+    }
+}
+
+/// [`Walk`], that marks visited cells in the board and keeps the queue in its traversal
+/// buffer, see [`Board::walk`].
+pub(crate) struct Flags<'a, C, B> {
+    board: &'a mut Board<C, B>,
+    head: usize,
+    len: usize,
+}
+impl<C, B> Walk<C, B> for Flags<'_, C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    fn board(&self) -> &Board<C, B> {
+        self.board
+    }
+    fn is_visited(&self, index: usize) -> bool {
+        self.board.cells.cell(index).visited
+    }
+    fn push(&mut self, index: usize) {
+        self.board.cells.cell_mut(index).visited = true;
+        self.board.buffer.as_mut()[self.len] = index;
+        self.len += 1;
+    }
+    fn pop(&mut self) -> Option<usize> {
+        if self.head == self.len {
+            return None;
+        }
+        self.head += 1;
+        Some(self.board.buffer.as_mut()[self.head - 1])
+    }
+    fn reset(&mut self) {
+        for visited in 0..self.len {
+            let i = self.board.buffer.as_mut()[visited];
+            self.board.cells.cell_mut(i).visited = false;
+        }
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+/// [`Walk`] over the buffer of the caller, see [`Board::walk_in`]. Visited cells are linked
+/// in the order of visits: the element of the cell is the next visited cell, the cell itself
+/// for the last one, or [`UNSEEN`].
+pub(crate) struct Links<'a, C, B> {
+    board: &'a Board<C, B>,
+    next: &'a mut [usize],
+    first: usize,
+    head: usize,
+    tail: usize,
+}
+impl<C, B> Walk<C, B> for Links<'_, C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    fn board(&self) -> &Board<C, B> {
+        self.board
+    }
+    fn is_visited(&self, index: usize) -> bool {
+        self.next[index] != UNSEEN
+    }
+    fn push(&mut self, index: usize) {
+        self.next[index] = index;
+        if self.first == UNSEEN {
+            self.first = index;
+        } else {
+            self.next[self.tail] = index;
+        }
+        if self.head == UNSEEN {
+            self.head = index;
+        }
+        self.tail = index;
+    }
+    fn pop(&mut self) -> Option<usize> {
+        let current = self.head;
+        if current == UNSEEN {
+            return None;
+        }
+        let next = self.next[current];
+        self.head = if next == current { UNSEEN } else { next };
+        Some(current)
+    }
+    fn reset(&mut self) {
+        let mut current = self.first;
+        while current != UNSEEN {
+            let next = self.next[current];
+            self.next[current] = UNSEEN;
+            current = if next == current { UNSEEN } else { next };
+        }
+        self.first = UNSEEN;
+        self.head = UNSEEN;
+        self.tail = UNSEEN;
+    }
+}
+impl<C, B> PartialEq for Board<C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.adjacency == other.adjacency
+            && self.placement == other.placement
+            && self.rules == other.rules
+            && self.counters == other.counters
+            && self.starts == other.starts
+            && (0..Self::storage_len(self.width, self.height))
+                .all(|i| self.cells.cell(i) == other.cells.cell(i))
+    }
+}
+impl<C, B> Eq for Board<C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+}
+impl<C, B> GameBoard for Board<C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    type Index = usize;
+    type Adjacent = AdjacentCells;
+    type Player = usize;
+
+    fn adjacent(&self, index: usize) -> AdjacentCells {
+        self.cells_around(index, &self.adjacency)
+    }
+    fn kind(&self, index: usize) -> CellKind {
+        self.cells.cell(index).kind
+    }
+    fn player(&self, index: usize) -> usize {
+        self.cells.cell(index).player()
+    }
+}
+impl<C, B> Board<C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    fn cells_around(&self, index: usize, adjacency: &Adjacency) -> AdjacentCells {
+        let w = self.width + 2;
+        let mut adjacent = AdjacentCells {
+            cells: [0; MAX_ADJACENT],
+            len: 0,
+            next: 0,
+        };
+        // Adjacent cells of cells inside of the board are inside of the border.
+        let inside = adjacency.radius <= 1;
+        let (x, y) = (index % w, index / w);
+        for &(dx, dy) in adjacency.offsets() {
+            let (dx, dy) = (dx as isize, dy as isize);
+            if !inside {
+                let (x, y) = (x as isize + dx, y as isize + dy);
+                if x < 0 || y < 0 || x >= w as isize || y >= self.height as isize + 2 {
+                    continue;
+                }
+            }
+            adjacent.cells[adjacent.len as usize] =
+                (index as isize + dy * w as isize + dx) as usize;
+            adjacent.len += 1;
+        }
+        adjacent
+    }
+}
+impl<C, B> IbtsBoard for Board<C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    fn is_important(&self, index: usize) -> bool {
+        self.cells.cell(index).flags.is_important()
+    }
+    fn set_important(&mut self, index: usize, new: bool) {
+        self.cells.cell_mut(index).flags.set_important(new)
+    }
+    fn is_alive(&self, index: usize) -> bool {
+        self.cells.cell(index).flags.is_alive()
+    }
+    fn set_alive(&mut self, index: usize, new: bool) {
+        self.cells.cell_mut(index).flags.set_alive(new)
+    }
+
+    fn revive(&mut self, index: usize) {
+        self.spread(index, true)
+    }
+    fn kill(&mut self, index: usize) {
+        self.spread(index, false)
+    }
+    fn search(&mut self, index: usize) -> Option<SearchResult<usize>> {
+        let player = self.cells.cell(index).player();
+        self.cells.cell_mut(index).visited = true;
+        self.buffer.as_mut()[0] = index;
+        let (mut head, mut len) = (0, 1);
+        let mut result = None;
+        'search: while head < len {
+            let current = self.buffer.as_mut()[head];
+            head += 1;
+            for i in self.adjacent(current) {
+                let cell = self.cells.cell(i);
+                if cell.player() != player || cell.visited {
+                    continue;
+                }
+                match cell.kind {
+                    CellKind::Cross => {
+                        result = Some(SearchResult {
+                            filled: current,
+                            cross: i,
+                        });
+                        break 'search;
+                    }
+                    CellKind::Filled if cell.flags.is_alive() => {
+                        self.cells.cell_mut(i).visited = true;
+                        self.buffer.as_mut()[len] = i;
+                        len += 1;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for visited in 0..len {
+            let i = self.buffer.as_mut()[visited];
+            self.cells.cell_mut(i).visited = false;
+        }
+        result
+    }
+    fn searches_activators(&self) -> bool {
+        true
+    }
+}
+impl<C, B> Engine for Board<C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    type Data = Cell;
+    type Counters = Counters<MAX_PLAYERS>;
+
+    fn get(&self, index: usize) -> Cell {
+        *self.cells.cell(index)
+    }
+    fn set(&mut self, index: usize, data: Cell) {
+        *self.cells.cell_mut(index) = data
+    }
+    fn with_cell<R>(&mut self, index: usize, f: impl FnOnce(&mut Cell) -> R) -> R {
+        f(self.cells.cell_mut(index))
+    }
+    fn reach(&self, index: usize) -> AdjacentCells {
+        self.cells_around(index, &self.placement)
+    }
+    fn for_each_dead(&self, mut f: impl FnMut(usize)) {
+        for packed in 0..self.width * self.height {
+            let index = self.unpack_index(packed);
+            let cell = self.cells.cell(index);
+            if cell.kind == CellKind::Filled && !cell.flags.is_alive() {
+                f(index)
+            }
+        }
+    }
+    fn counters(&self) -> &Counters<MAX_PLAYERS> {
+        &self.counters
+    }
+    fn counters_mut(&mut self) -> &mut Counters<MAX_PLAYERS> {
+        &mut self.counters
+    }
+    fn rules(&self) -> RuleSet {
+        self.rules
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct BoardRef<'a, C> {
+    width: usize,
+    height: usize,
+    adjacency: Adjacency,
+    placement: Adjacency,
+    rules: RuleSet,
+    players: usize,
+    starts: &'a [Option<usize>; MAX_PLAYERS],
+    cells: &'a C,
+}
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct BoardData<C> {
+    width: usize,
+    height: usize,
+    #[serde(default)]
+    adjacency: Adjacency,
+    #[serde(default)]
+    placement: Option<Adjacency>,
+    #[serde(default)]
+    rules: RuleSet,
+    players: usize,
+    starts: [Option<usize>; MAX_PLAYERS],
+    cells: C,
+}
+#[cfg(feature = "serde")]
+impl<C, B> Serialize for Board<C, B>
+where
+    C: CellStorage + Serialize,
+    B: AsMut<[usize]>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BoardRef {
+            width: self.width,
+            height: self.height,
+            adjacency: self.adjacency,
+            placement: self.placement,
+            rules: self.rules,
+            players: self.counters.players(),
+            starts: &self.starts,
+            cells: &self.cells,
+        }
+        .serialize(serializer)
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de, C, B> Deserialize<'de> for Board<C, B>
+where
+    C: CellStorage + Deserialize<'de>,
+    B: AsMut<[usize]> + FromLen,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = BoardData::<C>::deserialize(deserializer)?;
+        let (width, height, players) = (data.width, data.height, data.players);
+        if width == 0 || height == 0 || players == 0 || players > MAX_PLAYERS {
+            return Err(D::Error::custom("invalid size of the board"));
+        }
+        let len = width
+            .checked_add(2)
+            .and_then(|w| w.checked_mul(height.checked_add(2)?))
+            .ok_or_else(|| D::Error::custom("invalid size of the board"))?;
+        if data.cells.len() < len {
+            return Err(D::Error::custom("cells storage is too small"));
+        }
+        let buffer = B::from_len(len).ok_or_else(|| D::Error::custom("buffer is too small"))?;
+        let mut board = Self {
+            width,
+            height,
+            adjacency: data.adjacency,
+            placement: data.placement.unwrap_or(data.adjacency),
+            rules: data.rules,
+            counters: Counters::new(players),
+            starts: data.starts,
+            cells: data.cells,
+            buffer,
+        };
+        for i in 0..len {
+            let (x, y) = (i % (width + 2), i / (width + 2));
+            let is_border = x == 0 || y == 0 || x == width + 1 || y == height + 1;
+            let cell = board.cells.cell(i);
+            let is_valid = match cell.kind {
+                CellKind::Border => true,
+                _ if is_border => false,
+                CellKind::Empty => true,
+                CellKind::Cross => cell.player() < players,
+                CellKind::Filled => cell.player() < players && cell.previous_player() < players,
+            };
+            if !is_valid {
+                return Err(D::Error::custom("invalid cell"));
+            }
+        }
+        let is_inside = |start: &Option<usize>| {
+            start.map_or(true, |i| {
+                let (x, y) = (i % (width + 2), i / (width + 2));
+                (1..=width).contains(&x) && (1..=height).contains(&y)
+            })
+        };
+        if !board.starts[..players].iter().all(is_inside)
+            || board.starts[players..].iter().any(Option::is_some)
+        {
+            return Err(D::Error::custom("invalid starting cell"));
+        }
+        board.init();
+        Ok(board)
+    }
+}
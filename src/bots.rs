@@ -0,0 +1,558 @@
+//! Bots
+//!
+//! This module defines simple bots, that choose moves for the current player of [`Game`].
+//! Bots don't need allocation or operating system, randomness comes from [`Rng`],
+//! which is implemented by the user, e.g. over `rand` crate, or by [`SplitMix64`].
+//! [`Game::hint`] suggests moves of [`GreedyBot`] with explanations for beginners.
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::ops::IndexMut;
+
+use crate::base::CellKind;
+use crate::board::{Board, CellStorage};
+use crate::engine::{ChangeLog, Data, Engine};
+use crate::game::Game;
+use crate::ibts::IbtsBoard;
+use crate::player_manager::{GameOver, GameState, LoseData};
+use crate::search::{DefaultEvaluator, Searcher};
+
+/// Source of random numbers, like `RngCore` of `rand` crate.
+pub trait Rng {
+    /// Returns next random `u32`.
+    fn next_u32(&mut self) -> u32;
+    /// Returns next random `u64`.
+    fn next_u64(&mut self) -> u64 {
+        (self.next_u32() as u64) << 32 | self.next_u32() as u64
+    }
+    /// Returns random number in `0..bound`, `bound` shouldn't be zero.
+    fn below(&mut self, bound: usize) -> usize {
+        debug_assert_ne!(bound, 0);
+        let bound = bound as u64;
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return (value % bound) as usize;
+            }
+        }
+    }
+}
+impl<R: Rng + ?Sized> Rng for &mut R {
+    fn next_u32(&mut self) -> u32 {
+        (**self).next_u32()
+    }
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
+
+/// Player of games, that chooses moves for the current player of `G`.
+/// It's object safe, so different bots can play against each other, see
+/// [`simulate`](crate::simulate).
+pub trait Bot<G> {
+    /// Returns index of the cell for the move of the current player
+    /// or `None` if the game has ended. The game should be left unchanged.
+    fn choose_move(&mut self, game: &mut G) -> Option<usize>;
+}
+
+/// Small and fast generator SplitMix64. It isn't cryptographically secure,
+/// but it's enough for bots and test games.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SplitMix64(pub u64);
+impl Rng for SplitMix64 {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Bot, that chooses uniformly among legal moves.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::bots::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+/// let mut game = Game::new(board, PlayerManager::new(3, 2, [None; 2]));
+/// let mut bot = RandomBot::new(SplitMix64(42));
+/// while let Some(index) = bot.choose_move(&game) {
+///     game.play(index).unwrap();
+/// }
+/// assert_ne!(game.state(), GameState::Ongoing);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct RandomBot<R> {
+    rng: R,
+}
+impl<R: Rng> RandomBot<R> {
+    /// Creates new bot with given source of random numbers.
+    pub fn new(rng: R) -> Self {
+        Self { rng }
+    }
+    /// Returns the source of random numbers.
+    pub fn rng(&mut self) -> &mut R {
+        &mut self.rng
+    }
+    /// Unwraps the source of random numbers.
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+    /// Returns index of the cell for the move of the current player
+    /// or `None` if the game has ended.
+    pub fn choose_move<C, B, S, L>(&mut self, game: &Game<Board<C, B>, S, L>) -> Option<usize>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        if game.state() != GameState::Ongoing {
+            return None;
+        }
+        let mut moves = game.board().legal_moves(game.current_player());
+        let count = moves.clone().count();
+        if count == 0 {
+            return None;
+        }
+        moves.nth(self.rng.below(count))
+    }
+}
+impl<R, C, B, S, L> Bot<Game<Board<C, B>, S, L>> for RandomBot<R>
+where
+    R: Rng,
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    fn choose_move(&mut self, game: &mut Game<Board<C, B>, S, L>) -> Option<usize> {
+        RandomBot::choose_move(self, game)
+    }
+}
+
+/// Weights of [`GreedyBot`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct GreedyWeights {
+    /// Weight of filling a cross of other player
+    pub capture: i64,
+    /// Weight of one filled cell of the player revived by the move
+    pub revival: i64,
+    /// Weight of one move gained by the player
+    pub mobility: i64,
+    /// Weight of one move lost by other players
+    pub enemy_mobility: i64,
+}
+impl Default for GreedyWeights {
+    fn default() -> Self {
+        Self {
+            capture: 10,
+            revival: 3,
+            mobility: 1,
+            enemy_mobility: 2,
+        }
+    }
+}
+
+/// Bot, that looks one move ahead: it tries every legal move, scores it by the record
+/// of the move and counters after it (see [`GreedyWeights`]) and plays the best one.
+/// Moves that win the game are always preferred, moves that lose it are avoided.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::bots::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+/// let mut game = Game::new(board, PlayerManager::new(3, 2, [None; 2]));
+/// let (mut random, greedy) = (RandomBot::new(SplitMix64(7)), GreedyBot::default());
+/// while game.state() == GameState::Ongoing {
+///     let index = match game.current_player() {
+///         0 => greedy.choose_move(&mut game),
+///         _ => random.choose_move(&game),
+///     };
+///     game.play(index.unwrap()).unwrap();
+/// }
+/// assert_eq!(game.state(), GameState::Ended(GameOver::Win(0)));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct GreedyBot {
+    weights: GreedyWeights,
+}
+impl GreedyBot {
+    /// Creates new bot with given weights.
+    pub fn new(weights: GreedyWeights) -> Self {
+        Self { weights }
+    }
+    /// Returns weights of the bot.
+    pub fn weights(&self) -> GreedyWeights {
+        self.weights
+    }
+    /// Returns score of the move of the current player at given index or `None`
+    /// if the move is illegal. The game is left unchanged.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::bots::*;
+    /// # use crosses_utils::game::*;
+    /// let fen = "x3./4./2.X./o3. 2 x 1/1 - 4";
+    /// let mut game = Game::from_fen(fen, [Cell::new(); 36], [0; 36], [None; 2]).unwrap();
+    /// let plain = GreedyBot::new(GreedyWeights { revival: 0, ..GreedyWeights::default() });
+    /// let (revives, other) = (game.board().index(1, 1), game.board().index(1, 0));
+    /// let score = |bot: GreedyBot, game: &mut Game<_, _>, index| bot.score_move(game, index);
+    /// let revival = GreedyWeights::default().revival;
+    /// let expected = score(plain, &mut game, revives).map(|score| score + revival);
+    /// assert_eq!(score(GreedyBot::default(), &mut game, revives), expected);
+    /// let expected = score(plain, &mut game, other);
+    /// assert_eq!(score(GreedyBot::default(), &mut game, other), expected);
+    /// ```
+    pub fn score_move<C, B, S, L>(
+        &self,
+        game: &mut Game<Board<C, B>, S, L>,
+        index: usize,
+    ) -> Option<i64>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        let player = game.current_player();
+        let enemy_mobility = |game: &Game<Board<C, B>, S, L>| {
+            (0..game.player_manager().max_players)
+                .filter(|other| *other != player)
+                .map(|other| game.mobility(other) as i64)
+                .sum::<i64>()
+        };
+        let before = enemy_mobility(game);
+        let alive = alive_filled(game.board(), player);
+        let played = game.play(index).ok()?;
+        let score = match game.state() {
+            GameState::Ended(GameOver::Win(winner)) if winner == player => i64::MAX,
+            GameState::Ended(GameOver::Win(_)) => i64::MIN,
+            _ => {
+                let capture = (played.record.prior_kind == CellKind::Cross) as i64;
+                // The captured cross is alive after the move, it isn't revived.
+                let revived = alive_filled(game.board(), player) as i64 - alive as i64 - capture;
+                self.weights.capture * capture
+                    + self.weights.revival * revived
+                    + self.weights.mobility * played.record.counters_delta.moves as i64
+                    + self.weights.enemy_mobility * (before - enemy_mobility(game))
+            }
+        };
+        game.undo(played).expect("the move was just made");
+        Some(score)
+    }
+    /// Returns index of the cell with the best move of the current player
+    /// or `None` if the game has ended.
+    pub fn choose_move<C, B, S, L>(&self, game: &mut Game<Board<C, B>, S, L>) -> Option<usize>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        let mut best = None;
+        let cells = game.board().width() * game.board().height();
+        for packed in 0..cells {
+            let index = game.board().unpack_index(packed);
+            if let Some(score) = self.score_move(game, index) {
+                if best.map_or(true, |(_, best)| score > best) {
+                    best = Some((index, score));
+                }
+            }
+        }
+        best.map(|(index, _)| index)
+    }
+}
+impl<C, B, S, L> Bot<Game<Board<C, B>, S, L>> for GreedyBot
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    fn choose_move(&mut self, game: &mut Game<Board<C, B>, S, L>) -> Option<usize> {
+        GreedyBot::choose_move(self, game)
+    }
+}
+
+/// Named difficulty of [`ConfiguredBot`], see [`BotConfig::preset`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Difficulty {
+    /// Shallow search, random choice among good moves and frequent mistakes
+    Beginner,
+    /// Medium search with playouts and rare mistakes
+    Club,
+    /// Deep search without randomness
+    Strong,
+}
+
+/// Settings of [`ConfiguredBot`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BotConfig {
+    /// Depth of the search of every move in plies, `0` disables the search
+    pub depth: usize,
+    /// Number of random games played after every move. [`PLAYOUT_SCORE`] times the share
+    /// of won games minus the share of lost ones is added to the score of the move.
+    pub playouts: usize,
+    /// Maximum random noise added to scores of moves, so moves, that are worse than
+    /// the best one at most by temperature, can be chosen. `0` means always the best move.
+    /// Scores are from [`DefaultEvaluator`], e.g. mobility of one cell is worth `3`.
+    pub temperature: i32,
+    /// Probability in percents of a random move instead of a chosen one
+    pub mistakes: u32,
+}
+impl BotConfig {
+    /// Returns settings of given difficulty.
+    pub fn preset(difficulty: Difficulty) -> Self {
+        match difficulty {
+            Difficulty::Beginner => Self {
+                depth: 1,
+                playouts: 0,
+                temperature: 12,
+                mistakes: 20,
+            },
+            Difficulty::Club => Self {
+                depth: 2,
+                playouts: 4,
+                temperature: 3,
+                mistakes: 5,
+            },
+            Difficulty::Strong => Self {
+                depth: 4,
+                playouts: 0,
+                temperature: 0,
+                mistakes: 0,
+            },
+        }
+    }
+}
+impl From<Difficulty> for BotConfig {
+    fn from(difficulty: Difficulty) -> Self {
+        Self::preset(difficulty)
+    }
+}
+
+/// Score of the move, that wins all playouts of [`ConfiguredBot`].
+pub const PLAYOUT_SCORE: i32 = 60;
+
+/// Bot with adjustable strength, see [`BotConfig`]. Moves are scored with
+/// [`Searcher::score_moves`] with [`DefaultEvaluator`] and games of [`RandomBot`]
+/// after them (playouts). The game should be cloneable for playouts.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::bots::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// let board = Board::new(4, 4, 2, [Cell::new(); 36], [0; 36]);
+/// let mut game = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+/// let mut bots = [
+///     ConfiguredBot::new(Difficulty::Club.into(), SplitMix64(1)),
+///     ConfiguredBot::new(Difficulty::Beginner.into(), SplitMix64(2)),
+/// ];
+/// while let Some(index) = bots[game.current_player()].choose_move(&mut game) {
+///     game.play(index).unwrap();
+/// }
+/// assert_ne!(game.state(), GameState::Ongoing);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ConfiguredBot<R> {
+    config: BotConfig,
+    rng: R,
+    searcher: Searcher<DefaultEvaluator>,
+}
+impl<R: Rng> ConfiguredBot<R> {
+    /// Creates new bot with given settings and source of random numbers.
+    pub fn new(config: BotConfig, rng: R) -> Self {
+        Self {
+            config,
+            rng,
+            searcher: Searcher::new(DefaultEvaluator::default()),
+        }
+    }
+    /// Returns settings of the bot.
+    pub fn config(&self) -> BotConfig {
+        self.config
+    }
+    /// Unwraps the source of random numbers.
+    pub fn into_inner(self) -> R {
+        self.rng
+    }
+    /// Returns index of the cell for the move of the current player
+    /// or `None` if the game has ended. The game is left unchanged.
+    pub fn choose_move<C, B, S, L>(&mut self, game: &mut Game<Board<C, B>, S, L>) -> Option<usize>
+    where
+        C: CellStorage + Clone,
+        B: AsMut<[usize]> + Clone,
+        S: IndexMut<usize, Output = Option<LoseData>> + Clone,
+        L: ChangeLog<usize> + Clone,
+    {
+        if game.state() != GameState::Ongoing {
+            return None;
+        }
+        let Self {
+            config,
+            rng,
+            searcher,
+        } = self;
+        if rng.below(100) < config.mistakes as usize {
+            return RandomBot::new(rng).choose_move(game);
+        }
+        let player = game.current_player();
+        let mut best: Option<(usize, i32)> = None;
+        let mut consider = |after: &Game<Board<C, B>, S, L>, index, score: i32| {
+            let noise = rng.below(config.temperature.max(0) as usize + 1) as i32;
+            let score = score
+                .saturating_add(playouts(after, player, config.playouts, rng))
+                .saturating_add(noise);
+            if best.map_or(true, |(_, best)| score > best) {
+                best = Some((index, score));
+            }
+        };
+        if config.depth > 0 {
+            searcher.score_moves(game, config.depth, consider);
+        } else {
+            let cells = game.board().width() * game.board().height();
+            for packed in 0..cells {
+                let index = game.board().unpack_index(packed);
+                if let Ok(record) = game.play(index) {
+                    consider(game, index, 0);
+                    game.undo(record).expect("the move was just made");
+                }
+            }
+        }
+        best.map(|(index, _)| index)
+    }
+}
+impl<R, C, B, S, L> Bot<Game<Board<C, B>, S, L>> for ConfiguredBot<R>
+where
+    R: Rng,
+    C: CellStorage + Clone,
+    B: AsMut<[usize]> + Clone,
+    S: IndexMut<usize, Output = Option<LoseData>> + Clone,
+    L: ChangeLog<usize> + Clone,
+{
+    fn choose_move(&mut self, game: &mut Game<Board<C, B>, S, L>) -> Option<usize> {
+        ConfiguredBot::choose_move(self, game)
+    }
+}
+
+/// Returns [`PLAYOUT_SCORE`] times the share of random games won by the player
+/// minus the share of lost ones.
+fn playouts<C, B, S, L>(
+    game: &Game<Board<C, B>, S, L>,
+    player: usize,
+    count: usize,
+    rng: &mut impl Rng,
+) -> i32
+where
+    C: CellStorage + Clone,
+    B: AsMut<[usize]> + Clone,
+    S: IndexMut<usize, Output = Option<LoseData>> + Clone,
+    L: ChangeLog<usize> + Clone,
+{
+    if count == 0 {
+        return 0;
+    }
+    let mut balance = 0;
+    for _ in 0..count {
+        let mut game = game.clone();
+        let mut bot = RandomBot::new(&mut *rng);
+        while let Some(index) = bot.choose_move(&game) {
+            game.play(index).expect("the move of the bot is legal");
+        }
+        balance += match game.state() {
+            GameState::Ended(GameOver::Win(winner)) if winner == player => 1,
+            GameState::Ended(GameOver::Win(_)) => -1,
+            _ => 0,
+        };
+    }
+    balance * PLAYOUT_SCORE / count as i32
+}
+
+/// Reason of the move suggested by [`Game::hint`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum HintReason {
+    /// The move wins the game
+    Win,
+    /// The move revives a dead chain of the player
+    SaveChain,
+    /// The move fills a cross of other player
+    Capture,
+    /// The move gives the player more moves or takes them from others
+    ExpandMobility,
+}
+impl Display for HintReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            HintReason::Win => write!(f, "this move wins the game"),
+            HintReason::SaveChain => write!(f, "this move revives your dead chain"),
+            HintReason::Capture => write!(f, "this move fills a cross of the opponent"),
+            HintReason::ExpandMobility => write!(f, "this move expands your zone of reach"),
+        }
+    }
+}
+
+impl<C, B, S, L> Game<Board<C, B>, S, L>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    /// Suggests the move of [`GreedyBot`] with default weights to the player and explains it.
+    /// Returns `None` if it isn't the turn of the player or the game has ended.
+    /// The game is left unchanged.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::bots::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+    /// let mut game = Game::new(board, PlayerManager::new(3, 2, [None; 2]));
+    /// assert_eq!(game.hint(1), None);
+    /// let (index, reason) = game.hint(0).unwrap();
+    /// assert_eq!((index, reason), (game.board().index(0, 0), HintReason::ExpandMobility));
+    /// assert_eq!(reason.to_string(), "this move expands your zone of reach");
+    /// let fen = "2x2./2.x./3.o/3.o 2 x 1/2 - 5";
+    /// let mut game = Game::from_fen(fen, [Cell::new(); 36], [0; 36], [None; 2]).unwrap();
+    /// assert_eq!(game.hint(0).unwrap().1, HintReason::Capture);
+    /// ```
+    pub fn hint(&mut self, player: usize) -> Option<(usize, HintReason)> {
+        if player != self.current_player() {
+            return None;
+        }
+        let index = GreedyBot::default().choose_move(self)?;
+        let alive = alive_filled(self.board(), player);
+        let played = self.play(index).expect("the move of the bot is legal");
+        let captured = played.record.prior_kind == CellKind::Cross;
+        let reason = match self.state() {
+            GameState::Ended(GameOver::Win(winner)) if winner == player => HintReason::Win,
+            _ if alive_filled(self.board(), player) > alive + captured as usize => {
+                HintReason::SaveChain
+            }
+            _ if captured => HintReason::Capture,
+            _ => HintReason::ExpandMobility,
+        };
+        self.undo(played).expect("the move was just made");
+        Some((index, reason))
+    }
+}
+
+fn alive_filled<C: CellStorage, B: AsMut<[usize]>>(board: &Board<C, B>, player: usize) -> usize {
+    (0..board.width() * board.height())
+        .map(|packed| board.unpack_index(packed))
+        .filter(|index| {
+            let cell = board.get(*index);
+            cell.kind() == CellKind::Filled && cell.player() == player && board.is_alive(*index)
+        })
+        .count()
+}
@@ -0,0 +1,254 @@
+//! Game clocks
+//!
+//! This module defines [`Clock`], which counts thinking time of players. It doesn't read
+//! system clocks: every method takes the current timestamp `now` from the caller, in any
+//! units, e.g. milliseconds of a hardware timer. Time of a turn is charged, when the turn
+//! passes to other player, the player gets [`TimeControl::increment`] after every turn.
+//! When main time is over, the player has byo-yomi periods: every turn should fit into
+//! one period, otherwise periods are used up. The player, who has no time left, is flagged
+//! and loses with [`EliminationReason::Timeout`] at next [`Clock::check`] or [`Clock::play`].
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::clock::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+//! let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+//! let mut clock = Clock::new(TimeControl::byo_yomi(100, 2, 30), 2);
+//! clock.start(0, 0);
+//! let index = game.board().index(0, 0);
+//! clock.play(&mut game, index, 40).unwrap();
+//! assert_eq!(clock.time_left(0, 40), 60 + 2 * 30);
+//! let index = game.board().index(2, 2);
+//! clock.play(&mut game, index, 180).unwrap();
+//! assert_eq!((clock.main_time(1, 180), clock.periods(1)), (0, 1));
+//! assert_eq!(clock.check(&mut game, 200), None);
+//! assert_eq!(clock.check(&mut game, 300), Some(0));
+//! assert_eq!(game.state(), GameState::Ended(GameOver::Win(1)));
+//! ```
+//!
+//! [`EliminationReason::Timeout`]: crate::summary::EliminationReason::Timeout
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::ops::IndexMut;
+
+use crate::board::MAX_PLAYERS;
+use crate::engine::{Engine, MoveError};
+use crate::game::{Game, GameMove};
+use crate::player_manager::{GameState, LoseData};
+use crate::summary::EliminationReason;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Time limits of players, in units of timestamps.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct TimeControl {
+    /// Main time of every player
+    pub main: u64,
+    /// Time added to main time after every turn
+    pub increment: u64,
+    /// Number of byo-yomi periods
+    pub periods: u32,
+    /// Length of byo-yomi period
+    pub period: u64,
+}
+impl TimeControl {
+    /// Creates time control with Fischer increment and without byo-yomi.
+    pub fn fischer(main: u64, increment: u64) -> Self {
+        Self {
+            main,
+            increment,
+            ..Self::default()
+        }
+    }
+    /// Creates time control with byo-yomi and without increment.
+    pub fn byo_yomi(main: u64, periods: u32, period: u64) -> Self {
+        Self {
+            main,
+            periods,
+            period,
+            ..Self::default()
+        }
+    }
+}
+
+/// Error of [`Clock::play`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ClockError {
+    /// The current player has run out of time and lost, the move wasn't made
+    Timeout(usize),
+    /// The move is illegal
+    Move(MoveError),
+}
+impl Display for ClockError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ClockError::Timeout(player) => write!(f, "player {} has run out of time", player),
+            ClockError::Move(error) => write!(f, "{}", error),
+        }
+    }
+}
+impl From<MoveError> for ClockError {
+    fn from(error: MoveError) -> Self {
+        ClockError::Move(error)
+    }
+}
+
+/// Clock of players of a game, see module docs.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Clock {
+    control: TimeControl,
+    players: usize,
+    main: [u64; MAX_PLAYERS],
+    periods: [u32; MAX_PLAYERS],
+    flagged: [bool; MAX_PLAYERS],
+    running: Option<(usize, u64)>,
+}
+impl Clock {
+    /// Creates stopped clock, every player has full time.
+    /// # Panics
+    /// Panics if there are more than [`MAX_PLAYERS`] players.
+    pub fn new(control: TimeControl, players: usize) -> Self {
+        assert!(players <= MAX_PLAYERS, "too many players");
+        Self {
+            control,
+            players,
+            main: [control.main; MAX_PLAYERS],
+            periods: [control.periods; MAX_PLAYERS],
+            flagged: [false; MAX_PLAYERS],
+            running: None,
+        }
+    }
+    /// Returns the time control.
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+    /// Returns number of players.
+    pub fn players(&self) -> usize {
+        self.players
+    }
+    /// Returns the player, whose time is running, if any.
+    pub fn running(&self) -> Option<usize> {
+        self.running.map(|(player, _)| player)
+    }
+
+    /// Returns main time left to the player at the moment `now`.
+    pub fn main_time(&self, player: usize, now: u64) -> u64 {
+        self.main[player].saturating_sub(self.elapsed(player, now))
+    }
+    /// Returns number of byo-yomi periods left to the player before the current turn.
+    pub fn periods(&self, player: usize) -> u32 {
+        self.periods[player]
+    }
+    /// Returns all time left to the player in the current turn at the moment `now`.
+    pub fn time_left(&self, player: usize, now: u64) -> u64 {
+        self.budget(player)
+            .saturating_sub(self.elapsed(player, now))
+    }
+    /// Checks if the player has no time left at the moment `now`.
+    pub fn is_flagged(&self, player: usize, now: u64) -> bool {
+        self.flagged[player] || self.time_left(player, now) == 0
+    }
+
+    /// Starts the turn of the player at the moment `now`. If time of other player
+    /// is running, their turn ends.
+    pub fn start(&mut self, player: usize, now: u64) {
+        self.stop(now);
+        if !self.flagged[player] {
+            self.running = Some((player, now));
+        }
+    }
+    /// Ends the turn of the running player at the moment `now`: charges its time and adds
+    /// the increment. Returns the player, if they were flagged.
+    pub fn stop(&mut self, now: u64) -> Option<usize> {
+        let (player, _) = self.running?;
+        let elapsed = self.elapsed(player, now);
+        self.running = None;
+        if elapsed >= self.budget(player) {
+            self.main[player] = 0;
+            self.periods[player] = 0;
+            self.flagged[player] = true;
+            return Some(player);
+        }
+        if elapsed <= self.main[player] {
+            self.main[player] -= elapsed;
+        } else {
+            let overtime = elapsed - self.main[player];
+            self.main[player] = 0;
+            self.periods[player] -= (overtime / self.control.period) as u32;
+        }
+        self.main[player] += self.control.increment;
+        None
+    }
+    /// Checks the clock of the game at the moment `now`. If the current player is flagged,
+    /// they lose with [`EliminationReason::Timeout`] and the clock of the next player
+    /// starts. Returns the flagged player. The clock follows the current player of the
+    /// game, if it has changed, and stops, when the game ends.
+    pub fn check<B, S>(&mut self, game: &mut Game<B, S>, now: u64) -> Option<usize>
+    where
+        B: Engine,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+    {
+        let mut flagged = None;
+        if let Some(player) = self.running() {
+            if self.is_flagged(player, now) {
+                flagged = self.stop(now);
+                // The game is ongoing, because the clock is running.
+                let _ = game.mark_loser(player, EliminationReason::Timeout);
+            }
+        }
+        self.follow(game, now);
+        flagged
+    }
+    /// Makes a move of the current player at the moment `now` and switches the clock,
+    /// if the turn has passed. The clock starts, if it was stopped.
+    /// # Errors
+    /// Returns [`ClockError::Timeout`] if the current player is flagged (see
+    /// [`Clock::check`]) and [`ClockError::Move`] if the move is illegal.
+    pub fn play<B, S>(
+        &mut self,
+        game: &mut Game<B, S>,
+        index: B::Index,
+        now: u64,
+    ) -> Result<GameMove<B::Index>, ClockError>
+    where
+        B: Engine,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+    {
+        if let Some(player) = self.check(game, now) {
+            return Err(ClockError::Timeout(player));
+        }
+        let record = game.play(index)?;
+        self.follow(game, now);
+        Ok(record)
+    }
+
+    /// Runs the clock of the current player of the game.
+    fn follow<B, S>(&mut self, game: &Game<B, S>, now: u64)
+    where
+        B: Engine,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+    {
+        if game.state() != GameState::Ongoing {
+            self.stop(now);
+        } else if self.running() != Some(game.current_player()) {
+            self.start(game.current_player(), now);
+        }
+    }
+    /// Returns time of the current turn of the player.
+    fn elapsed(&self, player: usize, now: u64) -> u64 {
+        match self.running {
+            Some((running, since)) if running == player => now.saturating_sub(since),
+            _ => 0,
+        }
+    }
+    /// Returns all time of the player for a turn.
+    fn budget(&self, player: usize) -> u64 {
+        if self.flagged[player] {
+            return 0;
+        }
+        self.main[player] + self.periods[player] as u64 * self.control.period
+    }
+}
@@ -0,0 +1,304 @@
+//! Conformance tests
+//!
+//! This module checks implementations of [`Engine`], so authors of boards can certify them
+//! with one test: `conformance::run::<MyBoard>().unwrap()`. The board under test implements
+//! [`Subject`] to be created from a [`Reference`] board, then both boards play the same games
+//! in [`Game`]: the positions of [`FIXTURES`] with their moves and [`RANDOM_GAMES`] random
+//! games with different sizes of boards, numbers of players, budgets and rules. At the end
+//! of every game all its moves are cancelled one by one. Before the first move and after
+//! every move or cancelling everything, that can be observed, is compared, see [`compare`]:
+//! legality of moves, content of cells, aliveness, activations and counters.
+//!
+//! Games are deterministic, so [`Failure`] is reproduced by the same call.
+//! The module is available with `testing` feature.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::conformance::*;
+//! # use crosses_utils::ibts::*;
+//! assert_eq!(run::<Reference>(), Ok(()));
+//! assert_eq!(run::<Anchored<Reference, [Option<Anchor<usize>>; MAX_CELLS]>>(), Ok(()));
+//! ```
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::base::CellKind;
+use crate::board::{Board, Cell, MAX_PLAYERS};
+use crate::bots::{Rng, SplitMix64};
+use crate::counters::{Counter, PlayerCounters};
+use crate::engine::{Change, ChangeStack, Data, Engine};
+use crate::fixtures::FIXTURES;
+use crate::game::{Game, GameMove};
+use crate::ibts::{Anchor, Anchored, IbtsBoard};
+use crate::player_manager::{GameState, LoseData, PlayerManager};
+use crate::reference::Divergence;
+use crate::rules::RuleSet;
+
+/// Maximum number of cells of boards with borders, boards of random games are
+/// from `3×3` to `8×8`.
+pub const MAX_CELLS: usize = 100;
+/// Number of random games played by [`run`].
+pub const RANDOM_GAMES: u64 = 64;
+/// Maximum number of moves of a random game.
+pub const MAX_MOVES: usize = 128;
+const MAX_CHANGES: usize = 4096;
+
+/// Rules of random games, the game with seed `n` uses rules `n % 6`.
+const RULES: [RuleSet; 6] = [
+    RuleSet::STANDARD,
+    RuleSet {
+        convert_chains: true,
+        ..RuleSet::STANDARD
+    },
+    RuleSet {
+        remove_dead_chains: true,
+        ..RuleSet::STANDARD
+    },
+    RuleSet {
+        strong_crosses: true,
+        ..RuleSet::STANDARD
+    },
+    RuleSet {
+        fill_requires_reach: false,
+        ..RuleSet::STANDARD
+    },
+    RuleSet {
+        self_fill: true,
+        ..RuleSet::STANDARD
+    },
+];
+
+/// The board, that the board under test is compared with.
+pub type Reference = Board<[Cell; MAX_CELLS], [usize; MAX_CELLS]>;
+
+type Losers = [Option<LoseData>; MAX_PLAYERS];
+type Log<I> = ChangeStack<[Change<I>; MAX_CHANGES]>;
+type Records<I> = [Option<(GameMove<usize>, GameMove<I>)>; MAX_MOVES];
+
+/// The board under test.
+pub trait Subject: Engine + Sized {
+    /// Creates the board with the size, players, starting cells, rules and cells
+    /// of the reference one, including aliveness of filled cells, activations and counters.
+    fn from_reference(reference: &Reference) -> Self;
+    /// Returns index of the cell at given coordinates, see [`Board::index`].
+    fn index(&self, x: usize, y: usize) -> Self::Index;
+}
+impl Subject for Reference {
+    fn from_reference(reference: &Reference) -> Self {
+        reference.clone()
+    }
+    fn index(&self, x: usize, y: usize) -> usize {
+        Board::index(self, x, y)
+    }
+}
+impl Subject for Anchored<Reference, [Option<Anchor<usize>>; MAX_CELLS]> {
+    fn from_reference(reference: &Reference) -> Self {
+        Anchored::new(reference.clone(), [None; MAX_CELLS])
+    }
+    fn index(&self, x: usize, y: usize) -> usize {
+        self.board().index(x, y)
+    }
+}
+
+/// The game of [`run`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Case {
+    /// The fixture with given name, see [`fixture`](crate::fixtures::fixture)
+    Fixture(&'static str),
+    /// The random game with given seed
+    Random(u64),
+}
+impl Display for Case {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Case::Fixture(name) => write!(f, "fixture {}", name),
+            Case::Random(seed) => write!(f, "random game {}", seed),
+        }
+    }
+}
+
+/// The first difference found by [`run`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Failure {
+    /// The game, where the boards differ
+    pub case: Case,
+    /// Number of moves and cancellings made in the game, `0` is the initial position
+    pub step: usize,
+    /// What differs, indices are ones of the reference board
+    pub divergence: Divergence,
+}
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}, step {}: {}", self.case, self.step, self.divergence)
+    }
+}
+
+/// Runs all games of the suite on the board under test, see module docs.
+/// # Errors
+/// Returns the first [`Failure`], games are played in the order fixtures, then random ones.
+pub fn run<T: Subject>() -> Result<(), Failure> {
+    for fixture in &FIXTURES {
+        let game = fixture
+            .game(
+                [Cell::new(); MAX_CELLS],
+                [0; MAX_CELLS],
+                [None; MAX_PLAYERS],
+            )
+            .expect("fixtures fit the reference board");
+        let mut pair = Pair::<T>::new(Case::Fixture(fixture.name), game);
+        pair.check()?;
+        for (x, y) in fixture.moves {
+            pair.play(*x, *y)?;
+        }
+        pair.undo_all()?;
+    }
+    for seed in 0..RANDOM_GAMES {
+        random_game::<T>(seed)?;
+    }
+    Ok(())
+}
+
+/// Compares everything, that can be observed, of the board under test and the reference
+/// board, like [`reference::compare`](crate::reference::compare).
+/// # Errors
+/// Returns the first [`Divergence`], cells are compared row by row.
+pub fn compare<T: Subject>(reference: &Reference, subject: &T) -> Result<(), Divergence> {
+    let players = reference.counters().players();
+    for y in 0..reference.height() {
+        for x in 0..reference.width() {
+            let (index, other) = (reference.index(x, y), subject.index(x, y));
+            let (cell, data) = (reference.get(index), subject.get(other));
+            let filled = cell.kind() == CellKind::Filled;
+            if cell.kind() != data.kind()
+                || cell.player() != data.player()
+                || cell.damage() != data.damage()
+                || filled && cell.previous_player() != data.previous_player()
+            {
+                return Err(Divergence::Content(index));
+            }
+            if filled && reference.is_alive(index) != subject.is_alive(other) {
+                return Err(Divergence::Alive(index));
+            }
+            for player in 0..players {
+                if cell.is_active(player) != data.is_active(player) {
+                    return Err(Divergence::Active(index, player));
+                }
+            }
+        }
+    }
+    let (counters, other) = (reference.counters(), subject.counters());
+    for player in 0..players {
+        for counter in [Counter::Crosses, Counter::Moves] {
+            if counters.get(counter, player) != other.get(counter, player) {
+                return Err(Divergence::Counter(counter, player));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn random_game<T: Subject>(seed: u64) -> Result<(), Failure> {
+    let mut rng = SplitMix64(seed);
+    let (width, height) = (3 + rng.below(6), 3 + rng.below(6));
+    let players = 2 + rng.below(3);
+    let mut board = Board::new(
+        width,
+        height,
+        players,
+        [Cell::new(); MAX_CELLS],
+        [0; MAX_CELLS],
+    );
+    board.set_rules(RULES[seed as usize % RULES.len()]);
+    let manager = PlayerManager::new(1 + rng.below(3), players, [None; MAX_PLAYERS]);
+    let mut pair = Pair::<T>::new(Case::Random(seed), Game::new(board, manager));
+    pair.check()?;
+    while pair.depth < MAX_MOVES && pair.reference.state() == GameState::Ongoing {
+        let board = pair.reference.board();
+        // Some moves are arbitrary, so illegal moves are checked too.
+        let (x, y) = if rng.below(8) == 0 {
+            (rng.below(width), rng.below(height))
+        } else {
+            let moves = board.legal_moves(pair.reference.current_player());
+            let count = moves.clone().count();
+            match moves.clone().nth(rng.below(count.max(1))) {
+                Some(index) => board.coords(index),
+                None => break,
+            }
+        };
+        pair.play(x, y)?;
+    }
+    pair.undo_all()
+}
+
+/// The reference game and the game on the board under test with made moves.
+struct Pair<T: Subject> {
+    case: Case,
+    step: usize,
+    reference: Game<Reference, Losers, Log<usize>>,
+    subject: Game<T, Losers, Log<T::Index>>,
+    records: Records<T::Index>,
+    depth: usize,
+}
+impl<T: Subject> Pair<T> {
+    fn new(case: Case, game: Game<Reference, Losers>) -> Self {
+        let (board, manager) = game.into_parts();
+        let subject = T::from_reference(&board);
+        let log = ChangeStack::new([blank(subject.index(0, 0)); MAX_CHANGES]);
+        Self {
+            case,
+            step: 0,
+            subject: Game::with_log(subject, manager.clone(), log),
+            reference: Game::with_log(board, manager, ChangeStack::new([blank(0); MAX_CHANGES])),
+            records: [None; MAX_MOVES],
+            depth: 0,
+        }
+    }
+    fn check(&self) -> Result<(), Failure> {
+        compare(self.reference.board(), self.subject.board()).map_err(|d| self.fail(d))
+    }
+    fn fail(&self, divergence: Divergence) -> Failure {
+        Failure {
+            case: self.case,
+            step: self.step,
+            divergence,
+        }
+    }
+    fn play(&mut self, x: usize, y: usize) -> Result<(), Failure> {
+        let expected = self.reference.play(self.reference.board().index(x, y));
+        let result = self.subject.play(self.subject.board().index(x, y));
+        self.step += 1;
+        match (expected, result) {
+            (Ok(expected), Ok(result)) => {
+                self.records[self.depth] = Some((expected, result));
+                self.depth += 1;
+            }
+            (Err(expected), Err(result)) if expected == result => {}
+            (expected, result) => {
+                let (expected, result) = (expected.map(|_| ()), result.map(|_| ()));
+                return Err(self.fail(Divergence::Legality(result, expected)));
+            }
+        }
+        self.check()
+    }
+    fn undo_all(&mut self) -> Result<(), Failure> {
+        while self.depth > 0 {
+            self.depth -= 1;
+            let (expected, result) = self.records[self.depth].take().unwrap();
+            let (expected, result) = (self.reference.undo(expected), self.subject.undo(result));
+            self.step += 1;
+            if expected != result {
+                return Err(self.fail(Divergence::Legality(result, expected)));
+            }
+            self.check()?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns filler of logs.
+fn blank<I>(index: I) -> Change<I> {
+    Change {
+        index,
+        player: 0,
+        previous_player: 0,
+    }
+}
@@ -0,0 +1,140 @@
+//! Per-player counters
+//!
+//! This module defines [`PlayerCounters`] trait, which is a storage of the numbers of crosses
+//! and available moves of each player, and its implementations: [`Counters`], which is backed
+//! by an array, and `VecCounters` (requires `alloc` feature).
+//! [`Engine`] keeps them up to date and [`Game`] uses them to tell [`PlayerManager`] who lost.
+//!
+//! [`Engine`]: crate::engine::Engine
+//! [`Game`]: crate::game::Game
+//! [`PlayerManager`]: crate::player_manager::PlayerManager
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A kind of counter.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Counter {
+    /// Number of crosses on the board
+    Crosses,
+    /// Number of cells, where the player can make a move
+    Moves,
+}
+
+/// Storage of per-player counters.
+pub trait PlayerCounters {
+    /// Returns number of players.
+    fn players(&self) -> usize;
+    /// Returns value of the counter of the player.
+    fn get(&self, counter: Counter, player: usize) -> usize;
+    /// Returns mutable reference to the counter of the player.
+    fn get_mut(&mut self, counter: Counter, player: usize) -> &mut usize;
+
+    /// Increments the counter of the player.
+    /// # Panics
+    /// Panics if the counter overflows.
+    fn increment(&mut self, counter: Counter, player: usize) {
+        let value = self.get_mut(counter, player);
+        *value = value.checked_add(1).expect("counter overflow");
+    }
+    /// Decrements the counter of the player.
+    /// # Panics
+    /// Panics if the counter is already `0`. It means that
+    /// the counter has gotten out of sync with the board.
+    fn decrement(&mut self, counter: Counter, player: usize) {
+        let value = self.get_mut(counter, player);
+        *value = value.checked_sub(1).expect("counter underflow");
+    }
+    /// Checks if the counter of the player is `0`.
+    fn is_exhausted(&self, counter: Counter, player: usize) -> bool {
+        self.get(counter, player) == 0
+    }
+}
+
+/// Array-backed counters for at most `N` players.
+/// # Example
+/// ```
+/// # use crosses_utils::counters::*;
+/// let mut counters = Counters::<2>::new(2);
+/// counters.increment(Counter::Crosses, 1);
+/// assert_eq!(counters.get(Counter::Crosses, 1), 1);
+/// assert!(counters.is_exhausted(Counter::Moves, 1));
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Counters<const N: usize> {
+    players: usize,
+    crosses: [usize; N],
+    moves: [usize; N],
+}
+impl<const N: usize> Counters<N> {
+    /// Creates new counters for `players` players, all of them are `0`.
+    /// # Panics
+    /// Panics if `players` is greater than `N`.
+    pub fn new(players: usize) -> Self {
+        assert!(players <= N, "too many players");
+        Self {
+            players,
+            crosses: [0; N],
+            moves: [0; N],
+        }
+    }
+}
+impl<const N: usize> PlayerCounters for Counters<N> {
+    fn players(&self) -> usize {
+        self.players
+    }
+    fn get(&self, counter: Counter, player: usize) -> usize {
+        debug_assert!(player < self.players);
+        match counter {
+            Counter::Crosses => self.crosses[player],
+            Counter::Moves => self.moves[player],
+        }
+    }
+    fn get_mut(&mut self, counter: Counter, player: usize) -> &mut usize {
+        debug_assert!(player < self.players);
+        match counter {
+            Counter::Crosses => &mut self.crosses[player],
+            Counter::Moves => &mut self.moves[player],
+        }
+    }
+}
+
+/// Vec-backed counters for any number of players.
+#[cfg(feature = "alloc")]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct VecCounters {
+    crosses: Vec<usize>,
+    moves: Vec<usize>,
+}
+#[cfg(feature = "alloc")]
+impl VecCounters {
+    /// Creates new counters for `players` players, all of them are `0`.
+    pub fn new(players: usize) -> Self {
+        Self {
+            crosses: alloc::vec![0; players],
+            moves: alloc::vec![0; players],
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl PlayerCounters for VecCounters {
+    fn players(&self) -> usize {
+        self.crosses.len()
+    }
+    fn get(&self, counter: Counter, player: usize) -> usize {
+        match counter {
+            Counter::Crosses => self.crosses[player],
+            Counter::Moves => self.moves[player],
+        }
+    }
+    fn get_mut(&mut self, counter: Counter, player: usize) -> &mut usize {
+        match counter {
+            Counter::Crosses => &mut self.crosses[player],
+            Counter::Moves => &mut self.moves[player],
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Position diffs
+//!
+//! This module defines [`diff`], which finds the cells that differ between two positions
+//! on boards of the same size, and [`apply_diff`], which changes them. So spectators can
+//! receive only the changes between keyframes instead of whole boards.
+//!
+//! [`Game::last_move_diff`] lists transitions of cells caused by the last move, including
+//! aliveness of filled cells, so front-ends can animate captures and deaths of chains.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::diff::*;
+//! # use crosses_utils::engine::*;
+//! let mut board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+//! let mut spectator = board.clone();
+//! for (x, y) in [(0, 0), (1, 1), (2, 2)] {
+//!     let index = board.index(x, y);
+//!     make_move(&mut board, index, 0).unwrap();
+//! }
+//! let mut changes = [None; 9];
+//! for (change, slot) in diff(&spectator, &board).zip(&mut changes) {
+//!     *slot = Some(change);
+//! }
+//! assert_eq!(changes.iter().flatten().count(), 3);
+//! apply_diff(&mut spectator, changes.iter().flatten().copied()).unwrap();
+//! assert_eq!(spectator, board);
+//! ```
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use core::ops::IndexMut;
+
+use crate::base::CellKind;
+use crate::board::{Board, Cell, CellStorage, HistoryIndex};
+use crate::counters::PlayerCounters;
+use crate::engine::{ChangeLog, Data, Engine, MoveError};
+use crate::game::{Game, GameMove};
+use crate::ibts::IbtsBoard;
+use crate::player_manager::LoseData;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// New content of a cell.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct CellChange {
+    /// Packed index of the cell, see [`Board::pack_index`]
+    pub index: usize,
+    /// New kind of the cell
+    pub kind: CellKind,
+    /// New player of the cell
+    pub player: usize,
+    /// New previous owner of the cell, if it's filled
+    pub previous_player: usize,
+}
+
+/// Iterator over changes between two positions, returned by [`diff`].
+#[derive(Clone, Debug)]
+pub struct PositionDiff<'a, C, B, D, E> {
+    from: &'a Board<C, B>,
+    to: &'a Board<D, E>,
+    position: usize,
+}
+impl<'a, C, B, D, E> Iterator for PositionDiff<'a, C, B, D, E>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    D: CellStorage,
+    E: AsMut<[usize]>,
+{
+    type Item = CellChange;
+
+    fn next(&mut self) -> Option<CellChange> {
+        while self.position < self.to.width() * self.to.height() {
+            let index = self.to.unpack_index(self.position);
+            self.position += 1;
+            let (old, new) = (self.from.get(index), self.to.get(index));
+            let previous = |cell: &Cell| match cell.kind() {
+                CellKind::Filled => cell.previous_player(),
+                _ => 0,
+            };
+            if old.kind() != new.kind()
+                || old.player() != new.player()
+                || previous(&old) != previous(&new)
+            {
+                return Some(CellChange {
+                    index: self.position - 1,
+                    kind: new.kind(),
+                    player: new.player(),
+                    previous_player: previous(&new),
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Returns changes of cells, that turn position `from` into position `to`.
+/// Only the content of cells is compared.
+/// # Panics
+/// Panics if the boards have different size.
+pub fn diff<'a, C, B, D, E>(
+    from: &'a Board<C, B>,
+    to: &'a Board<D, E>,
+) -> PositionDiff<'a, C, B, D, E>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    D: CellStorage,
+    E: AsMut<[usize]>,
+{
+    assert!(
+        from.width() == to.width() && from.height() == to.height(),
+        "boards have different size"
+    );
+    PositionDiff {
+        from,
+        to,
+        position: 0,
+    }
+}
+
+/// Error of [`apply_diff`]: the change with given number has wrong index or player.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct InvalidChange(pub usize);
+impl Display for InvalidChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "change {} is invalid", self.0)
+    }
+}
+
+/// Applies changes of cells to the board and recomputes the rest of it with [`Board::init`].
+/// IBTS may mark other cells as important than on the original board.
+/// # Errors
+/// Returns [`InvalidChange`] if a change is out of the board or has wrong player.
+/// Changes before it are applied.
+pub fn apply_diff<C, B>(
+    board: &mut Board<C, B>,
+    changes: impl IntoIterator<Item = CellChange>,
+) -> Result<(), InvalidChange>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let players = board.counters().players();
+    let mut result = Ok(());
+    for (number, change) in changes.into_iter().enumerate() {
+        if change.index >= board.width() * board.height()
+            || change.player >= players
+            || change.previous_player >= players
+        {
+            result = Err(InvalidChange(number));
+            break;
+        }
+        let index = board.unpack_index(change.index);
+        board.with_cell(index, |cell| {
+            cell.set_content(change.kind, change.player);
+            cell.set_previous_player(change.previous_player);
+        });
+    }
+    board.init();
+    result
+}
+
+/// Visible content of a cell.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct CellState {
+    /// Kind of the cell
+    pub kind: CellKind,
+    /// Player of the cell
+    pub player: usize,
+    /// Whether the cell is an alive filled cell
+    pub alive: bool,
+}
+
+/// Transition of a cell, see [`Game::last_move_diff`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Transition {
+    /// Packed index of the cell, see [`Board::pack_index`]
+    pub index: usize,
+    /// Content of the cell before the move
+    pub from: CellState,
+    /// Content of the cell after the move
+    pub to: CellState,
+}
+
+impl<C, B, S, L> Game<Board<C, B>, S, L>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    /// Lists transitions of cells caused by the last move, described by `played`: the cell
+    /// of the move and filled cells of chains, that were killed or revived. Transitions are
+    /// ordered by distance from the cell of the move, so they can be animated step by step.
+    /// Writes them into the beginning of `out`, fills the rest of it with `None` and returns
+    /// their number. The move is cancelled and made again, so the game is left unchanged.
+    /// Index of the move can be stored as any [`HistoryIndex`].
+    /// # Errors
+    /// Returns [`MoveError`] in the same cases as [`Game::undo`].
+    /// # Panics
+    /// Panics if `out` has less than `width * height` elements.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::base::CellKind;
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// let fen = "xXX./o3./4./4. 2 o 1/1 - 4";
+    /// let mut game = Game::from_fen(fen, [Cell::new(); 36], [0; 36], [None; 2]).unwrap();
+    /// let record = game.play(game.board().index(0, 0)).unwrap();
+    /// let initial = game.clone();
+    /// let mut out = [None; 16];
+    /// assert_eq!(game.last_move_diff(record, &mut out), Ok(3));
+    /// assert_eq!(game, initial);
+    /// let first = out[0].unwrap();
+    /// assert_eq!((first.index, first.to.kind), (0, CellKind::Filled));
+    /// assert!(out[1..3].iter().flatten().all(|flip| flip.from.alive && !flip.to.alive));
+    /// ```
+    pub fn last_move_diff<I: HistoryIndex>(
+        &mut self,
+        played: GameMove<I>,
+        out: &mut [Option<Transition>],
+    ) -> Result<usize, MoveError> {
+        let board = self.board();
+        let played = played.map_index(|index| index.to_index(board));
+        let cells = board.width() * board.height();
+        let out = &mut out[..cells];
+        let state = |board: &Board<C, B>, packed: usize| {
+            let index = board.unpack_index(packed);
+            let cell = board.get(index);
+            CellState {
+                kind: cell.kind(),
+                player: cell.player(),
+                alive: cell.kind() == CellKind::Filled && board.is_alive(index),
+            }
+        };
+        for (packed, transition) in out.iter_mut().enumerate() {
+            let to = state(board, packed);
+            *transition = Some(Transition {
+                index: packed,
+                from: to,
+                to,
+            });
+        }
+        let game_state = self.state();
+        self.undo(played)?;
+        for transition in out.iter_mut().flatten() {
+            transition.from = state(self.board(), transition.index);
+        }
+        self.play(played.record.index)
+            .expect("the move was just cancelled");
+        // The move doesn't repeat adjudication.
+        self.parts_mut().1.game_state = game_state;
+        let mut len = 0;
+        for packed in 0..cells {
+            match out[packed] {
+                Some(transition) if transition.from != transition.to => {
+                    out[packed] = None;
+                    out[len] = Some(transition);
+                    len += 1;
+                }
+                _ => out[packed] = None,
+            }
+        }
+        let (x, y) = self.board().coords(played.record.index);
+        let width = self.board().width();
+        out[..len].sort_unstable_by_key(|transition| {
+            let index = transition.map_or(0, |transition| transition.index);
+            let distance = (index % width).abs_diff(x).max((index / width).abs_diff(y));
+            (distance, index)
+        });
+        Ok(len)
+    }
+}
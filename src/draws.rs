@@ -0,0 +1,294 @@
+//! Draw rules
+//!
+//! This module defines optional rules, that end long games with a draw. A position repeats,
+//! when the board, the current player and their remaining moves are the same, the position
+//! is identified by its [`Zobrist`] hash. Positions can repeat only with rules, that empty
+//! cells, e.g. [`RuleSet::remove_dead_chains`]. A turn makes progress, if a cross of other
+//! player is filled or damaged, or a new cross touches cells of other players, see
+//! [`is_progress`].
+//!
+//! [`DrawTracker`] keeps the history of positions in storage provided by the caller,
+//! [`DrawTracker::play`] and [`DrawTracker::undo`] make and cancel moves of [`Game`] and
+//! end it with [`Game::end`], when a rule is triggered. Such rules need a change log,
+//! so games of the tracker can have one, see [`Game::with_log`].
+//!
+//! [`RuleSet::remove_dead_chains`]: crate::rules::RuleSet::remove_dead_chains
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::draws::*;
+//! # use crosses_utils::engine::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::rules::*;
+//! # use crosses_utils::summary::EndReason;
+//! let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+//! let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+//! let rules = DrawRules { quiet_turns: 2, ..DrawRules::default() };
+//! let mut tracker = DrawTracker::new(rules, [DrawEntry::default(); 16], &game);
+//! let (first, second) = (game.board().index(0, 0), game.board().index(4, 4));
+//! tracker.play(&mut game, first).unwrap();
+//! assert_eq!(game.state(), GameState::Ongoing);
+//! let record = tracker.play(&mut game, second).unwrap();
+//! assert_eq!(game.state(), GameState::Ended(GameOver::Draw));
+//! assert_eq!(game.end_reason(), Some(EndReason::NoProgress));
+//! tracker.undo(&mut game, record).unwrap();
+//! assert_eq!((game.state(), tracker.len()), (GameState::Ongoing, 2));
+//!
+//! let mut board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+//! board.set_rules(RuleSet { remove_dead_chains: true, ..RuleSet::STANDARD });
+//! let log = ChangeStack::new([Change::default(); 64]);
+//! let mut game = Game::with_log(board, PlayerManager::new(1, 2, [None; 2]), log);
+//! let rules = DrawRules { repetitions: 3, ..DrawRules::default() };
+//! let mut tracker = DrawTracker::new(rules, [DrawEntry::default(); 16], &game);
+//! let corner = game.board().index(0, 0);
+//! tracker.play(&mut game, corner).unwrap();
+//! assert_eq!(tracker.len(), 2);
+//! ```
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::ops::IndexMut;
+
+use crate::base::{CellKind, GameBoard};
+use crate::board::{Board, CellStorage};
+use crate::engine::{ChangeLog, Data, Engine, MoveError, MoveRecord};
+use crate::game::{Game, GameMove};
+use crate::player_manager::{GameOver, LoseData};
+use crate::summary::EndReason;
+use crate::zobrist::Zobrist;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Options of draw rules, `0` disables a rule.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct DrawRules {
+    /// Number of occurrences of the same position, that ends the game, e.g. `3`
+    pub repetitions: usize,
+    /// Number of turns in a row without progress, that ends the game
+    pub quiet_turns: usize,
+}
+
+/// Position in the history of [`DrawTracker`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct DrawEntry {
+    key: u64,
+    quiet_since: usize,
+}
+
+/// Error of [`DrawTracker::play`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DrawError {
+    /// The history has no room for the position after the move
+    HistoryFull,
+    /// The move is illegal
+    Move(MoveError),
+}
+impl Display for DrawError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            DrawError::HistoryFull => write!(f, "history of positions is full"),
+            DrawError::Move(error) => write!(f, "{}", error),
+        }
+    }
+}
+impl From<MoveError> for DrawError {
+    fn from(error: MoveError) -> Self {
+        DrawError::Move(error)
+    }
+}
+
+/// History of positions of a game, see module docs.
+/// `H` - is type of storage of the history, every move takes one [`DrawEntry`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DrawTracker<H> {
+    rules: DrawRules,
+    zobrist: Zobrist,
+    history: H,
+    len: usize,
+}
+impl<H: AsMut<[DrawEntry]> + AsRef<[DrawEntry]>> DrawTracker<H> {
+    /// Creates the history, that starts with the current position of the game.
+    /// # Panics
+    /// Panics if `history` is empty.
+    pub fn new<C, B, S, L>(rules: DrawRules, history: H, game: &Game<Board<C, B>, S, L>) -> Self
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        let mut tracker = Self {
+            rules,
+            zobrist: Zobrist::default(),
+            history,
+            len: 0,
+        };
+        let key = tracker.key(game);
+        tracker.push(key, 0, false);
+        tracker
+    }
+    /// Returns the rules.
+    pub fn rules(&self) -> DrawRules {
+        self.rules
+    }
+    /// Returns number of positions in the history.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Checks if the history is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Adds the position with given key, that is reached in given turn, to the history.
+    /// `progress` tells if the move to it has made progress. Returns the reason
+    /// of the draw, if the position triggers a rule.
+    /// # Panics
+    /// Panics if the history is full.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::draws::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::summary::EndReason;
+    /// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// let game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+    /// let rules = DrawRules { repetitions: 3, ..DrawRules::default() };
+    /// let mut tracker = DrawTracker::new(rules, [DrawEntry::default(); 8], &game);
+    /// let key = tracker.key(&game);
+    /// assert_eq!(tracker.push(1, 1, true), None);
+    /// assert_eq!(tracker.push(key, 2, false), None);
+    /// assert_eq!(tracker.push(key, 3, false), Some(EndReason::Repetition));
+    /// tracker.pop();
+    /// assert_eq!(tracker.len(), 3);
+    /// ```
+    pub fn push(&mut self, key: u64, turn: usize, progress: bool) -> Option<EndReason> {
+        let quiet_since = match self.history.as_ref()[..self.len].last() {
+            Some(_) if progress => turn,
+            Some(last) => last.quiet_since,
+            None => turn,
+        };
+        let history = self.history.as_mut();
+        assert!(self.len < history.len(), "history is full");
+        history[self.len] = DrawEntry { key, quiet_since };
+        self.len += 1;
+        let history = &self.history.as_ref()[..self.len];
+        let occurrences = history.iter().filter(|entry| entry.key == key).count();
+        if self.rules.repetitions > 0 && occurrences >= self.rules.repetitions {
+            Some(EndReason::Repetition)
+        } else if self.rules.quiet_turns > 0 && turn - quiet_since >= self.rules.quiet_turns {
+            Some(EndReason::NoProgress)
+        } else {
+            None
+        }
+    }
+    /// Removes the last position from the history, the first one isn't removed.
+    pub fn pop(&mut self) {
+        if self.len > 1 {
+            self.len -= 1;
+        }
+    }
+    /// Returns the key of the current position of the game.
+    pub fn key<C, B, S, L>(&self, game: &Game<Board<C, B>, S, L>) -> u64
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        let board = game.board();
+        let cells = (0..board.width() * board.height()).map(|packed| board.unpack_index(packed));
+        let manager = game.player_manager();
+        let turn = manager.current_player * (manager.max_moves + 1) + manager.remaining_moves;
+        self.zobrist.hash(board, cells) ^ self.zobrist.player_key(turn)
+    }
+    /// Checks if the history has no room for more positions.
+    pub fn is_full(&self) -> bool {
+        self.len == self.history.as_ref().len()
+    }
+    /// Makes a move of the current player like [`Game::play`] and adds the position
+    /// to the history. If it triggers a rule, the game ends with a draw.
+    /// # Errors
+    /// Returns [`DrawError::HistoryFull`] if the history is full
+    /// and [`DrawError::Move`] in the same cases as [`Game::play`].
+    /// Then the move isn't made.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::draws::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+    /// let mut tracker = DrawTracker::new(DrawRules::default(), [DrawEntry::default(); 2], &game);
+    /// let (first, second) = (game.board().index(0, 0), game.board().index(2, 2));
+    /// tracker.play(&mut game, first).unwrap();
+    /// assert_eq!(tracker.play(&mut game, second), Err(DrawError::HistoryFull));
+    /// assert_eq!(game.player_manager().current_move, 1);
+    /// ```
+    pub fn play<C, B, S, L>(
+        &mut self,
+        game: &mut Game<Board<C, B>, S, L>,
+        index: usize,
+    ) -> Result<GameMove<usize>, DrawError>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        if self.is_full() {
+            return Err(DrawError::HistoryFull);
+        }
+        let played = game.play(index)?;
+        let progress = is_progress(game.board(), &played.record);
+        let key = self.key(game);
+        let turn = game.player_manager().current_turn;
+        if let Some(reason) = self.push(key, turn, progress) {
+            // Other endings take precedence.
+            let _ = game.end(GameOver::Draw, reason);
+        }
+        Ok(played)
+    }
+    /// Cancels the move like [`Game::undo`] and removes the position from the history.
+    /// # Errors
+    /// Returns [`MoveError`] in the same cases as [`Game::undo`].
+    pub fn undo<C, B, S, L>(
+        &mut self,
+        game: &mut Game<Board<C, B>, S, L>,
+        played: GameMove<usize>,
+    ) -> Result<(), MoveError>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        game.undo(played)?;
+        self.pop();
+        Ok(())
+    }
+}
+
+/// Checks if the move described by `record` has made progress on the board after it:
+/// it has filled or damaged a cross of other player or its cross is adjacent
+/// to crosses or filled cells of other players.
+pub fn is_progress<C, B>(board: &Board<C, B>, record: &MoveRecord<usize>) -> bool
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    if record
+        .prior_owner
+        .map_or(false, |owner| owner != record.player)
+    {
+        return true;
+    }
+    record.prior_kind == CellKind::Empty
+        && board.adjacent(record.index).into_iter().any(|index| {
+            let cell = board.get(index);
+            matches!(cell.kind(), CellKind::Cross | CellKind::Filled)
+                && cell.player() != record.player
+        })
+}
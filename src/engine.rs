@@ -0,0 +1,962 @@
+//! High-level game engine
+//!
+//! This module defines [`Engine`] and [`Data`] traits and two functions built on top of them:
+//! [`make_move`] and [`cancel_move`]. They check whether a move is legal, change the cell,
+//! keep track of players' counters and zones of reach and call IBTS methods, so the only
+//! thing left to the user is storage of cells and traversal of filled chains.
+//!
+//! Zone of reach is tracked with full counting: every cell stores the number of activations
+//! for each player. A cell is activated by crosses and alive filled cells of the player, that
+//! reach it, see [`Engine::reach`]. By default they reach adjacent cells, chains are always
+//! connected through adjacent cells.
+//! Implementations of [`IbtsBoard::revive`] and [`IbtsBoard::kill`] must call
+//! [`activate_around`] and [`deactivate_around`] for every cell that changed its aliveness.
+//!
+//! The crate provides ready-to-use implementation in [`board`](crate::board) module.
+use core::fmt::Display;
+
+use core::ops::IndexMut;
+
+use crate::base::CellKind;
+use crate::counters::{Counter, PlayerCounters};
+use crate::ibts::{Anchor, Anchored, IbtsBoard};
+use crate::rules::RuleSet;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Data of a single cell, used by [`Engine`].
+pub trait Data: Copy {
+    /// Returns the type of cell
+    fn kind(&self) -> CellKind;
+    /// Returns the player of cell
+    fn player(&self) -> usize;
+    /// Sets new type and player of cell. Activations and IBTS
+    /// flags should stay untouched.
+    fn set_content(&mut self, kind: CellKind, player: usize);
+    /// Returns the player, whose cross was filled. Meaningful only for filled cells.
+    fn previous_player(&self) -> usize;
+    /// Sets the player, whose cross was filled.
+    fn set_previous_player(&mut self, player: usize);
+    /// Checks if the cell is activated by the player at least once.
+    fn is_active(&self, player: usize) -> bool;
+    /// Adds one activation for the player.
+    fn activate(&mut self, player: usize);
+    /// Removes one activation for the player.
+    fn deactivate(&mut self, player: usize);
+    /// Checks if the cell is protected: a cross on it can't be filled.
+    /// Default implementation returns `false`.
+    fn is_protected(&self) -> bool {
+        false
+    }
+    /// Checks if the cell is a stronghold: a cross on it is filled only by the second
+    /// attack. Default implementation returns `false`.
+    fn is_stronghold(&self) -> bool {
+        false
+    }
+    /// Returns number of attacks on the cross, that didn't fill it. Default implementation
+    /// returns `0`, cells, that can be strongholds, should store it and override
+    /// [`Data::set_damage`] too.
+    fn damage(&self) -> u8 {
+        0
+    }
+    /// Sets number of attacks on the cross. Default implementation does nothing.
+    fn set_damage(&mut self, _damage: u8) {}
+}
+
+/// The board that can be used by [`make_move`] and [`cancel_move`].
+pub trait Engine: IbtsBoard<Player = usize> {
+    /// The type of cell data
+    type Data: Data;
+
+    /// Returns data of cell at given index
+    fn get(&self, index: Self::Index) -> Self::Data;
+    /// Sets new data of cell at given index
+    fn set(&mut self, index: Self::Index, data: Self::Data);
+    /// Calls `f` with mutable data of cell at given index. Default implementation
+    /// uses [`get`] and [`set`], boards that can give out mutable references
+    /// to their cells should override it, so each call touches storage once.
+    ///
+    /// [`get`]: Engine::get
+    /// [`set`]: Engine::set
+    fn with_cell<R>(&mut self, index: Self::Index, f: impl FnOnce(&mut Self::Data) -> R) -> R {
+        let mut data = self.get(index);
+        let result = f(&mut data);
+        self.set(index, data);
+        result
+    }
+    /// Returns indices of cells, that are activated by the cross or alive filled cell
+    /// at given index. Default implementation returns [`adjacent`] cells, boards,
+    /// where moves are made with other adjacency than connections of chains,
+    /// should override it. Wrappers of engines should return cells of the wrapped one.
+    ///
+    /// [`adjacent`]: crate::base::GameBoard::adjacent
+    fn reach(&self, index: Self::Index) -> Self::Adjacent {
+        self.adjacent(index)
+    }
+    /// Calls `f` with index of every dead filled cell. It's used by rules, that remove
+    /// dead chains, see [`remove_dead_chains`]. Default implementation calls nothing,
+    /// so dead chains of such engines stay on the board. Wrappers of engines should
+    /// visit cells of the wrapped one.
+    fn for_each_dead(&self, _f: impl FnMut(Self::Index)) {}
+    /// The type of counters storage
+    type Counters: PlayerCounters;
+
+    /// Returns counters of players
+    fn counters(&self) -> &Self::Counters;
+    /// Returns mutable counters of players
+    fn counters_mut(&mut self) -> &mut Self::Counters;
+    /// Returns the rules of moves. Default implementation returns
+    /// [`RuleSet::STANDARD`], wrappers of engines should return rules of the wrapped one.
+    fn rules(&self) -> RuleSet {
+        RuleSet::STANDARD
+    }
+}
+
+impl<B, S> Engine for Anchored<B, S>
+where
+    B: Engine,
+    B::Index: PartialEq,
+    S: IndexMut<B::Index, Output = Option<Anchor<B::Index>>>,
+{
+    type Data = B::Data;
+    type Counters = B::Counters;
+
+    fn get(&self, index: Self::Index) -> Self::Data {
+        self.board().get(index)
+    }
+    fn set(&mut self, index: Self::Index, data: Self::Data) {
+        self.board_mut().set(index, data)
+    }
+    fn with_cell<R>(&mut self, index: Self::Index, f: impl FnOnce(&mut Self::Data) -> R) -> R {
+        self.board_mut().with_cell(index, f)
+    }
+    fn reach(&self, index: Self::Index) -> Self::Adjacent {
+        self.board().reach(index)
+    }
+    fn for_each_dead(&self, f: impl FnMut(Self::Index)) {
+        self.board().for_each_dead(f)
+    }
+    fn counters(&self) -> &Self::Counters {
+        self.board().counters()
+    }
+    fn counters_mut(&mut self) -> &mut Self::Counters {
+        self.board_mut().counters_mut()
+    }
+    fn rules(&self) -> RuleSet {
+        self.board().rules()
+    }
+}
+
+/// Makes a move of the `player` at given index. Cross is placed on empty cell
+/// and cross of other player is filled, see [`Engine::rules`]. Returned [`MoveRecord`]
+/// can be passed to [`cancel_move`] to cancel the move.
+/// # Errors
+/// Returns [`MoveError`] if the move is illegal or the rules change cells after moves,
+/// then [`make_move_logged`] should be used. In this case nothing is changed.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::engine::*;
+/// # use crosses_utils::base::CellKind;
+/// # use crosses_utils::counters::*;
+/// let mut board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+/// let (corner, far) = (board.index(0, 0), board.index(2, 2));
+/// let record = make_move(&mut board, corner, 0).unwrap();
+/// assert_eq!(board.get(corner).kind(), CellKind::Cross);
+/// assert_eq!(board.counters().get(Counter::Crosses, 0), 1);
+/// assert_eq!(board.counters().get(Counter::Moves, 0), 3);
+/// assert_eq!(record.prior_kind, CellKind::Empty);
+/// assert_eq!(record.counters_delta, CountersDelta { crosses: 1, moves: 2 });
+/// assert_eq!(make_move(&mut board, far, 0), Err(MoveError::Unreachable));
+/// ```
+pub fn make_move<E: Engine>(
+    engine: &mut E,
+    index: E::Index,
+    player: usize,
+) -> Result<MoveRecord<E::Index>, MoveError> {
+    make_move_logged(engine, index, player, &mut ())
+}
+
+/// Makes a move like [`make_move`] and stores changes of cells, that the rules make after
+/// the move, in the `log`, see [`RuleSet::needs_log`]. Returned [`MoveRecord`] and the log
+/// can be passed to [`cancel_move_logged`] to cancel the move.
+/// # Errors
+/// Returns [`MoveError`] if the move is illegal or the rules change cells after moves
+/// and the log can't store changes. In this case nothing is changed.
+/// # Example
+/// ```
+/// # use crosses_utils::base::*;
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::engine::*;
+/// # use crosses_utils::ibts::*;
+/// # use crosses_utils::rules::*;
+/// let mut board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+/// board.set_rules(RuleSet { convert_chains: true, ..RuleSet::STANDARD });
+/// let mut log = ChangeStack::new([Change::default(); 16]);
+/// let moves = [((0, 0), 0), ((2, 2), 1), ((1, 1), 0), ((2, 1), 1), ((2, 1), 0)];
+/// for ((x, y), player) in moves {
+///     let index = board.index(x, y);
+///     make_move_logged(&mut board, index, player, &mut log).unwrap();
+/// }
+/// let initial = board.clone();
+/// let (center, side) = (board.index(1, 1), board.index(2, 1));
+/// assert_eq!(make_move(&mut board, center, 1), Err(MoveError::NoChangeLog));
+/// let record = make_move_logged(&mut board, center, 1, &mut log).unwrap();
+/// assert_eq!(record.changes, 1);
+/// assert_eq!(log.changes(), [Change { index: side, player: 0, previous_player: 1 }]);
+/// assert_eq!(board.player(side), 1);
+/// assert!(board.is_alive(side));
+/// cancel_move_logged(&mut board, record, &mut log).unwrap();
+/// assert_eq!(board.player(side), 0);
+/// assert_eq!(board.counters(), initial.counters());
+/// ```
+pub fn make_move_logged<E: Engine, L: ChangeLog<E::Index>>(
+    engine: &mut E,
+    index: E::Index,
+    player: usize,
+    log: &mut L,
+) -> Result<MoveRecord<E::Index>, MoveError> {
+    let data = engine.get(index);
+    let rules = engine.rules();
+    rules.check_move(data, player)?;
+    if rules.needs_log() && !log.is_enabled() {
+        return Err(MoveError::NoChangeLog);
+    }
+    let (crosses, moves) = mover_counters(engine, player);
+    let was_important = engine.is_important(index);
+    let prior_owner = match data.kind() {
+        CellKind::Empty => {
+            place_cross(engine, index, data, player);
+            None
+        }
+        _ if rules.damages(data) => {
+            engine.with_cell(index, |data| data.set_damage(data.damage() + 1));
+            return Ok(MoveRecord {
+                index,
+                player,
+                prior_kind: data.kind(),
+                prior_owner: Some(data.player()),
+                counters_delta: CountersDelta::default(),
+                was_important,
+                changes: 0,
+                prior_damage: data.damage(),
+            });
+        }
+        _ => {
+            place_filled(engine, index, data, player);
+            Some(data.player())
+        }
+    };
+    let changes = match prior_owner {
+        Some(owner) if owner != player && rules.convert_chains => {
+            convert_killed(engine, index, owner, player, log)
+        }
+        _ => 0,
+    };
+    let (new_crosses, new_moves) = mover_counters(engine, player);
+    Ok(MoveRecord {
+        index,
+        player,
+        prior_kind: data.kind(),
+        prior_owner,
+        counters_delta: CountersDelta {
+            crosses: new_crosses - crosses,
+            moves: new_moves - moves,
+        },
+        was_important,
+        changes,
+        prior_damage: data.damage(),
+    })
+}
+
+/// Cancels the move described by `record`, returning the cell to its previous state.
+/// Moves should be cancelled in reverse order.
+/// # Errors
+/// Returns [`MoveError::NothingToCancel`] if the cell is empty or border and
+/// [`MoveError::RecordMismatch`] if the cell doesn't look like the move was made there.
+/// In this case nothing is changed.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::engine::*;
+/// let mut board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+/// let initial = board.clone();
+/// let (a, b) = (board.index(0, 0), board.index(1, 1));
+/// let first = make_move(&mut board, a, 0).unwrap();
+/// let second = make_move(&mut board, b, 0).unwrap();
+/// make_move(&mut board, b, 1).unwrap_err();
+/// cancel_move(&mut board, second).unwrap();
+/// cancel_move(&mut board, first).unwrap();
+/// assert_eq!(board, initial);
+/// ```
+pub fn cancel_move<E: Engine>(
+    engine: &mut E,
+    record: MoveRecord<E::Index>,
+) -> Result<(), MoveError> {
+    cancel_move_logged(engine, record, &mut ())
+}
+
+/// Cancels the move like [`cancel_move`] and reverts changes of cells made by the rules
+/// after the move, taking them from the `log`, see [`make_move_logged`].
+/// # Errors
+/// Returns [`MoveError`] in the same cases as [`cancel_move`], [`MoveError::NoChangeLog`]
+/// if the move made changes and the log can't store them and [`MoveError::RecordMismatch`]
+/// if the log doesn't have them. In this case nothing is changed.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::engine::*;
+/// # use crosses_utils::rules::*;
+/// # use crosses_utils::validate::validate_board;
+/// let mut board = Board::new(3, 3, 3, [Cell::new(); 25], [0; 25]);
+/// board.set_rules(RuleSet {
+///     fill_requires_reach: false,
+///     self_fill: true,
+///     convert_chains: true,
+///     remove_dead_chains: false,
+///     strong_crosses: true,
+/// });
+/// let mut log = ChangeStack::new([Change::default(); 16]);
+/// let moves = [
+///     (0, 0, 0), (2, 0, 2), (0, 1, 0), (1, 1, 2), (0, 1, 1), (1, 1, 2), (1, 1, 1),
+///     (0, 1, 1), (2, 2, 1), (2, 2, 0), (1, 0, 1), (1, 0, 1), (2, 2, 2), (1, 0, 2),
+/// ];
+/// let mut records = [None; 14];
+/// for (record, (x, y, player)) in records.iter_mut().zip(moves) {
+///     let index = board.index(x, y);
+///     *record = Some(make_move_logged(&mut board, index, player, &mut log).unwrap());
+/// }
+/// for record in records.into_iter().rev().flatten() {
+///     cancel_move_logged(&mut board, record, &mut log).unwrap();
+///     assert!(validate_board(&board, &mut [0; 25]).is_valid());
+/// }
+/// ```
+pub fn cancel_move_logged<E: Engine, L: ChangeLog<E::Index>>(
+    engine: &mut E,
+    record: MoveRecord<E::Index>,
+    log: &mut L,
+) -> Result<(), MoveError> {
+    let index = record.index;
+    if record.changes > 0 && !log.is_enabled() {
+        return Err(MoveError::NoChangeLog);
+    }
+    let changes = log.changes();
+    if changes.len() < record.changes {
+        return Err(MoveError::RecordMismatch);
+    }
+    let changes = &changes[changes.len() - record.changes..];
+    let is_removal = |change: &Change<E::Index>| engine.kind(change.index) == CellKind::Empty;
+    if changes.iter().any(|change| {
+        !is_removal(change)
+            && (engine.kind(change.index) != CellKind::Filled
+                || engine.player(change.index) != record.player)
+    }) {
+        return Err(MoveError::RecordMismatch);
+    }
+    let mut data = engine.get(index);
+    // The filled cell of the move itself could be removed at the end of the turn,
+    // then it's checked as one of removed cells, that it will be after reverting them.
+    if data.kind() == CellKind::Empty && record.prior_kind != CellKind::Empty {
+        if let Some(change) = changes.iter().find(|change| {
+            is_removal(change)
+                && change.player == record.player
+                && Some(change.previous_player) == record.prior_owner
+        }) {
+            data.set_content(CellKind::Filled, change.player);
+            data.set_previous_player(change.previous_player);
+        }
+    }
+    let expected = match record.prior_kind {
+        CellKind::Empty => CellKind::Cross,
+        _ => CellKind::Filled,
+    };
+    // The cross stays on the cell, if the move only damaged it.
+    let damaged = record.prior_kind == CellKind::Cross && data.kind() == CellKind::Cross;
+    match data.kind() {
+        _ if damaged => {
+            let expected_damage = record.prior_damage.wrapping_add(1);
+            if Some(data.player()) != record.prior_owner || data.damage() != expected_damage {
+                return Err(MoveError::RecordMismatch);
+            }
+        }
+        CellKind::Empty | CellKind::Border => return Err(MoveError::NothingToCancel),
+        kind if kind != expected || data.player() != record.player => {
+            return Err(MoveError::RecordMismatch)
+        }
+        CellKind::Filled if Some(data.previous_player()) != record.prior_owner => {
+            return Err(MoveError::RecordMismatch)
+        }
+        _ => {}
+    }
+    // Removed cells are empty, converted cells are filled by the player of the move.
+    for _ in 0..record.changes {
+        if let Some(change) = log.pop() {
+            match engine.kind(change.index) {
+                CellKind::Empty => restore(engine, change),
+                _ => release(engine, change, record.player),
+            }
+        }
+    }
+    let data = engine.get(index);
+    match data.kind() {
+        _ if damaged => {}
+        CellKind::Cross => remove_cross(engine, index, data),
+        _ => remove_filled(engine, index, data),
+    }
+    engine.with_cell(index, |data| data.set_damage(record.prior_damage));
+    // Later moves could make the cell an activator of other chains, so importance is only
+    // restored, extra importance costs just a search.
+    if record.was_important {
+        engine.set_important(index, true);
+    }
+    Ok(())
+}
+
+/// Removes dead chains, if the rules say so, see [`RuleSet::remove_dead_chains`]:
+/// every dead filled cell becomes empty. Removals are stored in the `log`, so it should
+/// be called at the end of the turn and returned number of removed cells should be added
+/// to [`MoveRecord::changes`] of the last move, then [`cancel_move_logged`] reverts them
+/// together with the move. [`Game`](crate::game::Game) does it by itself.
+/// # Errors
+/// Returns [`MoveError::NoChangeLog`] if the log can't store changes.
+/// In this case nothing is changed.
+/// # Example
+/// ```
+/// # use crosses_utils::base::*;
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::engine::*;
+/// # use crosses_utils::rules::*;
+/// let mut board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+/// board.set_rules(RuleSet { remove_dead_chains: true, ..RuleSet::STANDARD });
+/// let mut log = ChangeStack::new([Change::default(); 16]);
+/// let moves = [((0, 0), 0), ((2, 2), 1), ((1, 1), 0), ((2, 1), 1), ((2, 1), 0)];
+/// for ((x, y), player) in moves {
+///     let index = board.index(x, y);
+///     make_move_logged(&mut board, index, player, &mut log).unwrap();
+/// }
+/// let initial = board.clone();
+/// let (center, side) = (board.index(1, 1), board.index(2, 1));
+/// let mut record = make_move_logged(&mut board, center, 1, &mut log).unwrap();
+/// assert_eq!(board.get(side).kind(), CellKind::Filled);
+/// record.changes += remove_dead_chains(&mut board, &mut log).unwrap();
+/// assert_eq!(record.changes, 1);
+/// assert_eq!(board.get(side).kind(), CellKind::Empty);
+/// cancel_move_logged(&mut board, record, &mut log).unwrap();
+/// assert_eq!(board.player(side), 0);
+/// assert_eq!(board.counters(), initial.counters());
+/// ```
+pub fn remove_dead_chains<E: Engine, L: ChangeLog<E::Index>>(
+    engine: &mut E,
+    log: &mut L,
+) -> Result<usize, MoveError> {
+    if !engine.rules().remove_dead_chains {
+        return Ok(0);
+    }
+    if !log.is_enabled() {
+        return Err(MoveError::NoChangeLog);
+    }
+    let start = log.changes().len();
+    let board: &E = engine;
+    board.for_each_dead(|index| {
+        let data = board.get(index);
+        log.push(Change {
+            index,
+            player: data.player(),
+            previous_player: data.previous_player(),
+        })
+    });
+    for i in start..log.changes().len() {
+        remove(engine, log.changes()[i].index);
+    }
+    Ok(log.changes().len() - start)
+}
+
+/// Everything that is needed to cancel a move. It is produced by [`make_move`]
+/// and consumed by [`cancel_move`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct MoveRecord<I> {
+    /// Index of the cell
+    pub index: I,
+    /// The player, who made the move
+    pub player: usize,
+    /// Type of the cell before the move
+    pub prior_kind: CellKind,
+    /// The player, whose cross was filled, if any
+    pub prior_owner: Option<usize>,
+    /// Change of the counters of the player who made the move
+    pub counters_delta: CountersDelta,
+    /// Importance of the cell before the move
+    pub was_important: bool,
+    /// Number of changes of cells made by the rules after the move, see [`ChangeLog`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub changes: usize,
+    /// Damage of the cross before the move, see [`Data::damage`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub prior_damage: u8,
+}
+
+impl<I> MoveRecord<I> {
+    /// Converts index of the record, e.g. to store records with small indices.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::engine::*;
+    /// let mut board = Board::new(16, 16, 2, [Cell::new(); 324], [0; 324]);
+    /// let corner = board.index(0, 0);
+    /// let record = make_move(&mut board, corner, 0).unwrap();
+    /// let small = record.map_index(|i| board.pack_index(i) as u8);
+    /// assert_eq!(small.index, 0);
+    /// let record = small.map_index(|i| board.unpack_index(i as usize));
+    /// cancel_move(&mut board, record).unwrap();
+    /// ```
+    pub fn map_index<J>(self, f: impl FnOnce(I) -> J) -> MoveRecord<J> {
+        MoveRecord {
+            index: f(self.index),
+            player: self.player,
+            prior_kind: self.prior_kind,
+            prior_owner: self.prior_owner,
+            counters_delta: self.counters_delta,
+            was_important: self.was_important,
+            changes: self.changes,
+            prior_damage: self.prior_damage,
+        }
+    }
+}
+
+/// A change of a cell made by the rules after the move itself, e.g. conversion
+/// or removal of a chain, see [`RuleSet::needs_log`]. The cell was filled before the change.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Change<I> {
+    /// Index of the cell
+    pub index: I,
+    /// The player of the cell before the change
+    pub player: usize,
+    /// The player, whose cross was filled in the cell before the change
+    pub previous_player: usize,
+}
+
+/// Storage of [`Change`]s, used by [`make_move_logged`] and [`cancel_move_logged`].
+/// Changes of all moves are stored one after another, so moves should be cancelled
+/// in reverse order. It is implemented for [`ChangeStack`], Vec (requires `alloc` feature)
+/// and for `()`, which can't store changes.
+pub trait ChangeLog<I> {
+    /// Returns stored changes, the latest one is the last.
+    fn changes(&self) -> &[Change<I>];
+    /// Stores new change.
+    fn push(&mut self, change: Change<I>);
+    /// Removes and returns the latest change.
+    fn pop(&mut self) -> Option<Change<I>>;
+    /// Checks if the log can store changes. Default implementation returns `true`.
+    fn is_enabled(&self) -> bool {
+        true
+    }
+}
+impl<I> ChangeLog<I> for () {
+    fn changes(&self) -> &[Change<I>] {
+        &[]
+    }
+    fn push(&mut self, _change: Change<I>) {}
+    fn pop(&mut self) -> Option<Change<I>> {
+        None
+    }
+    fn is_enabled(&self) -> bool {
+        false
+    }
+}
+
+/// [`ChangeLog`] over a buffer, e.g. an array.
+/// # Panics
+/// [`ChangeLog::push`] panics if the buffer is full.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ChangeStack<S> {
+    buffer: S,
+    len: usize,
+}
+impl<S> ChangeStack<S> {
+    /// Creates empty stack, that keeps changes in the buffer.
+    pub fn new(buffer: S) -> Self {
+        Self { buffer, len: 0 }
+    }
+    /// Returns number of changes in the stack.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Checks if the stack has no changes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Unwraps the buffer.
+    pub fn into_inner(self) -> S {
+        self.buffer
+    }
+}
+impl<I: Copy, S: AsRef<[Change<I>]> + AsMut<[Change<I>]>> ChangeLog<I> for ChangeStack<S> {
+    fn changes(&self) -> &[Change<I>] {
+        &self.buffer.as_ref()[..self.len]
+    }
+    fn push(&mut self, change: Change<I>) {
+        let buffer = self.buffer.as_mut();
+        assert!(self.len < buffer.len(), "stack is full");
+        buffer[self.len] = change;
+        self.len += 1;
+    }
+    fn pop(&mut self) -> Option<Change<I>> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(self.buffer.as_ref()[self.len])
+    }
+}
+#[cfg(feature = "alloc")]
+impl<I> ChangeLog<I> for Vec<Change<I>> {
+    fn changes(&self) -> &[Change<I>] {
+        self
+    }
+    fn push(&mut self, change: Change<I>) {
+        Vec::push(self, change)
+    }
+    fn pop(&mut self) -> Option<Change<I>> {
+        Vec::pop(self)
+    }
+}
+
+/// Change of counters of a player.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct CountersDelta {
+    /// Change of number of crosses
+    pub crosses: isize,
+    /// Change of number of available moves
+    pub moves: isize,
+}
+
+/// Checks whether the `player` can make a move at the cell with standard rules,
+/// see [`RuleSet::check_move`] for other rules.
+pub fn check_move<D: Data>(data: D, player: usize) -> Result<(), MoveError> {
+    RuleSet::STANDARD.check_move(data, player)
+}
+
+/// Adds one activation for the player to the cell at given index.
+/// Border cells are ignored.
+pub fn activate<E: Engine>(engine: &mut E, index: E::Index, player: usize) {
+    let rules = engine.rules();
+    let became_movable = engine.with_cell(index, |data| {
+        if data.kind() == CellKind::Border {
+            return false;
+        }
+        let was_active = data.is_active(player);
+        data.activate(player);
+        !was_active && rules.needs_reach(*data, player)
+    });
+    if became_movable {
+        engine.counters_mut().increment(Counter::Moves, player);
+    }
+}
+/// Removes one activation for the player from the cell at given index.
+/// Border cells are ignored.
+pub fn deactivate<E: Engine>(engine: &mut E, index: E::Index, player: usize) {
+    let rules = engine.rules();
+    let became_unmovable = engine.with_cell(index, |data| {
+        if data.kind() == CellKind::Border {
+            return false;
+        }
+        data.deactivate(player);
+        !data.is_active(player) && rules.needs_reach(*data, player)
+    });
+    if became_unmovable {
+        engine.counters_mut().decrement(Counter::Moves, player);
+    }
+}
+/// Activates all cells in reach of given index for the player, see [`Engine::reach`].
+pub fn activate_around<E: Engine>(engine: &mut E, index: E::Index, player: usize) {
+    for i in engine.reach(index) {
+        activate(engine, i, player)
+    }
+}
+/// Deactivates all cells in reach of given index for the player, see [`Engine::reach`].
+pub fn deactivate_around<E: Engine>(engine: &mut E, index: E::Index, player: usize) {
+    for i in engine.reach(index) {
+        deactivate(engine, i, player)
+    }
+}
+
+/// Errors of [`make_move`] and [`cancel_move`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum MoveError {
+    /// The cell is border
+    OutOfBounds,
+    /// The cell is already filled
+    Occupied,
+    /// The cell has cross of the same player
+    SelfFill,
+    /// The cell is out of player's zone of reach
+    Unreachable,
+    /// The cell has no move to cancel
+    NothingToCancel,
+    /// The cell doesn't match the record of the move
+    RecordMismatch,
+    /// The game has already ended
+    GameOver,
+    /// The cell has cross, that can't be filled
+    ProtectedCell,
+    /// The rules change cells after moves, but changes can't be stored
+    NoChangeLog,
+}
+impl Display for MoveError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MoveError::OutOfBounds => write!(f, "cell is out of bounds"),
+            MoveError::Occupied => write!(f, "cell is already filled"),
+            MoveError::SelfFill => write!(f, "player can't fill their own cross"),
+            MoveError::Unreachable => write!(f, "cell is out of player's reach"),
+            MoveError::NothingToCancel => write!(f, "cell has no move to cancel"),
+            MoveError::RecordMismatch => write!(f, "cell doesn't match the record of the move"),
+            MoveError::GameOver => write!(f, "game has already ended"),
+            MoveError::ProtectedCell => write!(f, "cell is protected"),
+            MoveError::NoChangeLog => write!(f, "changes of cells can't be stored"),
+        }
+    }
+}
+
+fn mover_counters<E: Engine>(engine: &E, player: usize) -> (isize, isize) {
+    let counters = engine.counters();
+    (
+        counters.get(Counter::Crosses, player) as isize,
+        counters.get(Counter::Moves, player) as isize,
+    )
+}
+pub(crate) fn is_movable<D: Data>(data: D, player: usize) -> bool {
+    match data.kind() {
+        CellKind::Empty => true,
+        CellKind::Cross => data.player() != player && !data.is_protected(),
+        _ => false,
+    }
+}
+/// Changes moves counters of players, that are able to fill the cross
+/// of `owner`, because it was either placed or removed.
+fn update_fillers<E: Engine>(engine: &mut E, data: E::Data, owner: usize, placed: bool) {
+    let (players, rules) = (engine.counters().players(), engine.rules());
+    for player in (0..players).filter(|p| rules.can_fill(data, owner, *p)) {
+        if placed {
+            engine.counters_mut().increment(Counter::Moves, player)
+        } else {
+            engine.counters_mut().decrement(Counter::Moves, player)
+        }
+    }
+}
+/// Changes moves counters of players, that can fill the cross of `owner` out of their
+/// zone of reach, because it was either placed on an empty cell or removed from it.
+/// Empty cells are already moves of players, who reach them.
+fn update_remote_fillers<E: Engine>(engine: &mut E, data: E::Data, owner: usize, placed: bool) {
+    let (players, rules) = (engine.counters().players(), engine.rules());
+    if rules.fill_requires_reach {
+        return;
+    }
+    for player in (0..players).filter(|p| rules.can_fill(data, owner, *p) && !data.is_active(*p)) {
+        if placed {
+            engine.counters_mut().increment(Counter::Moves, player)
+        } else {
+            engine.counters_mut().decrement(Counter::Moves, player)
+        }
+    }
+}
+/// Changes moves counters of other players, who reach the cell, but can't fill the cross
+/// of `owner` on it, because it was either placed on the empty cell or removed from it.
+fn update_blocked<E: Engine>(engine: &mut E, data: E::Data, owner: usize, placed: bool) {
+    let (players, rules) = (engine.counters().players(), engine.rules());
+    let blocked = |p: &usize| *p != owner && data.is_active(*p) && !rules.can_fill(data, owner, *p);
+    for player in (0..players).filter(blocked) {
+        if placed {
+            engine.counters_mut().decrement(Counter::Moves, player)
+        } else {
+            engine.counters_mut().increment(Counter::Moves, player)
+        }
+    }
+}
+fn is_supported<E: Engine>(engine: &E, index: E::Index, player: usize) -> bool {
+    engine.adjacent(index).into_iter().any(|i| {
+        engine.player(i) == player
+            && match engine.kind(i) {
+                CellKind::Cross => true,
+                CellKind::Filled => engine.is_alive(i),
+                _ => false,
+            }
+    })
+}
+fn place_cross<E: Engine>(engine: &mut E, index: E::Index, mut data: E::Data, player: usize) {
+    data.set_content(CellKind::Cross, player);
+    // The cell stays a move of the player, if they can fill their own cross.
+    if !engine.rules().can_fill(data, player, player) {
+        engine.counters_mut().decrement(Counter::Moves, player);
+    }
+    engine.set(index, data);
+    update_remote_fillers(engine, data, player, true);
+    update_blocked(engine, data, player, true);
+    engine.counters_mut().increment(Counter::Crosses, player);
+    activate_around(engine, index, player);
+    engine.on_place_cross(index);
+}
+fn place_filled<E: Engine>(engine: &mut E, index: E::Index, mut data: E::Data, player: usize) {
+    let previous_player = data.player();
+    update_fillers(engine, data, previous_player, false);
+    data.set_content(CellKind::Filled, player);
+    data.set_damage(0);
+    data.set_previous_player(previous_player);
+    engine.set(index, data);
+    engine
+        .counters_mut()
+        .decrement(Counter::Crosses, previous_player);
+    deactivate_around(engine, index, previous_player);
+    // Own cross could be the activator of adjacent chains, they are checked
+    // without it before they support the cell.
+    let self_fill = previous_player == player;
+    if self_fill {
+        engine.on_remove_cross(index, previous_player);
+    }
+    // The cell may be reached only through the starting cell or filled without reach,
+    // then it has no activator and only the cross of previous player is gone.
+    if is_supported(engine, index, player) {
+        engine.set_alive(index, true);
+        activate_around(engine, index, player);
+        engine.on_place_filled(index, previous_player);
+    } else if !self_fill {
+        engine.on_remove_cross(index, previous_player);
+    }
+}
+fn remove_cross<E: Engine>(engine: &mut E, index: E::Index, mut data: E::Data) {
+    let player = data.player();
+    let was_move = engine.rules().can_fill(data, player, player);
+    update_blocked(engine, data, player, false);
+    data.set_content(CellKind::Empty, 0);
+    engine.set(index, data);
+    update_remote_fillers(engine, data, player, false);
+    if data.is_active(player) && !was_move {
+        engine.counters_mut().increment(Counter::Moves, player);
+    }
+    engine.counters_mut().decrement(Counter::Crosses, player);
+    deactivate_around(engine, index, player);
+    engine.on_remove_cross(index, player);
+}
+fn remove_filled<E: Engine>(engine: &mut E, index: E::Index, data: E::Data) {
+    let (previous_player, player) = (data.player(), data.previous_player());
+    if engine.is_alive(index) {
+        deactivate_around(engine, index, previous_player);
+    }
+    engine.set_alive(index, false);
+    let data = engine.with_cell(index, |data| {
+        data.set_content(CellKind::Cross, player);
+        data.set_previous_player(0);
+        *data
+    });
+    update_fillers(engine, data, player, true);
+    engine.counters_mut().increment(Counter::Crosses, player);
+    activate_around(engine, index, player);
+    engine.on_remove_filled(index, previous_player);
+}
+/// Converts chains of `owner`, killed by the fill at given index, to `player`
+/// and returns number of converted cells.
+fn convert_killed<E: Engine, L: ChangeLog<E::Index>>(
+    engine: &mut E,
+    index: E::Index,
+    owner: usize,
+    player: usize,
+    log: &mut L,
+) -> usize {
+    let is_killed = |engine: &E, i| {
+        engine.kind(i) == CellKind::Filled && engine.player(i) == owner && !engine.is_alive(i)
+    };
+    let start = log.changes().len();
+    for i in engine.adjacent(index) {
+        if is_killed(engine, i) {
+            convert(engine, i, owner, player, log);
+        }
+    }
+    // Converted cells are visited breadth-first, the log is the queue.
+    let mut head = start;
+    while head < log.changes().len() {
+        let current = log.changes()[head].index;
+        head += 1;
+        for i in engine.adjacent(current) {
+            if is_killed(engine, i) {
+                convert(engine, i, owner, player, log);
+            }
+        }
+    }
+    log.changes().len() - start
+}
+/// Converts dead filled cell of `owner` to `player`. The cell activates nothing,
+/// so it's the same as the fill of a cross, that had no activations.
+fn convert<E: Engine, L: ChangeLog<E::Index>>(
+    engine: &mut E,
+    index: E::Index,
+    owner: usize,
+    player: usize,
+    log: &mut L,
+) {
+    let previous_player = engine.with_cell(index, |data| {
+        let previous_player = data.previous_player();
+        data.set_content(CellKind::Filled, player);
+        previous_player
+    });
+    log.push(Change {
+        index,
+        player: owner,
+        previous_player,
+    });
+    if is_supported(engine, index, player) {
+        engine.set_alive(index, true);
+        activate_around(engine, index, player);
+        engine.on_place_filled(index, owner);
+    }
+}
+/// Reverts the change of filled cell of `player`, making it dead filled cell of its owner.
+fn release<E: Engine>(engine: &mut E, change: Change<E::Index>, player: usize) {
+    let index = change.index;
+    if engine.is_alive(index) {
+        deactivate_around(engine, index, player);
+        engine.set_alive(index, false);
+    }
+    engine.with_cell(index, |data| {
+        data.set_content(CellKind::Filled, change.player);
+        data.set_previous_player(change.previous_player);
+    });
+    engine.on_release_filled(index, player);
+}
+/// Removes dead filled cell, making it empty. The cell activates nothing, so only
+/// moves of players, who reach it, are added.
+fn remove<E: Engine>(engine: &mut E, index: E::Index) {
+    let data = engine.with_cell(index, |data| {
+        data.set_content(CellKind::Empty, 0);
+        data.set_previous_player(0);
+        *data
+    });
+    engine.set_important(index, false);
+    update_reachers(engine, data, true);
+}
+/// Reverts the removal of dead filled cell.
+fn restore<E: Engine>(engine: &mut E, change: Change<E::Index>) {
+    let data = engine.get(change.index);
+    update_reachers(engine, data, false);
+    engine.with_cell(change.index, |data| {
+        data.set_content(CellKind::Filled, change.player);
+        data.set_previous_player(change.previous_player);
+    });
+}
+/// Changes moves counters of players, who reach the empty cell,
+/// because it was either freed or occupied.
+fn update_reachers<E: Engine>(engine: &mut E, data: E::Data, freed: bool) {
+    let players = engine.counters().players();
+    for player in (0..players).filter(|p| data.is_active(*p)) {
+        if freed {
+            engine.counters_mut().increment(Counter::Moves, player)
+        } else {
+            engine.counters_mut().decrement(Counter::Moves, player)
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Game events
+//!
+//! This module defines [`GameEvent`], a single change of the game caused by a move.
+//! [`Game::play_with_events`] passes events of the move to a callback in the order
+//! they happen, so UIs and network layers can follow the game without re-deriving
+//! effects of moves. Events of chains are reported by the default hooks of
+//! [`IbtsBoard`], see [`Game::enable_stats`].
+//! # Example
+//! ```
+//! # use crosses_utils::base::CellKind;
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::engine::Data;
+//! # use crosses_utils::events::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::summary::EliminationReason;
+//! let board = Board::new(2, 1, 2, [Cell::new(); 12], [0; 12]);
+//! let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+//! for x in [0, 1] {
+//!     game.play(game.board().index(x, 0)).unwrap();
+//! }
+//! let mut events = [None; 4];
+//! let mut len = 0;
+//! let index = game.board().index(1, 0);
+//! game.play_with_events(index, |event| {
+//!     events[len] = Some(event);
+//!     len += 1;
+//! })
+//! .unwrap();
+//! assert_eq!(len, 3);
+//! match events[0] {
+//!     Some(GameEvent::CellChanged { index: changed, from, to }) => {
+//!         assert_eq!(changed, index);
+//!         assert_eq!((from.kind(), from.player()), (CellKind::Cross, 1));
+//!         assert_eq!((to.kind(), to.player()), (CellKind::Filled, 0));
+//!     }
+//!     _ => panic!("the cell should change first"),
+//! }
+//! let eliminated = GameEvent::PlayerEliminated {
+//!     player: 1,
+//!     reason: EliminationReason::OutOfCrosses,
+//! };
+//! assert_eq!(events[1], Some(eliminated));
+//! assert_eq!(events[2], Some(GameEvent::GameEnded { result: GameOver::Win(0) }));
+//! ```
+//!
+//! [`Game::play_with_events`]: crate::game::Game::play_with_events
+//! [`Game::enable_stats`]: crate::game::Game::enable_stats
+//! [`IbtsBoard`]: crate::ibts::IbtsBoard
+use crate::player_manager::GameOver;
+use crate::summary::EliminationReason;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A change of the game, see module docs.
+/// `I` - is type of indices of cells, `D` - is type of data of cells.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GameEvent<I, D> {
+    /// Type or player of the cell has changed
+    CellChanged {
+        /// Index of the cell
+        index: I,
+        /// Data of the cell before the change
+        from: D,
+        /// Data of the cell after the change
+        to: D,
+    },
+    /// The chain of filled cells, that contains the seed, has become alive
+    ChainRevived {
+        /// Index of a cell of the chain
+        seed: I,
+    },
+    /// The chain of filled cells, that contains the seed, has become dead
+    ChainKilled {
+        /// Index of a cell of the chain
+        seed: I,
+    },
+    /// The player has lost
+    PlayerEliminated {
+        /// The player
+        player: usize,
+        /// Reason of the loss
+        reason: EliminationReason,
+    },
+    /// The game has ended
+    GameEnded {
+        /// Result of the game
+        result: GameOver,
+    },
+}
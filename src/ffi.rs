@@ -0,0 +1,356 @@
+//! C API
+//!
+//! This module exports flat `extern "C"` functions over [`Game`] with [`Board`], so the
+//! engine can be embedded into C, C++ or Swift apps. A game is created with
+//! [`crosses_game_new`], used through an opaque pointer and destroyed with
+//! [`crosses_game_free`]. Cells are addressed by coordinates, lists of moves use packed
+//! indices (see [`Board::pack_index`]). Enums have fixed values, which won't change.
+//! Functions check their arguments and return null or [`CrossesStatus`] instead of
+//! panicking, because `ffi` feature doesn't require `std` to catch panics.
+//! Requires `ffi` feature.
+//!
+//! Matching C declarations:
+//! ```c
+//! typedef struct CrossesGame CrossesGame;
+//! CrossesGame *crosses_game_new(uint32_t width, uint32_t height, uint32_t players, uint32_t max_moves);
+//! void crosses_game_free(CrossesGame *game);
+//! uint32_t crosses_game_width(const CrossesGame *game);
+//! uint32_t crosses_game_height(const CrossesGame *game);
+//! int32_t crosses_game_play(CrossesGame *game, uint32_t x, uint32_t y);
+//! int32_t crosses_game_undo(CrossesGame *game);
+//! int32_t crosses_game_cell(const CrossesGame *game, uint32_t x, uint32_t y, int32_t *kind, uint32_t *player);
+//! size_t crosses_game_legal_moves(const CrossesGame *game, uint32_t *out, size_t capacity);
+//! uint32_t crosses_game_current_player(const CrossesGame *game);
+//! uint32_t crosses_game_remaining_moves(const CrossesGame *game);
+//! int32_t crosses_game_state(const CrossesGame *game, uint32_t *winner);
+//! ```
+//! # Example
+//! ```
+//! # use crosses_utils::ffi::*;
+//! unsafe {
+//!     let game = crosses_game_new(3, 3, 2, 1);
+//!     assert!(!game.is_null());
+//!     assert_eq!(crosses_game_play(game, 1, 1), CrossesStatus::Unreachable);
+//!     assert_eq!(crosses_game_play(game, 0, 0), CrossesStatus::Ok);
+//!     let (mut kind, mut player) = (CrossesCellKind::Empty, 0);
+//!     assert_eq!(crosses_game_cell(game, 0, 0, &mut kind, &mut player), CrossesStatus::Ok);
+//!     assert_eq!((kind, player), (CrossesCellKind::Cross, 0));
+//!     let mut moves = [0; 9];
+//!     assert_eq!(crosses_game_legal_moves(game, moves.as_mut_ptr(), moves.len()), 1);
+//!     assert_eq!(moves[0], 8);
+//!     assert_eq!(crosses_game_undo(game), CrossesStatus::Ok);
+//!     assert_eq!(crosses_game_current_player(game), 0);
+//!     crosses_game_free(game);
+//! }
+//! ```
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::base::CellKind;
+use crate::board::{Board, Cell, MAX_PLAYERS};
+use crate::engine::{Data, Engine, MoveError};
+use crate::game::{Game, GameMove};
+use crate::player_manager::{GameOver, GameState, LoseData, PlayerManager};
+
+/// Result of functions, that change the game.
+#[repr(i32)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CrossesStatus {
+    /// Success
+    Ok = 0,
+    /// See [`MoveError::OutOfBounds`]
+    OutOfBounds = 1,
+    /// See [`MoveError::Occupied`]
+    Occupied = 2,
+    /// See [`MoveError::SelfFill`]
+    SelfFill = 3,
+    /// See [`MoveError::Unreachable`]
+    Unreachable = 4,
+    /// See [`MoveError::NothingToCancel`]
+    NothingToCancel = 5,
+    /// See [`MoveError::RecordMismatch`]
+    RecordMismatch = 6,
+    /// See [`MoveError::GameOver`]
+    GameOver = 7,
+    /// A pointer is null
+    NullPointer = 8,
+    /// See [`MoveError::ProtectedCell`]
+    ProtectedCell = 9,
+    /// See [`MoveError::NoChangeLog`]
+    NoChangeLog = 10,
+}
+impl From<MoveError> for CrossesStatus {
+    fn from(error: MoveError) -> Self {
+        match error {
+            MoveError::OutOfBounds => CrossesStatus::OutOfBounds,
+            MoveError::Occupied => CrossesStatus::Occupied,
+            MoveError::SelfFill => CrossesStatus::SelfFill,
+            MoveError::Unreachable => CrossesStatus::Unreachable,
+            MoveError::NothingToCancel => CrossesStatus::NothingToCancel,
+            MoveError::RecordMismatch => CrossesStatus::RecordMismatch,
+            MoveError::GameOver => CrossesStatus::GameOver,
+            MoveError::ProtectedCell => CrossesStatus::ProtectedCell,
+            MoveError::NoChangeLog => CrossesStatus::NoChangeLog,
+        }
+    }
+}
+
+/// Kind of the cell, see [`CellKind`].
+#[repr(i32)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CrossesCellKind {
+    /// Empty cell
+    Empty = 0,
+    /// Cell with cross
+    Cross = 1,
+    /// Filled cell
+    Filled = 2,
+    /// Border
+    Border = 3,
+}
+impl From<CellKind> for CrossesCellKind {
+    fn from(kind: CellKind) -> Self {
+        match kind {
+            CellKind::Empty => CrossesCellKind::Empty,
+            CellKind::Cross => CrossesCellKind::Cross,
+            CellKind::Filled => CrossesCellKind::Filled,
+            CellKind::Border => CrossesCellKind::Border,
+        }
+    }
+}
+
+/// State of the game, see [`GameState`].
+#[repr(i32)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CrossesGameState {
+    /// The game hasn't ended
+    Ongoing = 0,
+    /// The game has a winner
+    Win = 1,
+    /// The game has ended with a draw
+    Draw = 2,
+}
+
+type VecGame = Game<Board<Vec<Cell>, Vec<usize>>, Vec<Option<LoseData>>>;
+
+/// Opaque game with history of its moves.
+#[derive(Clone, Debug)]
+pub struct CrossesGame {
+    game: VecGame,
+    history: Vec<GameMove<usize>>,
+}
+impl CrossesGame {
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        let board = self.game.board();
+        let (x, y) = (x as usize, y as usize);
+        (x < board.width() && y < board.height()).then(|| board.index(x, y))
+    }
+}
+
+/// Creates new game on empty board. Returns null if the width or the height is zero
+/// or greater than `u16::MAX` (like in [`binary`](crate::binary) form), number of players
+/// isn't in `1..=8`, `max_moves` is zero or storages of the board can't be allocated.
+/// # Example
+/// ```
+/// # use crosses_utils::ffi::*;
+/// assert!(crosses_game_new(0, 3, 2, 1).is_null());
+/// assert!(crosses_game_new(u32::MAX, u32::MAX, 2, 1).is_null());
+/// assert!(crosses_game_new(3, 3, 9, 1).is_null());
+/// ```
+#[no_mangle]
+pub extern "C" fn crosses_game_new(
+    width: u32,
+    height: u32,
+    players: u32,
+    max_moves: u32,
+) -> *mut CrossesGame {
+    let (width, height, players) = (width as usize, height as usize, players as usize);
+    let max_side = u16::MAX as usize;
+    if !(1..=max_side).contains(&width)
+        || !(1..=max_side).contains(&height)
+        || players == 0
+        || players > MAX_PLAYERS
+        || max_moves == 0
+    {
+        return core::ptr::null_mut();
+    }
+    let storages = (width + 2)
+        .checked_mul(height + 2)
+        .and_then(|len| Some((filled(Cell::new(), len)?, filled(0, len)?)));
+    let (cells, buffer) = match storages {
+        Some(storages) => storages,
+        None => return core::ptr::null_mut(),
+    };
+    let board = Board::new(width, height, players, cells, buffer);
+    let manager = PlayerManager::new(max_moves as usize, players, vec![None; players]);
+    Box::into_raw(Box::new(CrossesGame {
+        game: Game::new(board, manager),
+        history: Vec::new(),
+    }))
+}
+
+/// Returns vector of `len` copies of the value or `None`, if it can't be allocated.
+fn filled<T: Clone>(value: T, len: usize) -> Option<Vec<T>> {
+    let mut vec = Vec::new();
+    vec.try_reserve_exact(len).ok()?;
+    vec.resize(len, value);
+    Some(vec)
+}
+
+/// Destroys the game. Null is ignored.
+/// # Safety
+/// `game` should be null or returned by [`crosses_game_new`] and not destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn crosses_game_free(game: *mut CrossesGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// Returns width of the board.
+/// # Safety
+/// `game` should be a valid pointer to the game.
+#[no_mangle]
+pub unsafe extern "C" fn crosses_game_width(game: *const CrossesGame) -> u32 {
+    (*game).game.board().width() as u32
+}
+
+/// Returns height of the board.
+/// # Safety
+/// `game` should be a valid pointer to the game.
+#[no_mangle]
+pub unsafe extern "C" fn crosses_game_height(game: *const CrossesGame) -> u32 {
+    (*game).game.board().height() as u32
+}
+
+/// Makes a move of the current player at given coordinates.
+/// # Safety
+/// `game` should be a valid pointer to the game.
+#[no_mangle]
+pub unsafe extern "C" fn crosses_game_play(
+    game: *mut CrossesGame,
+    x: u32,
+    y: u32,
+) -> CrossesStatus {
+    let game = &mut *game;
+    let index = match game.index(x, y) {
+        Some(index) => index,
+        None => return CrossesStatus::OutOfBounds,
+    };
+    match game.game.play(index) {
+        Ok(record) => {
+            game.history.push(record);
+            CrossesStatus::Ok
+        }
+        Err(error) => error.into(),
+    }
+}
+
+/// Cancels the last move.
+/// # Safety
+/// `game` should be a valid pointer to the game.
+#[no_mangle]
+pub unsafe extern "C" fn crosses_game_undo(game: *mut CrossesGame) -> CrossesStatus {
+    let game = &mut *game;
+    let record = match game.history.last() {
+        Some(record) => *record,
+        None => return CrossesStatus::NothingToCancel,
+    };
+    match game.game.undo(record) {
+        Ok(()) => {
+            game.history.pop();
+            CrossesStatus::Ok
+        }
+        Err(error) => error.into(),
+    }
+}
+
+/// Writes kind and player of the cell at given coordinates.
+/// # Safety
+/// `game` should be a valid pointer to the game, `kind` and `player`
+/// should be valid for writes or null.
+#[no_mangle]
+pub unsafe extern "C" fn crosses_game_cell(
+    game: *const CrossesGame,
+    x: u32,
+    y: u32,
+    kind: *mut CrossesCellKind,
+    player: *mut u32,
+) -> CrossesStatus {
+    if kind.is_null() || player.is_null() {
+        return CrossesStatus::NullPointer;
+    }
+    let game = &*game;
+    let index = match game.index(x, y) {
+        Some(index) => index,
+        None => return CrossesStatus::OutOfBounds,
+    };
+    let cell = game.game.board().get(index);
+    *kind = cell.kind().into();
+    *player = cell.player() as u32;
+    CrossesStatus::Ok
+}
+
+/// Writes packed indices of the cells, where the current player can make a move,
+/// into `out` and returns number of such cells. Only first `capacity` indices are
+/// written, so the function can be called with zero capacity to get the size.
+/// # Safety
+/// `game` should be a valid pointer to the game and `out` should be
+/// valid for writes of `capacity` values.
+#[no_mangle]
+pub unsafe extern "C" fn crosses_game_legal_moves(
+    game: *const CrossesGame,
+    out: *mut u32,
+    capacity: usize,
+) -> usize {
+    let game = &(*game).game;
+    if game.state() != GameState::Ongoing {
+        return 0;
+    }
+    let board = game.board();
+    let mut count = 0;
+    for index in board.legal_moves(game.current_player()) {
+        if count < capacity {
+            *out.add(count) = board.pack_index(index) as u32;
+        }
+        count += 1;
+    }
+    count
+}
+
+/// Returns the player, that makes the move now.
+/// # Safety
+/// `game` should be a valid pointer to the game.
+#[no_mangle]
+pub unsafe extern "C" fn crosses_game_current_player(game: *const CrossesGame) -> u32 {
+    (*game).game.current_player() as u32
+}
+
+/// Returns number of moves of the current player left in this turn.
+/// # Safety
+/// `game` should be a valid pointer to the game.
+#[no_mangle]
+pub unsafe extern "C" fn crosses_game_remaining_moves(game: *const CrossesGame) -> u32 {
+    (*game).game.player_manager().remaining_moves as u32
+}
+
+/// Returns the state of the game and writes the winner, if any.
+/// # Safety
+/// `game` should be a valid pointer to the game and `winner` should be valid for writes or null.
+#[no_mangle]
+pub unsafe extern "C" fn crosses_game_state(
+    game: *const CrossesGame,
+    winner: *mut u32,
+) -> CrossesGameState {
+    match (*game).game.state() {
+        GameState::Ongoing => CrossesGameState::Ongoing,
+        GameState::Ended(GameOver::Draw) => CrossesGameState::Draw,
+        GameState::Ended(GameOver::Win(player)) => {
+            if !winner.is_null() {
+                *winner = player as u32;
+            }
+            CrossesGameState::Win
+        }
+    }
+}
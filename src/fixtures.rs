@@ -0,0 +1,194 @@
+//! Test positions
+//!
+//! This module defines [`FIXTURES`], named positions for regression tests of corner cases
+//! of the rules: revival of chains, merges of chains and simultaneous eliminations.
+//! Every [`Fixture`] is a position in the FEN-like form of [`Game::to_fen`] with its rules,
+//! moves made from it and the expected result: the board in text form with aliveness
+//! markers, see [`render`], and the position after the moves. Implementations of boards
+//! can run the same suite as the crate, see [`Fixture::matches`].
+//! The module is available with `testing` feature.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::fixtures::*;
+//! for fixture in &FIXTURES {
+//!     let mut game = fixture.game([Cell::new(); 64], [0; 64], [None; 3]).unwrap();
+//!     assert!(!fixture.matches(&game));
+//!     fixture.play(&mut game).unwrap();
+//!     assert!(fixture.matches(&game), "{}", fixture.name);
+//! }
+//! assert_eq!(fixture("death-by-fill").unwrap().moves, [(2, 0)]);
+//! ```
+use core::fmt::{Result as FmtResult, Write};
+use core::ops::IndexMut;
+
+use crate::board::{Board, CellStorage};
+use crate::engine::{ChangeLog, MoveError};
+use crate::game::Game;
+use crate::player_manager::LoseData;
+use crate::rules::RuleSet;
+use crate::text::{render, ParseError};
+
+/// Named position with moves and their expected result.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Fixture {
+    /// Name of the position, unique in [`FIXTURES`]
+    pub name: &'static str,
+    /// What the position checks
+    pub description: &'static str,
+    /// Rules of the position, they never need a change log
+    pub rules: RuleSet,
+    /// The position before the moves, see [`Game::to_fen`]
+    pub position: &'static str,
+    /// Coordinates of cells of the moves
+    pub moves: &'static [(usize, usize)],
+    /// The board after the moves in text form with aliveness markers
+    pub board: &'static str,
+    /// The position after the moves
+    pub result: &'static str,
+}
+impl Fixture {
+    /// Creates the game at the position of the fixture with its rules,
+    /// see [`Game::from_fen`].
+    /// # Errors
+    /// Returns [`ParseError`] if the storages are too small.
+    pub fn game<C, B, S>(
+        &self,
+        cells: C,
+        buffer: B,
+        losers: S,
+    ) -> Result<Game<Board<C, B>, S>, ParseError>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+    {
+        let (mut board, manager) =
+            Game::from_fen(self.position, cells, buffer, losers)?.into_parts();
+        board.set_rules(self.rules);
+        Ok(Game::new(board, manager))
+    }
+    /// Makes the moves of the fixture.
+    /// # Errors
+    /// Returns [`MoveError`] of the first failed move, the moves before it stay made.
+    pub fn play<C, B, S, L>(&self, game: &mut Game<Board<C, B>, S, L>) -> Result<(), MoveError>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        for (x, y) in self.moves {
+            game.play(game.board().index(*x, *y))?;
+        }
+        Ok(())
+    }
+    /// Checks if the game matches the expected result of the fixture.
+    pub fn matches<C, B, S, L>(&self, game: &Game<Board<C, B>, S, L>) -> bool
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        let board = render(game.board()).aliveness();
+        let mut expected = Expected(self.board);
+        let same_board = write!(expected, "{}", board).is_ok() && expected.0.is_empty();
+        let mut expected = Expected(self.result);
+        same_board && write!(expected, "{}", game.to_fen()).is_ok() && expected.0.is_empty()
+    }
+}
+
+/// Returns the fixture with given name.
+pub fn fixture(name: &str) -> Option<&'static Fixture> {
+    FIXTURES.iter().find(|fixture| fixture.name == name)
+}
+
+/// Test positions, see module docs. Boards have at most `64` cells with borders
+/// and `3` players.
+pub const FIXTURES: [Fixture; 8] = [
+    Fixture {
+        name: "revival-corner",
+        description: "a dead chain in the corner is revived by a diagonal cross",
+        rules: RuleSet::STANDARD,
+        position: "Xo2./o3./2.x./3.x 2 x 1/1 - 8",
+        moves: &[(1, 1)],
+        board: "X+ o  .  . \no  x  .  . \n.  .  x  . \n.  .  .  x \n",
+        result: "Xo2./ox2./2.x./3.x 2 o 1/1 - 9",
+    },
+    Fixture {
+        name: "revival-two-chains",
+        description: "one cross revives two dead chains",
+        rules: RuleSet::STANDARD,
+        position: "X.X./o.o./.x2./4. 2 x 1/1 - 8",
+        moves: &[(1, 1)],
+        board: "X+ .  X+ . \no  x  o  . \n.  x  .  . \n.  .  .  . \n",
+        result: "X.X./oxo./.x2./4. 2 o 1/1 - 9",
+    },
+    Fixture {
+        name: "revival-by-fill",
+        description: "a filled cross connects a dead chain to crosses of its player",
+        rules: RuleSet::STANDARD,
+        position: "Xox/3./o2. 2 x 1/1 - 6",
+        moves: &[(1, 0)],
+        board: "X+ X+ x \n.  .  . \no  .  . \n",
+        result: "2Xx/3./o2. 2 o 1/1 - 7",
+    },
+    Fixture {
+        name: "revival-activates",
+        description: "a revived chain activates cells in its reach in the same turn",
+        rules: RuleSet::STANDARD,
+        position: ".3X./o.o.o/2.x2. 2 x 2/2 - 6",
+        moves: &[(1, 1), (4, 0)],
+        board: ".  X+ X+ X+ x \no  x  o  .  o \n.  .  x  .  . \n",
+        result: ".3Xx/oxo.o/2.x2. 2 o 2/2 - 8",
+    },
+    Fixture {
+        name: "death-by-fill",
+        description: "filling the only cross of a chain kills it",
+        rules: RuleSet::STANDARD,
+        position: "XXx./3.o/x3. 2 o 1/1 - 6",
+        moves: &[(2, 0)],
+        board: "X- X- O+ . \n.  .  .  o \nx  .  .  . \n",
+        result: "2XO./3.o/x3. 2 x 1/1 - 7",
+    },
+    Fixture {
+        name: "merge-keeps-alive",
+        description: "chains merged by a cross survive the loss of one of their crosses",
+        rules: RuleSet::STANDARD,
+        position: "xX.Xx/o4./4.o 2 x 1/1 - 8",
+        moves: &[(2, 0), (0, 0)],
+        board: "O+ X+ x  X+ x \no  .  .  .  . \n.  .  .  .  o \n",
+        result: "OXxXx/o4./4.o 2 x 1/1 - 10",
+    },
+    Fixture {
+        name: "remote-fill",
+        description: "crosses are filled out of reach, a player without crosses can still fill one",
+        rules: RuleSet {
+            fill_requires_reach: false,
+            ..RuleSet::STANDARD
+        },
+        position: "XXx./4./3.o 2 o 1/1 - 4",
+        moves: &[(2, 0), (3, 2)],
+        board: "X- X- O- . \n.  .  .  . \n.  .  .  X-\n",
+        result: "2XO./4./3.X 2 x 0/1 o:5:0 6",
+    },
+    Fixture {
+        name: "simultaneous-elimination",
+        description: "one move takes the last cross of one player and the last move of other",
+        rules: RuleSet::STANDARD,
+        position: "3AO/2OXa/xo2A 3 x 1/1 - 21",
+        moves: &[(1, 2)],
+        board: "A+ A+ A+ O-\nO- O- X+ a \nx  X+ A+ A+\n",
+        result: "3AO/2OXa/xX2A 3 x 0/1 o:21:0,a:21:0 22",
+    },
+];
+
+/// Compares written text with the expected one, consuming it.
+struct Expected<'a>(&'a str);
+impl Write for Expected<'_> {
+    fn write_str(&mut self, s: &str) -> FmtResult {
+        self.0 = self.0.strip_prefix(s).ok_or(core::fmt::Error)?;
+        Ok(())
+    }
+}
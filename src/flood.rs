@@ -0,0 +1,254 @@
+//! Flood fill
+//!
+//! This module defines [`flood_fill`], traversal of cells of any [`GameBoard`] connected
+//! through cells, that satisfy a predicate. It doesn't allocate: cells waiting for a visit
+//! are kept in a [`Frontier`] and visited cells are marked in a [`Visited`] set, both
+//! provided by the user. [`Queue`] makes the traversal breadth-first and [`Stack`] makes
+//! it depth-first. With `alloc` feature `VecDeque` and `Vec` can be used instead of them,
+//! and `BTreeSet` can be used as a set of visited cells.
+//! # Example
+//! ```
+//! # use crosses_utils::base::*;
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::flood::*;
+//! let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+//! let mut frontier = Queue::new([0; 25]);
+//! let mut visited = [false; 25];
+//! let mut order = [0; 9];
+//! let mut len = 0;
+//! let start = board.index(0, 0);
+//! let empty = |board: &Board<_, _>, index| board.kind(index) == CellKind::Empty;
+//! flood_fill(&board, start, &mut frontier, &mut visited[..], empty, |_, index| {
+//!     order[len] = index;
+//!     len += 1;
+//!     false
+//! });
+//! assert_eq!(len, 9);
+//! assert_eq!(order[..4], [start, board.index(1, 0), board.index(0, 1), board.index(1, 1)]);
+//! ```
+#[cfg(feature = "alloc")]
+use alloc::collections::{BTreeSet, VecDeque};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::base::GameBoard;
+
+/// Container of cells waiting for a visit.
+pub trait Frontier<I> {
+    /// Adds the cell.
+    fn push(&mut self, index: I);
+    /// Removes the next cell to visit.
+    fn pop(&mut self) -> Option<I>;
+    /// Removes all cells.
+    fn clear(&mut self);
+}
+
+/// Set of visited cells.
+pub trait Visited<I> {
+    /// Marks the cell as visited. Returns `false` if it was already visited.
+    fn insert(&mut self, index: I) -> bool;
+}
+
+/// First-in-first-out [`Frontier`] over a buffer, e.g. an array.
+/// # Panics
+/// [`Frontier::push`] panics if the buffer is full.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Queue<S> {
+    buffer: S,
+    head: usize,
+    len: usize,
+}
+impl<S> Queue<S> {
+    /// Creates empty queue, that keeps cells in the buffer.
+    pub fn new(buffer: S) -> Self {
+        Self {
+            buffer,
+            head: 0,
+            len: 0,
+        }
+    }
+    /// Returns number of cells in the queue.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Checks if the queue has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Unwraps the buffer.
+    pub fn into_inner(self) -> S {
+        self.buffer
+    }
+}
+impl<I: Copy, S: AsMut<[I]>> Frontier<I> for Queue<S> {
+    fn push(&mut self, index: I) {
+        let buffer = self.buffer.as_mut();
+        assert!(self.len < buffer.len(), "queue is full");
+        buffer[(self.head + self.len) % buffer.len()] = index;
+        self.len += 1;
+    }
+    fn pop(&mut self) -> Option<I> {
+        if self.len == 0 {
+            return None;
+        }
+        let buffer = self.buffer.as_mut();
+        let index = buffer[self.head];
+        self.head = (self.head + 1) % buffer.len();
+        self.len -= 1;
+        Some(index)
+    }
+    fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+/// Last-in-first-out [`Frontier`] over a buffer, e.g. an array.
+/// # Panics
+/// [`Frontier::push`] panics if the buffer is full.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Stack<S> {
+    buffer: S,
+    len: usize,
+}
+impl<S> Stack<S> {
+    /// Creates empty stack, that keeps cells in the buffer.
+    pub fn new(buffer: S) -> Self {
+        Self { buffer, len: 0 }
+    }
+    /// Returns number of cells in the stack.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Checks if the stack has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Unwraps the buffer.
+    pub fn into_inner(self) -> S {
+        self.buffer
+    }
+}
+impl<I: Copy, S: AsMut<[I]>> Frontier<I> for Stack<S> {
+    fn push(&mut self, index: I) {
+        let buffer = self.buffer.as_mut();
+        assert!(self.len < buffer.len(), "stack is full");
+        buffer[self.len] = index;
+        self.len += 1;
+    }
+    fn pop(&mut self) -> Option<I> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(self.buffer.as_mut()[self.len])
+    }
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<I> Frontier<I> for VecDeque<I> {
+    fn push(&mut self, index: I) {
+        self.push_back(index)
+    }
+    fn pop(&mut self) -> Option<I> {
+        self.pop_front()
+    }
+    fn clear(&mut self) {
+        VecDeque::clear(self)
+    }
+}
+#[cfg(feature = "alloc")]
+impl<I> Frontier<I> for Vec<I> {
+    fn push(&mut self, index: I) {
+        Vec::push(self, index)
+    }
+    fn pop(&mut self) -> Option<I> {
+        Vec::pop(self)
+    }
+    fn clear(&mut self) {
+        Vec::clear(self)
+    }
+}
+
+/// Cells are indices in the slice, it should be as long as the storage of the board.
+impl Visited<usize> for [bool] {
+    fn insert(&mut self, index: usize) -> bool {
+        !core::mem::replace(&mut self[index], true)
+    }
+}
+#[cfg(feature = "alloc")]
+impl<I: Ord> Visited<I> for BTreeSet<I> {
+    fn insert(&mut self, index: I) -> bool {
+        BTreeSet::insert(self, index)
+    }
+}
+
+/// Visits cells connected to `start` through cells, that satisfy `predicate`, in the order
+/// of `frontier`. Calls `visitor` with the board and index of every such cell, including
+/// `start`, and stops if it returns `true`. Returns whether the traversal was stopped.
+/// Nothing is visited, if `start` doesn't satisfy `predicate`. Cells already marked in
+/// `visited` are skipped, `frontier` is cleared before the traversal. Border cells of
+/// [`Board`] have no adjacent cells, so `predicate` should reject them.
+/// # Example
+/// ```
+/// # use crosses_utils::base::*;
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::flood::*;
+/// # use crosses_utils::game::*;
+/// let fen = "x3./2.o./2.o./x3. 2 x 1/1 - 4";
+/// let game = Game::from_fen(fen, [Cell::new(); 36], [0; 36], [None; 2]).unwrap();
+/// let board = game.board();
+/// let mut frontier = Stack::new([0; 36]);
+/// let crosses = |board: &Board<_, _>, index| board.kind(index) == CellKind::Cross;
+/// let mut size = 0;
+/// let start = board.index(2, 1);
+/// flood_fill(board, start, &mut frontier, &mut [false; 36][..], crosses, |_, _| {
+///     size += 1;
+///     false
+/// });
+/// assert_eq!(size, 2);
+///
+/// let passable = |board: &Board<_, _>, index| match board.kind(index) {
+///     CellKind::Empty => true,
+///     CellKind::Cross => board.player(index) == 0,
+///     _ => false,
+/// };
+/// let corner = board.index(0, 3);
+/// let (start, visited) = (board.index(0, 0), &mut [false; 36][..]);
+/// assert!(flood_fill(board, start, &mut frontier, visited, passable, |_, index| index == corner));
+/// ```
+///
+/// [`Board`]: crate::board::Board
+pub fn flood_fill<G, F, V>(
+    board: &G,
+    start: G::Index,
+    frontier: &mut F,
+    visited: &mut V,
+    mut predicate: impl FnMut(&G, G::Index) -> bool,
+    mut visitor: impl FnMut(&G, G::Index) -> bool,
+) -> bool
+where
+    G: GameBoard,
+    F: Frontier<G::Index>,
+    V: Visited<G::Index> + ?Sized,
+{
+    frontier.clear();
+    if !predicate(board, start) || !visited.insert(start) {
+        return false;
+    }
+    frontier.push(start);
+    while let Some(current) = frontier.pop() {
+        if visitor(board, current) {
+            return true;
+        }
+        for i in board.adjacent(current) {
+            if predicate(board, i) && visited.insert(i) {
+                frontier.push(i);
+            }
+        }
+    }
+    false
+}
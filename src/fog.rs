@@ -0,0 +1,152 @@
+//! Fog of war
+//!
+//! This module computes, what a player sees in variants with hidden information. A cell is
+//! visible to the player, if it's within given distance of their crosses or filled cells,
+//! the distance is the greatest difference of coordinates, see [`visibility`]. Visibility
+//! is written into a slice provided by the caller, indexed by packed indices of cells
+//! (see [`Board::pack_index`]), so it should have `width * height` elements.
+//!
+//! [`CensoredView`] is the board seen by the player: hidden cells have no content and
+//! visible cells show only their type and player. Activations and IBTS flags of cells
+//! depend on hidden neighbours, so they aren't shown. With `serde` feature the view is
+//! serialized only with what it shows, so it can be sent to clients, which can read it
+//! as a struct with `width`, `height` and `cells`: a sequence of `Option<SeenCell>`
+//! row by row.
+//! # Example
+//! ```
+//! # use crosses_utils::base::*;
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::engine::*;
+//! # use crosses_utils::fog::*;
+//! let mut board = Board::new(5, 1, 2, [Cell::new(); 21], [0; 21]);
+//! let (left, right) = (board.index(0, 0), board.index(4, 0));
+//! make_move(&mut board, left, 0).unwrap();
+//! make_move(&mut board, right, 1).unwrap();
+//! let mut visible = [false; 5];
+//! assert_eq!(visibility(&board, 0, 1, &mut visible), 2);
+//! assert_eq!(visible, [true, true, false, false, false]);
+//! let view = CensoredView::new(&board, &visible);
+//! let cross = SeenCell { kind: CellKind::Cross, player: 0 };
+//! assert_eq!((view.get(0, 0), view.get(4, 0)), (Some(cross), None));
+//! ```
+use crate::base::CellKind;
+use crate::board::{Board, CellStorage};
+use crate::engine::{Data, Engine};
+#[cfg(feature = "serde")]
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
+
+/// Content of a visible cell, see [`CensoredView`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SeenCell {
+    /// Type of the cell
+    pub kind: CellKind,
+    /// Player of the cell, `0` for empty cells
+    pub player: usize,
+}
+
+/// Finds cells visible to the player: cells within `radius` of their crosses and
+/// filled cells. Writes whether every cell is visible and returns number of visible cells.
+/// # Panics
+/// Panics if `out` has less than `width * height` elements.
+pub fn visibility<C, B>(
+    board: &Board<C, B>,
+    player: usize,
+    radius: usize,
+    out: &mut [bool],
+) -> usize
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let (width, height) = (board.width(), board.height());
+    let out = &mut out[..width * height];
+    out.fill(false);
+    for packed in 0..width * height {
+        let cell = board.get(board.unpack_index(packed));
+        if cell.kind() == CellKind::Empty || cell.player() != player {
+            continue;
+        }
+        let (x, y) = (packed % width, packed / width);
+        for y in y.saturating_sub(radius)..=(y + radius).min(height - 1) {
+            let row = &mut out[y * width..][..width];
+            row[x.saturating_sub(radius)..=(x + radius).min(width - 1)].fill(true);
+        }
+    }
+    out.iter().filter(|visible| **visible).count()
+}
+
+/// The board seen by a player, see module docs.
+#[derive(Clone, Copy, Debug)]
+pub struct CensoredView<'a, C, B> {
+    board: &'a Board<C, B>,
+    visible: &'a [bool],
+}
+impl<'a, C, B> CensoredView<'a, C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    /// Creates the view of the board with visibility found by [`visibility`].
+    /// # Panics
+    /// Panics if `visible` has less than `width * height` elements.
+    pub fn new(board: &'a Board<C, B>, visible: &'a [bool]) -> Self {
+        assert!(
+            visible.len() >= board.width() * board.height(),
+            "visibility is too small"
+        );
+        Self { board, visible }
+    }
+    /// Returns width of the board.
+    pub fn width(&self) -> usize {
+        self.board.width()
+    }
+    /// Returns height of the board.
+    pub fn height(&self) -> usize {
+        self.board.height()
+    }
+    /// Checks if the cell with given coordinates is visible.
+    pub fn is_visible(&self, x: usize, y: usize) -> bool {
+        self.visible[y * self.width() + x]
+    }
+    /// Returns content of the cell with given coordinates or `None` if it's hidden.
+    pub fn get(&self, x: usize, y: usize) -> Option<SeenCell> {
+        if !self.is_visible(x, y) {
+            return None;
+        }
+        let cell = self.board.get(self.board.index(x, y));
+        Some(SeenCell {
+            kind: cell.kind(),
+            player: cell.player(),
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<C, B> Serialize for CensoredView<'_, C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CensoredView", 3)?;
+        state.serialize_field("width", &self.width())?;
+        state.serialize_field("height", &self.height())?;
+        state.serialize_field("cells", &SeenCells(self))?;
+        state.end()
+    }
+}
+#[cfg(feature = "serde")]
+struct SeenCells<'v, 'a, C, B>(&'v CensoredView<'a, C, B>);
+#[cfg(feature = "serde")]
+impl<C, B> Serialize for SeenCells<'_, '_, C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let view = self.0;
+        let (width, height) = (view.width(), view.height());
+        serializer.collect_seq((0..height).flat_map(|y| (0..width).map(move |x| view.get(x, y))))
+    }
+}
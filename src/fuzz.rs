@@ -0,0 +1,119 @@
+//! Fuzzing harness
+//!
+//! [`drive`] interprets arbitrary bytes as a program of operations on [`Game`] and checks
+//! invariants with [`debug_validate`] after every operation, so the crate can be hooked
+//! into a fuzzer with one call, e.g. in a target of `cargo fuzz`:
+//! `fuzz_target!(|bytes: &[u8]| { drive(&mut new_game(), bytes); })`.
+//!
+//! Every operation takes two bytes, the first one selects the operation by its remainder
+//! by four and the second one is its argument:
+//! - `0` - a legal move of the current player, the argument selects one of them,
+//! - `1` - a move of the current player at an arbitrary cell, including border ones,
+//!   such moves are mostly illegal,
+//! - `2` - cancelling of the last move, that hasn't been cancelled yet,
+//! - `3` - resigning of the player selected by the argument.
+//!
+//! The rules have no passes, so a player gives up their moves only by resigning.
+//! Errors of rejected moves and cancellations without moves are expected and ignored,
+//! other errors and broken invariants panic.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::fuzz::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+//! let mut game = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+//! let moves = drive(&mut game, &[0, 0, 1, 0, 0, 7, 2, 0, 4, 3, 1, 200, 3, 1]);
+//! assert_eq!(moves, 2);
+//! assert_eq!(game.state(), GameState::Ended(GameOver::Win(0)));
+//! ```
+//!
+//! [`debug_validate`]: crate::validate::debug_validate
+use core::ops::IndexMut;
+
+use crate::board::{Board, CellStorage};
+use crate::engine::{ChangeLog, MoveError};
+use crate::game::{Game, GameMove};
+use crate::player_manager::{GameState, LoseData};
+use crate::summary::EliminationReason;
+use crate::validate::validate_in_place;
+
+/// Maximum number of moves, that [`drive`] can cancel, further moves are skipped.
+pub const MAX_DEPTH: usize = 256;
+
+/// Runs the program encoded by `bytes` on the game, see module docs. A trailing odd byte
+/// is ignored. Returns number of moves made and not cancelled.
+/// # Panics
+/// Panics with the report of [`debug_validate`] if an operation breaks invariants,
+/// or if a move fails unexpectedly or its cancelling fails.
+///
+/// [`debug_validate`]: crate::validate::debug_validate
+pub fn drive<C, B, S, L>(game: &mut Game<Board<C, B>, S, L>, bytes: &[u8]) -> usize
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    let mut records = [None::<GameMove<usize>>; MAX_DEPTH];
+    let mut depth = 0;
+    for (step, operation) in bytes.chunks_exact(2).enumerate() {
+        let argument = operation[1] as usize;
+        match operation[0] % 4 {
+            0 | 1 if depth == MAX_DEPTH => continue,
+            0 => {
+                let board = game.board();
+                let count = board.legal_moves(game.current_player()).count();
+                let index = board
+                    .legal_moves(game.current_player())
+                    .nth(argument % count.max(1));
+                if let Some(index) = index.filter(|_| game.state() == GameState::Ongoing) {
+                    let record = game.play(index).unwrap_or_else(|error| {
+                        panic!("step {}: legal move failed: {}", step, error)
+                    });
+                    records[depth] = Some(record);
+                    depth += 1;
+                }
+            }
+            1 => {
+                let board = game.board();
+                let index = argument % ((board.width() + 2) * (board.height() + 2));
+                match game.play(index) {
+                    Ok(record) => {
+                        records[depth] = Some(record);
+                        depth += 1;
+                    }
+                    Err(error) => check_rejection(step, error),
+                }
+            }
+            2 if depth > 0 => {
+                depth -= 1;
+                let record = records[depth].take().unwrap();
+                if let Err(error) = game.undo(record) {
+                    panic!("step {}: cancelling failed: {}", step, error);
+                }
+            }
+            2 => {}
+            _ => {
+                let player = argument % game.player_manager().max_players;
+                if let Err(error) = game.mark_loser(player, EliminationReason::Resigned) {
+                    check_rejection(step, error);
+                }
+            }
+        }
+        let report = validate_in_place(game);
+        assert!(report.is_valid(), "step {}: {}", step, report);
+    }
+    depth
+}
+
+/// Panics if the error isn't expected for an arbitrary move.
+fn check_rejection(step: usize, error: MoveError) {
+    if matches!(
+        error,
+        MoveError::NothingToCancel | MoveError::RecordMismatch
+    ) {
+        panic!("step {}: unexpected error: {}", step, error);
+    }
+}
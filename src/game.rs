@@ -0,0 +1,728 @@
+//! Game façade
+//!
+//! This module defines [`Game`] struct, which combines a board implementing [`Engine`]
+//! with [`PlayerManager`]. It makes moves of the current player and advances the state of
+//! the game, using counters of the board to find out who has lost.
+//!
+//! Methods of [`Game`] with [`Board`] named `checked_*` are the strict API for input,
+//! that comes from players, e.g. on game servers: they return errors instead of panics for
+//! indices out of the board and records, that contradict the state of the game.
+//! [`PlayerManager::checked_advance`] and [`PlayerManager::checked_reverse`] do the same
+//! for the player manager.
+use core::ops::IndexMut;
+
+use crate::board::{Board, CellStorage, MAX_PLAYERS};
+use crate::counters::{Counter, PlayerCounters};
+use crate::engine::{
+    cancel_move_logged, make_move_logged, remove_dead_chains, ChangeLog, Engine, MoveError,
+    MoveRecord,
+};
+use crate::events::GameEvent;
+use crate::player_manager::{
+    GameOver, GameState, LoseData, PlayerManager, ReverseError, Stalemate,
+};
+use crate::stats::{tally, GameStats};
+use crate::summary::{elimination_reason, EliminationReason, EndReason};
+#[cfg(feature = "serde")]
+use serde::{de::Error, Deserialize, Deserializer, Serialize};
+
+/// A game of crosses.
+/// `B` - is type of the board, `S` - is type of losers storage of [`PlayerManager`],
+/// `L` - is type of [`ChangeLog`] for rules, that change cells after moves.
+/// By default there is no log, see [`Game::with_log`].
+///
+/// With `serde` feature game is serialized with the board and the player manager.
+/// Deserialization checks that they have the same number of players and the manager
+/// is in valid state, like [`debug_validate`] does: the current player of an ongoing game
+/// hasn't lost and has remaining moves within the budget of the turn, nobody has lost
+/// after the current move and the winner hasn't lost. Statistics and the change log
+/// aren't serialized.
+///
+/// [`debug_validate`]: crate::validate::debug_validate
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Game<B: Engine, S: IndexMut<usize, Output = Option<LoseData>>, L = ()> {
+    board: B,
+    manager: PlayerManager<S>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    log: L,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stats: Option<GameStats>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    reasons: [Option<EliminationReason>; MAX_PLAYERS],
+    #[cfg_attr(feature = "serde", serde(skip))]
+    end_reason: Option<EndReason>,
+}
+
+/// A move made by [`Game::play`]: the record of the move on the board and the state
+/// of the turn before it, which is restored by [`Game::undo`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct GameMove<I> {
+    /// The record of the move on the board, see [`make_move_logged`]
+    pub record: MoveRecord<I>,
+    /// Number of the move in the game, see [`PlayerManager::current_move`]
+    pub move_index: usize,
+    /// Turn of the move, see [`PlayerManager::reverse_with`]
+    pub turn: usize,
+    /// Remaining moves of the player before the move, `0` if unknown,
+    /// then the state is reversed by [`PlayerManager::reverse`]
+    pub remaining_moves: usize,
+}
+impl<I> GameMove<I> {
+    /// Converts index of the move, see [`MoveRecord::map_index`].
+    pub fn map_index<J>(self, f: impl FnOnce(I) -> J) -> GameMove<J> {
+        GameMove {
+            record: self.record.map_index(f),
+            move_index: self.move_index,
+            turn: self.turn,
+            remaining_moves: self.remaining_moves,
+        }
+    }
+}
+
+impl<B, S> Game<B, S>
+where
+    B: Engine,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+{
+    /// Creates new [`Game`]. Number of players of the board and
+    /// the manager should be the same.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// let board = Board::new(2, 1, 2, [Cell::new(); 12], [0; 12]);
+    /// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+    /// for x in [0, 1, 1] {
+    ///     let index = game.board().index(x, 0);
+    ///     game.play(index).unwrap();
+    /// }
+    /// assert_eq!(game.state(), GameState::Ended(GameOver::Win(0)));
+    /// ```
+    pub fn new(board: B, manager: PlayerManager<S>) -> Self {
+        Self::with_log(board, manager, ())
+    }
+}
+impl<B, S, L> Game<B, S, L>
+where
+    B: Engine,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<B::Index>,
+{
+    /// Creates new [`Game`] like [`Game::new`], that stores changes of cells made by
+    /// the rules after moves in the `log`, see [`RuleSet::needs_log`].
+    ///
+    /// [`RuleSet::needs_log`]: crate::rules::RuleSet::needs_log
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::engine::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::rules::*;
+    /// let mut board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// board.set_rules(RuleSet { convert_chains: true, ..RuleSet::STANDARD });
+    /// let mut game = Game::new(board.clone(), PlayerManager::new(1, 2, [None; 2]));
+    /// assert_eq!(game.play(board.index(0, 0)), Err(MoveError::NoChangeLog));
+    /// let log = ChangeStack::new([Change::default(); 16]);
+    /// let mut game = Game::with_log(board, PlayerManager::new(1, 2, [None; 2]), log);
+    /// let record = game.play(game.board().index(0, 0)).unwrap();
+    /// game.undo(record).unwrap();
+    /// ```
+    pub fn with_log(board: B, manager: PlayerManager<S>, log: L) -> Self {
+        debug_assert_eq!(board.counters().players(), manager.max_players);
+        Self {
+            board,
+            manager,
+            log,
+            stats: None,
+            reasons: [None; MAX_PLAYERS],
+            end_reason: None,
+        }
+    }
+    /// Returns the change log.
+    pub fn log(&self) -> &L {
+        &self.log
+    }
+    /// Returns the board.
+    pub fn board(&self) -> &B {
+        &self.board
+    }
+    /// Returns the player manager.
+    pub fn player_manager(&self) -> &PlayerManager<S> {
+        &self.manager
+    }
+    /// Returns counters of players.
+    pub fn counters(&self) -> &B::Counters {
+        self.board.counters()
+    }
+    /// Returns mobility of the player: number of cells, where the player can make a move.
+    /// It's maintained by the engine as cells activate and deactivate, so it's `O(1)`.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// let mut game = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+    /// assert_eq!(game.mobility(0), 1);
+    /// game.play(game.board().index(1, 1)).unwrap_err();
+    /// game.play(game.board().index(0, 0)).unwrap();
+    /// assert_eq!(game.mobility(0), 3);
+    /// game.play(game.board().index(1, 1)).unwrap();
+    /// assert_eq!((game.mobility(0), game.mobility(1)), (7, 1));
+    /// ```
+    pub fn mobility(&self, player: usize) -> usize {
+        self.board.counters().get(Counter::Moves, player)
+    }
+    /// Returns the player, that makes the move now.
+    pub fn current_player(&self) -> usize {
+        self.manager.current_player
+    }
+    /// Returns the state of the game.
+    pub fn state(&self) -> GameState {
+        self.manager.game_state
+    }
+    /// Starts accumulation of statistics of moves from zero, see [`GameStats`].
+    /// Kills and revivals of chains are counted with default hooks of [`IbtsBoard`],
+    /// so boards should only override [`IbtsBoard::on_split`] of them.
+    ///
+    /// [`IbtsBoard`]: crate::ibts::IbtsBoard
+    /// [`IbtsBoard::on_split`]: crate::ibts::IbtsBoard::on_split
+    pub fn enable_stats(&mut self) {
+        self.stats = Some(GameStats::default());
+    }
+    /// Stops accumulation of statistics and returns them, if they were enabled.
+    pub fn take_stats(&mut self) -> Option<GameStats> {
+        self.stats.take()
+    }
+    /// Returns statistics of moves, if they are enabled.
+    pub fn stats(&self) -> Option<&GameStats> {
+        self.stats.as_ref()
+    }
+    /// Returns the board and the player manager for changes, that keep them consistent.
+    pub(crate) fn parts_mut(&mut self) -> (&mut B, &mut PlayerManager<S>) {
+        (&mut self.board, &mut self.manager)
+    }
+    /// Unwraps the board and the player manager.
+    pub fn into_parts(self) -> (B, PlayerManager<S>) {
+        (self.board, self.manager)
+    }
+
+    /// Makes a move of the current player at given index and advances the state of the game.
+    /// Returned [`GameMove`] can be passed to [`Game::undo`].
+    /// Losers are determined by the counters of the board, see
+    /// [`PlayerStatusSource`](crate::player_manager::PlayerStatusSource).
+    /// If the move ends the turn and the rules remove dead chains, they are removed before
+    /// losers are determined, see [`remove_dead_chains`].
+    /// # Errors
+    /// Returns [`MoveError`] if the move is illegal or the game has ended.
+    /// In this case nothing is changed.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::base::*;
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::engine::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::ibts::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::rules::*;
+    /// let mut board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// board.set_rules(RuleSet { remove_dead_chains: true, ..RuleSet::STANDARD });
+    /// let log = ChangeStack::new([Change::default(); 16]);
+    /// let mut game = Game::with_log(board, PlayerManager::new(1, 2, [None; 2]), log);
+    /// for (x, y) in [(0, 0), (2, 2), (1, 1), (2, 1), (2, 1)] {
+    ///     game.play(game.board().index(x, y)).unwrap();
+    /// }
+    /// let side = game.board().index(2, 1);
+    /// let record = game.play(game.board().index(1, 1)).unwrap();
+    /// assert_eq!(game.board().get(side).kind(), CellKind::Empty);
+    /// game.undo(record).unwrap();
+    /// assert_eq!(game.board().get(side).kind(), CellKind::Filled);
+    /// assert!(game.board().is_alive(side));
+    /// ```
+    pub fn play(&mut self, index: B::Index) -> Result<GameMove<B::Index>, MoveError> {
+        if self.stats.is_some() {
+            return self.play_with_events(index, |_| {});
+        }
+        if self.manager.game_state != GameState::Ongoing {
+            return Err(MoveError::GameOver);
+        }
+        let (player, remaining) = (self.manager.current_player, self.manager.remaining_moves);
+        let mut record = make_move_logged(&mut self.board, index, player, &mut self.log)?;
+        record.changes += end_turn(&mut self.board, &mut self.log, player, remaining);
+        let played = GameMove {
+            record,
+            move_index: self.manager.current_move,
+            turn: self.manager.current_turn,
+            remaining_moves: remaining,
+        };
+        self.manager.advance(self.board.counters());
+        Ok(played)
+    }
+    /// Makes a move like [`Game::play`] and passes its events to `f` in the order they
+    /// happen: changes of cells and chains, then eliminations and the end of the game,
+    /// see [`events`](crate::events). Chains are tracked like in [`Game::enable_stats`].
+    /// # Errors
+    /// Returns [`MoveError`] in the same cases as [`Game::play`], nothing is passed to `f`.
+    pub fn play_with_events(
+        &mut self,
+        index: B::Index,
+        mut f: impl FnMut(GameEvent<B::Index, B::Data>),
+    ) -> Result<GameMove<B::Index>, MoveError> {
+        if self.manager.game_state != GameState::Ongoing {
+            return Err(MoveError::GameOver);
+        }
+        let (player, log) = (self.manager.current_player, &mut self.log);
+        let remaining = self.manager.remaining_moves;
+        let (record, move_) = tally(&mut self.board, player, &mut f, |board| {
+            let mut record = make_move_logged(board, index, player, log)?;
+            record.changes += end_turn(board, log, player, remaining);
+            Ok(record)
+        });
+        let played = GameMove {
+            record: record?,
+            move_index: self.manager.current_move,
+            turn: self.manager.current_turn,
+            remaining_moves: remaining,
+        };
+        if let Some(stats) = &mut self.stats {
+            stats.record(&played.record, move_, false);
+        }
+        let move_index = self.manager.current_move;
+        self.manager.advance(self.board.counters());
+        for player in 0..self.manager.max_players {
+            if matches!(self.manager.losers[player], Some(lose_data) if lose_data.move_index == move_index)
+            {
+                let reason = elimination_reason(self.board.counters(), player);
+                f(GameEvent::PlayerEliminated { player, reason });
+            }
+        }
+        if let GameState::Ended(result) = self.manager.game_state {
+            f(GameEvent::GameEnded { result });
+        }
+        Ok(played)
+    }
+    /// Cancels the move described by `played` and reverses the state of the game.
+    /// Moves should be cancelled in reverse order.
+    /// # Errors
+    /// Returns [`MoveError`] in the same cases as [`cancel_move_logged`]
+    /// or if there are no moves to cancel.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+    /// let initial = game.clone();
+    /// let record = game.play(game.board().index(0, 0)).unwrap();
+    /// game.undo(record).unwrap();
+    /// assert_eq!(game, initial);
+    /// ```
+    pub fn undo(&mut self, played: GameMove<B::Index>) -> Result<(), MoveError> {
+        if self.stats.is_some() {
+            return self.undo_with_events(played, |_| {});
+        }
+        if self.manager.current_move == 0 {
+            return Err(MoveError::NothingToCancel);
+        }
+        cancel_move_logged(&mut self.board, played.record, &mut self.log)?;
+        self.reverse(&played);
+        Ok(())
+    }
+    /// Cancels the move like [`Game::undo`] and passes changes of cells and chains
+    /// to `f` in the order they happen.
+    /// # Errors
+    /// Returns [`MoveError`] in the same cases as [`Game::undo`], nothing is passed to `f`.
+    pub fn undo_with_events(
+        &mut self,
+        played: GameMove<B::Index>,
+        f: impl FnMut(GameEvent<B::Index, B::Data>),
+    ) -> Result<(), MoveError> {
+        if self.manager.current_move == 0 {
+            return Err(MoveError::NothingToCancel);
+        }
+        let (record, log) = (played.record, &mut self.log);
+        let (result, move_) = tally(&mut self.board, record.player, f, |board| {
+            cancel_move_logged(board, record, log)
+        });
+        result?;
+        if let Some(stats) = &mut self.stats {
+            stats.record(&record, move_, true);
+        }
+        self.reverse(&played);
+        Ok(())
+    }
+    /// Marks the player as a loser between moves with given reason, e.g. when their time
+    /// is over, see [`PlayerManager::mark_loser`]. Like other losses, it's reverted by
+    /// [`Game::undo`] of the last move, unless it's marked before the first move.
+    /// The reason is reported by [`Game::summary`].
+    /// # Errors
+    /// Returns [`MoveError::GameOver`] if the game has ended.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::summary::EliminationReason;
+    /// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+    /// let record = game.play(game.board().index(0, 0)).unwrap();
+    /// game.mark_loser(1, EliminationReason::Timeout).unwrap();
+    /// assert_eq!(game.state(), GameState::Ended(GameOver::Win(0)));
+    /// let elimination = game.summary().eliminations()[0];
+    /// assert_eq!(elimination.reason(), EliminationReason::Timeout);
+    /// game.undo(record).unwrap();
+    /// assert_eq!(game.state(), GameState::Ongoing);
+    /// assert!(game.summary().eliminations().is_empty());
+    ///
+    /// let board = Board::new(3, 3, 3, [Cell::new(); 25], [0; 25]);
+    /// let mut game = Game::new(board, PlayerManager::new(1, 3, [None; 3]));
+    /// game.mark_loser(2, EliminationReason::Resigned).unwrap();
+    /// let record = game.play(game.board().index(0, 0)).unwrap();
+    /// game.undo(record).unwrap();
+    /// assert!(!game.player_manager().is_playing(2));
+    /// assert_eq!(game.summary().eliminations()[0].reason(), EliminationReason::Resigned);
+    /// ```
+    pub fn mark_loser(
+        &mut self,
+        player: usize,
+        reason: EliminationReason,
+    ) -> Result<(), MoveError> {
+        if self.manager.game_state != GameState::Ongoing {
+            return Err(MoveError::GameOver);
+        }
+        if player < self.manager.max_players.min(MAX_PLAYERS) && self.manager.is_playing(player) {
+            self.reasons[player] = Some(reason);
+        }
+        self.manager.mark_loser(player, self.board.counters());
+        Ok(())
+    }
+    /// Ends the game with given result by a rule, that isn't tracked by the game itself,
+    /// e.g. by [`draws`](crate::draws). Like other endings, it's reverted by [`Game::undo`]
+    /// of the last move. The reason is reported by [`Game::summary`].
+    /// # Errors
+    /// Returns [`MoveError::GameOver`] if the game has ended.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::summary::EndReason;
+    /// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+    /// let record = game.play(game.board().index(0, 0)).unwrap();
+    /// game.end(GameOver::Draw, EndReason::NoProgress).unwrap();
+    /// assert_eq!(game.state(), GameState::Ended(GameOver::Draw));
+    /// assert_eq!(game.end_reason(), Some(EndReason::NoProgress));
+    /// game.undo(record).unwrap();
+    /// assert_eq!((game.state(), game.end_reason()), (GameState::Ongoing, None));
+    /// ```
+    pub fn end(&mut self, result: GameOver, reason: EndReason) -> Result<(), MoveError> {
+        if self.manager.game_state != GameState::Ongoing {
+            return Err(MoveError::GameOver);
+        }
+        self.manager.game_state = GameState::Ended(result);
+        self.end_reason = Some(reason);
+        Ok(())
+    }
+    /// Returns the reason given to [`Game::end`], if the game has ended by it.
+    pub fn end_reason(&self) -> Option<EndReason> {
+        self.end_reason
+    }
+    /// Returns the reason given to [`Game::mark_loser`], if the player has lost by it.
+    pub(crate) fn marked_reason(&self, player: usize) -> Option<EliminationReason> {
+        self.reasons.get(player).copied().flatten()
+    }
+    /// Reverses the state of the game and forgets reasons of reverted losses.
+    /// Moves without the state of the turn are reversed by [`PlayerManager::reverse`].
+    fn reverse(&mut self, played: &GameMove<B::Index>) {
+        let (manager, player) = (&mut self.manager, played.record.player);
+        match played.remaining_moves {
+            0 => manager.reverse(player),
+            remaining => manager.reverse_with(player, played.turn, remaining),
+        }
+        self.end_reason = None;
+        let players = self.manager.max_players.min(MAX_PLAYERS);
+        for (player, reason) in self.reasons.iter_mut().enumerate().take(players) {
+            if self.manager.losers[player].is_none() {
+                *reason = None;
+            }
+        }
+    }
+}
+
+impl<C, B, S, L> Game<Board<C, B>, S, L>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    /// Makes a move like [`Game::play`], but checks, that the index is on the board.
+    /// # Errors
+    /// Returns [`MoveError::OutOfBounds`] if the index is out of the storage of the board
+    /// and [`MoveError`] in the same cases as [`Game::play`].
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::engine::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+    /// assert_eq!(game.checked_play(1000), Err(MoveError::OutOfBounds));
+    /// assert_eq!(game.board().checked_index(3, 0), None);
+    /// game.checked_play(game.board().index(0, 0)).unwrap();
+    /// ```
+    pub fn checked_play(&mut self, index: usize) -> Result<GameMove<usize>, MoveError> {
+        self.check_index(index)?;
+        self.play(index)
+    }
+    /// Cancels the move like [`Game::undo`], but first checks the move against the last one
+    /// of the game: its number, index, player, the cell of the move and the state of the turn,
+    /// so forged or misordered moves don't corrupt the game, see
+    /// [`PlayerManager::checked_reverse`]. Moves without the state of the turn are rejected.
+    /// With [`Stalemate::Skip`] turns of moves, after which turns were skipped, aren't
+    /// checked exactly.
+    /// # Errors
+    /// Returns [`MoveError::OutOfBounds`] if the index is out of the storage of the board,
+    /// [`MoveError::RecordMismatch`] if the move isn't the last one
+    /// and [`MoveError`] in the same cases as [`Game::undo`]. In this case nothing is changed.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::engine::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// let mut game = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+    /// let played = game.play(game.board().index(0, 0)).unwrap();
+    /// let record = MoveRecord { player: 7, ..played.record };
+    /// let forged = GameMove { record, ..played };
+    /// assert_eq!(game.checked_undo(forged), Err(MoveError::RecordMismatch));
+    /// let forged = GameMove { remaining_moves: 5, ..played };
+    /// assert_eq!(game.checked_undo(forged), Err(MoveError::RecordMismatch));
+    /// let last = game.play(game.board().index(1, 1)).unwrap();
+    /// assert_eq!(game.checked_undo(played), Err(MoveError::RecordMismatch));
+    /// let forged = GameMove { move_index: 1, ..played };
+    /// assert_eq!(game.checked_undo(forged), Err(MoveError::RecordMismatch));
+    /// game.checked_undo(last).unwrap();
+    /// game.checked_undo(played).unwrap();
+    /// assert_eq!(game.checked_undo(played), Err(MoveError::NothingToCancel));
+    /// ```
+    pub fn checked_undo(&mut self, played: GameMove<usize>) -> Result<(), MoveError> {
+        let record = played.record;
+        self.check_index(record.index)?;
+        let manager = &self.manager;
+        match manager.check_reverse(record.player) {
+            Err(ReverseError::NothingToReverse) => return Err(MoveError::NothingToCancel),
+            Err(_) => return Err(MoveError::RecordMismatch),
+            Ok(()) => {}
+        }
+        let (turn, remaining) = (played.turn, played.remaining_moves);
+        let ongoing = manager.game_state == GameState::Ongoing;
+        let in_progress = ongoing && manager.remaining_moves < manager.budget(manager.current_turn);
+        // The turn of the last move has passed, so it was its last move, unless the turn
+        // ended before it.
+        let player = record.player;
+        let lost = manager.losers[player].map(|data| data.move_index) == Some(played.move_index);
+        let ended_early =
+            !ongoing || lost || self.board.counters().get(Counter::Moves, player) == 0;
+        let turn_mismatch = match in_progress {
+            true => (turn, remaining) != (manager.current_turn, manager.remaining_moves + 1),
+            false if manager.stalemate == Stalemate::Skip => {
+                ongoing && turn >= manager.current_turn || remaining > 1 && !ended_early
+            }
+            false => turn != manager.last_turn(player) || remaining > 1 && !ended_early,
+        };
+        if played.move_index + 1 != manager.current_move
+            || remaining == 0
+            || turn > manager.current_turn
+            || remaining > manager.budget(turn)
+            || turn_mismatch
+        {
+            return Err(MoveError::RecordMismatch);
+        }
+        self.undo(played)
+    }
+    fn check_index(&self, index: usize) -> Result<(), MoveError> {
+        let board = &self.board;
+        if index < Board::<C, B>::storage_len(board.width(), board.height()) {
+            Ok(())
+        } else {
+            Err(MoveError::OutOfBounds)
+        }
+    }
+}
+
+/// Removes dead chains after the move, if it ends the turn: it was the last move of the turn
+/// or the player can't move anymore, see [`RuleSet::remove_dead_chains`]. Returns number
+/// of removed cells.
+///
+/// [`RuleSet::remove_dead_chains`]: crate::rules::RuleSet::remove_dead_chains
+fn end_turn<E: Engine, L: ChangeLog<E::Index>>(
+    board: &mut E,
+    log: &mut L,
+    player: usize,
+    remaining_moves: usize,
+) -> usize {
+    if remaining_moves > 1 && board.counters().get(Counter::Moves, player) > 0 {
+        return 0;
+    }
+    // The log is already checked by the move.
+    remove_dead_chains(board, log).unwrap_or(0)
+}
+
+/// Read-only snapshot of [`Game`]: the board and the player manager, that can be handed
+/// to spectators, renderers or other threads. [`Game::view`] borrows the board, so it's
+/// cheap, and [`GameView::cloned`] makes an independent copy. Losers are stored inline,
+/// so games with more than [`MAX_PLAYERS`] players, e.g. on other engines, can't be viewed.
+/// `B` - is type of the board or a reference to it.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// let board = Board::new(2, 1, 2, [Cell::new(); 12], [0; 12]);
+/// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+/// game.play(game.board().index(0, 0)).unwrap();
+/// let view = game.view().unwrap();
+/// assert_eq!((view.current_player(), view.state()), (1, GameState::Ongoing));
+/// assert!(!view.has_lost(0) && !view.has_lost(MAX_PLAYERS));
+/// assert_eq!(view.board(), &game.board());
+/// let snapshot = view.cloned();
+/// game.play(game.board().index(1, 0)).unwrap();
+/// assert_eq!(snapshot.current_player(), 1);
+/// assert_ne!(snapshot.board(), game.board());
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GameView<B> {
+    board: B,
+    manager: PlayerManager<[Option<LoseData>; MAX_PLAYERS]>,
+}
+impl<B> GameView<B> {
+    /// Returns the board.
+    pub fn board(&self) -> &B {
+        &self.board
+    }
+    /// Returns the player manager.
+    pub fn player_manager(&self) -> &PlayerManager<[Option<LoseData>; MAX_PLAYERS]> {
+        &self.manager
+    }
+    /// Returns the player, that makes the move now.
+    pub fn current_player(&self) -> usize {
+        self.manager.current_player
+    }
+    /// Returns the state of the game.
+    pub fn state(&self) -> GameState {
+        self.manager.game_state
+    }
+    /// Checks if the player has lost, `false` for players, that aren't in the game.
+    pub fn has_lost(&self, player: usize) -> bool {
+        let manager = &self.manager;
+        player < manager.max_players && matches!(manager.losers.get(player), Some(Some(_)))
+    }
+}
+impl<B: Clone> GameView<&B> {
+    /// Returns the snapshot with a copy of the board.
+    pub fn cloned(&self) -> GameView<B> {
+        GameView {
+            board: self.board.clone(),
+            manager: self.manager.clone(),
+        }
+    }
+}
+impl<B, S, L> Game<B, S, L>
+where
+    B: Engine,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+{
+    /// Returns read-only snapshot of the game, see [`GameView`], or `None`,
+    /// if the game has more than [`MAX_PLAYERS`] players.
+    pub fn view(&self) -> Option<GameView<&B>> {
+        let manager = &self.manager;
+        if manager.max_players > MAX_PLAYERS {
+            return None;
+        }
+        let mut losers = [None; MAX_PLAYERS];
+        for (player, loser) in losers.iter_mut().enumerate().take(manager.max_players) {
+            *loser = manager.losers[player];
+        }
+        Some(GameView {
+            board: &self.board,
+            manager: PlayerManager {
+                remaining_moves: manager.remaining_moves,
+                max_moves: manager.max_moves,
+                current_player: manager.current_player,
+                max_players: manager.max_players,
+                current_move: manager.current_move,
+                game_state: manager.game_state,
+                losers,
+                current_turn: manager.current_turn,
+                schedule: manager.schedule,
+                stalemate: manager.stalemate,
+                neutral: manager.neutral,
+            },
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct GameData<B, S: IndexMut<usize, Output = Option<LoseData>>> {
+    board: B,
+    manager: PlayerManager<S>,
+}
+#[cfg(feature = "serde")]
+impl<'de, B, S> Deserialize<'de> for Game<B, S>
+where
+    B: Engine + Deserialize<'de>,
+    S: IndexMut<usize, Output = Option<LoseData>> + AsRef<[Option<LoseData>]> + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let GameData { board, manager } = GameData::<B, S>::deserialize(deserializer)?;
+        if board.counters().players() != manager.max_players {
+            return Err(D::Error::custom(
+                "board and manager have different number of players",
+            ));
+        }
+        let players = manager.max_players;
+        let losers = manager
+            .losers
+            .as_ref()
+            .get(..players)
+            .ok_or_else(|| D::Error::custom("losers storage is too small"))?;
+        if manager.current_player >= manager.max_players
+            || manager.remaining_moves > manager.max_moves
+            || manager.max_moves == 0
+            || matches!(manager.neutral, Some(neutral) if neutral >= manager.max_players)
+        {
+            return Err(D::Error::custom("invalid state of the player manager"));
+        }
+        let mut losses = losers.iter().flatten();
+        if losses.any(|data| data.is_after(manager.current_move)) {
+            return Err(D::Error::custom("player has lost in the future"));
+        }
+        let consistent = match manager.game_state {
+            GameState::Ongoing => {
+                let remaining = manager.remaining_moves;
+                manager.is_playing(manager.current_player)
+                    && remaining != 0
+                    && remaining <= manager.budget(manager.current_turn)
+            }
+            GameState::Ended(GameOver::Win(winner)) => {
+                winner < players && manager.is_playing(winner)
+            }
+            GameState::Ended(GameOver::Draw) => true,
+        };
+        if !consistent {
+            return Err(D::Error::custom("invalid state of the game"));
+        }
+        Ok(Self::new(board, manager))
+    }
+}
@@ -0,0 +1,110 @@
+//! Handicaps
+//!
+//! This module sets up handicap games, e.g. for teaching: the weaker player gets extra
+//! crosses around their starting cell before the game starts. Crosses are placed by the
+//! standard pattern [`HANDICAP_PATTERN`], the level of handicap is the number of crosses.
+//! Every cross of the pattern is adjacent to one of the previous ones, so the handicap
+//! of level `n` is the same as `n` free moves from the starting cell.
+//! # Example
+//! ```
+//! # use crosses_utils::base::*;
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::counters::*;
+//! # use crosses_utils::engine::*;
+//! # use crosses_utils::handicap::*;
+//! let mut board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+//! place_handicap(&mut board, 1, 3).unwrap();
+//! for (x, y) in [(4, 4), (3, 3), (2, 2)] {
+//!     assert_eq!(board.kind(board.index(x, y)), CellKind::Cross);
+//! }
+//! assert_eq!(board.counters().get(Counter::Crosses, 1), 3);
+//! let corner = board.index(0, 0);
+//! assert!(make_move(&mut board, corner, 0).is_ok());
+//! assert_eq!(place_handicap(&mut board, 1, 9), Err(HandicapError::TooLarge));
+//! assert_eq!(place_handicap(&mut board, 0, 1), Err(HandicapError::Occupied));
+//! ```
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::base::{CellKind, GameBoard};
+use crate::board::{Board, CellStorage};
+
+/// The greatest level of handicap.
+pub const MAX_HANDICAP: usize = 8;
+
+/// Offsets `(dx, dy)` of handicap crosses from the starting cell towards the center
+/// of the board, the first `level` of them are placed.
+pub const HANDICAP_PATTERN: [(usize, usize); MAX_HANDICAP] = [
+    (0, 0),
+    (1, 1),
+    (2, 2),
+    (2, 0),
+    (0, 2),
+    (3, 1),
+    (1, 3),
+    (3, 3),
+];
+
+/// Errors of [`place_handicap`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum HandicapError {
+    /// The player has no starting cell
+    NoStart,
+    /// The level is greater than [`MAX_HANDICAP`] or the pattern doesn't fit the board
+    TooLarge,
+    /// A cell of the pattern isn't empty
+    Occupied,
+}
+impl Display for HandicapError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            HandicapError::NoStart => write!(f, "player has no starting cell"),
+            HandicapError::TooLarge => write!(f, "handicap doesn't fit the board"),
+            HandicapError::Occupied => write!(f, "cell of handicap is occupied"),
+        }
+    }
+}
+
+/// Places crosses of the player by [`HANDICAP_PATTERN`] with given level and recomputes
+/// the rest of the board with [`Board::init`]. The pattern is reflected, so it goes from
+/// the starting cell towards the center of the board.
+/// # Errors
+/// Returns [`HandicapError`] if the pattern can't be placed. In this case nothing is changed.
+pub fn place_handicap<C, B>(
+    board: &mut Board<C, B>,
+    player: usize,
+    level: usize,
+) -> Result<(), HandicapError>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let start = board.start(player).ok_or(HandicapError::NoStart)?;
+    let pattern = HANDICAP_PATTERN
+        .get(..level)
+        .ok_or(HandicapError::TooLarge)?;
+    let (width, height) = (board.width(), board.height());
+    let (x, y) = board.coords(start);
+    let towards = |start: usize, offset: usize, size: usize| {
+        if start * 2 < size {
+            Some(start + offset).filter(|coord| *coord < size)
+        } else {
+            start.checked_sub(offset)
+        }
+    };
+    let cell = |(dx, dy): (usize, usize)| {
+        let (x, y) = (towards(x, dx, width)?, towards(y, dy, height)?);
+        Some(board.index(x, y))
+    };
+    let mut cells = [0; MAX_HANDICAP];
+    for (index, offset) in cells.iter_mut().zip(pattern) {
+        *index = cell(*offset).ok_or(HandicapError::TooLarge)?;
+        if board.kind(*index) != CellKind::Empty {
+            return Err(HandicapError::Occupied);
+        }
+    }
+    for index in &cells[..level] {
+        board.set_up(*index, CellKind::Cross, player);
+    }
+    board.init();
+    Ok(())
+}
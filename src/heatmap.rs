@@ -0,0 +1,129 @@
+//! Move heatmaps
+//!
+//! This module defines [`Heatmap`], the matrix of numbers of moves into every cell of
+//! boards of the same size, accumulated over one or many games. Moves into empty cells
+//! are counted as plays and moves into crosses of other players as captures. Heatmaps
+//! don't allocate: counters are kept in a storage provided by the user, e.g. an array.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::heatmap::*;
+//! # use crosses_utils::player_manager::*;
+//! let mut heatmap = Heatmap::new(2, 1, [Heat::default(); 2]);
+//! for _ in 0..2 {
+//!     let board = Board::new(2, 1, 2, [Cell::new(); 12], [0; 12]);
+//!     let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+//!     for x in [0, 1, 1] {
+//!         let played = game.play(game.board().index(x, 0)).unwrap();
+//!         heatmap.record(game.board(), &played.record);
+//!     }
+//! }
+//! assert_eq!((heatmap.get(0, 0).plays(), heatmap.get(0, 0).captures()), (2, 0));
+//! assert_eq!((heatmap.get(1, 0).plays(), heatmap.get(1, 0).captures()), (2, 2));
+//! assert_eq!(heatmap.max().total(), 4);
+//! let row = heatmap.rows().next().unwrap();
+//! assert_eq!(row.iter().map(Heat::total).sum::<u32>(), 6);
+//! ```
+use crate::base::CellKind;
+use crate::board::{Board, CellStorage};
+use crate::engine::MoveRecord;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Numbers of moves into a cell, see [`Heatmap`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Heat {
+    plays: u32,
+    captures: u32,
+}
+impl Heat {
+    /// Returns number of moves into the cell, when it was empty.
+    pub fn plays(&self) -> u32 {
+        self.plays
+    }
+    /// Returns number of moves, that filled a cross of other player in the cell.
+    pub fn captures(&self) -> u32 {
+        self.captures
+    }
+    /// Returns number of all moves into the cell.
+    pub fn total(&self) -> u32 {
+        self.plays + self.captures
+    }
+}
+
+/// Matrix of [`Heat`] of cells, see module docs.
+/// `S` - is type of storage of counters, it should have `width * height` elements,
+/// which are indexed by packed indices of cells (see [`Board::pack_index`]).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Heatmap<S> {
+    width: usize,
+    height: usize,
+    cells: S,
+}
+impl<S: AsRef<[Heat]> + AsMut<[Heat]>> Heatmap<S> {
+    /// Creates new heatmap of boards with given size.
+    /// # Panics
+    /// Panics if the storage has less than `width * height` elements.
+    pub fn new(width: usize, height: usize, cells: S) -> Self {
+        assert!(
+            cells.as_ref().len() >= width * height,
+            "storage is too small"
+        );
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+    /// Returns width of the boards.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    /// Returns height of the boards.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    /// Returns heat of the cell with given coordinates.
+    pub fn get(&self, x: usize, y: usize) -> Heat {
+        debug_assert!(x < self.width && y < self.height);
+        self.cells.as_ref()[y * self.width + x]
+    }
+    /// Returns heat of cells row by row.
+    pub fn rows(&self) -> impl Iterator<Item = &[Heat]> + '_ {
+        self.cells.as_ref()[..self.width * self.height].chunks(self.width.max(1))
+    }
+    /// Returns the greatest heat by [`Heat::total`], e.g. for normalization of colors.
+    pub fn max(&self) -> Heat {
+        self.cells.as_ref()[..self.width * self.height]
+            .iter()
+            .copied()
+            .max_by_key(Heat::total)
+            .unwrap_or_default()
+    }
+    /// Counts the move made on the board, which should have the size of the heatmap.
+    pub fn record<C: CellStorage, B: AsMut<[usize]>>(
+        &mut self,
+        board: &Board<C, B>,
+        record: &MoveRecord<usize>,
+    ) {
+        debug_assert!(board.width() == self.width && board.height() == self.height);
+        let heat = &mut self.cells.as_mut()[board.pack_index(record.index)];
+        match record.prior_kind {
+            CellKind::Cross => heat.captures += 1,
+            _ => heat.plays += 1,
+        }
+    }
+    /// Sets all counters to `0`.
+    pub fn clear(&mut self) {
+        for heat in self.cells.as_mut() {
+            *heat = Heat::default();
+        }
+    }
+    /// Unwraps the storage.
+    pub fn into_inner(self) -> S {
+        self.cells
+    }
+}
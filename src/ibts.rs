@@ -12,10 +12,24 @@
 //! removed, all adjacent important cells, if they have no other important neighbors, cease
 //! to be important and in the case of filled cells the search for a new activator begins
 //! (And, of course, if no such activator is found, the chain is killed).
+//!
+//! If searches are still too expensive, [`IbtsBoard`] can be wrapped in [`Anchored`],
+//! which remembers found activators and checks them before the full search.
+use core::ops::IndexMut;
+
 use crate::base::{CellKind, GameBoard};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// An implementation of IBTS.
 /// It manages the importance and aliveness of cells.
+/// By default hooks call [`kill`] on important alive chains around the removed
+/// important cell. Boards, that return `true` from [`searches_activators`],
+/// get [`search`] for such chains first and [`kill`] only if nothing was found.
+///
+/// [`kill`]: IbtsBoard::kill
+/// [`search`]: IbtsBoard::search
+/// [`searches_activators`]: IbtsBoard::searches_activators
 pub trait IbtsBoard: GameBoard {
     /// Checks if the cell at given index is important.
     fn is_important(&self, index: Self::Index) -> bool;
@@ -29,12 +43,22 @@ pub trait IbtsBoard: GameBoard {
     /// Revives the chain of filled cells (Sets their alive value to `true`)
     /// It's guaranteed that cell at given index whould be dead.
     fn revive(&mut self, index: Self::Index);
-    /// Kills the chain of filled cells (Sets their alive value to `true`)
+    /// Kills the chain of filled cells (Sets their alive value to `false`)
     /// It's guaranteed that cell at given index whould be alive.
     fn kill(&mut self, index: Self::Index);
     /// Searches for new activator. Should return `None` or index of new
     /// activator with adjacent filled cell that belongs to the searched chain.
     fn search(&mut self, index: Self::Index) -> Option<SearchResult<Self::Index>>;
+    /// Checks if hooks should call [`search`] before [`kill`]. Then removal
+    /// of a filled cell also checks every part of its chain, that could be split,
+    /// after [`on_split`]. Returns `false` by default.
+    ///
+    /// [`kill`]: IbtsBoard::kill
+    /// [`search`]: IbtsBoard::search
+    /// [`on_split`]: IbtsBoard::on_split
+    fn searches_activators(&self) -> bool {
+        false
+    }
 
     /// Should be called after changing cell at given index from
     /// [`CellKind::Empty`] to [`CellKind::Cross`].
@@ -46,7 +70,7 @@ pub trait IbtsBoard: GameBoard {
     /// Former player of cell should be passed as previous_player.
     fn on_place_filled(&mut self, index: Self::Index, previous_player: Self::Player) {
         if self.is_important(index) {
-            kill_around(self, index, previous_player);
+            kill_around(self, index, previous_player, false);
         }
         if !are_alive_filled_around(self, index, self.player(index)) {
             self.set_important(index, true);
@@ -58,17 +82,28 @@ pub trait IbtsBoard: GameBoard {
     /// [`CellKind::Filled`] to [`CellKind::Cross`].
     /// Former player of cell should be passed as previous_player.
     fn on_remove_filled(&mut self, index: Self::Index, previous_player: Self::Player) {
-        if self.is_important(index) {
-            kill_around(self, index, previous_player);
-        }
+        split_around(self, index, previous_player);
         revive_around(self, index, self.player(index));
     }
+    /// Should be called after changing filled cell at given index of `previous_player`
+    /// to dead filled cell of other player, e.g. when conversion of a chain is cancelled.
+    fn on_release_filled(&mut self, index: Self::Index, previous_player: Self::Player) {
+        split_around(self, index, previous_player);
+    }
+    /// Called by [`on_remove_filled`] and [`on_release_filled`] of boards, that search
+    /// activators, before the search for every part of the chain, that could be split
+    /// by the removed cell. Does nothing by default, boards, that cache activators,
+    /// should forget them here.
+    ///
+    /// [`on_remove_filled`]: IbtsBoard::on_remove_filled
+    /// [`on_release_filled`]: IbtsBoard::on_release_filled
+    fn on_split(&mut self, _index: Self::Index) {}
     /// Should be called after changing cell at given index from
     /// [`CellKind::Cross`] to [`CellKind::Empty`].
     /// Former player of cell should be passed as previous_player.
     fn on_remove_cross(&mut self, index: Self::Index, previous_player: Self::Player) {
         if self.is_important(index) {
-            kill_around(self, index, previous_player);
+            kill_around(self, index, previous_player, false);
         }
     }
 }
@@ -82,6 +117,224 @@ pub struct SearchResult<I> {
     pub cross: I,
 }
 
+/// A packed storage for IBTS flags of a single cell.
+/// It can be embedded into cell type of the board, so that
+/// flag related methods of [`IbtsBoard`] just delegate to it.
+/// # Example
+/// ```
+/// # use crosses_utils::ibts::IbtsCellFlags;
+/// let mut flags = IbtsCellFlags::new();
+/// flags.set_alive(true);
+/// flags.set_important(true);
+/// assert!(flags.is_alive() && flags.is_important());
+/// flags.reset();
+/// assert_eq!(flags, IbtsCellFlags::new());
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct IbtsCellFlags(u8);
+impl IbtsCellFlags {
+    const ALIVE: u8 = 0b01;
+    const IMPORTANT: u8 = 0b10;
+
+    /// Creates new flags. Cell is neither alive nor important.
+    pub const fn new() -> Self {
+        Self(0)
+    }
+    /// Checks if the cell is important.
+    pub fn is_important(&self) -> bool {
+        self.0 & Self::IMPORTANT != 0
+    }
+    /// Sets new importance value.
+    pub fn set_important(&mut self, new: bool) {
+        self.set(Self::IMPORTANT, new)
+    }
+    /// Checks if the cell is alive.
+    pub fn is_alive(&self) -> bool {
+        self.0 & Self::ALIVE != 0
+    }
+    /// Sets new aliveness value.
+    pub fn set_alive(&mut self, new: bool) {
+        self.set(Self::ALIVE, new)
+    }
+    /// Resets both flags, as if the flags were just created.
+    pub fn reset(&mut self) {
+        self.0 = 0
+    }
+    fn set(&mut self, mask: u8, new: bool) {
+        if new {
+            self.0 |= mask
+        } else {
+            self.0 &= !mask
+        }
+    }
+}
+
+/// A wrapper, that caches results of [`IbtsBoard::search`].
+/// Every found activator is stored in `anchors` at indices of the cell the search
+/// started from and of the found filled cell. Next search from these cells checks
+/// the stored activator first and performs the full search only if it is no longer valid.
+/// Only [`on_split`] invalidates stored activators, because only removal of a filled
+/// cell can separate the chain from the activator it was found for, kills and fills
+/// are caught by the check of the activator itself.
+/// Cached activators can differ from the ones found by the full search, so other cells
+/// can become important, but moves have the same results as with the wrapped board.
+/// `S` - is type of storage. It can be Vec or simple array.
+///
+/// [`on_split`]: IbtsBoard::on_split
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::ibts::*;
+/// # use crosses_utils::player_manager::*;
+/// # use crosses_utils::simulate::*;
+/// let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+/// let manager = PlayerManager::new(2, 2, [None; 2]);
+/// let mut anchored = Game::new(Anchored::new(board.clone(), [None; 49]), manager.clone());
+/// let mut plain = Game::new(board.clone(), manager.clone());
+/// for (index, _) in RandomGame::new(Game::new(board, manager), 14) {
+///     anchored.play(index).unwrap();
+///     plain.play(index).unwrap();
+///     assert!((0..49).all(|i| anchored.board().is_alive(i) == plain.board().is_alive(i)));
+/// }
+/// // The plain board traversed chains for these searches.
+/// assert!(anchored.board().hits() > 0);
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Anchored<B: IbtsBoard, S: IndexMut<B::Index, Output = Option<Anchor<B::Index>>>> {
+    board: B,
+    anchors: S,
+    generation: usize,
+    hits: usize,
+}
+impl<B, S> Anchored<B, S>
+where
+    B: IbtsBoard,
+    S: IndexMut<B::Index, Output = Option<Anchor<B::Index>>>,
+{
+    /// Wraps the `board`. `anchors` should be able to work with every
+    /// index of the board, and all values should be `None` initially.
+    pub fn new(board: B, anchors: S) -> Self {
+        Self {
+            board,
+            anchors,
+            generation: 0,
+            hits: 0,
+        }
+    }
+    /// Returns reference to the wrapped board.
+    pub fn board(&self) -> &B {
+        &self.board
+    }
+    /// Returns mutable reference to the wrapped board.
+    pub fn board_mut(&mut self) -> &mut B {
+        &mut self.board
+    }
+    /// Returns number of searches answered by cached activators,
+    /// that is number of searches of the wrapped board avoided.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+    /// Unwraps the board, dropping cached activators.
+    pub fn into_inner(self) -> B {
+        self.board
+    }
+    fn invalidate(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+    fn is_valid(&self, anchor: Anchor<B::Index>, player: B::Player) -> bool
+    where
+        B::Index: PartialEq,
+    {
+        let SearchResult { filled, cross } = anchor.result;
+        anchor.generation == self.generation
+            && self.board.kind(cross) == CellKind::Cross
+            && self.board.player(cross) == player
+            && self.board.kind(filled) == CellKind::Filled
+            && self.board.player(filled) == player
+            && self.board.adjacent(filled).into_iter().any(|i| i == cross)
+    }
+}
+impl<B, S> GameBoard for Anchored<B, S>
+where
+    B: IbtsBoard,
+    S: IndexMut<B::Index, Output = Option<Anchor<B::Index>>>,
+{
+    type Index = B::Index;
+    type Adjacent = B::Adjacent;
+    type Player = B::Player;
+
+    fn adjacent(&self, index: Self::Index) -> Self::Adjacent {
+        self.board.adjacent(index)
+    }
+    fn kind(&self, index: Self::Index) -> CellKind {
+        self.board.kind(index)
+    }
+    fn player(&self, index: Self::Index) -> Self::Player {
+        self.board.player(index)
+    }
+}
+impl<B, S> IbtsBoard for Anchored<B, S>
+where
+    B: IbtsBoard,
+    B::Index: PartialEq,
+    S: IndexMut<B::Index, Output = Option<Anchor<B::Index>>>,
+{
+    fn is_important(&self, index: Self::Index) -> bool {
+        self.board.is_important(index)
+    }
+    fn set_important(&mut self, index: Self::Index, new: bool) {
+        self.board.set_important(index, new)
+    }
+    fn is_alive(&self, index: Self::Index) -> bool {
+        self.board.is_alive(index)
+    }
+    fn set_alive(&mut self, index: Self::Index, new: bool) {
+        self.board.set_alive(index, new)
+    }
+
+    fn revive(&mut self, index: Self::Index) {
+        self.board.revive(index)
+    }
+    fn kill(&mut self, index: Self::Index) {
+        self.board.kill(index)
+    }
+    fn search(&mut self, index: Self::Index) -> Option<SearchResult<Self::Index>> {
+        if let Some(anchor) = self.anchors[index] {
+            if self.is_valid(anchor, self.board.player(index)) {
+                self.hits += 1;
+                return Some(anchor.result);
+            }
+        }
+        let result = self.board.search(index);
+        let anchor = result.map(|result| Anchor {
+            generation: self.generation,
+            result,
+        });
+        self.anchors[index] = anchor;
+        if let Some(result) = result {
+            self.anchors[result.filled] = anchor;
+        }
+        result
+    }
+    fn searches_activators(&self) -> bool {
+        true
+    }
+
+    fn on_split(&mut self, index: Self::Index) {
+        self.invalidate();
+        self.board.on_split(index)
+    }
+}
+
+/// A cached activator of [`Anchored`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Anchor<I> {
+    generation: usize,
+    result: SearchResult<I>,
+}
+
 fn revive_around<M: IbtsBoard + ?Sized>(manager: &mut M, index: M::Index, player: M::Player) {
     for i in manager.adjacent(index) {
         if manager.kind(i) == CellKind::Filled
@@ -94,15 +347,38 @@ fn revive_around<M: IbtsBoard + ?Sized>(manager: &mut M, index: M::Index, player
         }
     }
 }
-fn kill_around<M: IbtsBoard + ?Sized>(manager: &mut M, index: M::Index, player: M::Player) {
+/// Handles removal of the filled cell of `player` from its chain.
+fn split_around<M: IbtsBoard + ?Sized>(manager: &mut M, index: M::Index, player: M::Player) {
+    if manager.searches_activators() {
+        // Removed cell could connect parts of the chain, so every part is checked.
+        manager.on_split(index);
+        kill_around(manager, index, player, true);
+    } else if manager.is_important(index) {
+        kill_around(manager, index, player, false);
+    }
+}
+fn kill_around<M: IbtsBoard + ?Sized>(
+    manager: &mut M,
+    index: M::Index,
+    player: M::Player,
+    check_all: bool,
+) {
     manager.set_important(index, false);
+    let searches = manager.searches_activators();
     for i in manager.adjacent(index) {
         if manager.kind(i) == CellKind::Filled
             && manager.player(i) == player
             && manager.is_alive(i)
-            && manager.is_important(i)
+            && (check_all || manager.is_important(i))
         {
-            manager.kill(i);
+            let found = if searches { manager.search(i) } else { None };
+            match found {
+                Some(SearchResult { filled, cross }) => {
+                    manager.set_important(filled, true);
+                    manager.set_important(cross, true);
+                }
+                None => manager.kill(i),
+            }
             if !is_paired(manager, i, player) {
                 manager.set_important(i, false)
             }
@@ -110,7 +386,7 @@ fn kill_around<M: IbtsBoard + ?Sized>(manager: &mut M, index: M::Index, player:
     }
 }
 fn are_alive_filled_around<M: IbtsBoard + ?Sized>(
-    manager: &mut M,
+    manager: &M,
     index: M::Index,
     player: M::Player,
 ) -> bool {
@@ -136,7 +412,7 @@ fn mark_adjacent_as_important<M: IbtsBoard + ?Sized>(
         manager.set_important(important_index, true);
     }
 }
-fn is_paired<M: IbtsBoard + ?Sized>(manager: &mut M, index: M::Index, player: M::Player) -> bool {
+fn is_paired<M: IbtsBoard + ?Sized>(manager: &M, index: M::Index, player: M::Player) -> bool {
     manager
         .adjacent(index)
         .into_iter()
@@ -1,13 +1,77 @@
 //! A set of useful things for crosses connoisseurs. What are crosses?
 //! You can find them in the russian book “Логика или фортуна”,
 //! though there they are called “Война вирусов”.
-//! Currently there are two useful things:
-//! [`PlayerManager`] and [`IbtsBoard`].
+//! The main useful things are [`PlayerManager`] and [`IbtsBoard`].
+//! On top of them [`engine`] implements the moves themselves,
+//! [`Board`] is a ready-to-use rectangular board for it
+//! and [`Game`] ties everything together.
 //!
 //! [`PlayerManager`]: player_manager::PlayerManager
 //! [`IbtsBoard`]: ibts::IbtsBoard
+//! [`Board`]: board::Board
+//! [`Game`]: game::Game
 
 #![no_std]
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod adjudication;
+pub mod analysis;
 pub mod base;
+pub mod binary;
+pub mod bitmap;
+pub mod board;
+pub mod bots;
+pub mod clock;
+#[cfg(feature = "testing")]
+pub mod conformance;
+pub mod counters;
+pub mod diff;
+pub mod draws;
+pub mod engine;
+pub mod events;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "testing")]
+pub mod fixtures;
+pub mod flood;
+pub mod fog;
+#[cfg(feature = "testing")]
+pub mod fuzz;
+pub mod game;
+pub mod handicap;
+pub mod heatmap;
 pub mod ibts;
+pub mod maps;
+pub mod match_play;
+pub mod net;
+pub mod observer;
+pub mod perft;
+#[cfg(feature = "alloc")]
+pub mod persistent;
 pub mod player_manager;
+pub mod puzzle;
+pub mod rating;
+pub mod record;
+#[cfg(feature = "testing")]
+pub mod reference;
+pub mod render;
+pub mod replay;
+pub mod rules;
+pub mod search;
+pub mod season;
+pub mod sgf;
+#[cfg(feature = "std")]
+pub mod shared;
+pub mod simulate;
+pub mod solver;
+pub mod stats;
+pub mod summary;
+pub mod symmetry;
+pub mod text;
+pub mod tournament;
+pub mod transposition;
+pub mod validate;
+pub mod zobrist;
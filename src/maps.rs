@@ -0,0 +1,208 @@
+//! Generation of maps
+//!
+//! This module generates layouts of obstacles for [`Board`]: border cells inside the board,
+//! where nobody can move. [`generate_obstacles`] places them randomly with given density and
+//! symmetric under given [`SymmetryOp`], so players with symmetric starting cells are
+//! in the same position, and keeps starting cells of all players connected, so nobody is
+//! locked out. Randomness comes from [`Rng`], so the same seed of e.g. [`SplitMix64`]
+//! gives the same map.
+//!
+//! [`SplitMix64`]: crate::bots::SplitMix64
+//! # Example
+//! ```
+//! # use crosses_utils::base::*;
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::bots::*;
+//! # use crosses_utils::maps::*;
+//! let mut board = Board::new(7, 7, 2, [Cell::new(); 81], [0; 81]);
+//! let config = MapConfig { density: 30, ..MapConfig::default() };
+//! let obstacles = generate_obstacles(&mut board, config, &mut SplitMix64(7));
+//! assert!(obstacles > 0);
+//! let kind = |x, y| board.kind(board.index(x, y));
+//! for (x, y) in (0..7).flat_map(|y| (0..7).map(move |x| (x, y))) {
+//!     assert_eq!(kind(x, y), kind(6 - x, 6 - y));
+//! }
+//! let mut other = Board::new(7, 7, 2, [Cell::new(); 81], [0; 81]);
+//! generate_obstacles(&mut other, config, &mut SplitMix64(7));
+//! assert_eq!(board, other);
+//! ```
+use crate::base::{CellKind, GameBoard};
+use crate::board::{Board, CellStorage, Walk};
+use crate::bots::Rng;
+use crate::counters::PlayerCounters;
+use crate::engine::{Data, Engine};
+use crate::symmetry::SymmetryOp;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Options of [`generate_obstacles`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct MapConfig {
+    /// Chance of obstacles in percents for every group of symmetric cells
+    pub density: u8,
+    /// Symmetry of the map
+    pub symmetry: SymmetryOp,
+}
+impl Default for MapConfig {
+    /// Obstacles on `20%` of cells, symmetric under rotation by 180 degrees like
+    /// starting cells of two players of [`Board::new`].
+    fn default() -> Self {
+        Self {
+            density: 20,
+            symmetry: SymmetryOp::Rotate180,
+        }
+    }
+}
+
+/// Places obstacles on empty cells of the board, that aren't starting cells, see module
+/// docs. Groups of symmetric cells become obstacles together, if all of them are empty.
+/// Then obstacles are removed, until starting cells are connected through adjacent cells.
+/// The rest of the board is recomputed with [`Board::init`]. Returns number of obstacles
+/// on the board.
+/// # Panics
+/// Panics if the symmetry doesn't fit the size of the board, see [`SymmetryOp::all`].
+pub fn generate_obstacles<C, B>(
+    board: &mut Board<C, B>,
+    config: MapConfig,
+    rng: &mut impl Rng,
+) -> usize
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let (width, height, symmetry) = (board.width(), board.height(), config.symmetry);
+    assert!(
+        SymmetryOp::all(width, height).contains(&symmetry),
+        "symmetry doesn't fit the board"
+    );
+    for packed in 0..width * height {
+        let (x, y) = (packed % width, packed / width);
+        // Every group is decided once, at its first cell.
+        if orbit(symmetry, width, height, x, y).any(|(x, y)| y * width + x < packed) {
+            continue;
+        }
+        if rng.below(100) >= config.density as usize {
+            continue;
+        }
+        let is_free = |(x, y)| {
+            let index = board.index(x, y);
+            board.get(index).kind() == CellKind::Empty && !is_start(board, index)
+        };
+        if orbit(symmetry, width, height, x, y).all(is_free) {
+            set_group(board, symmetry, x, y, CellKind::Border);
+        }
+    }
+    connect(board, symmetry, rng);
+    board.init();
+    (0..width * height)
+        .filter(|packed| board.kind(board.unpack_index(*packed)) == CellKind::Border)
+        .count()
+}
+
+/// Removes random obstacles next to cells connected to the first starting cell,
+/// until all starting cells are connected.
+fn connect<C, B>(board: &mut Board<C, B>, symmetry: SymmetryOp, rng: &mut impl Rng)
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let players = board.counters().players();
+    let starts = (0..players).filter(|p| board.start(*p).is_some()).count();
+    let first = match (0..players).find_map(|p| board.start(p)) {
+        Some(first) => first,
+        None => return,
+    };
+    let is_open = |board: &Board<C, B>, index| board.kind(index) != CellKind::Border;
+    loop {
+        let (mut reached, mut candidates) = (0, 0);
+        board.walk().traverse(
+            |_, index| index == first,
+            is_open,
+            |board, index| {
+                reached += (0..players)
+                    .filter(|p| board.start(*p) == Some(index))
+                    .count();
+                candidates += obstacles_around(board, index).count();
+                false
+            },
+        );
+        if reached >= starts || candidates == 0 {
+            return;
+        }
+        let (mut chosen, mut removed) = (rng.below(candidates), None);
+        board.walk().traverse(
+            |_, index| index == first,
+            is_open,
+            |board, index| {
+                for obstacle in obstacles_around(board, index) {
+                    if chosen == 0 {
+                        removed = Some(obstacle);
+                        return true;
+                    }
+                    chosen -= 1;
+                }
+                false
+            },
+        );
+        if let Some(obstacle) = removed {
+            let (x, y) = board.coords(obstacle);
+            set_group(board, symmetry, x, y, CellKind::Empty);
+        }
+    }
+}
+
+/// Returns obstacles adjacent to the cell, border around the board isn't included.
+fn obstacles_around<C, B>(board: &Board<C, B>, index: usize) -> impl Iterator<Item = usize> + '_
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let (width, height) = (board.width(), board.height());
+    board.adjacent(index).filter(move |i| {
+        let (x, y) = (i % (width + 2), i / (width + 2));
+        board.kind(*i) == CellKind::Border && (1..=width).contains(&x) && (1..=height).contains(&y)
+    })
+}
+
+fn is_start<C, B>(board: &Board<C, B>, index: usize) -> bool
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    (0..board.counters().players()).any(|player| board.start(player) == Some(index))
+}
+
+fn set_group<C, B>(
+    board: &mut Board<C, B>,
+    symmetry: SymmetryOp,
+    x: usize,
+    y: usize,
+    kind: CellKind,
+) where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let (width, height) = (board.width(), board.height());
+    for (x, y) in orbit(symmetry, width, height, x, y) {
+        let index = board.index(x, y);
+        board.set_up(index, kind, 0);
+    }
+}
+
+/// Returns coordinates of the cell and cells, where the symmetry moves it.
+fn orbit(
+    symmetry: SymmetryOp,
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    let mut current = Some((x, y));
+    core::iter::from_fn(move || {
+        let cell = current?;
+        let next = symmetry.apply(width, height, cell.0, cell.1);
+        current = if next == (x, y) { None } else { Some(next) };
+        Some(cell)
+    })
+}
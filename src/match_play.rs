@@ -0,0 +1,212 @@
+//! Matches between two bots
+//!
+//! This module plays best-of-N series between two [`Bot`]s like [`simulate`], but reports
+//! every game, so games can be archived, e.g. with [`record::write`]. Bots change colors
+//! every game: bot `0` plays for player `0` in even games. The series stops, when its
+//! winner is decided, i.e. the leader can't be caught in the remaining games.
+//! Wins give `1` point and draws give `1/2` point to both bots.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::bots::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::match_play::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::record;
+//! let new_game = |_| {
+//!     let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+//!     Game::new(board, PlayerManager::new(3, 2, [None; 2]))
+//! };
+//! let (mut greedy, mut random) = (GreedyBot::default(), RandomBot::new(SplitMix64(1)));
+//! let mut moves = [None::<GameMove<u8>>; 50];
+//! let mut archive = String::new();
+//! let score = match_play([&mut greedy, &mut random], 7, new_game, &mut moves, |played| {
+//!     assert_eq!(played.first(), played.number() % 2);
+//!     let tags = [("Round", "1")];
+//!     record::write(&mut archive, &tags, played.initial(), played.moves(), played.state())
+//!         .unwrap();
+//! });
+//! assert!(score.games() >= 4 && score.games() <= 7);
+//! assert_eq!(score.leader(), Some(0));
+//! assert!(score.points(0) > 3.5);
+//! assert_eq!(archive.matches("[Round \"1\"]").count() as u64, score.games());
+//! ```
+//!
+//! [`simulate`]: crate::simulate::simulate
+//! [`record::write`]: crate::record::write
+use core::ops::IndexMut;
+
+use crate::board::{Board, CellStorage, HistoryIndex};
+use crate::bots::Bot;
+use crate::engine::ChangeLog;
+use crate::game::{Game, GameMove};
+use crate::player_manager::{GameOver, GameState, LoseData};
+use crate::simulate::play_out;
+
+/// A game of the match, passed to the callback of [`match_play`].
+#[derive(Debug)]
+pub struct PlayedGame<'a, C, B, S, L = (), I = usize>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+    I: HistoryIndex,
+{
+    number: usize,
+    initial: &'a Game<Board<C, B>, S, L>,
+    game: &'a Game<Board<C, B>, S, L>,
+    moves: &'a [Option<GameMove<I>>],
+}
+impl<'a, C, B, S, L, I> PlayedGame<'a, C, B, S, L, I>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+    I: HistoryIndex,
+{
+    /// Returns the number of the game in the match, starting with `0`.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+    /// Returns the bot, that played for player `0`.
+    pub fn first(&self) -> usize {
+        self.number % 2
+    }
+    /// Returns the bot, that played for the player.
+    pub fn bot(&self, player: usize) -> usize {
+        (player + self.number) % 2
+    }
+    /// Returns the position before the first move.
+    pub fn initial(&self) -> &'a Game<Board<C, B>, S, L> {
+        self.initial
+    }
+    /// Returns the position after the last move.
+    pub fn game(&self) -> &'a Game<Board<C, B>, S, L> {
+        self.game
+    }
+    /// Returns the moves of the game.
+    pub fn moves(&self) -> impl Iterator<Item = &'a GameMove<I>> + 'a {
+        self.moves.iter().flatten()
+    }
+    /// Returns the state of the game after the last move. The game is ongoing, if it
+    /// was stopped, because a bot didn't choose a legal move or the buffer of moves
+    /// was full.
+    pub fn state(&self) -> GameState {
+        self.game.state()
+    }
+    /// Returns the bot, that won the game, if any.
+    pub fn winner(&self) -> Option<usize> {
+        match self.state() {
+            GameState::Ended(GameOver::Win(player)) => Some(self.bot(player)),
+            _ => None,
+        }
+    }
+}
+
+/// Score of the match, returned by [`match_play`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct MatchScore {
+    games: u64,
+    wins: [u64; 2],
+    draws: u64,
+    unfinished: u64,
+}
+impl MatchScore {
+    /// Returns number of played games.
+    pub fn games(&self) -> u64 {
+        self.games
+    }
+    /// Returns number of games won by the bot.
+    pub fn wins(&self, bot: usize) -> u64 {
+        self.wins[bot]
+    }
+    /// Returns number of games ended with a draw.
+    pub fn draws(&self) -> u64 {
+        self.draws
+    }
+    /// Returns number of stopped games, see [`PlayedGame::state`]. They give no points.
+    pub fn unfinished(&self) -> u64 {
+        self.unfinished
+    }
+    /// Returns points of the bot.
+    pub fn points(&self, bot: usize) -> f64 {
+        self.wins[bot] as f64 + self.draws as f64 / 2.0
+    }
+    /// Returns the bot with more points, if any.
+    pub fn leader(&self) -> Option<usize> {
+        match self.wins[0].cmp(&self.wins[1]) {
+            core::cmp::Ordering::Greater => Some(0),
+            core::cmp::Ordering::Less => Some(1),
+            core::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+/// Plays the match of at most `games` games between two bots, see module docs.
+/// `new_game` creates the initial position of the game with given number, it should
+/// have two players. Moves of the game are stored in `moves` with any [`HistoryIndex`],
+/// the game is stopped, when it's full: `2 * width * height` moves are always enough.
+/// `on_game` is called after every game.
+/// # Panics
+/// Panics if the game doesn't have two players, `moves` is empty or indices of the board
+/// don't fit `I`.
+pub fn match_play<C, B, S, L, I>(
+    mut bots: [&mut dyn Bot<Game<Board<C, B>, S, L>>; 2],
+    games: usize,
+    mut new_game: impl FnMut(usize) -> Game<Board<C, B>, S, L>,
+    moves: &mut [Option<GameMove<I>>],
+    mut on_game: impl FnMut(&PlayedGame<'_, C, B, S, L, I>),
+) -> MatchScore
+where
+    C: CellStorage + Clone,
+    B: AsMut<[usize]> + Clone,
+    S: IndexMut<usize, Output = Option<LoseData>> + Clone,
+    L: ChangeLog<usize> + Clone,
+    I: HistoryIndex,
+{
+    assert!(!moves.is_empty(), "buffer of moves is empty");
+    let mut score = MatchScore::default();
+    for number in 0..games {
+        let lead = score.wins[0].abs_diff(score.wins[1]);
+        if lead as usize > games - number {
+            break;
+        }
+        let initial = new_game(number);
+        assert_eq!(
+            initial.player_manager().max_players,
+            2,
+            "match needs two players"
+        );
+        let board = initial.board();
+        let last = board.index(board.width() - 1, board.height() - 1);
+        assert!(
+            I::from_index(board, last).is_some(),
+            "indices of the board don't fit the history"
+        );
+        let mut game = initial.clone();
+        let bot_of = |player: usize| (player + number) % 2;
+        let mut len = 0;
+        play_out(&mut bots, &mut game, bot_of, |record| {
+            let stored = |index| I::from_index(board, index).expect("index of the board fits");
+            moves[len] = Some(record.map_index(stored));
+            len += 1;
+            len < moves.len()
+        });
+        let played = PlayedGame {
+            number,
+            initial: &initial,
+            game: &game,
+            moves: &moves[..len],
+        };
+        score.games += 1;
+        match played.state() {
+            GameState::Ongoing => score.unfinished += 1,
+            GameState::Ended(GameOver::Draw) => score.draws += 1,
+            GameState::Ended(GameOver::Win(player)) => score.wins[bot_of(player)] += 1,
+        }
+        on_game(&played);
+    }
+    score
+}
@@ -0,0 +1,267 @@
+//! Messages for online play
+//!
+//! This module defines messages, that peers of an online game exchange, and [`Session`],
+//! a state machine, which numbers the messages and detects desync. Every message has
+//! the sequence number of the last move and the hash of the position after it, e.g.
+//! [`Game::consistency_hash`](crate::game::Game::consistency_hash). If a received move is lost, illegal
+//! or leads to another hash, session asks for [`FullState`] or [`ResyncDiff`]
+//! with [`SyncRequest`] and ignores moves until the position is restored.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::engine::*;
+//! # use crosses_utils::net::*;
+//! # use crosses_utils::zobrist::*;
+//! let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+//! let mut host = Hashed::new(board.clone(), Zobrist::default(), 0..25);
+//! let mut guest = host.clone();
+//! let (mut host_session, mut guest_session) = (Session::new(host.hash()), Session::new(guest.hash()));
+//! let corner = host.engine().index(0, 0);
+//! make_move(&mut host, corner, 0).unwrap();
+//! let message = host_session.send_move(host.engine().pack_index(corner) as u32, 0, host.hash());
+//! fn apply(guest: &mut Hashed<Board<[Cell; 25], [usize; 25]>>, message: &MoveMsg) -> Option<u64> {
+//!     let index = guest.engine().unpack_index(message.index as usize);
+//!     make_move(guest, index, message.player as usize).ok().map(|_| guest.hash())
+//! }
+//! assert_eq!(guest_session.receive_move(&message, |m| apply(&mut guest, m)), Ok(()));
+//! // The guest loses the next move.
+//! let center = host.engine().index(1, 1);
+//! make_move(&mut host, center, 0).unwrap();
+//! host_session.send_move(host.engine().pack_index(center) as u32, 0, host.hash());
+//! let next = host.engine().index(2, 2);
+//! make_move(&mut host, next, 0).unwrap();
+//! let message = host_session.send_move(host.engine().pack_index(next) as u32, 0, host.hash());
+//! let request = guest_session.receive_move(&message, |m| apply(&mut guest, m)).unwrap_err();
+//! assert_eq!(guest_session.state(), SyncState::AwaitingState);
+//! assert_eq!(request.seq, 1);
+//! let state = host_session.full_state(host.clone());
+//! let result = guest_session.receive_full_state(state, |state| {
+//!     guest = state;
+//!     guest.hash()
+//! });
+//! assert_eq!(result, Ok(()));
+//! assert_eq!(guest_session.state(), SyncState::InSync);
+//! ```
+use crate::player_manager::GameOver;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A move of a player.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct MoveMsg {
+    /// Sequence number of the move, the first move has `1`
+    pub seq: u64,
+    /// Packed index of the cell, see [`Board::pack_index`](crate::board::Board::pack_index)
+    pub index: u32,
+    /// The player, who made the move
+    pub player: u8,
+    /// Hash of the position after the move
+    pub hash: u64,
+}
+/// A request of the position, sent after desync. It has the last
+/// sequence number and the hash, that both peers agreed on.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct SyncRequest {
+    /// Sequence number of the last agreed move
+    pub seq: u64,
+    /// Hash of the last agreed position
+    pub hash: u64,
+}
+/// The whole state of the game, e.g. [`Game`](crate::game::Game) or a board.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct FullState<G> {
+    /// Sequence number of the last move
+    pub seq: u64,
+    /// Hash of the position
+    pub hash: u64,
+    /// The state
+    pub state: G,
+}
+/// Changes since the last agreed position, e.g. a list of
+/// [`CellChange`](crate::diff::CellChange).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct ResyncDiff<D> {
+    /// Sequence number of the last move
+    pub seq: u64,
+    /// Hash of the position after the changes
+    pub hash: u64,
+    /// The changes
+    pub changes: D,
+}
+/// End of the game.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct GameOverMsg {
+    /// Sequence number of the last move
+    pub seq: u64,
+    /// Hash of the final position
+    pub hash: u64,
+    /// Result of the game
+    pub result: GameOver,
+}
+
+/// State of [`Session`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum SyncState {
+    /// Both peers have the same position
+    InSync,
+    /// Desync was detected, moves are ignored until the position is restored
+    AwaitingState,
+    /// The game has ended
+    Finished(GameOver),
+}
+
+/// State machine of one peer, see module docs.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Session {
+    seq: u64,
+    hash: u64,
+    state: SyncState,
+}
+impl Session {
+    /// Creates new session with the hash of the initial position.
+    pub fn new(hash: u64) -> Self {
+        Self {
+            seq: 0,
+            hash,
+            state: SyncState::InSync,
+        }
+    }
+    /// Returns sequence number of the last move.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+    /// Returns hash of the last agreed position.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+    /// Returns the state of the session.
+    pub fn state(&self) -> SyncState {
+        self.state
+    }
+
+    /// Records a local move and returns the message for it.
+    /// `hash` is the hash of the position after the move.
+    pub fn send_move(&mut self, index: u32, player: u8, hash: u64) -> MoveMsg {
+        debug_assert_eq!(self.state, SyncState::InSync);
+        self.seq += 1;
+        self.hash = hash;
+        MoveMsg {
+            seq: self.seq,
+            index,
+            player,
+            hash,
+        }
+    }
+    /// Handles the move of other peer. `apply` should make the move locally and
+    /// return the hash after it or `None` if the move is illegal. Already received
+    /// moves and moves during resync are ignored.
+    /// # Errors
+    /// Returns [`SyncRequest`], that should be sent to other peer, if desync is detected.
+    pub fn receive_move(
+        &mut self,
+        message: &MoveMsg,
+        apply: impl FnOnce(&MoveMsg) -> Option<u64>,
+    ) -> Result<(), SyncRequest> {
+        if self.state != SyncState::InSync || message.seq <= self.seq {
+            return Ok(());
+        }
+        if message.seq != self.seq + 1 {
+            return Err(self.desync());
+        }
+        match apply(message) {
+            Some(hash) if hash == message.hash => {
+                self.seq = message.seq;
+                self.hash = hash;
+                Ok(())
+            }
+            _ => Err(self.desync()),
+        }
+    }
+    /// Returns the request of the position, e.g. to send it again.
+    pub fn sync_request(&self) -> SyncRequest {
+        SyncRequest {
+            seq: self.seq,
+            hash: self.hash,
+        }
+    }
+    /// Returns the message with the whole state of the game.
+    pub fn full_state<G>(&self, state: G) -> FullState<G> {
+        FullState {
+            seq: self.seq,
+            hash: self.hash,
+            state,
+        }
+    }
+    /// Returns the message with the changes since the position of [`SyncRequest`].
+    pub fn resync_diff<D>(&self, changes: D) -> ResyncDiff<D> {
+        ResyncDiff {
+            seq: self.seq,
+            hash: self.hash,
+            changes,
+        }
+    }
+    /// Handles the whole state of the game. `apply` should replace
+    /// the local state and return its hash.
+    /// # Errors
+    /// Returns [`SyncRequest`] if the hash doesn't match.
+    pub fn receive_full_state<G>(
+        &mut self,
+        message: FullState<G>,
+        apply: impl FnOnce(G) -> u64,
+    ) -> Result<(), SyncRequest> {
+        let hash = apply(message.state);
+        self.resync(message.seq, message.hash, hash)
+    }
+    /// Handles the changes since the last agreed position. `apply` should
+    /// change the local state and return its hash.
+    /// # Errors
+    /// Returns [`SyncRequest`] if the hash doesn't match.
+    pub fn receive_resync_diff<D>(
+        &mut self,
+        message: ResyncDiff<D>,
+        apply: impl FnOnce(D) -> u64,
+    ) -> Result<(), SyncRequest> {
+        let hash = apply(message.changes);
+        self.resync(message.seq, message.hash, hash)
+    }
+    /// Finishes the session and returns the message about it.
+    pub fn game_over(&mut self, result: GameOver) -> GameOverMsg {
+        self.state = SyncState::Finished(result);
+        GameOverMsg {
+            seq: self.seq,
+            hash: self.hash,
+            result,
+        }
+    }
+    /// Handles the end of the game, announced by other peer.
+    /// # Errors
+    /// Returns [`SyncRequest`] if the peers have different positions.
+    pub fn receive_game_over(&mut self, message: &GameOverMsg) -> Result<(), SyncRequest> {
+        if message.seq != self.seq || message.hash != self.hash {
+            return Err(self.desync());
+        }
+        self.state = SyncState::Finished(message.result);
+        Ok(())
+    }
+
+    fn desync(&mut self) -> SyncRequest {
+        self.state = SyncState::AwaitingState;
+        self.sync_request()
+    }
+    fn resync(&mut self, seq: u64, expected: u64, hash: u64) -> Result<(), SyncRequest> {
+        if hash != expected {
+            return Err(self.desync());
+        }
+        self.seq = seq;
+        self.hash = hash;
+        self.state = SyncState::InSync;
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+//! Game observers
+//!
+//! This module defines [`GameObserver`], hooks called on changes of [`Game`], e.g. by
+//! loggers, GUIs or sound effects. All hooks do nothing by default. [`Game::observe`]
+//! attaches an observer to the game: moves and cancellations made through returned
+//! [`Observed`] are reported to it in the order of their [`GameEvent`]s. Several
+//! observers can be attached at once as a tuple or a slice of observers.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::engine::MoveRecord;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::observer::*;
+//! # use crosses_utils::player_manager::*;
+//! #[derive(Default)]
+//! struct Log {
+//!     moves: usize,
+//!     changes: usize,
+//!     result: Option<GameOver>,
+//! }
+//! impl GameObserver<usize, Cell> for Log {
+//!     fn on_move(&mut self, _: &MoveRecord<usize>) {
+//!         self.moves += 1;
+//!     }
+//!     fn on_undo(&mut self, _: &MoveRecord<usize>) {
+//!         self.moves -= 1;
+//!     }
+//!     fn on_cell_changed(&mut self, _: usize, _: Cell, _: Cell) {
+//!         self.changes += 1;
+//!     }
+//!     fn on_game_over(&mut self, result: GameOver) {
+//!         self.result = Some(result);
+//!     }
+//! }
+//! let board = Board::new(2, 1, 2, [Cell::new(); 12], [0; 12]);
+//! let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+//! let (mut log, mut other) = (Log::default(), Log::default());
+//! let mut observed = game.observe((&mut log, &mut other));
+//! let record = observed.play(observed.game().board().index(0, 0)).unwrap();
+//! observed.undo(record).unwrap();
+//! for x in [0, 1, 1] {
+//!     observed.play(observed.game().board().index(x, 0)).unwrap();
+//! }
+//! assert_eq!((log.moves, log.changes, log.result), (3, 5, Some(GameOver::Win(0))));
+//! assert_eq!(other.moves, 3);
+//! ```
+use core::ops::IndexMut;
+
+use crate::engine::{Engine, MoveError, MoveRecord};
+use crate::events::GameEvent;
+use crate::game::{Game, GameMove};
+use crate::player_manager::{GameOver, LoseData};
+use crate::summary::EliminationReason;
+
+/// Hooks called on changes of the game, see module docs.
+/// `I` - is type of indices of cells, `D` - is type of data of cells.
+pub trait GameObserver<I, D> {
+    /// Called after the move, when other hooks of the move have been called.
+    fn on_move(&mut self, _record: &MoveRecord<I>) {}
+    /// Called after cancellation of the move, when its cells have been reported.
+    fn on_undo(&mut self, _record: &MoveRecord<I>) {}
+    /// Called, when type or player of the cell changes during a move or its cancellation.
+    fn on_cell_changed(&mut self, _index: I, _from: D, _to: D) {}
+    /// Called, when the player loses.
+    fn on_player_lost(&mut self, _player: usize, _reason: EliminationReason) {}
+    /// Called, when the game ends.
+    fn on_game_over(&mut self, _result: GameOver) {}
+}
+impl<I, D, O: GameObserver<I, D> + ?Sized> GameObserver<I, D> for &mut O {
+    fn on_move(&mut self, record: &MoveRecord<I>) {
+        (**self).on_move(record)
+    }
+    fn on_undo(&mut self, record: &MoveRecord<I>) {
+        (**self).on_undo(record)
+    }
+    fn on_cell_changed(&mut self, index: I, from: D, to: D) {
+        (**self).on_cell_changed(index, from, to)
+    }
+    fn on_player_lost(&mut self, player: usize, reason: EliminationReason) {
+        (**self).on_player_lost(player, reason)
+    }
+    fn on_game_over(&mut self, result: GameOver) {
+        (**self).on_game_over(result)
+    }
+}
+impl<I: Copy, D: Copy, A: GameObserver<I, D>, B: GameObserver<I, D>> GameObserver<I, D> for (A, B) {
+    fn on_move(&mut self, record: &MoveRecord<I>) {
+        self.0.on_move(record);
+        self.1.on_move(record);
+    }
+    fn on_undo(&mut self, record: &MoveRecord<I>) {
+        self.0.on_undo(record);
+        self.1.on_undo(record);
+    }
+    fn on_cell_changed(&mut self, index: I, from: D, to: D) {
+        self.0.on_cell_changed(index, from, to);
+        self.1.on_cell_changed(index, from, to);
+    }
+    fn on_player_lost(&mut self, player: usize, reason: EliminationReason) {
+        self.0.on_player_lost(player, reason);
+        self.1.on_player_lost(player, reason);
+    }
+    fn on_game_over(&mut self, result: GameOver) {
+        self.0.on_game_over(result);
+        self.1.on_game_over(result);
+    }
+}
+impl<I: Copy, D: Copy, O: GameObserver<I, D>> GameObserver<I, D> for [O] {
+    fn on_move(&mut self, record: &MoveRecord<I>) {
+        self.iter_mut()
+            .for_each(|observer| observer.on_move(record))
+    }
+    fn on_undo(&mut self, record: &MoveRecord<I>) {
+        self.iter_mut()
+            .for_each(|observer| observer.on_undo(record))
+    }
+    fn on_cell_changed(&mut self, index: I, from: D, to: D) {
+        self.iter_mut()
+            .for_each(|observer| observer.on_cell_changed(index, from, to))
+    }
+    fn on_player_lost(&mut self, player: usize, reason: EliminationReason) {
+        self.iter_mut()
+            .for_each(|observer| observer.on_player_lost(player, reason))
+    }
+    fn on_game_over(&mut self, result: GameOver) {
+        self.iter_mut()
+            .for_each(|observer| observer.on_game_over(result))
+    }
+}
+
+/// The game with attached observer, see [`Game::observe`].
+#[derive(Debug)]
+pub struct Observed<'a, B: Engine, S: IndexMut<usize, Output = Option<LoseData>>, O> {
+    game: &'a mut Game<B, S>,
+    observer: O,
+}
+impl<'a, B, S, O> Observed<'a, B, S, O>
+where
+    B: Engine,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    O: GameObserver<B::Index, B::Data>,
+{
+    /// Returns the game.
+    pub fn game(&self) -> &Game<B, S> {
+        self.game
+    }
+    /// Returns the observer.
+    pub fn observer(&mut self) -> &mut O {
+        &mut self.observer
+    }
+    /// Detaches the observer and returns it.
+    pub fn into_inner(self) -> O {
+        self.observer
+    }
+    /// Makes a move like [`Game::play`] and reports it to the observer.
+    /// # Errors
+    /// Returns [`MoveError`] in the same cases as [`Game::play`], nothing is reported.
+    pub fn play(&mut self, index: B::Index) -> Result<GameMove<B::Index>, MoveError> {
+        let observer = &mut self.observer;
+        let played = self.game.play_with_events(index, |event| match event {
+            GameEvent::CellChanged { index, from, to } => observer.on_cell_changed(index, from, to),
+            GameEvent::PlayerEliminated { player, reason } => {
+                observer.on_player_lost(player, reason)
+            }
+            GameEvent::GameEnded { result } => observer.on_game_over(result),
+            _ => {}
+        })?;
+        self.observer.on_move(&played.record);
+        Ok(played)
+    }
+    /// Cancels the move like [`Game::undo`] and reports it to the observer.
+    /// # Errors
+    /// Returns [`MoveError`] in the same cases as [`Game::undo`], nothing is reported.
+    pub fn undo(&mut self, played: GameMove<B::Index>) -> Result<(), MoveError> {
+        let observer = &mut self.observer;
+        self.game.undo_with_events(played, |event| {
+            if let GameEvent::CellChanged { index, from, to } = event {
+                observer.on_cell_changed(index, from, to)
+            }
+        })?;
+        self.observer.on_undo(&played.record);
+        Ok(())
+    }
+}
+
+impl<B, S> Game<B, S>
+where
+    B: Engine,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+{
+    /// Attaches the observer to the game, until returned [`Observed`] is dropped.
+    pub fn observe<O: GameObserver<B::Index, B::Data>>(
+        &mut self,
+        observer: O,
+    ) -> Observed<'_, B, S, O> {
+        Observed {
+            game: self,
+            observer,
+        }
+    }
+}
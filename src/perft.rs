@@ -0,0 +1,99 @@
+//! Perft
+//!
+//! This module counts positions of the tree of legal moves, like `perft` of chess engines.
+//! Moves are made with [`Game::play`] and cancelled with [`Game::undo`], so known counts
+//! of small boards validate both the move generation and cancellation of moves. Every
+//! move is a ply, even if the player makes several moves in a turn. Positions, where
+//! the game has ended before the depth is reached, aren't counted.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::perft::*;
+//! # use crosses_utils::player_manager::*;
+//! let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+//! let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+//! let initial = game.clone();
+//! assert_eq!(perft(&mut game, 0), 1);
+//! assert_eq!(perft(&mut game, 2), 1);
+//! assert_eq!(perft(&mut game, 4), 9);
+//! assert_eq!(perft(&mut game, 6), 172);
+//! assert_eq!(game, initial);
+//! ```
+use core::ops::IndexMut;
+
+use crate::board::{Board, CellStorage};
+use crate::engine::ChangeLog;
+use crate::game::Game;
+use crate::player_manager::{GameState, LoseData};
+
+/// Returns number of positions reached from the current one after `depth` legal moves.
+/// The game is returned to its initial state.
+pub fn perft<C, B, S, L>(game: &mut Game<Board<C, B>, S, L>, depth: usize) -> u64
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    if depth == 0 {
+        return 1;
+    }
+    let mut count = 0;
+    divide(game, depth, |_, nodes| count += nodes);
+    count
+}
+
+/// Like [`perft`], but calls `f` with index of the cell of every legal move
+/// and number of positions after it, in the order of rows.
+/// # Panics
+/// Panics if `depth` is zero.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::perft::*;
+/// # use crosses_utils::player_manager::*;
+/// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+/// let mut game = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+/// game.play(game.board().index(0, 0)).unwrap();
+/// let mut moves = [(0, 0); 3];
+/// let mut len = 0;
+/// divide(&mut game, 2, |index, nodes| {
+///     moves[len] = (index, nodes);
+///     len += 1;
+/// });
+/// let board = game.board();
+/// assert_eq!(moves, [(board.index(1, 0), 1), (board.index(0, 1), 1), (board.index(1, 1), 1)]);
+/// assert_eq!(perft(&mut game, 2), 3);
+/// ```
+pub fn divide<C, B, S, L>(
+    game: &mut Game<Board<C, B>, S, L>,
+    depth: usize,
+    mut f: impl FnMut(usize, u64),
+) where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    assert_ne!(depth, 0, "depth is zero");
+    if game.state() != GameState::Ongoing {
+        return;
+    }
+    let cells = game.board().width() * game.board().height();
+    for packed in 0..cells {
+        let index = game.board().unpack_index(packed);
+        let record = match game.play(index) {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+        let nodes = if depth == 1 {
+            1
+        } else {
+            perft(game, depth - 1)
+        };
+        game.undo(record).expect("the move was just made");
+        f(index, nodes);
+    }
+}
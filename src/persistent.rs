@@ -0,0 +1,133 @@
+//! Persistent board
+//!
+//! This module defines [`Chunks`], a copy-on-write [`CellStorage`], and [`LazyBuffer`],
+//! a traversal buffer that is allocated only when it is needed. [`Board`] built from them
+//! is cheap to clone: clones share all chunks of cells until one of them changes a chunk.
+//! So an AI can branch positions during lookahead without full copies and without
+//! undoing every move. Requires `alloc` feature.
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::board::{Board, Cell, CellStorage, FromLen};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Number of cells in one chunk of [`Chunks`].
+pub const CHUNK_LEN: usize = 64;
+
+/// Copy-on-write storage of cells, split into shared chunks of [`CHUNK_LEN`] cells.
+/// Cloning copies only pointers to the chunks, and a chunk is copied
+/// when it's changed while being shared.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Chunks {
+    chunks: Vec<Arc<[Cell; CHUNK_LEN]>>,
+    len: usize,
+}
+impl Chunks {
+    /// Creates new storage of `len` empty cells.
+    pub fn new(len: usize) -> Self {
+        let chunk = Arc::new([Cell::new(); CHUNK_LEN]);
+        Self {
+            chunks: alloc::vec![chunk; (len + CHUNK_LEN - 1) / CHUNK_LEN],
+            len,
+        }
+    }
+    /// Checks if both storages share the chunk, that contains cell at given index.
+    pub fn is_shared_with(&self, other: &Self, index: usize) -> bool {
+        Arc::ptr_eq(&self.chunks[index / CHUNK_LEN], &other.chunks[index / CHUNK_LEN])
+    }
+}
+impl CellStorage for Chunks {
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn cell(&self, index: usize) -> &Cell {
+        debug_assert!(index < self.len);
+        &self.chunks[index / CHUNK_LEN][index % CHUNK_LEN]
+    }
+    fn cell_mut(&mut self, index: usize) -> &mut Cell {
+        debug_assert!(index < self.len);
+        &mut Arc::make_mut(&mut self.chunks[index / CHUNK_LEN])[index % CHUNK_LEN]
+    }
+}
+impl FromLen for Chunks {
+    fn from_len(len: usize) -> Option<Self> {
+        Some(Self::new(len))
+    }
+}
+/// Chunks are serialized as a sequence of cells.
+#[cfg(feature = "serde")]
+impl Serialize for Chunks {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq((0..self.len).map(|i| self.cell(i)))
+    }
+}
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Chunks {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cells = Vec::<Cell>::deserialize(deserializer)?;
+        let mut chunks = Self::new(cells.len());
+        for (i, cell) in cells.into_iter().enumerate() {
+            *chunks.cell_mut(i) = cell;
+        }
+        Ok(chunks)
+    }
+}
+
+/// Traversal buffer, that allocates its elements on first access.
+/// Its clones are empty, so cloning [`Board`] doesn't copy the buffer.
+#[derive(Debug)]
+pub struct LazyBuffer {
+    buffer: Vec<usize>,
+    len: usize,
+}
+impl LazyBuffer {
+    /// Creates new buffer of `len` elements without allocating it.
+    pub fn new(len: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            len,
+        }
+    }
+}
+impl Clone for LazyBuffer {
+    fn clone(&self) -> Self {
+        Self::new(self.len)
+    }
+}
+impl FromLen for LazyBuffer {
+    fn from_len(len: usize) -> Option<Self> {
+        Some(Self::new(len))
+    }
+}
+impl AsMut<[usize]> for LazyBuffer {
+    fn as_mut(&mut self) -> &mut [usize] {
+        if self.buffer.len() < self.len {
+            self.buffer.resize(self.len, 0)
+        }
+        &mut self.buffer
+    }
+}
+
+/// [`Board`] with copy-on-write storage.
+pub type PersistentBoard = Board<Chunks, LazyBuffer>;
+
+impl PersistentBoard {
+    /// Creates new empty [`PersistentBoard`]. See [`Board::new`] for details.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::engine::*;
+    /// # use crosses_utils::persistent::*;
+    /// let mut board = PersistentBoard::persistent(10, 10, 2);
+    /// let index = board.index(0, 0);
+    /// let branch = board.clone();
+    /// assert!(board.storage().is_shared_with(branch.storage(), index));
+    /// make_move(&mut board, index, 0).unwrap();
+    /// assert!(!board.storage().is_shared_with(branch.storage(), index));
+    /// assert_ne!(board, branch);
+    /// ```
+    pub fn persistent(width: usize, height: usize, players: usize) -> Self {
+        let len = Self::storage_len(width, height);
+        Board::new(width, height, players, Chunks::new(len), LazyBuffer::new(len))
+    }
+}
@@ -10,6 +10,8 @@
 
 use core::{fmt::Display, ops::IndexMut};
 
+use crate::counters::{Counter, PlayerCounters};
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -25,6 +27,18 @@ pub struct PlayerManager<S: IndexMut<usize, Output = Option<LoseData>>> {
     pub current_move: usize,
     pub game_state: GameState,
     pub losers: S,
+    /// Number of turns passed, see [`MoveSchedule`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub current_turn: usize,
+    /// Schedule of budgets of turns
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub schedule: MoveSchedule,
+    /// What happens to players, who can't make a move
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stalemate: Stalemate,
+    /// The neutral player, see [`PlayerManager::with_neutral`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub neutral: Option<usize>,
 }
 impl<S> PlayerManager<S>
 where
@@ -43,7 +57,7 @@ where
     /// let pm = PlayerManager::new(4, 4, [None; 4]);
     /// ```
     pub fn new(max_moves: usize, max_players: usize, losers: S) -> Self {
-        debug_assert!((0..max_players).all(|i| losers[i] == None));
+        debug_assert!((0..max_players).all(|i| losers[i].is_none()));
         Self {
             remaining_moves: max_moves,
             max_moves,
@@ -52,13 +66,94 @@ where
             losers,
             current_move: 0,
             game_state: GameState::Ongoing,
+            current_turn: 0,
+            schedule: MoveSchedule::default(),
+            stalemate: Stalemate::default(),
+            neutral: None,
         }
     }
+    /// Sets what happens to players, who can't make a move, see [`Stalemate`].
+    /// # Example
+    /// ```
+    /// # use crosses_utils::player_manager::*;
+    /// let mut pm = PlayerManager::new(2, 3, [None; 3]).with_stalemate(Stalemate::Skip);
+    /// let (turn, remaining) = (pm.current_turn, pm.remaining_moves);
+    /// pm.advance(&(|player| player != 2, |_| false));
+    /// assert_eq!((pm.current_player, pm.remaining_moves), (2, 2));
+    /// assert!(pm.losers.iter().all(Option::is_none));
+    /// pm.reverse_with(0, turn, remaining);
+    /// assert_eq!((pm.current_player, pm.remaining_moves), (0, 2));
+    ///
+    /// let mut pm = PlayerManager::new(2, 2, [None; 2]).with_stalemate(Stalemate::End);
+    /// pm.advance(&(|_| true, |_| false));
+    /// assert_eq!(pm.game_state, GameState::Ended(GameOver::Draw));
+    /// ```
+    pub fn with_stalemate(mut self, stalemate: Stalemate) -> Self {
+        self.stalemate = stalemate;
+        self
+    }
+    /// Makes the player neutral: they never get turns, never lose and can't win. Their cells
+    /// are walls of scenario maps, see [`Board::set_neutral`]. If it's the current player,
+    /// the next player starts the game. It should be set before the first move.
+    ///
+    /// [`Board::set_neutral`]: crate::board::Board::set_neutral
+    /// # Panics
+    /// Panics if `player` isn't less than `max_players`.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::player_manager::*;
+    /// let mut pm = PlayerManager::new(1, 3, [None; 3]).with_neutral(0);
+    /// assert_eq!(pm.current_player, 1);
+    /// pm.advance(&(|_| false, |_| false));
+    /// pm.advance(&(|_| false, |_| false));
+    /// assert_eq!(pm.current_player, 1);
+    /// pm.advance(&(|player| player == 2, |_| false));
+    /// assert_eq!(pm.game_state, GameState::Ended(GameOver::Win(1)));
+    /// ```
+    pub fn with_neutral(mut self, player: usize) -> Self {
+        assert!(player < self.max_players, "neutral player doesn't exist");
+        self.neutral = Some(player);
+        if self.current_player == player {
+            self.current_player = (player + 1) % self.max_players;
+        }
+        self
+    }
+    /// Checks if the player takes part in the game: they aren't neutral and haven't lost.
+    pub fn is_playing(&self, player: usize) -> bool {
+        self.neutral != Some(player) && self.losers[player].is_none()
+    }
+    /// Sets the schedule of budgets of turns, see [`MoveSchedule`].
+    /// It should be set before the first move.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::player_manager::*;
+    /// let schedule = MoveSchedule { period: 2, min_moves: 2 };
+    /// let mut pm = PlayerManager::new(3, 2, [None; 2]).with_schedule(schedule);
+    /// let mut budgets = [0; 5];
+    /// for budget in &mut budgets {
+    ///     *budget = pm.remaining_moves;
+    ///     for _ in 0..pm.remaining_moves {
+    ///         pm.advance(&(|_| false, |_| false));
+    ///     }
+    /// }
+    /// assert_eq!(budgets, [3, 3, 2, 2, 2]);
+    /// assert_eq!(pm.current_turn, 5);
+    /// pm.reverse(0);
+    /// assert_eq!((pm.current_turn, pm.current_player, pm.remaining_moves), (4, 0, 1));
+    /// ```
+    pub fn with_schedule(mut self, schedule: MoveSchedule) -> Self {
+        self.schedule = schedule;
+        self.remaining_moves = self.budget(self.current_turn);
+        self
+    }
+    /// Returns the budget of moves of given turn, see [`MoveSchedule`].
+    pub fn budget(&self, turn: usize) -> usize {
+        self.schedule.budget(self.max_moves, turn)
+    }
     /// Advances state of the game. It decrements number of moves,
     /// changes current_player if needed, etc.
-    /// `is_ran_ot_of_...` - are functions, that need to tell player_manager
-    /// about player at given index. They are used for marking players as
-    /// loosers.
+    /// `status` tells player_manager about player at given index.
+    /// It is used for marking players as loosers.
     /// # Panics
     /// Panics if the game is over.
     /// # Example
@@ -74,26 +169,22 @@ where
     ///     ),
     ///     (4, 0, 0, GameState::Ongoing)
     /// );
-    /// pm.advance(|_| false, |_| false);
+    /// pm.advance(&(|_| false, |_| false));
     /// assert_eq!(
     ///     (pm.remaining_moves, pm.current_player, pm.current_move),
     ///     (3, 0, 1)
     /// );
     /// for _ in 0..3 {
-    ///     pm.advance(|_| false, |_| false);
+    ///     pm.advance(&(|_| false, |_| false));
     /// }
     /// assert_eq!(
     ///     (pm.remaining_moves, pm.current_player, pm.current_move),
     ///     (4, 1, 4)
     /// );
-    /// pm.advance(|_| true, |_| false);
+    /// pm.advance(&(|_| true, |_| false));
     /// assert_eq!(pm.game_state, GameState::Ended(GameOver::Win(0)));
     /// ```
-    pub fn advance(
-        &mut self,
-        is_ran_out_of_moves: impl Fn(usize) -> bool,
-        is_ran_out_of_crosses: impl Fn(usize) -> bool,
-    ) {
+    pub fn advance(&mut self, status: &impl PlayerStatusSource) {
         if self.game_state != GameState::Ongoing {
             panic!("Game has already ended, can't advance further!")
         }
@@ -102,49 +193,69 @@ where
         let mut should_check_everyone = false;
         if self.remaining_moves == 0 {
             should_change_player = true
-        } else if is_ran_out_of_moves(self.current_player) {
-            self.losers[self.current_player] = Some(LoseData {
-                move_index: self.current_move,
-                remaining_moves: self.remaining_moves,
-            });
-            should_change_player = true;
-            should_check_everyone = true
+        } else if status.out_of_moves(self.current_player) {
+            match self.stalemate {
+                Stalemate::Lose => {
+                    self.losers[self.current_player] = Some(LoseData {
+                        move_index: self.current_move,
+                        remaining_moves: self.remaining_moves,
+                    });
+                    should_check_everyone = true
+                }
+                Stalemate::Skip => {}
+                Stalemate::End => self.game_state = GameState::Ended(self.result_by_score(status)),
+            }
+            should_change_player = self.game_state == GameState::Ongoing;
         }
         if should_change_player {
-            self.check_if_other_players_have_lost(
-                should_check_everyone,
-                is_ran_out_of_moves,
-                is_ran_out_of_crosses,
-            );
+            self.check_if_other_players_have_lost(should_check_everyone, status);
             match self.count_not_losers() {
                 0 => self.game_state = GameState::Ended(GameOver::Draw),
                 1 => {
                     self.game_state = GameState::Ended(GameOver::Win(
                         (0..self.max_players)
-                            .find(|idx| self.losers[*idx].is_none())
+                            .find(|idx| self.is_playing(*idx))
                             .unwrap(),
                     ))
                 }
-                _ => {
-                    self.current_player = self.next_player_idx();
-                    self.remaining_moves = self.max_moves;
-                }
+                _ => self.pass_turn(status),
             }
         }
         self.current_move += 1;
     }
+    /// Advances state of the game like [`PlayerManager::advance`], but doesn't panic.
+    /// # Errors
+    /// Returns the result of the game, if it has already ended.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::player_manager::*;
+    /// let mut pm = PlayerManager::new(1, 2, [None; 2]);
+    /// pm.checked_advance(&(|_| true, |_| false)).unwrap();
+    /// assert_eq!(pm.checked_advance(&(|_| false, |_| false)), Err(GameOver::Win(0)));
+    /// ```
+    pub fn checked_advance(&mut self, status: &impl PlayerStatusSource) -> Result<(), GameOver> {
+        match self.game_state {
+            GameState::Ongoing => {
+                self.advance(status);
+                Ok(())
+            }
+            GameState::Ended(result) => Err(result),
+        }
+    }
     /// Reverses state of the game. It increments number of moves,
     /// changes current_player if needed, etc.
     /// To reverse the game state, we need to know what player
     /// was making the move. We can't get this info only from
     /// state of game_manager, so we must ask BoardManager for that.
+    /// With [`Stalemate::Skip`] the number of moves, that the player has lost
+    /// by the skip, is unknown, use [`PlayerManager::reverse_with`].
     /// # Panics:
     /// if current_move is 0, the function will panic.
     /// # Example
     /// ```
     /// # use crosses_utils::player_manager::*;
     /// let mut pm = PlayerManager::new(4, 2, [None; 2]);
-    /// pm.advance(|_| true, |_| false);
+    /// pm.advance(&(|_| true, |_| false));
     /// assert_eq!(
     ///     (
     ///         pm.remaining_moves,
@@ -167,6 +278,8 @@ where
     /// ```
     pub fn reverse(&mut self, player: usize) {
         self.current_move -= 1;
+        let passes = self.passes(player, self.current_move);
+        self.current_turn = self.current_turn.saturating_sub(passes);
         self.game_state = GameState::Ongoing;
         if let Some(LoseData {
             move_index: _,
@@ -174,54 +287,160 @@ where
         }) = self.losers[player]
         {
             self.remaining_moves = remaining_moves;
-            let mut loser_idx = player;
-            loop {
-                if let Some(LoseData {
-                    move_index,
-                    remaining_moves: _,
-                }) = self.losers[loser_idx]
-                {
-                    if move_index == self.current_move {
-                        self.losers[loser_idx] = None
-                    }
-                }
-                if loser_idx == self.current_player {
-                    break;
-                } else {
-                    loser_idx = (loser_idx + 1) % self.max_players;
-                }
-            }
-        } else if self.remaining_moves == self.max_moves {
+        } else if passes > 0 {
             self.remaining_moves = 0
         }
+        // Other players may have lost on this move even if the player hasn't.
+        self.clear_losers();
         self.current_player = player;
         self.remaining_moves += 1;
     }
+    /// Reverses state of the game like [`PlayerManager::reverse`], but restores the turn
+    /// and remaining moves before the move, which should be taken before
+    /// [`PlayerManager::advance`]. It works with all options of [`Stalemate`].
+    /// # Panics:
+    /// if current_move is 0, the function will panic.
+    pub fn reverse_with(&mut self, player: usize, turn: usize, remaining_moves: usize) {
+        self.current_move -= 1;
+        self.game_state = GameState::Ongoing;
+        self.clear_losers();
+        self.current_player = player;
+        self.current_turn = turn;
+        self.remaining_moves = remaining_moves;
+    }
+    /// Reverses state of the game like [`PlayerManager::reverse`], but first checks,
+    /// that `player` could have made the last move by the state of the manager.
+    /// The manager keeps no history of moves, so only contradictions with its state
+    /// are found, e.g. undo of moves in wrong order.
+    /// # Errors
+    /// Returns [`ReverseError`] if the player couldn't have made the last move.
+    /// In this case nothing is changed.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::player_manager::*;
+    /// let mut pm = PlayerManager::new(1, 3, [None; 3]);
+    /// let status = (|_| false, |_| false);
+    /// pm.advance(&status);
+    /// pm.advance(&status);
+    /// pm.mark_loser(0, &status);
+    /// pm.advance(&status);
+    /// assert_eq!(pm.checked_reverse(0), Err(ReverseError::Lost));
+    /// assert_eq!(pm.checked_reverse(3), Err(ReverseError::UnknownPlayer));
+    /// pm.checked_reverse(2).unwrap();
+    /// let mut pm = PlayerManager::new(2, 2, [None; 2]);
+    /// pm.advance(&status);
+    /// assert_eq!(pm.checked_reverse(1), Err(ReverseError::TurnInProgress));
+    /// pm.checked_reverse(0).unwrap();
+    /// assert_eq!(pm.checked_reverse(0), Err(ReverseError::NothingToReverse));
+    /// ```
+    pub fn checked_reverse(&mut self, player: usize) -> Result<(), ReverseError> {
+        self.check_reverse(player)?;
+        self.reverse(player);
+        Ok(())
+    }
+    /// Checks if the player could have made the last move, see
+    /// [`PlayerManager::checked_reverse`].
+    pub(crate) fn check_reverse(&self, player: usize) -> Result<(), ReverseError> {
+        let last = self
+            .current_move
+            .checked_sub(1)
+            .ok_or(ReverseError::NothingToReverse)?;
+        if player >= self.max_players || self.neutral == Some(player) {
+            return Err(ReverseError::UnknownPlayer);
+        }
+        if matches!(self.losers[player], Some(data) if data.is_before(last)) {
+            return Err(ReverseError::Lost);
+        }
+        let in_progress = self.remaining_moves < self.budget(self.current_turn);
+        if self.game_state == GameState::Ongoing && in_progress && player != self.current_player {
+            return Err(ReverseError::TurnInProgress);
+        }
+        Ok(())
+    }
+    /// Marks the player as a loser between moves, e.g. when their time is over.
+    /// If it's the current player, the turn passes to the next player, who loses too,
+    /// if `status` says they can't make a move. The loss is dated by the last move,
+    /// so it's reversed with it. Losses before the first move are dated
+    /// [`LoseData::BEFORE_START`] and can't be reversed. Does nothing, if the game
+    /// is over, the player doesn't exist or has already lost.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::player_manager::*;
+    /// let mut pm = PlayerManager::new(2, 3, [None; 3]);
+    /// pm.advance(&(|_| false, |_| false));
+    /// pm.mark_loser(0, &(|_| false, |_| false));
+    /// assert_eq!((pm.current_player, pm.remaining_moves), (1, 2));
+    /// pm.mark_loser(2, &(|_| false, |_| false));
+    /// assert_eq!(pm.game_state, GameState::Ended(GameOver::Win(1)));
+    /// pm.reverse(0);
+    /// assert_eq!((pm.current_player, pm.remaining_moves), (0, 2));
+    /// assert_eq!(pm.game_state, GameState::Ongoing);
+    /// assert!(pm.losers.iter().all(Option::is_none));
+    /// pm.mark_loser(2, &(|_| false, |_| false));
+    /// pm.advance(&(|_| false, |_| false));
+    /// pm.reverse(0);
+    /// assert!(!pm.is_playing(2));
+    /// ```
+    pub fn mark_loser(&mut self, player: usize, status: &impl PlayerStatusSource) {
+        if self.game_state != GameState::Ongoing
+            || player >= self.max_players
+            || !self.is_playing(player)
+        {
+            return;
+        }
+        let current = player == self.current_player;
+        self.losers[player] = Some(LoseData {
+            move_index: match self.current_move {
+                0 => LoseData::BEFORE_START,
+                current_move => current_move - 1,
+            },
+            remaining_moves: if current { self.remaining_moves } else { 0 },
+        });
+        match self.count_not_losers() {
+            0 => self.game_state = GameState::Ended(GameOver::Draw),
+            1 => {
+                self.game_state = GameState::Ended(GameOver::Win(
+                    (0..self.max_players)
+                        .find(|idx| self.is_playing(*idx))
+                        .unwrap(),
+                ))
+            }
+            _ if current => {
+                self.pass_turn(status);
+                if status.out_of_moves(self.current_player) {
+                    self.mark_loser(self.current_player, status);
+                }
+            }
+            _ => {}
+        }
+    }
     fn check_if_other_players_have_lost(
         &mut self,
         check_all: bool,
-        is_ran_out_of_moves: impl Fn(usize) -> bool,
-        is_ran_out_of_crosses: impl Fn(usize) -> bool,
+        status: &impl PlayerStatusSource,
     ) {
         let mut maybe_not_losers = self.count_not_losers();
         for delta in 1..self.max_players {
             let not_loser_idx = (self.current_player + delta) % self.max_players;
-            if self.losers[not_loser_idx].is_none() {
+            if self.is_playing(not_loser_idx) {
                 {
-                    if is_ran_out_of_crosses(not_loser_idx) {
+                    if status.out_of_crosses(not_loser_idx) {
                         self.losers[not_loser_idx] = Some(LoseData {
                             move_index: self.current_move,
                             remaining_moves: 0,
                         });
                         maybe_not_losers -= 1;
-                    } else if is_ran_out_of_moves(not_loser_idx) {
-                        if maybe_not_losers > 1 {
-                            self.losers[not_loser_idx] = Some(LoseData {
-                                move_index: self.current_move,
-                                remaining_moves: 0,
-                            });
-                        } else {
-                            break;
+                    } else if status.out_of_moves(not_loser_idx) {
+                        match self.stalemate {
+                            Stalemate::Lose if maybe_not_losers > 1 => {
+                                self.losers[not_loser_idx] = Some(LoseData {
+                                    move_index: self.current_move,
+                                    remaining_moves: 0,
+                                });
+                            }
+                            // The turn of the player will be skipped.
+                            Stalemate::Skip => {}
+                            _ => break,
                         }
                     } else if !check_all {
                         break;
@@ -230,21 +449,170 @@ where
             }
         }
     }
+    /// Returns the result of the game, that is ended by [`Stalemate::End`]: the player,
+    /// who hasn't lost and has the greatest score, wins, or it's a draw, if there are several
+    /// such players, see [`PlayerStatusSource::score`].
+    pub fn result_by_score(&self, status: &impl PlayerStatusSource) -> GameOver {
+        let not_losers = || (0..self.max_players).filter(|idx| self.is_playing(*idx));
+        let best = not_losers().map(|idx| status.score(idx)).max();
+        let mut leaders = not_losers().filter(|idx| Some(status.score(*idx)) == best);
+        match (leaders.next(), leaders.next()) {
+            (Some(winner), None) => GameOver::Win(winner),
+            _ => GameOver::Draw,
+        }
+    }
+    /// Passes the turn to the next player, who hasn't lost, or ends the game,
+    /// if nobody can take it, see [`Stalemate`].
+    fn pass_turn(&mut self, status: &impl PlayerStatusSource) {
+        match self.next_player_idx(status) {
+            Some(next) => {
+                self.current_player = next;
+                self.current_turn += 1;
+                self.remaining_moves = self.budget(self.current_turn);
+            }
+            None => self.game_state = GameState::Ended(self.result_by_score(status)),
+        }
+    }
+    fn clear_losers(&mut self) {
+        for loser_idx in 0..self.max_players {
+            if let Some(LoseData {
+                move_index,
+                remaining_moves: _,
+            }) = self.losers[loser_idx]
+            {
+                if move_index == self.current_move {
+                    self.losers[loser_idx] = None
+                }
+            }
+        }
+    }
+    /// Returns the turn, in which the player made the last move, if no turns were skipped
+    /// after it, see [`Stalemate::Skip`].
+    pub(crate) fn last_turn(&self, player: usize) -> usize {
+        let passes = self.passes(player, self.current_move.saturating_sub(1));
+        self.current_turn.saturating_sub(passes)
+    }
+    /// Returns number of times the turn was passed after the move with given index
+    /// of the player. Every pass but the last one is caused by the loss of the current
+    /// player, the last one lands on the current player, unless they have lost too.
+    fn passes(&self, player: usize, move_index: usize) -> usize {
+        let lost_in_turn = |p: usize| match self.losers[p] {
+            Some(data) => data.move_index == move_index && data.remaining_moves > 0,
+            None => false,
+        };
+        let others = (0..self.max_players)
+            .filter(|p| *p != player && lost_in_turn(*p))
+            .count();
+        let current = self.current_player;
+        if current == player && others == 0 {
+            return 0;
+        }
+        others + 1 - (current != player && lost_in_turn(current)) as usize
+    }
     fn count_not_losers(&self) -> usize {
         (0..self.max_players)
-            .filter(|idx| self.losers[*idx].is_none())
+            .filter(|idx| self.is_playing(*idx))
             .count()
     }
-    fn next_player_idx(&self) -> usize {
-        for delta in 1..self.max_players {
-            let not_loser_idx = (self.current_player + delta) % self.max_players;
-            if self.losers[not_loser_idx].is_none() {
-                return not_loser_idx;
-            }
+    fn next_player_idx(&self, status: &impl PlayerStatusSource) -> Option<usize> {
+        // With skips the player may take the next turn too.
+        let last = match self.stalemate {
+            Stalemate::Skip => self.max_players,
+            _ => self.max_players - 1,
+        };
+        let mut not_losers = (1..=last)
+            .map(|delta| (self.current_player + delta) % self.max_players)
+            .filter(|idx| self.is_playing(*idx));
+        match self.stalemate {
+            Stalemate::Lose => not_losers.next(),
+            Stalemate::Skip => not_losers.find(|idx| !status.out_of_moves(*idx)),
+            Stalemate::End => not_losers.next().filter(|idx| !status.out_of_moves(*idx)),
         }
-        unreachable!()
     }
 }
+/// Schedule of budgets of turns: the budget starts at `max_moves` of [`PlayerManager`]
+/// and decreases by one every `period` turns, but not below `min_moves`. Turns are
+/// counted from zero by all players together. Default schedule has zero period,
+/// which keeps the budget constant.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct MoveSchedule {
+    /// Number of turns between decreases of the budget
+    pub period: usize,
+    /// The smallest budget, at least `1`
+    pub min_moves: usize,
+}
+impl MoveSchedule {
+    /// Returns the budget of given turn with given initial budget.
+    pub fn budget(&self, max_moves: usize, turn: usize) -> usize {
+        if self.period == 0 {
+            return max_moves;
+        }
+        let floor = self.min_moves.clamp(1, max_moves.max(1));
+        max_moves.saturating_sub(turn / self.period).max(floor)
+    }
+}
+
+/// What happens to a player, who can't make a move, see [`PlayerManager::with_stalemate`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Stalemate {
+    /// The player loses, it's the default
+    Lose,
+    /// The rest of the turn and next turns of the player are skipped, until they can make
+    /// a move again. If nobody can, the game ends like with [`Stalemate::End`]
+    Skip,
+    /// The game ends, see [`PlayerManager::result_by_score`]
+    End,
+}
+impl Default for Stalemate {
+    fn default() -> Self {
+        Stalemate::Lose
+    }
+}
+
+/// A source of information about players, used by [`PlayerManager::advance`]
+/// for marking players as loosers. It is implemented for pairs of functions
+/// `(out_of_moves, out_of_crosses)` and for all [`PlayerCounters`].
+pub trait PlayerStatusSource {
+    /// Checks if the player can't make a move.
+    fn out_of_moves(&self, player: usize) -> bool;
+    /// Checks if the player has lost all of their crosses,
+    /// so they lose even during the turn of other player.
+    fn out_of_crosses(&self, player: usize) -> bool;
+    /// Returns the score of the player, which decides the game ended by [`Stalemate::End`].
+    /// It's `0` by default.
+    fn score(&self, _player: usize) -> usize {
+        0
+    }
+}
+impl<F, G> PlayerStatusSource for (F, G)
+where
+    F: Fn(usize) -> bool,
+    G: Fn(usize) -> bool,
+{
+    fn out_of_moves(&self, player: usize) -> bool {
+        (self.0)(player)
+    }
+    fn out_of_crosses(&self, player: usize) -> bool {
+        (self.1)(player)
+    }
+}
+/// Player can't make a move, if their moves counter is `0`. Player has lost
+/// all of their crosses, if both counters are `0`, because player can have
+/// no crosses before the first move. The score of player is the number of their crosses.
+impl<C: PlayerCounters> PlayerStatusSource for C {
+    fn out_of_moves(&self, player: usize) -> bool {
+        self.is_exhausted(Counter::Moves, player)
+    }
+    fn out_of_crosses(&self, player: usize) -> bool {
+        self.is_exhausted(Counter::Crosses, player) && self.is_exhausted(Counter::Moves, player)
+    }
+    fn score(&self, player: usize) -> usize {
+        self.get(Counter::Crosses, player)
+    }
+}
+
 /// An information about losers. `move_index` is the index of move
 /// when player lost. `remaining_moves` is the number of moves, that
 /// player had before loosing.
@@ -254,6 +622,20 @@ pub struct LoseData {
     pub move_index: usize,
     pub remaining_moves: usize,
 }
+impl LoseData {
+    /// `move_index` of losses marked before the first move, see
+    /// [`PlayerManager::mark_loser`]. Such losses aren't reversed with moves.
+    pub const BEFORE_START: usize = usize::MAX;
+
+    /// Checks if the loss is dated before the move with given index.
+    pub(crate) fn is_before(&self, move_index: usize) -> bool {
+        self.move_index == Self::BEFORE_START || self.move_index < move_index
+    }
+    /// Checks if the loss is dated after the move with given index.
+    pub(crate) fn is_after(&self, move_index: usize) -> bool {
+        self.move_index != Self::BEFORE_START && self.move_index > move_index
+    }
+}
 /// The state of the game.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
@@ -280,3 +662,26 @@ impl Display for GameOver {
         }
     }
 }
+
+/// Errors of [`PlayerManager::checked_reverse`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ReverseError {
+    /// No moves have been made
+    NothingToReverse,
+    /// The player is out of range or neutral
+    UnknownPlayer,
+    /// The player has lost before the last move
+    Lost,
+    /// The turn of the current player hasn't ended, so the last move is theirs
+    TurnInProgress,
+}
+impl Display for ReverseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ReverseError::NothingToReverse => write!(f, "no moves to reverse"),
+            ReverseError::UnknownPlayer => write!(f, "player can't make moves"),
+            ReverseError::Lost => write!(f, "player has lost before the last move"),
+            ReverseError::TurnInProgress => write!(f, "last move is of the current player"),
+        }
+    }
+}
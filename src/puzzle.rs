@@ -0,0 +1,273 @@
+//! Puzzles
+//!
+//! This module finds puzzles: positions, where the player to move wins by force within
+//! a few plies. [`generate`] plays games between [`Bot`]s and searches every position
+//! with [`Searcher`]: the first position of a game with a forced win of the right length
+//! becomes a puzzle, then the next game starts. Positions are passed to the callback,
+//! so they can be written with [`Game::to_fen`]. [`verify`] checks claimed solutions of
+//! puzzles, e.g. submitted by users, with exhaustive search.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::bots::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::puzzle::*;
+//! # use crosses_utils::search::*;
+//! let new_game = |_| {
+//!     let board = Board::new(4, 4, 2, [Cell::new(); 36], [0; 36]);
+//!     Game::new(board, PlayerManager::new(2, 2, [None; 2]))
+//! };
+//! let (mut first, mut second) = (RandomBot::new(SplitMix64(3)), RandomBot::new(SplitMix64(4)));
+//! let mut searcher = Searcher::new(Mobility);
+//! let mut puzzles = String::new();
+//! let bots: &mut [&mut dyn Bot<_>] = &mut [&mut first, &mut second];
+//! let found = generate(bots, 5, new_game, &mut searcher, 2..=3, |game, puzzle| {
+//!     assert!(puzzle.plies() >= 2 && puzzle.plies() <= 3);
+//!     assert_eq!(puzzle.player(), game.current_player());
+//!     puzzles += &format!("{}\n", game.to_fen());
+//! });
+//! assert!(found > 0);
+//! assert_eq!(puzzles.lines().count(), found);
+//! ```
+//!
+//! [`Game::to_fen`]: crate::game::Game::to_fen
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::ops::{IndexMut, RangeInclusive};
+
+use crate::board::{Board, CellStorage};
+use crate::bots::Bot;
+use crate::engine::ChangeLog;
+use crate::game::Game;
+use crate::player_manager::{GameOver, GameState, LoseData};
+use crate::search::{Evaluator, Line, Searcher, MAX_DEPTH};
+use crate::transposition::EntryStorage;
+
+/// Puzzle found by [`generate`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Puzzle {
+    player: usize,
+    plies: usize,
+    solution: Line,
+}
+impl Puzzle {
+    /// Returns the player, who wins.
+    pub fn player(&self) -> usize {
+        self.player
+    }
+    /// Returns number of plies to the win.
+    pub fn plies(&self) -> usize {
+        self.plies
+    }
+    /// Returns the winning line with the best defence found by the search.
+    pub fn solution(&self) -> &Line {
+        &self.solution
+    }
+}
+
+/// Plays `games` games between the bots and finds puzzles, which are won within `plies`
+/// plies, see module docs. `new_game` creates the initial position of the game with given
+/// number, number of bots should be the same as number of players. Bots change their
+/// players every game like in [`simulate`]. `on_puzzle` is called with the position
+/// of every puzzle. Returns number of found puzzles.
+/// # Panics
+/// Panics if number of bots and players differ or the end of `plies` is greater than
+/// [`MAX_DEPTH`].
+///
+/// [`simulate`]: crate::simulate::simulate
+pub fn generate<C, B, S, L, E, T>(
+    bots: &mut [&mut dyn Bot<Game<Board<C, B>, S, L>>],
+    games: usize,
+    mut new_game: impl FnMut(usize) -> Game<Board<C, B>, S, L>,
+    searcher: &mut Searcher<E, T>,
+    plies: RangeInclusive<usize>,
+    mut on_puzzle: impl FnMut(&Game<Board<C, B>, S, L>, &Puzzle),
+) -> usize
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+    E: Evaluator<Game<Board<C, B>, S, L>>,
+    T: EntryStorage,
+{
+    let n = bots.len();
+    let mut found = 0;
+    for number in 0..games {
+        let mut game = new_game(number);
+        assert_eq!(
+            game.player_manager().max_players,
+            n,
+            "number of bots and players differ"
+        );
+        while game.state() == GameState::Ongoing {
+            let report = searcher.search(&mut game, *plies.end());
+            match report.win_in() {
+                Some(win) if win > 0 && plies.contains(&(win as usize)) => {
+                    let puzzle = Puzzle {
+                        player: game.current_player(),
+                        plies: win as usize,
+                        solution: *report.pv(),
+                    };
+                    on_puzzle(&game, &puzzle);
+                    found += 1;
+                    break;
+                }
+                _ => {}
+            }
+            let bot = &mut bots[(game.current_player() + n - number % n) % n];
+            match bot.choose_move(&mut game).map(|index| game.play(index)) {
+                Some(Ok(_)) => {}
+                _ => break,
+            }
+        }
+    }
+    found
+}
+
+/// Error of [`verify`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum VerifyError {
+    /// The move of the solution with given number is illegal
+    IllegalMove(usize),
+    /// The win isn't forced
+    Refuted,
+}
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            VerifyError::IllegalMove(number) => write!(f, "move {} is illegal", number),
+            VerifyError::Refuted => write!(f, "win isn't forced"),
+        }
+    }
+}
+
+/// Checks, that the player to move wins within `max_depth` plies, if they follow
+/// the solution. The solution is the main line: moves of the winner and expected replies
+/// of other players. Every reply of other players is checked: if they leave the main line,
+/// the winner should still have a forced win, which is found by exhaustive search.
+/// If the solution ends before the game, the rest of it is searched too.
+/// The game is returned to its initial state.
+/// # Errors
+/// Returns [`VerifyError::IllegalMove`] if a move of the winner in the solution is
+/// illegal and [`VerifyError::Refuted`] if the win isn't forced. In the last case
+/// `refutation` is set to the shortest found line from the position, after which
+/// the player to move in the position can't win in the remaining plies.
+/// # Panics
+/// Panics if `max_depth` is greater than [`MAX_DEPTH`].
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// # use crosses_utils::puzzle::*;
+/// # use crosses_utils::search::Line;
+/// let board = Board::new(2, 1, 2, [Cell::new(); 12], [0; 12]);
+/// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+/// let (a1, b1) = (game.board().index(0, 0), game.board().index(1, 0));
+/// let mut refutation = Line::new();
+/// assert_eq!(verify(&mut game, &[a1, b1, b1], 3, &mut refutation), Ok(()));
+/// assert_eq!(verify(&mut game, &[a1], 3, &mut refutation), Ok(()));
+/// let illegal = verify(&mut game, &[b1], 3, &mut refutation);
+/// assert_eq!(illegal, Err(VerifyError::IllegalMove(0)));
+/// let refuted = verify(&mut game, &[a1, b1, b1], 2, &mut refutation);
+/// assert_eq!(refuted, Err(VerifyError::Refuted));
+/// assert_eq!(refutation.moves(), [a1, b1]);
+/// ```
+pub fn verify<C, B, S, L>(
+    game: &mut Game<Board<C, B>, S, L>,
+    solution: &[usize],
+    max_depth: usize,
+    refutation: &mut Line,
+) -> Result<(), VerifyError>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    assert!(max_depth <= MAX_DEPTH, "depth is too large");
+    let mut verifier = Verifier {
+        winner: game.current_player(),
+        line: Line::new(),
+        refutation: None,
+    };
+    if verifier.wins(game, Some(solution), max_depth)? {
+        Ok(())
+    } else {
+        *refutation = verifier.refutation.unwrap_or_default();
+        Err(VerifyError::Refuted)
+    }
+}
+
+/// State of [`verify`].
+struct Verifier {
+    winner: usize,
+    line: Line,
+    refutation: Option<Line>,
+}
+impl Verifier {
+    /// Checks, that the winner wins within `depth` plies. `solution` is the rest of
+    /// the main line, if the game follows it.
+    fn wins<C, B, S, L>(
+        &mut self,
+        game: &mut Game<Board<C, B>, S, L>,
+        solution: Option<&[usize]>,
+        depth: usize,
+    ) -> Result<bool, VerifyError>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        match game.state() {
+            GameState::Ended(GameOver::Win(player)) if player == self.winner => return Ok(true),
+            GameState::Ended(_) => return Ok(self.refuted()),
+            GameState::Ongoing if depth == 0 => return Ok(self.refuted()),
+            GameState::Ongoing => {}
+        }
+        let attacking = game.current_player() == self.winner;
+        let planned = solution.and_then(|solution| solution.split_first());
+        if let (true, Some((&index, rest))) = (attacking, planned) {
+            let record = game
+                .play(index)
+                .map_err(|_| VerifyError::IllegalMove(self.line.len()))?;
+            self.line.push(index);
+            let wins = self.wins(game, Some(rest), depth - 1);
+            self.line.pop();
+            game.undo(record).unwrap();
+            return wins;
+        }
+        let cells = game.board().width() * game.board().height();
+        for packed in 0..cells {
+            let index = game.board().unpack_index(packed);
+            let record = match game.play(index) {
+                Ok(record) => record,
+                Err(_) => continue,
+            };
+            let rest = match planned {
+                Some((&expected, rest)) if expected == index => Some(rest),
+                _ => None,
+            };
+            self.line.push(index);
+            let wins = self.wins(game, rest, depth - 1);
+            self.line.pop();
+            game.undo(record).unwrap();
+            match wins {
+                Ok(wins) if wins != attacking => {}
+                result => return result,
+            }
+        }
+        Ok(!attacking || self.refuted())
+    }
+    /// Remembers the current line as a refutation, if it's the shortest one.
+    /// Returns `false`.
+    fn refuted(&mut self) -> bool {
+        match self.refutation {
+            Some(refutation) if refutation.len() <= self.line.len() => {}
+            _ => self.refutation = Some(self.line),
+        }
+        false
+    }
+}
@@ -0,0 +1,379 @@
+//! Ratings
+//!
+//! This module computes Elo and Glicko-2 ratings from results of games, e.g. of bot-tuning
+//! runs or club ladders. Ratings are [`Fixed`] numbers, so they are computed without
+//! floating point functions of `std` and give the same results on every target.
+//! # Example
+//! ```
+//! # use crosses_utils::rating::*;
+//! # use crosses_utils::tournament::Outcome;
+//! let elo = Elo::new(Fixed::from_int(32));
+//! let (a, b) = elo.update_pair(Fixed::from_int(1500), Fixed::from_int(1500), Outcome::Win);
+//! assert_eq!((a.round(), b.round()), (1516, 1484));
+//!
+//! let player = Glicko2Rating::new(Fixed::from_int(1500), Fixed::from_int(200));
+//! let opponent = |rating, deviation| {
+//!     Glicko2Rating::new(Fixed::from_int(rating), Fixed::from_int(deviation))
+//! };
+//! let games = [
+//!     (opponent(1400, 30), Outcome::Win),
+//!     (opponent(1550, 100), Outcome::Loss),
+//!     (opponent(1700, 300), Outcome::Loss),
+//! ];
+//! let updated = Glicko2::default().update(player, &games);
+//! assert_eq!(updated.rating.round(), 1464);
+//! assert_eq!(updated.deviation.round(), 152);
+//! assert_eq!(format!("{}", updated.volatility), "0.0599");
+//! ```
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::tournament::Outcome;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Number with 32 fractional bits. Results of operations, that don't fit into it,
+/// are saturated.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Fixed(i64);
+impl Fixed {
+    /// Zero
+    pub const ZERO: Fixed = Fixed(0);
+    /// One
+    pub const ONE: Fixed = Fixed(1 << Self::FRACTION);
+    /// Natural logarithm of 2
+    const LN2: Fixed = Fixed(2977044472);
+    /// Natural logarithm of 10
+    const LN10: Fixed = Fixed(9889527671);
+    /// Pi
+    const PI: Fixed = Fixed(13493037705);
+    const FRACTION: u32 = 32;
+
+    /// Creates number from its bits, i.e. the number multiplied by `2³²`.
+    pub const fn from_bits(bits: i64) -> Self {
+        Self(bits)
+    }
+    /// Returns bits of the number, i.e. the number multiplied by `2³²`.
+    pub const fn to_bits(self) -> i64 {
+        self.0
+    }
+    /// Creates integer number.
+    pub const fn from_int(value: i32) -> Self {
+        Self((value as i64) << Self::FRACTION)
+    }
+    /// Creates number `numerator / denominator`.
+    /// # Panics
+    /// Panics if `denominator` is zero.
+    pub fn from_ratio(numerator: i64, denominator: i64) -> Self {
+        Self::saturate(((numerator as i128) << Self::FRACTION) / denominator as i128)
+    }
+    /// Returns the nearest integer, halves are rounded up.
+    pub fn round(self) -> i64 {
+        (self.0 + (1 << (Self::FRACTION - 1))) >> Self::FRACTION
+    }
+    /// Returns absolute value of the number.
+    pub fn abs(self) -> Self {
+        Self(self.0.saturating_abs())
+    }
+    /// Returns `e` raised to the power of the number.
+    pub fn exp(self) -> Self {
+        // exp(x) = 2^k * exp(r), where x = k * ln(2) + r and |r| <= ln(2) / 2.
+        let k = (self + Self::LN2 / Self::from_int(2))
+            .0
+            .div_euclid(Self::LN2.0);
+        if k >= 31 {
+            return Self(i64::MAX);
+        }
+        if k <= -(Self::FRACTION as i64) {
+            return Self::ZERO;
+        }
+        let r = self - Self(Self::LN2.0 * k);
+        let (mut sum, mut term) = (Self::ONE, Self::ONE);
+        for n in 1..16 {
+            term = term * r / Self::from_int(n);
+            sum = sum + term;
+        }
+        if k >= 0 {
+            Self(sum.0 << k)
+        } else {
+            Self(sum.0 >> -k)
+        }
+    }
+    /// Returns natural logarithm of the number.
+    /// # Panics
+    /// Panics if the number isn't positive.
+    pub fn ln(self) -> Self {
+        assert!(self.0 > 0, "logarithm of non-positive number");
+        // ln(x) = k * ln(2) + ln(m), where x = 2^k * m and 1 <= m < 2.
+        let k = 63 - self.0.leading_zeros() as i64 - Self::FRACTION as i64;
+        let m = if k >= 0 {
+            Self(self.0 >> k)
+        } else {
+            Self(self.0 << -k)
+        };
+        // ln(m) = 2 * atanh(z), where z = (m - 1) / (m + 1) <= 1/3.
+        let z = (m - Self::ONE) / (m + Self::ONE);
+        let z2 = z * z;
+        let (mut sum, mut power) = (Self::ZERO, z);
+        for n in 0..20 {
+            sum = sum + power / Self::from_int(2 * n + 1);
+            power = power * z2;
+        }
+        Self(Self::LN2.0 * k) + sum + sum
+    }
+    /// Returns square root of the number.
+    /// # Panics
+    /// Panics if the number is negative.
+    pub fn sqrt(self) -> Self {
+        assert!(self.0 >= 0, "square root of negative number");
+        let value = (self.0 as u128) << Self::FRACTION;
+        if value == 0 {
+            return Self::ZERO;
+        }
+        let mut root = 1u128 << ((128 - value.leading_zeros()) / 2 + 1);
+        loop {
+            let next = (root + value / root) / 2;
+            if next >= root {
+                return Self(root as i64);
+            }
+            root = next;
+        }
+    }
+    /// Returns `10` raised to the power of the number.
+    fn exp10(self) -> Self {
+        (self * Self::LN10).exp()
+    }
+    fn saturate(value: i128) -> Self {
+        Self(value.clamp(i64::MIN as i128, i64::MAX as i128) as i64)
+    }
+}
+impl Add for Fixed {
+    type Output = Fixed;
+    fn add(self, other: Fixed) -> Fixed {
+        Fixed(self.0.saturating_add(other.0))
+    }
+}
+impl Sub for Fixed {
+    type Output = Fixed;
+    fn sub(self, other: Fixed) -> Fixed {
+        Fixed(self.0.saturating_sub(other.0))
+    }
+}
+impl Mul for Fixed {
+    type Output = Fixed;
+    fn mul(self, other: Fixed) -> Fixed {
+        Fixed::saturate((self.0 as i128 * other.0 as i128) >> Fixed::FRACTION)
+    }
+}
+/// # Panics
+/// Panics if the divisor is zero.
+impl Div for Fixed {
+    type Output = Fixed;
+    fn div(self, other: Fixed) -> Fixed {
+        Fixed::saturate(((self.0 as i128) << Fixed::FRACTION) / other.0 as i128)
+    }
+}
+impl Neg for Fixed {
+    type Output = Fixed;
+    fn neg(self) -> Fixed {
+        Fixed(self.0.saturating_neg())
+    }
+}
+/// Writes the number with 4 decimal digits, truncated toward zero by default.
+/// Precision is respected.
+impl Display for Fixed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let digits = f.precision().unwrap_or(4);
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let bits = self.0.unsigned_abs();
+        write!(f, "{}{}", sign, bits >> Fixed::FRACTION)?;
+        if digits > 0 {
+            write!(f, ".")?;
+            let mut fraction = bits & ((1 << Fixed::FRACTION) - 1);
+            for _ in 0..digits {
+                fraction *= 10;
+                write!(f, "{}", fraction >> Fixed::FRACTION)?;
+                fraction &= (1 << Fixed::FRACTION) - 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Score of the outcome: `1`, `1/2` or `0`.
+fn score(outcome: Outcome) -> Fixed {
+    Fixed::from_ratio(outcome.score() as i64, 2)
+}
+
+/// Elo rating system.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Elo {
+    /// Maximum change of the rating after a game
+    pub k: Fixed,
+}
+impl Elo {
+    /// Creates Elo rating system with given K-factor.
+    pub fn new(k: Fixed) -> Self {
+        Self { k }
+    }
+    /// Returns expected score of the player against the opponent, from `0` to `1`.
+    pub fn expected(&self, rating: Fixed, opponent: Fixed) -> Fixed {
+        let difference = (opponent - rating) / Fixed::from_int(400);
+        Fixed::ONE / (Fixed::ONE + difference.exp10())
+    }
+    /// Returns the rating of the player after the game against the opponent.
+    pub fn update(&self, rating: Fixed, opponent: Fixed, outcome: Outcome) -> Fixed {
+        rating + self.k * (score(outcome) - self.expected(rating, opponent))
+    }
+    /// Returns ratings of both players after the game between them,
+    /// `outcome` is the outcome for the first player.
+    pub fn update_pair(&self, first: Fixed, second: Fixed, outcome: Outcome) -> (Fixed, Fixed) {
+        (
+            self.update(first, second, outcome),
+            self.update(second, first, outcome.reverse()),
+        )
+    }
+}
+/// K-factor is `32`.
+impl Default for Elo {
+    fn default() -> Self {
+        Self::new(Fixed::from_int(32))
+    }
+}
+
+/// Rating of Glicko-2 rating system in the scale of Elo.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Glicko2Rating {
+    /// The rating, `1500` for new players
+    pub rating: Fixed,
+    /// Rating deviation, `350` for new players
+    pub deviation: Fixed,
+    /// Volatility, `0.06` for new players
+    pub volatility: Fixed,
+}
+impl Glicko2Rating {
+    /// Creates rating with volatility of new players.
+    pub fn new(rating: Fixed, deviation: Fixed) -> Self {
+        Self {
+            rating,
+            deviation,
+            volatility: Fixed::from_ratio(6, 100),
+        }
+    }
+    /// Returns the rating and the deviation in the internal scale of Glicko-2.
+    fn scaled(&self) -> (Fixed, Fixed) {
+        (
+            (self.rating - Fixed::from_int(1500)) / Glicko2::SCALE,
+            self.deviation / Glicko2::SCALE,
+        )
+    }
+}
+/// Rating of new players.
+impl Default for Glicko2Rating {
+    fn default() -> Self {
+        Self::new(Fixed::from_int(1500), Fixed::from_int(350))
+    }
+}
+
+/// Glicko-2 rating system.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Glicko2 {
+    /// Constraint of changes of volatility, usually from `0.3` to `1.2`
+    pub tau: Fixed,
+}
+impl Glicko2 {
+    /// Ratio of Elo scale and internal scale of Glicko-2
+    const SCALE: Fixed = Fixed(746112269733);
+
+    /// Creates Glicko-2 rating system with given constraint of volatility.
+    pub fn new(tau: Fixed) -> Self {
+        Self { tau }
+    }
+    /// Returns the rating of the player after the rating period with given games
+    /// against opponents. Deviation of players without games grows.
+    pub fn update(
+        &self,
+        player: Glicko2Rating,
+        games: &[(Glicko2Rating, Outcome)],
+    ) -> Glicko2Rating {
+        let (mu, phi) = player.scaled();
+        let sigma = player.volatility;
+        if games.is_empty() {
+            let deviation = (phi * phi + sigma * sigma).sqrt();
+            return Glicko2Rating {
+                deviation: deviation * Self::SCALE,
+                ..player
+            };
+        }
+        let three = Fixed::from_int(3);
+        let g = |phi: Fixed| {
+            Fixed::ONE / (Fixed::ONE + three * phi * phi / (Fixed::PI * Fixed::PI)).sqrt()
+        };
+        let (mut inverse_v, mut improvement) = (Fixed::ZERO, Fixed::ZERO);
+        for (opponent, outcome) in games {
+            let (mu_j, phi_j) = opponent.scaled();
+            let g_j = g(phi_j);
+            let expected = Fixed::ONE / (Fixed::ONE + (-g_j * (mu - mu_j)).exp());
+            inverse_v = inverse_v + g_j * g_j * expected * (Fixed::ONE - expected);
+            improvement = improvement + g_j * (score(*outcome) - expected);
+        }
+        let v = Fixed::ONE / inverse_v.max(Fixed(1));
+        let delta = v * improvement;
+        // Finds new volatility by Illinois algorithm.
+        let a = (sigma * sigma).ln();
+        let tau2 = self.tau * self.tau;
+        let f = |x: Fixed| {
+            let ex = x.exp();
+            let denominator = phi * phi + v + ex;
+            ex * (delta * delta - phi * phi - v - ex)
+                / (Fixed::from_int(2) * denominator * denominator)
+                - (x - a) / tau2
+        };
+        let mut low = a;
+        let mut high = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1;
+            while f(a - Fixed::from_int(k) * self.tau) < Fixed::ZERO && k < 100 {
+                k += 1;
+            }
+            a - Fixed::from_int(k) * self.tau
+        };
+        let (mut f_low, mut f_high) = (f(low), f(high));
+        let epsilon = Fixed::from_ratio(1, 1_000_000);
+        for _ in 0..100 {
+            if (high - low).abs() <= epsilon || f_high == f_low {
+                break;
+            }
+            let middle = low + (low - high) * f_low / (f_high - f_low);
+            let f_middle = f(middle);
+            if (f_middle < Fixed::ZERO) != (f_high < Fixed::ZERO) {
+                low = high;
+                f_low = f_high;
+            } else {
+                f_low = f_low / Fixed::from_int(2);
+            }
+            high = middle;
+            f_high = f_middle;
+        }
+        let volatility = (low / Fixed::from_int(2)).exp();
+        let phi_star = (phi * phi + volatility * volatility).sqrt();
+        let phi = Fixed::ONE / (Fixed::ONE / (phi_star * phi_star) + inverse_v).sqrt();
+        let mu = mu + phi * phi * improvement;
+        Glicko2Rating {
+            rating: mu * Self::SCALE + Fixed::from_int(1500),
+            deviation: phi * Self::SCALE,
+            volatility,
+        }
+    }
+}
+/// Constraint of volatility is `0.5`.
+impl Default for Glicko2 {
+    fn default() -> Self {
+        Self::new(Fixed::from_ratio(1, 2))
+    }
+}
@@ -0,0 +1,353 @@
+//! Game records
+//!
+//! This module defines PGN-like text format of a whole game, which is used for archiving
+//! games. Record starts with tags, one per line, like `[Event "Club championship"]`.
+//! `Position` tag has the initial position (see [`Game::to_fen`]) and `Result` tag has
+//! the result of the game. Tags are followed by the moves: every turn starts with its number
+//! and a dot, every move is written with a column letter and a row number: `a1` is the top-left
+//! cell, `b1` is on the right of it, `a2` is below it. Moves end with the result:
+//! `*` for ongoing game, `draw` or winner's letter with `-wins`, e.g. `x-wins`.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::record::*;
+//! let board = Board::new(2, 1, 2, [Cell::new(); 12], [0; 12]);
+//! let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+//! let initial = game.clone();
+//! let mut moves = [None; 3];
+//! for (x, record) in [0, 1, 1].into_iter().zip(&mut moves) {
+//!     *record = Some(game.play(game.board().index(x, 0)).unwrap());
+//! }
+//! let mut text = String::new();
+//! let tags = [("Event", "Club championship")];
+//! write(&mut text, &tags, &initial, moves.iter().flatten(), game.state()).unwrap();
+//! assert_eq!(
+//!     text,
+//!     "[Event \"Club championship\"]\n\
+//!      [Position \"2. 2 x 1/1 - 0\"]\n\
+//!      [Result \"x-wins\"]\n\
+//!      \n\
+//!      1. a1 2. b1 3. b1 x-wins\n"
+//! );
+//! let record = parse(&text).unwrap();
+//! assert_eq!(record.tag("Event"), Some("Club championship"));
+//! let replayed = record.replay([Cell::new(); 12], [0; 12], [None; 2]).unwrap();
+//! assert_eq!(replayed, game);
+//! let tags = [("Event", "\"Club\" championship")];
+//! assert!(write(&mut text, &tags, &initial, moves.iter().flatten(), game.state()).is_err());
+//! let text = "[Position \"2. 2 x 1/1 - 0\"]\n[Result \"y-wins\"]\n*\n";
+//! assert_eq!(parse(text), Err(RecordError::MalformedResult(1)));
+//! ```
+use core::fmt::{Display, Error, Formatter, Result as FmtResult, Write};
+use core::ops::IndexMut;
+
+use crate::base::CellKind;
+use crate::board::{Board, CellStorage, HistoryIndex};
+use crate::engine::{ChangeLog, MoveError};
+use crate::game::{Game, GameMove};
+use crate::player_manager::{GameOver, GameState, LoseData};
+use crate::text::{parse_symbol, ParseError, PLAYER_SYMBOLS};
+
+/// Writes the record of the game. `initial` is the position before the first move,
+/// `moves` are the moves made since then and `result` is the state of the game after them.
+/// Names of tags shouldn't be empty or contain whitespace and `"`, values of tags shouldn't
+/// contain `"` and line breaks. `Position` and `Result` tags are written automatically.
+/// Moves are numbered by their turns, see [`GameMove::turn`], their indices can be stored
+/// as any [`HistoryIndex`].
+/// # Errors
+/// Returns [`Error`] if writing fails or a tag can't be written.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// # use crosses_utils::record::*;
+/// # use crosses_utils::simulate::*;
+/// let board = Board::new(16, 16, 2, [Cell::new(); 324], [0; 324]);
+/// let initial = Game::new(board, PlayerManager::new(3, 2, [None; 2]));
+/// let mut game = initial.clone();
+/// let mut history = [None::<GameMove<u8>>; 64];
+/// for ((index, _), slot) in RandomGame::new(initial.clone(), 5).zip(&mut history) {
+///     let played = game.play(index).unwrap();
+///     *slot = Some(played.map_index(|i| u8::from_index(game.board(), i).unwrap()));
+/// }
+/// let mut text = String::new();
+/// write(&mut text, &[], &initial, history.iter().flatten(), game.state()).unwrap();
+/// let replayed = parse(&text).unwrap().replay([Cell::new(); 324], [0; 324], [None; 2]);
+/// assert_eq!(replayed.unwrap(), game);
+/// ```
+pub fn write<'a, W, C, B, S, L, I>(
+    out: &mut W,
+    tags: &[(&str, &str)],
+    initial: &Game<Board<C, B>, S, L>,
+    moves: impl IntoIterator<Item = &'a GameMove<I>>,
+    result: GameState,
+) -> FmtResult
+where
+    W: Write,
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+    I: HistoryIndex + 'a,
+{
+    if !tags.iter().all(|(name, value)| is_tag(name, value)) {
+        return Err(Error);
+    }
+    for (name, value) in tags {
+        writeln!(out, "[{} \"{}\"]", name, value)?;
+    }
+    writeln!(out, "[Position \"{}\"]", initial.to_fen())?;
+    writeln!(out, "[Result \"{}\"]", ResultText(result))?;
+    writeln!(out)?;
+    let board = initial.board();
+    let (mut number, mut previous_turn) = (0, None);
+    for &GameMove { record, turn, .. } in moves {
+        if previous_turn != Some(turn) {
+            number += 1;
+            previous_turn = Some(turn);
+            write!(out, "{}. ", number)?;
+        }
+        let (x, y) = board.coords(record.index.to_index(board));
+        write_column(out, x)?;
+        write!(out, "{} ", y + 1)?;
+    }
+    writeln!(out, "{}", ResultText(result))
+}
+
+/// Parsed game record, returned by [`parse`]. It borrows the text of the record.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Record<'a> {
+    text: &'a str,
+    position: &'a str,
+    result: GameState,
+}
+impl<'a> Record<'a> {
+    /// Returns tags of the record, including `Position` and `Result`.
+    pub fn tags(&self) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+        self.text.lines().filter_map(parse_tag)
+    }
+    /// Returns value of the tag with given name.
+    pub fn tag(&self, name: &str) -> Option<&'a str> {
+        self.tags()
+            .find(|(tag, _)| *tag == name)
+            .map(|(_, value)| value)
+    }
+    /// Returns the initial position of the game.
+    pub fn position(&self) -> &'a str {
+        self.position
+    }
+    /// Returns the result of the game.
+    pub fn result(&self) -> GameState {
+        self.result
+    }
+    /// Returns coordinates of the moves.
+    pub fn moves(&self) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.text
+            .lines()
+            .filter(|line| parse_tag(line).is_none())
+            .flat_map(str::split_whitespace)
+            .filter_map(parse_move)
+    }
+    /// Sets up the initial position in given storages (see [`Game::from_fen`])
+    /// and plays all moves of the record. Records keep only moves, so games ended
+    /// between moves, e.g. by [`Game::mark_loser`] or [`Game::end`], can't be replayed.
+    /// # Errors
+    /// Returns [`RecordError`] if the position is malformed, a move is illegal
+    /// or the game ends with different result. [`RecordError::UnrecordedEnd`]
+    /// is returned, if the game is still ongoing after all moves, but the record
+    /// has ended.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::record::*;
+    /// # use crosses_utils::summary::EliminationReason;
+    /// let board = Board::new(2, 1, 2, [Cell::new(); 12], [0; 12]);
+    /// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+    /// let initial = game.clone();
+    /// let played = game.play(game.board().index(0, 0)).unwrap();
+    /// game.mark_loser(1, EliminationReason::Resigned).unwrap();
+    /// let mut text = String::new();
+    /// write(&mut text, &[], &initial, [&played], game.state()).unwrap();
+    /// let replayed = parse(&text).unwrap().replay([Cell::new(); 12], [0; 12], [None; 2]);
+    /// assert_eq!(replayed.unwrap_err(), RecordError::UnrecordedEnd);
+    /// ```
+    pub fn replay<C, B, S>(
+        &self,
+        cells: C,
+        buffer: B,
+        losers: S,
+    ) -> Result<Game<Board<C, B>, S>, RecordError>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+    {
+        let mut game =
+            Game::from_fen(self.position, cells, buffer, losers).map_err(RecordError::Position)?;
+        for (number, (x, y)) in self.moves().enumerate() {
+            let board = game.board();
+            if x >= board.width() || y >= board.height() {
+                return Err(RecordError::Move(number, MoveError::OutOfBounds));
+            }
+            let index = board.index(x, y);
+            game.play(index)
+                .map_err(|error| RecordError::Move(number, error))?;
+        }
+        check_result(game.state(), self.result)?;
+        Ok(game)
+    }
+}
+
+/// Errors of [`parse`] and [`Record::replay`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum RecordError {
+    /// The line isn't a tag, a move, a turn number or a result
+    MalformedLine(usize),
+    /// The record has no `Position` tag
+    MissingPosition,
+    /// The record has no result after the moves or it doesn't match `Result` tag
+    MissingResult,
+    /// `Result` tag at the line with given number can't be parsed
+    MalformedResult(usize),
+    /// The initial position is malformed
+    Position(ParseError),
+    /// The move with given number is illegal
+    Move(usize, MoveError),
+    /// The game has ended with different result
+    ResultMismatch,
+    /// The game hasn't ended after all moves, but the record has, so it was ended
+    /// between moves, e.g. by resignation or timeout, see [`Record::replay`]
+    UnrecordedEnd,
+}
+impl Display for RecordError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            RecordError::MalformedLine(line) => write!(f, "line {} is malformed", line),
+            RecordError::MissingPosition => write!(f, "record has no position"),
+            RecordError::MissingResult => write!(f, "record has no result"),
+            RecordError::MalformedResult(line) => write!(f, "result at line {} is malformed", line),
+            RecordError::Position(error) => write!(f, "position is malformed: {}", error),
+            RecordError::Move(number, error) => write!(f, "move {} is illegal: {}", number, error),
+            RecordError::ResultMismatch => write!(f, "game has ended with different result"),
+            RecordError::UnrecordedEnd => write!(f, "game has ended between moves"),
+        }
+    }
+}
+
+/// Parses the record of the game. Moves aren't checked, use [`Record::replay`] for it.
+/// # Errors
+/// Returns [`RecordError`] if the text is malformed.
+pub fn parse(text: &str) -> Result<Record<'_>, RecordError> {
+    let (mut position, mut tag_result, mut result) = (None, None, None);
+    for (number, line) in text.lines().enumerate() {
+        if let Some((name, value)) = parse_tag(line) {
+            match name {
+                "Position" => position = Some(value),
+                "Result" => {
+                    tag_result =
+                        Some(parse_result(value).ok_or(RecordError::MalformedResult(number))?)
+                }
+                _ => {}
+            }
+            continue;
+        }
+        for token in line.split_whitespace() {
+            if result.is_some() {
+                return Err(RecordError::MalformedLine(number));
+            }
+            let is_turn = token
+                .strip_suffix('.')
+                .map_or(false, |turn| turn.parse::<usize>().is_ok());
+            if is_turn || parse_move(token).is_some() {
+                continue;
+            }
+            result = Some(parse_result(token).ok_or(RecordError::MalformedLine(number))?);
+        }
+    }
+    let position = position.ok_or(RecordError::MissingPosition)?;
+    match (result, tag_result) {
+        (Some(result), Some(tag)) if result == tag => Ok(Record {
+            text,
+            position,
+            result,
+        }),
+        _ => Err(RecordError::MissingResult),
+    }
+}
+
+/// Checks the result of the replayed game against the result of the record.
+pub(crate) fn check_result(replayed: GameState, recorded: GameState) -> Result<(), RecordError> {
+    match (replayed, recorded) {
+        (replayed, recorded) if replayed == recorded => Ok(()),
+        (GameState::Ongoing, GameState::Ended(_)) => Err(RecordError::UnrecordedEnd),
+        _ => Err(RecordError::ResultMismatch),
+    }
+}
+/// Displayable result of the game.
+struct ResultText(GameState);
+impl Display for ResultText {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.0 {
+            GameState::Ongoing => write!(f, "*"),
+            GameState::Ended(GameOver::Draw) => write!(f, "draw"),
+            GameState::Ended(GameOver::Win(winner)) => {
+                write!(f, "{}-wins", PLAYER_SYMBOLS[winner])
+            }
+        }
+    }
+}
+fn parse_result(text: &str) -> Option<GameState> {
+    match text {
+        "*" => Some(GameState::Ongoing),
+        "draw" => Some(GameState::Ended(GameOver::Draw)),
+        _ => {
+            let mut chars = text.strip_suffix("-wins")?.chars();
+            match (chars.next().and_then(parse_symbol), chars.next()) {
+                (Some((CellKind::Cross, winner)), None) => {
+                    Some(GameState::Ended(GameOver::Win(winner)))
+                }
+                _ => None,
+            }
+        }
+    }
+}
+/// Checks if the tag of the caller can be written and parsed back.
+fn is_tag(name: &str, value: &str) -> bool {
+    !name.is_empty()
+        && !name.contains(|c: char| c.is_whitespace() || c == '"')
+        && !matches!(name, "Position" | "Result")
+        && !value.contains(['"', '\n', '\r'])
+}
+fn parse_tag(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line
+        .trim()
+        .strip_prefix('[')?
+        .strip_suffix("\"]")?
+        .split_once(" \"")?;
+    Some((name, value))
+}
+/// Writes the column letters: `a`..`z`, then `aa`, `ab` and so on.
+fn write_column<W: Write>(out: &mut W, x: usize) -> FmtResult {
+    if x >= 26 {
+        write_column(out, x / 26 - 1)?;
+    }
+    out.write_char((b'a' + (x % 26) as u8) as char)
+}
+fn parse_move(token: &str) -> Option<(usize, usize)> {
+    let digits = token.find(|c: char| c.is_ascii_digit())?;
+    let (column, row) = token.split_at(digits);
+    if column.is_empty() || !column.bytes().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+    let mut x = 0usize;
+    for c in column.bytes() {
+        x = x.checked_mul(26)?.checked_add((c - b'a') as usize + 1)?;
+    }
+    let y = row.parse::<usize>().ok()?.checked_sub(1)?;
+    Some((x - 1, y))
+}
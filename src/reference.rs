@@ -0,0 +1,436 @@
+//! Reference engine
+//!
+//! This module defines [`NaiveBoard`], a deliberately simple implementation of the rules
+//! for differential testing of [`Board`] and [`engine`](crate::engine). It doesn't use IBTS
+//! or incremental counters: after every change aliveness of chains and activations of cells
+//! are recomputed from scratch by their definitions, so it's slow, but obviously correct.
+//! A filled cell is alive, if its chain is adjacent to a cross of its player, a cell is
+//! active for the player, if it's their starting cell or it's reached by their cross or alive
+//! filled cell. Counters are counted on demand.
+//!
+//! [`differential`] replays the same moves on both engines and compares everything, that
+//! can be observed: legality of moves, content of cells, aliveness, activations and counters.
+//! The module is available with `testing` feature.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::counters::*;
+//! # use crosses_utils::engine::*;
+//! # use crosses_utils::reference::*;
+//! # use crosses_utils::rules::*;
+//! let mut board = Board::new(4, 4, 2, [Cell::new(); 36], [0; 36]);
+//! board.set_rules(RuleSet { convert_chains: true, ..RuleSet::STANDARD });
+//! let mut naive = NaiveBoard::new(&board, [NaiveCell::default(); 36]);
+//! let coords = [(0, 0), (3, 3), (1, 1), (2, 2), (2, 2), (1, 1), (3, 2), (1, 2)];
+//! let mut moves = [(0, 0); 8];
+//! for (i, (x, y)) in coords.into_iter().enumerate() {
+//!     moves[i] = (board.index(x, y), i % 2);
+//! }
+//! let mut log = ChangeStack::new([Default::default(); 16]);
+//! assert_eq!(differential(&mut board, &mut naive, moves, &mut log), Ok(()));
+//! assert_eq!(naive.crosses(0), board.counters().get(Counter::Crosses, 0));
+//! ```
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::base::CellKind;
+use crate::board::{Adjacency, Board, CellStorage, MAX_PLAYERS};
+use crate::counters::{Counter, PlayerCounters};
+use crate::engine::{make_move_logged, remove_dead_chains, ChangeLog, Data, Engine, MoveError};
+use crate::ibts::IbtsBoard;
+use crate::rules::RuleSet;
+
+/// Cell of [`NaiveBoard`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct NaiveCell {
+    kind: CellKind,
+    player: usize,
+    previous_player: usize,
+    damage: u8,
+    protected: bool,
+    stronghold: bool,
+    alive: bool,
+    active: u32,
+    marked: bool,
+}
+impl Default for NaiveCell {
+    /// Empty cell.
+    fn default() -> Self {
+        Self {
+            kind: CellKind::Empty,
+            player: 0,
+            previous_player: 0,
+            damage: 0,
+            protected: false,
+            stronghold: false,
+            alive: false,
+            active: 0,
+            marked: false,
+        }
+    }
+}
+impl NaiveCell {
+    /// Checks if the filled cell is alive.
+    pub fn is_alive(&self) -> bool {
+        self.alive
+    }
+}
+impl Data for NaiveCell {
+    fn kind(&self) -> CellKind {
+        self.kind
+    }
+    fn player(&self) -> usize {
+        self.player
+    }
+    fn set_content(&mut self, kind: CellKind, player: usize) {
+        self.kind = kind;
+        self.player = player;
+    }
+    fn previous_player(&self) -> usize {
+        self.previous_player
+    }
+    fn set_previous_player(&mut self, player: usize) {
+        self.previous_player = player;
+    }
+    fn is_active(&self, player: usize) -> bool {
+        self.active & (1 << player) != 0
+    }
+    fn activate(&mut self, player: usize) {
+        self.active |= 1 << player;
+    }
+    fn deactivate(&mut self, player: usize) {
+        self.active &= !(1 << player);
+    }
+    fn is_protected(&self) -> bool {
+        self.protected
+    }
+    fn is_stronghold(&self) -> bool {
+        self.stronghold
+    }
+    fn damage(&self) -> u8 {
+        self.damage
+    }
+    fn set_damage(&mut self, damage: u8) {
+        self.damage = damage;
+    }
+}
+
+/// Reference engine, see module docs. Cells have the same indices as cells of [`Board`].
+/// `S` - is type of cells storage, it can be Vec or simple array.
+#[derive(Clone, Debug)]
+pub struct NaiveBoard<S> {
+    width: usize,
+    height: usize,
+    players: usize,
+    adjacency: Adjacency,
+    placement: Adjacency,
+    rules: RuleSet,
+    starts: [Option<usize>; MAX_PLAYERS],
+    cells: S,
+}
+impl<S: AsRef<[NaiveCell]> + AsMut<[NaiveCell]>> NaiveBoard<S> {
+    /// Creates a copy of the board: its size, adjacency, rules, starting cells
+    /// and content of cells. `cells` should have as many elements as the board.
+    /// # Panics
+    /// Panics if `cells` is too small.
+    pub fn new<C, B>(board: &Board<C, B>, mut cells: S) -> Self
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        let len = Board::<C, B>::storage_len(board.width(), board.height());
+        assert!(cells.as_ref().len() >= len, "cells storage is too small");
+        for (index, naive) in cells.as_mut()[..len].iter_mut().enumerate() {
+            let cell = board.get(index);
+            *naive = NaiveCell {
+                kind: cell.kind(),
+                player: cell.player(),
+                previous_player: cell.previous_player(),
+                damage: cell.damage(),
+                protected: cell.is_protected(),
+                stronghold: cell.is_stronghold(),
+                ..NaiveCell::default()
+            };
+        }
+        let players = board.counters().players();
+        let mut starts = [None; MAX_PLAYERS];
+        for (player, start) in starts.iter_mut().enumerate().take(players) {
+            *start = board.start(player);
+        }
+        let mut naive = Self {
+            width: board.width(),
+            height: board.height(),
+            players,
+            adjacency: board.adjacency(),
+            placement: board.placement_adjacency(),
+            rules: board.rules(),
+            starts,
+            cells,
+        };
+        naive.refresh();
+        naive
+    }
+    /// Returns the cell at given index.
+    pub fn get(&self, index: usize) -> NaiveCell {
+        self.cells.as_ref()[index]
+    }
+    /// Returns number of crosses of the player.
+    pub fn crosses(&self, player: usize) -> usize {
+        self.cells()
+            .filter(|cell| cell.kind == CellKind::Cross && cell.player == player)
+            .count()
+    }
+    /// Returns number of cells, where the player can make a move.
+    pub fn moves(&self, player: usize) -> usize {
+        let rules = self.rules;
+        self.cells()
+            .filter(|cell| rules.check_move(**cell, player).is_ok())
+            .count()
+    }
+    /// Makes a move of the player at given index by the rules of the board.
+    /// # Errors
+    /// Returns [`MoveError`] if the move is illegal. In this case nothing is changed.
+    pub fn play(&mut self, index: usize, player: usize) -> Result<(), MoveError> {
+        let cell = self.get(index);
+        self.rules.check_move(cell, player)?;
+        let cells = self.cells.as_mut();
+        match cell.kind {
+            CellKind::Empty => {
+                cells[index].kind = CellKind::Cross;
+                cells[index].player = player;
+            }
+            _ if self.rules.damages(cell) => cells[index].damage += 1,
+            _ => {
+                cells[index].kind = CellKind::Filled;
+                cells[index].player = player;
+                cells[index].previous_player = cell.player;
+                cells[index].damage = 0;
+                self.refresh();
+                if self.rules.convert_chains && cell.player != player {
+                    self.convert(index, cell.player, player);
+                }
+            }
+        }
+        self.refresh();
+        Ok(())
+    }
+    /// Removes dead chains, if the rules say so, see [`RuleSet::remove_dead_chains`].
+    /// Returns number of removed cells.
+    pub fn remove_dead_chains(&mut self) -> usize {
+        if !self.rules.remove_dead_chains {
+            return 0;
+        }
+        let mut removed = 0;
+        for cell in self.cells.as_mut() {
+            if cell.kind == CellKind::Filled && !cell.alive {
+                (cell.kind, cell.player, cell.previous_player) = (CellKind::Empty, 0, 0);
+                removed += 1;
+            }
+        }
+        self.refresh();
+        removed
+    }
+
+    /// Converts dead chains of `owner` connected to the filled cell to `player`.
+    fn convert(&mut self, index: usize, owner: usize, player: usize) {
+        self.cells.as_mut()[index].marked = true;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for i in self.inside() {
+                let cell = self.get(i);
+                let dead = cell.kind == CellKind::Filled && cell.player == owner && !cell.alive;
+                if dead
+                    && !cell.marked
+                    && self.around(i, self.adjacency).any(|j| self.get(j).marked)
+                {
+                    let cells = self.cells.as_mut();
+                    (cells[i].player, cells[i].marked) = (player, true);
+                    changed = true;
+                }
+            }
+        }
+        for cell in self.cells.as_mut() {
+            cell.marked = false;
+        }
+    }
+    /// Recomputes aliveness of filled cells and activations of cells.
+    fn refresh(&mut self) {
+        let inside = self.inside();
+        for i in inside.clone() {
+            self.cells.as_mut()[i].alive = false;
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for i in inside.clone() {
+                let cell = self.get(i);
+                if cell.kind != CellKind::Filled || cell.alive {
+                    continue;
+                }
+                let supported = self.around(i, self.adjacency).any(|j| {
+                    let other = self.get(j);
+                    other.player == cell.player
+                        && (other.kind == CellKind::Cross
+                            || other.kind == CellKind::Filled && other.alive)
+                });
+                if supported {
+                    self.cells.as_mut()[i].alive = true;
+                    changed = true;
+                }
+            }
+        }
+        for i in inside {
+            let mut active = 0;
+            for player in 0..self.players {
+                let reached = self.around(i, self.placement).any(|j| {
+                    let other = self.get(j);
+                    other.player == player
+                        && (other.kind == CellKind::Cross
+                            || other.kind == CellKind::Filled && other.alive)
+                });
+                if reached || self.starts[player] == Some(i) {
+                    active |= 1 << player;
+                }
+            }
+            self.cells.as_mut()[i].active = active;
+        }
+    }
+    /// Returns indices of cells inside of the border.
+    fn inside(&self) -> impl Iterator<Item = usize> + Clone {
+        let (width, height) = (self.width, self.height);
+        (0..width * height)
+            .map(move |packed| (packed / width + 1) * (width + 2) + packed % width + 1)
+    }
+    fn cells(&self) -> impl Iterator<Item = &NaiveCell> {
+        let cells = self.cells.as_ref();
+        self.inside().map(move |i| &cells[i])
+    }
+    /// Returns indices of cells at offsets of the adjacency from the cell.
+    fn around(&self, index: usize, adjacency: Adjacency) -> impl Iterator<Item = usize> {
+        let (width, height) = (self.width as isize + 2, self.height as isize + 2);
+        let (x, y) = (index as isize % width, index as isize / width);
+        let offsets = adjacency.offsets();
+        let mut around = [0; crate::board::MAX_ADJACENT];
+        let mut len = 0;
+        for (dx, dy) in offsets {
+            let (x, y) = (x + *dx as isize, y + *dy as isize);
+            if (0..width).contains(&x) && (0..height).contains(&y) {
+                around[len] = (y * width + x) as usize;
+                len += 1;
+            }
+        }
+        around.into_iter().take(len)
+    }
+}
+
+/// What differs between [`Board`] and [`NaiveBoard`], see [`differential`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Divergence {
+    /// The move is legal only for one of them, results of [`Board`] and [`NaiveBoard`]
+    Legality(Result<(), MoveError>, Result<(), MoveError>),
+    /// Type, player, previous player or damage of the cell at given index
+    Content(usize),
+    /// Aliveness of the filled cell at given index
+    Alive(usize),
+    /// Activation of the cell at given index for the player
+    Active(usize, usize),
+    /// The counter of the player
+    Counter(Counter, usize),
+}
+impl Display for Divergence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Divergence::Legality(board, naive) => {
+                write!(f, "legality differs: {:?} and {:?}", board, naive)
+            }
+            Divergence::Content(index) => write!(f, "content of cell {} differs", index),
+            Divergence::Alive(index) => write!(f, "aliveness of cell {} differs", index),
+            Divergence::Active(index, player) => {
+                write!(
+                    f,
+                    "activation of cell {} for player {} differs",
+                    index, player
+                )
+            }
+            Divergence::Counter(counter, player) => {
+                write!(f, "counter {:?} of player {} differs", counter, player)
+            }
+        }
+    }
+}
+
+/// Compares everything, that can be observed, of the board and the reference engine.
+/// # Errors
+/// Returns the first [`Divergence`], cells are compared row by row.
+pub fn compare<C, B, S>(board: &Board<C, B>, naive: &NaiveBoard<S>) -> Result<(), Divergence>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: AsRef<[NaiveCell]> + AsMut<[NaiveCell]>,
+{
+    for index in naive.inside() {
+        let (cell, expected) = (board.get(index), naive.get(index));
+        let filled = expected.kind == CellKind::Filled;
+        if cell.kind() != expected.kind
+            || cell.player() != expected.player
+            || cell.damage() != expected.damage
+            || filled && cell.previous_player() != expected.previous_player
+        {
+            return Err(Divergence::Content(index));
+        }
+        if filled && board.is_alive(index) != expected.alive {
+            return Err(Divergence::Alive(index));
+        }
+        for player in 0..naive.players {
+            if cell.is_active(player) != expected.is_active(player) {
+                return Err(Divergence::Active(index, player));
+            }
+        }
+    }
+    let counters = board.counters();
+    for player in 0..naive.players {
+        if counters.get(Counter::Crosses, player) != naive.crosses(player) {
+            return Err(Divergence::Counter(Counter::Crosses, player));
+        }
+        if counters.get(Counter::Moves, player) != naive.moves(player) {
+            return Err(Divergence::Counter(Counter::Moves, player));
+        }
+    }
+    Ok(())
+}
+
+/// Makes moves `(index, player)` on the board with [`make_move_logged`] and on the reference
+/// engine and compares them with [`compare`] before the first move and after every move.
+/// If the rules remove dead chains, they are removed after every legal move. Illegal moves
+/// are skipped, if both engines reject them with the same error.
+/// # Errors
+/// Returns index of the move, starting from `1` (`0` is the initial position),
+/// and the first [`Divergence`].
+pub fn differential<C, B, S, L>(
+    board: &mut Board<C, B>,
+    naive: &mut NaiveBoard<S>,
+    moves: impl IntoIterator<Item = (usize, usize)>,
+    log: &mut L,
+) -> Result<(), (usize, Divergence)>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: AsRef<[NaiveCell]> + AsMut<[NaiveCell]>,
+    L: ChangeLog<usize>,
+{
+    compare(board, naive).map_err(|divergence| (0, divergence))?;
+    for (step, (index, player)) in moves.into_iter().enumerate() {
+        let result = make_move_logged(board, index, player, log).map(|_| ());
+        let expected = naive.play(index, player);
+        if result != expected {
+            return Err((step + 1, Divergence::Legality(result, expected)));
+        }
+        if result.is_ok() {
+            // The log is checked by the move.
+            let _ = remove_dead_chains(board, log);
+            naive.remove_dead_chains();
+        }
+        compare(board, naive).map_err(|divergence| (step + 1, divergence))?;
+    }
+    Ok(())
+}
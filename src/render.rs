@@ -0,0 +1,390 @@
+//! Rendering of boards
+//!
+//! This module defines [`BoardRenderer`], which chooses the character and the style of
+//! every cell, and writes boards with them into anything implementing [`Write`]: as plain
+//! characters with [`BoardRenderer::write_ascii`] or with ANSI escape codes of colors with
+//! [`BoardRenderer::write_ansi`], so text UIs and embedded displays share one rendering
+//! path. [`DefaultRenderer`] uses characters of [`text`](crate::text) and [`PLAYER_COLORS`],
+//! overlays can show the zone of reach of a player and important cells. For debugging
+//! of IBTS and bug reports overlays can also be written as markers after cells, together
+//! with aliveness of filled cells and activations of cells for all players, so they are
+//! visible without colors.
+//!
+//! [`write_dot`] writes the graph of chains of filled cells in DOT language of Graphviz,
+//! which helps to debug IBTS on complex positions and to draw diagrams.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::engine::*;
+//! # use crosses_utils::render::*;
+//! let mut board = Board::new(2, 1, 2, [Cell::new(); 12], [0; 12]);
+//! let corner = board.index(0, 0);
+//! make_move(&mut board, corner, 0).unwrap();
+//! let mut text = String::new();
+//! DefaultRenderer::new().write_ascii(&board, &mut text).unwrap();
+//! assert_eq!(text, "x.\n");
+//! text.clear();
+//! DefaultRenderer::new().activity(0).write_ansi(&board, &mut text).unwrap();
+//! assert_eq!(text, "\x1b[31mx\x1b[0m\x1b[30;41m.\x1b[0m\n");
+//! text.clear();
+//! DefaultRenderer::new().importance().activations().write_ascii(&board, &mut text).unwrap();
+//! assert_eq!(text, "x  x. .  xo \n");
+//! ```
+use core::fmt::{Result as FmtResult, Write};
+
+use crate::base::{CellKind, GameBoard};
+use crate::board::{Board, CellStorage, MAX_PLAYERS};
+use crate::counters::PlayerCounters;
+use crate::engine::{Data, Engine};
+use crate::ibts::IbtsBoard;
+use crate::text::{symbol, PLAYER_SYMBOLS};
+
+/// Color of ANSI terminals.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Color {
+    /// Black
+    Black,
+    /// Red
+    Red,
+    /// Green
+    Green,
+    /// Yellow
+    Yellow,
+    /// Blue
+    Blue,
+    /// Magenta
+    Magenta,
+    /// Cyan
+    Cyan,
+    /// White
+    White,
+}
+
+/// Colors of players used by [`DefaultRenderer`].
+pub const PLAYER_COLORS: [Color; MAX_PLAYERS] = [
+    Color::Red,
+    Color::Blue,
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+    Color::Black,
+];
+
+/// Style of a character. Default style has no colors and attributes.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Style {
+    /// Color of the character
+    pub foreground: Option<Color>,
+    /// Color behind the character
+    pub background: Option<Color>,
+    /// Whether the character is bold
+    pub bold: bool,
+    /// Whether the character is dim
+    pub dim: bool,
+    /// Whether the character is underlined
+    pub underline: bool,
+}
+impl Style {
+    /// Writes ANSI escape code, that turns the style on, if it isn't default.
+    fn write_start(&self, out: &mut impl Write) -> FmtResult {
+        if *self == Style::default() {
+            return Ok(());
+        }
+        let mut separator = "";
+        let mut code = |out: &mut dyn Write, code: u8| {
+            let result = write!(out, "{}{}", separator, code);
+            separator = ";";
+            result
+        };
+        write!(out, "\x1b[")?;
+        if self.bold {
+            code(out, 1)?;
+        }
+        if self.dim {
+            code(out, 2)?;
+        }
+        if self.underline {
+            code(out, 4)?;
+        }
+        if let Some(color) = self.foreground {
+            code(out, 30 + color as u8)?;
+        }
+        if let Some(color) = self.background {
+            code(out, 40 + color as u8)?;
+        }
+        write!(out, "m")
+    }
+    /// Writes ANSI escape code, that turns the style off, if it isn't default.
+    fn write_end(&self, out: &mut impl Write) -> FmtResult {
+        if *self == Style::default() {
+            return Ok(());
+        }
+        write!(out, "\x1b[0m")
+    }
+}
+
+/// Chooses characters and styles of cells, see module docs.
+pub trait BoardRenderer {
+    /// Returns the character of the cell at given index.
+    fn symbol<C: CellStorage, B: AsMut<[usize]>>(&self, board: &Board<C, B>, index: usize) -> char {
+        let cell = board.get(index);
+        symbol(cell.kind(), cell.player())
+    }
+    /// Returns the style of the cell at given index.
+    fn style<C: CellStorage, B: AsMut<[usize]>>(
+        &self,
+        _board: &Board<C, B>,
+        _index: usize,
+    ) -> Style {
+        Style::default()
+    }
+
+    /// Writes markers of the cell at given index after its character, e.g. flags of IBTS.
+    /// Markers of all cells should have the same width. Default implementation writes nothing.
+    fn write_markers<C, B>(
+        &self,
+        _board: &Board<C, B>,
+        _index: usize,
+        _out: &mut impl Write,
+    ) -> FmtResult
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        Ok(())
+    }
+
+    /// Writes characters of cells with their markers, every row on its own line.
+    fn write_ascii<C, B>(&self, board: &Board<C, B>, out: &mut impl Write) -> FmtResult
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                let index = board.index(x, y);
+                out.write_char(self.symbol(board, index))?;
+                self.write_markers(board, index, out)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+    /// Writes characters of cells with their styles as ANSI escape codes and their markers,
+    /// every row on its own line.
+    fn write_ansi<C, B>(&self, board: &Board<C, B>, out: &mut impl Write) -> FmtResult
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                let index = board.index(x, y);
+                let style = self.style(board, index);
+                style.write_start(out)?;
+                out.write_char(self.symbol(board, index))?;
+                style.write_end(out)?;
+                self.write_markers(board, index, out)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renderer with characters of [`text`](crate::text) and [`PLAYER_COLORS`]. Alive filled
+/// cells are bold and dead ones are dim. With overlays cells of the zone of reach of
+/// a player have the color of the player behind them and important cells are underlined.
+///
+/// With markers every cell is followed by `+` or `-` for alive or dead filled cell,
+/// `!` for important cell with the importance overlay, `*` for a cell of the zone
+/// of reach with the activity overlay, letters of players, for whom the cell is active,
+/// with the activations overlay, and a space. Absent markers are spaces or `.` for activations.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DefaultRenderer {
+    activity: Option<usize>,
+    importance: bool,
+    markers: bool,
+    activations: bool,
+}
+impl DefaultRenderer {
+    /// Creates renderer without overlays.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Shows cells, where the player can make a move.
+    pub fn activity(mut self, player: usize) -> Self {
+        self.activity = Some(player);
+        self
+    }
+    /// Shows important cells.
+    pub fn importance(mut self) -> Self {
+        self.importance = true;
+        self
+    }
+    /// Writes overlays also as markers after cells.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::render::*;
+    /// # use crosses_utils::text::*;
+    /// let board = parse_into("xXo\n.XO", [Default::default(); 25], [0; 25]).unwrap();
+    /// let mut text = String::new();
+    /// DefaultRenderer::new().importance().markers().write_ascii(&board, &mut text).unwrap();
+    /// assert_eq!(text, "x ! X+! o ! \n.   X+  O+! \n");
+    /// ```
+    pub fn markers(mut self) -> Self {
+        self.markers = true;
+        self
+    }
+    /// Shows activations of cells for all players as markers, see [`Data::is_active`].
+    pub fn activations(mut self) -> Self {
+        self.activations = true;
+        self.markers()
+    }
+}
+impl BoardRenderer for DefaultRenderer {
+    fn style<C: CellStorage, B: AsMut<[usize]>>(&self, board: &Board<C, B>, index: usize) -> Style {
+        let cell = board.get(index);
+        let mut style = Style::default();
+        match cell.kind() {
+            CellKind::Cross => style.foreground = Some(PLAYER_COLORS[cell.player()]),
+            CellKind::Filled => {
+                style.foreground = Some(PLAYER_COLORS[cell.player()]);
+                style.bold = board.is_alive(index);
+                style.dim = !style.bold;
+            }
+            _ => {}
+        }
+        if let Some(player) = self.activity {
+            if board.rules().check_move(cell, player).is_ok() {
+                style.background = Some(PLAYER_COLORS[player]);
+                if cell.kind() == CellKind::Empty {
+                    style.foreground = Some(Color::Black);
+                }
+            }
+        }
+        style.underline = self.importance && board.is_important(index);
+        style
+    }
+    fn write_markers<C, B>(
+        &self,
+        board: &Board<C, B>,
+        index: usize,
+        out: &mut impl Write,
+    ) -> FmtResult
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+    {
+        if !self.markers {
+            return Ok(());
+        }
+        let cell = board.get(index);
+        let marker = |shown: bool, marker: char| if shown { marker } else { ' ' };
+        match cell.kind() {
+            CellKind::Filled => out.write_char(if board.is_alive(index) { '+' } else { '-' })?,
+            _ => out.write_char(' ')?,
+        }
+        if self.importance {
+            out.write_char(marker(board.is_important(index), '!'))?;
+        }
+        if let Some(player) = self.activity {
+            out.write_char(marker(board.rules().check_move(cell, player).is_ok(), '*'))?;
+        }
+        if self.activations {
+            let players = board.counters().players();
+            for (player, letter) in PLAYER_SYMBOLS.iter().enumerate().take(players) {
+                out.write_char(if cell.is_active(player) { *letter } else { '.' })?;
+            }
+        }
+        out.write_char(' ')
+    }
+}
+
+impl Color {
+    /// Returns name of the color in Graphviz.
+    fn dot_name(self) -> &'static str {
+        match self {
+            Color::Black => "black",
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Yellow => "yellow",
+            Color::Blue => "blue",
+            Color::Magenta => "magenta",
+            Color::Cyan => "cyan",
+            Color::White => "white",
+        }
+    }
+}
+
+/// Writes the graph of filled cells in DOT language. Nodes are filled cells named `c`
+/// with packed index (see [`Board::pack_index`]), labeled with their coordinates and
+/// colored by [`PLAYER_COLORS`]. Nodes have `player`, `alive` and `important` attributes,
+/// dead cells are dashed and important cells are bold. Edges connect adjacent filled
+/// cells of the same player, so connected components of the graph are chains.
+/// # Example
+/// ```
+/// # use crosses_utils::render::*;
+/// # use crosses_utils::text::*;
+/// let board = parse_into("xXo\n.XO", [Default::default(); 25], [0; 25]).unwrap();
+/// let mut dot = String::new();
+/// write_dot(&board, &mut dot).unwrap();
+/// assert!(dot.starts_with("graph chains {\n"));
+/// assert!(dot.contains("  c4 [label=\"1,1\", player=0, alive=true, important=false"));
+/// assert!(dot.contains("  c1 -- c4;\n"));
+/// assert!(!dot.contains("c4 -- c5"));
+/// ```
+pub fn write_dot<C, B>(board: &Board<C, B>, out: &mut impl Write) -> FmtResult
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    writeln!(out, "graph chains {{")?;
+    let cells = board.width() * board.height();
+    let filled = |index: usize| board.get(index).kind() == CellKind::Filled;
+    for packed in 0..cells {
+        let index = board.unpack_index(packed);
+        if !filled(index) {
+            continue;
+        }
+        let (x, y) = board.coords(index);
+        let (player, alive, important) = (
+            board.get(index).player(),
+            board.is_alive(index),
+            board.is_important(index),
+        );
+        let style = match (alive, important) {
+            (true, false) => "solid",
+            (true, true) => "bold",
+            (false, false) => "dashed",
+            (false, true) => "\"dashed,bold\"",
+        };
+        writeln!(
+            out,
+            "  c{} [label=\"{},{}\", player={}, alive={}, important={}, color={}, style={}];",
+            packed,
+            x,
+            y,
+            player,
+            alive,
+            important,
+            PLAYER_COLORS[player].dot_name(),
+            style,
+        )?;
+    }
+    for packed in 0..cells {
+        let index = board.unpack_index(packed);
+        if !filled(index) {
+            continue;
+        }
+        let player = board.get(index).player();
+        for i in board.adjacent(index) {
+            if filled(i) && board.get(i).player() == player && board.pack_index(i) > packed {
+                writeln!(out, "  c{} -- c{};", packed, board.pack_index(i))?;
+            }
+        }
+    }
+    writeln!(out, "}}")
+}
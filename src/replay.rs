@@ -0,0 +1,342 @@
+//! Replays
+//!
+//! This module defines append-only binary format of a game. Replay starts with
+//! a header: [`MAGIC`], maximum number of moves in a turn as little-endian `u16`
+//! and the initial board in binary form (see [`binary`](crate::binary)). Then every
+//! move takes [`MOVE_LEN`] bytes: packed index of the cell (see [`Board::pack_index`])
+//! and rolling hash of the position after the move, both as little-endian `u32`.
+//! Hash covers the previous hash, the move and counters of the player, so [`load`] fails
+//! at the first corrupted or tampered move.
+//!
+//! The header doesn't store rules, adjacency, starting, protected and stronghold cells
+//! of the board or the schedule, stalemate rule and neutral player of the game, so only
+//! games with default ones can be replayed, see [`ReplayWriter::start`].
+//!
+//! Records of games kept in other forms can be checked with [`verify_replay`], if they store
+//! [`Game::consistency_hash`] after every move, e.g. as peers of lockstep games exchange it.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::replay::*;
+//! let board = Board::new(2, 1, 2, [Cell::new(); 12], [0; 12]);
+//! let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+//! let mut bytes = [0; 64];
+//! let (mut writer, mut len) = ReplayWriter::start(&game, &mut bytes).unwrap();
+//! for x in [0, 1, 1] {
+//!     let record = game.play(game.board().index(x, 0)).unwrap();
+//!     len += writer.push(&game, &record, &mut bytes[len..]).unwrap();
+//! }
+//! let loaded = load(&bytes[..len], [Cell::new(); 12], [0; 12], [None; 2]).unwrap();
+//! assert_eq!(loaded, game);
+//! bytes[len - 1] ^= 1;
+//! let error = load(&bytes[..len], [Cell::new(); 12], [0; 12], [None; 2]).unwrap_err();
+//! assert_eq!(error, ReplayError::Checksum(2));
+//! ```
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::ops::IndexMut;
+
+use crate::base::CellKind;
+use crate::binary::{BinaryError, HEADER_LEN};
+use crate::board::{Adjacency, Board, CellStorage, HistoryIndex};
+use crate::counters::{Counter, PlayerCounters};
+use crate::engine::{ChangeLog, Data, Engine, MoveError};
+use crate::game::{Game, GameMove};
+use crate::player_manager::{GameState, LoseData, MoveSchedule, PlayerManager, Stalemate};
+use crate::rules::RuleSet;
+
+/// First bytes of every replay.
+pub const MAGIC: [u8; 4] = *b"XRP1";
+/// Number of bytes of one move.
+pub const MOVE_LEN: usize = 8;
+
+/// Writer of the replay. It only keeps the rolling hash,
+/// the bytes are written into buffers of the caller.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ReplayWriter {
+    hash: u32,
+}
+impl ReplayWriter {
+    /// Writes the header of the replay of the game into `out`. Returns the writer
+    /// and number of written bytes.
+    /// # Errors
+    /// Returns [`ReplayError::Started`] if the game already has moves,
+    /// [`ReplayError::Unsupported`] if it has settings, that the header doesn't store,
+    /// and [`ReplayError::Header`] if `out` is too small or the board can't be encoded.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::replay::*;
+    /// let mut board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+    /// board.set_start(0, Some(board.index(2, 2)));
+    /// let game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+    /// let mut bytes = [0; 64];
+    /// let error = ReplayWriter::start(&game, &mut bytes).unwrap_err();
+    /// assert_eq!(error, ReplayError::Unsupported);
+    /// let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+    /// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+    /// game.play(game.board().index(0, 0)).unwrap();
+    /// let error = ReplayWriter::start(&game, &mut bytes).unwrap_err();
+    /// assert_eq!(error, ReplayError::Started);
+    /// ```
+    pub fn start<C, B, S, L>(
+        game: &Game<Board<C, B>, S, L>,
+        out: &mut [u8],
+    ) -> Result<(Self, usize), ReplayError>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        let manager = game.player_manager();
+        if manager.current_move != 0 {
+            return Err(ReplayError::Started);
+        }
+        if !is_initial(manager) || !is_default(game.board()) {
+            return Err(ReplayError::Unsupported);
+        }
+        let start = MAGIC.len() + 2;
+        if out.len() < start {
+            return Err(ReplayError::Header(BinaryError::BufferTooSmall));
+        }
+        let max_moves = manager.max_moves;
+        if max_moves > u16::MAX as usize {
+            return Err(ReplayError::Header(BinaryError::TooLarge));
+        }
+        out[..MAGIC.len()].copy_from_slice(&MAGIC);
+        out[MAGIC.len()..start].copy_from_slice(&(max_moves as u16).to_le_bytes());
+        let len = start
+            + game
+                .board()
+                .to_bytes(&mut out[start..])
+                .map_err(ReplayError::Header)?;
+        let writer = Self {
+            hash: fnv(FNV_OFFSET, &out[..len]),
+        };
+        Ok((writer, len))
+    }
+    /// Writes the move into `out`. `game` is the game after the move and `played`
+    /// is returned by [`Game::play`], its index can be stored as any [`HistoryIndex`].
+    /// Returns [`MOVE_LEN`].
+    /// # Errors
+    /// Returns [`BinaryError::BufferTooSmall`] if `out` is too small.
+    pub fn push<C, B, S, L, I>(
+        &mut self,
+        game: &Game<Board<C, B>, S, L>,
+        played: &GameMove<I>,
+        out: &mut [u8],
+    ) -> Result<usize, BinaryError>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+        I: HistoryIndex,
+    {
+        if out.len() < MOVE_LEN {
+            return Err(BinaryError::BufferTooSmall);
+        }
+        let board = game.board();
+        let index = board.pack_index(played.record.index.to_index(board)) as u32;
+        self.hash = next_hash(self.hash, index, played.record.player, game.counters());
+        out[..4].copy_from_slice(&index.to_le_bytes());
+        out[4..MOVE_LEN].copy_from_slice(&self.hash.to_le_bytes());
+        Ok(MOVE_LEN)
+    }
+}
+
+/// Errors of [`ReplayWriter::start`], [`load`] and [`verify_replay`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ReplayError {
+    /// The header of the replay is malformed or can't be written
+    Header(BinaryError),
+    /// The game already has moves
+    Started,
+    /// The game has settings, that the header doesn't store
+    Unsupported,
+    /// The last move is incomplete
+    Truncated,
+    /// The move with given number is illegal
+    Move(usize, MoveError),
+    /// The hash of the move with given number doesn't match
+    Checksum(usize),
+}
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ReplayError::Header(error) => write!(f, "header is malformed: {}", error),
+            ReplayError::Started => write!(f, "game already has moves"),
+            ReplayError::Unsupported => write!(f, "game has settings, that replays don't store"),
+            ReplayError::Truncated => write!(f, "replay is truncated"),
+            ReplayError::Move(number, error) => write!(f, "move {} is illegal: {}", number, error),
+            ReplayError::Checksum(number) => write!(f, "move {} has wrong checksum", number),
+        }
+    }
+}
+
+/// Loads the replay into given storages and plays all its moves,
+/// checking the hash after every move.
+/// # Errors
+/// Returns [`ReplayError`] if the replay is malformed, a move is illegal
+/// or doesn't match its hash.
+pub fn load<C, B, S>(
+    bytes: &[u8],
+    cells: C,
+    buffer: B,
+    losers: S,
+) -> Result<Game<Board<C, B>, S>, ReplayError>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+{
+    let start = MAGIC.len() + 2;
+    if bytes.len() < start + HEADER_LEN || bytes[..MAGIC.len()] != MAGIC {
+        return Err(ReplayError::Header(BinaryError::Malformed));
+    }
+    let max_moves = u16::from_le_bytes([bytes[MAGIC.len()], bytes[MAGIC.len() + 1]]) as usize;
+    if max_moves == 0 {
+        return Err(ReplayError::Header(BinaryError::Malformed));
+    }
+    let board = Board::from_bytes(&bytes[start..], cells, buffer).map_err(ReplayError::Header)?;
+    let len = start + board.encoded_len();
+    let players = board.counters().players();
+    let mut game = Game::new(board, PlayerManager::new(max_moves, players, losers));
+    let mut hash = fnv(FNV_OFFSET, &bytes[..len]);
+    let moves = &bytes[len..];
+    if moves.len() % MOVE_LEN != 0 {
+        return Err(ReplayError::Truncated);
+    }
+    for (number, chunk) in moves.chunks(MOVE_LEN).enumerate() {
+        let packed = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        let expected = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+        let board = game.board();
+        if packed as usize >= board.width() * board.height() {
+            return Err(ReplayError::Move(number, MoveError::OutOfBounds));
+        }
+        let index = board.unpack_index(packed as usize);
+        let player = game.current_player();
+        game.play(index)
+            .map_err(|error| ReplayError::Move(number, error))?;
+        hash = next_hash(hash, packed, player, game.counters());
+        if hash != expected {
+            return Err(ReplayError::Checksum(number));
+        }
+    }
+    Ok(game)
+}
+
+/// Plays the moves from the initial position of the game, checking the hash of the game
+/// after every move. Every move is given by index of its cell and the expected
+/// [`Game::consistency_hash`] after it. Returns the hash of the final position.
+/// # Errors
+/// Returns [`ReplayError::Move`] if a move is illegal and [`ReplayError::Checksum`]
+/// if the hash after a move differs, with the number of the first divergent move.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// # use crosses_utils::replay::*;
+/// # use crosses_utils::simulate::*;
+/// let board = Board::new(4, 4, 2, [Cell::new(); 36], [0; 36]);
+/// let initial = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+/// let mut moves = [(0, 0); 32];
+/// let mut len = 0;
+/// for (slot, item) in moves.iter_mut().zip(RandomGame::new(initial.clone(), 1)) {
+///     (*slot, len) = (item, len + 1);
+/// }
+/// let hash = verify_replay(initial.clone(), moves[..len].iter().copied()).unwrap();
+/// assert_eq!(hash, moves[len - 1].1);
+/// assert_eq!(verify_replay(initial.clone(), []), Ok(initial.consistency_hash()));
+/// moves[3].1 ^= 1;
+/// let error = verify_replay(initial.clone(), moves[..len].iter().copied());
+/// assert_eq!(error, Err(ReplayError::Checksum(3)));
+/// moves[1].0 = moves[0].0;
+/// let error = verify_replay(initial, moves[..len].iter().copied());
+/// assert!(matches!(error, Err(ReplayError::Move(1, _))));
+/// ```
+pub fn verify_replay<C, B, S, L>(
+    mut game: Game<Board<C, B>, S, L>,
+    moves: impl IntoIterator<Item = (usize, u64)>,
+) -> Result<u64, ReplayError>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    let mut hash = game.consistency_hash();
+    for (number, (index, expected)) in moves.into_iter().enumerate() {
+        game.checked_play(index)
+            .map_err(|error| ReplayError::Move(number, error))?;
+        hash = game.consistency_hash();
+        if hash != expected {
+            return Err(ReplayError::Checksum(number));
+        }
+    }
+    Ok(hash)
+}
+
+/// Checks that the manager is in the state of [`PlayerManager::new`] with default settings.
+fn is_initial<S>(manager: &PlayerManager<S>) -> bool
+where
+    S: IndexMut<usize, Output = Option<LoseData>>,
+{
+    manager.remaining_moves == manager.max_moves
+        && manager.current_player == 0
+        && manager.current_turn == 0
+        && manager.game_state == GameState::Ongoing
+        && (0..manager.max_players).all(|player| manager.losers[player].is_none())
+        && manager.schedule == MoveSchedule::default()
+        && manager.stalemate == Stalemate::default()
+        && manager.neutral.is_none()
+}
+
+/// Checks that [`Board::from_bytes`] restores the board from its content.
+fn is_default<C, B>(board: &Board<C, B>) -> bool
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let players = board.counters().players();
+    if board.rules() != RuleSet::STANDARD
+        || board.adjacency() != Adjacency::MOORE
+        || board.placement_adjacency() != Adjacency::MOORE
+        || (0..players).any(|player| board.start(player) != board.default_start(player))
+    {
+        return false;
+    }
+    (0..board.height()).all(|y| {
+        (0..board.width()).all(|x| {
+            let index = board.index(x, y);
+            let cell = board.get(index);
+            !board.is_protected(index)
+                && !board.is_stronghold(index)
+                && cell.damage() == 0
+                && (cell.kind() != CellKind::Filled
+                    || cell.previous_player() == (cell.player() + 1) % players)
+        })
+    })
+}
+
+const FNV_OFFSET: u32 = 0x811c_9dc5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+fn fnv(hash: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(FNV_PRIME)
+    })
+}
+fn next_hash(hash: u32, index: u32, player: usize, counters: &impl PlayerCounters) -> u32 {
+    let crosses = counters.get(Counter::Crosses, player) as u32;
+    let moves = counters.get(Counter::Moves, player) as u32;
+    let hash = fnv(hash, &index.to_le_bytes());
+    let hash = fnv(hash, &[player as u8]);
+    let hash = fnv(hash, &crosses.to_le_bytes());
+    fnv(hash, &moves.to_le_bytes())
+}
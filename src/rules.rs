@@ -0,0 +1,171 @@
+//! Rules
+//!
+//! This module defines [`RuleSet`], options of the rules of moves. The standard rules
+//! are [`RuleSet::STANDARD`]: a cross is placed on an empty cell and a cross of other
+//! player is filled, both only in the zone of reach of the player. House rules change
+//! some of it, every option is a field of [`RuleSet`].
+//!
+//! Rules belong to the [`Engine`]: [`make_move`] asks [`Engine::rules`] and zones of reach
+//! and counters of moves are kept up to date according to them, so rules of a position
+//! can't change between moves. [`Board`] stores its rules, see [`Board::set_rules`].
+//! Some rules change cells after the move itself, then moves are cancelled with changes
+//! stored in a [`ChangeLog`], see [`RuleSet::needs_log`]. Such games are created with
+//! [`Game::with_log`], helpers of the crate, e.g. bots and searches, accept games with
+//! any log.
+//!
+//! [`Engine`]: crate::engine::Engine
+//! [`Engine::rules`]: crate::engine::Engine::rules
+//! [`make_move`]: crate::engine::make_move
+//! [`Board`]: crate::board::Board
+//! [`Board::set_rules`]: crate::board::Board::set_rules
+//! [`ChangeLog`]: crate::engine::ChangeLog
+//! [`Game::with_log`]: crate::game::Game::with_log
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::counters::*;
+//! # use crosses_utils::engine::*;
+//! # use crosses_utils::rules::*;
+//! let mut board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+//! let (corner, far) = (board.index(0, 0), board.index(2, 2));
+//! make_move(&mut board, corner, 0).unwrap();
+//! make_move(&mut board, far, 1).unwrap();
+//! assert_eq!(make_move(&mut board, far, 0), Err(MoveError::Unreachable));
+//! board.set_rules(RuleSet { fill_requires_reach: false, ..RuleSet::STANDARD });
+//! assert_eq!(board.counters().get(Counter::Moves, 0), 4);
+//! assert!(make_move(&mut board, far, 0).is_ok());
+//! ```
+//! Bots play games with rules, that need a log:
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::bots::*;
+//! # use crosses_utils::engine::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::rules::*;
+//! # use crosses_utils::search::*;
+//! let mut board = Board::new(4, 4, 2, [Cell::new(); 36], [0; 36]);
+//! board.set_rules(RuleSet { convert_chains: true, ..RuleSet::STANDARD });
+//! let log = ChangeStack::new([Change::default(); 64]);
+//! let mut game = Game::with_log(board, PlayerManager::new(2, 2, [None; 2]), log);
+//! let index = GreedyBot::default().choose_move(&mut game).unwrap();
+//! game.play(index).unwrap();
+//! assert!(Searcher::new(Mobility).search(&mut game, 3).best().is_some());
+//! ```
+use crate::base::CellKind;
+use crate::engine::{Data, MoveError};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Options of the rules, see module docs.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::counters::*;
+/// # use crosses_utils::engine::*;
+/// # use crosses_utils::ibts::*;
+/// # use crosses_utils::rules::*;
+/// let mut board = Board::new(3, 1, 2, [Cell::new(); 15], [0; 15]);
+/// let (a, b) = (board.index(0, 0), board.index(1, 0));
+/// make_move(&mut board, a, 0).unwrap();
+/// make_move(&mut board, b, 0).unwrap();
+/// assert_eq!(make_move(&mut board, a, 0), Err(MoveError::SelfFill));
+/// board.set_rules(RuleSet { self_fill: true, ..RuleSet::STANDARD });
+/// assert_eq!(board.counters().get(Counter::Moves, 0), 3);
+/// let record = make_move(&mut board, a, 0).unwrap();
+/// assert!(board.is_alive(a));
+/// assert_eq!(record.counters_delta, CountersDelta { crosses: -1, moves: -1 });
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct RuleSet {
+    /// Crosses of other players can be filled only in the zone of reach. Otherwise
+    /// any cross of other player can be filled, and it's counted as a move.
+    pub fill_requires_reach: bool,
+    /// Players can fill their own crosses, sacrificing them, like crosses of other players.
+    /// Otherwise it's [`MoveError::SelfFill`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub self_fill: bool,
+    /// Chains of filled cells of other player, killed by a fill, are converted to the player,
+    /// who made it. They are alive, if they are connected to alive chains or crosses
+    /// of the player. Otherwise chains stay dead with their owner.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub convert_chains: bool,
+    /// Dead chains of filled cells are removed at the end of the turn, their cells become
+    /// empty, see [`remove_dead_chains`]. Otherwise dead chains stay on the board.
+    ///
+    /// [`remove_dead_chains`]: crate::engine::remove_dead_chains
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub remove_dead_chains: bool,
+    /// All crosses are strongholds: the first attack on a cross only damages it and
+    /// the second one fills it, see [`Data::damage`]. Otherwise only cells marked
+    /// as strongholds are, see [`Data::is_stronghold`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub strong_crosses: bool,
+}
+impl RuleSet {
+    /// The standard rules.
+    pub const STANDARD: RuleSet = RuleSet {
+        fill_requires_reach: true,
+        self_fill: false,
+        convert_chains: false,
+        remove_dead_chains: false,
+        strong_crosses: false,
+    };
+
+    /// Checks if the rules change cells after moves, so moves should be made with
+    /// [`make_move_logged`] to be cancelled, see [`ChangeLog`].
+    ///
+    /// [`make_move_logged`]: crate::engine::make_move_logged
+    /// [`ChangeLog`]: crate::engine::ChangeLog
+    pub fn needs_log(&self) -> bool {
+        self.convert_chains || self.remove_dead_chains
+    }
+
+    /// Checks if the move at the cell with a cross damages it instead of filling,
+    /// see [`RuleSet::strong_crosses`].
+    pub fn damages<D: Data>(&self, data: D) -> bool {
+        data.kind() == CellKind::Cross
+            && (self.strong_crosses || data.is_stronghold())
+            && data.damage() == 0
+    }
+
+    /// Checks whether the `player` can make a move at the cell.
+    pub fn check_move<D: Data>(&self, data: D, player: usize) -> Result<(), MoveError> {
+        match data.kind() {
+            CellKind::Border => Err(MoveError::OutOfBounds),
+            CellKind::Filled => Err(MoveError::Occupied),
+            CellKind::Cross if data.player() == player && !self.self_fill => {
+                Err(MoveError::SelfFill)
+            }
+            CellKind::Cross if data.is_protected() => Err(MoveError::ProtectedCell),
+            CellKind::Cross if !self.fill_requires_reach => Ok(()),
+            _ if !data.is_active(player) => Err(MoveError::Unreachable),
+            _ => Ok(()),
+        }
+    }
+    /// Checks if the cell is a move of the player only in their zone of reach,
+    /// so activations of the cell change the counter of moves.
+    pub(crate) fn needs_reach<D: Data>(&self, data: D, player: usize) -> bool {
+        match data.kind() {
+            CellKind::Empty => true,
+            CellKind::Cross => {
+                (data.player() != player || self.self_fill)
+                    && !data.is_protected()
+                    && self.fill_requires_reach
+            }
+            _ => false,
+        }
+    }
+    /// Checks if the player can fill the cross of `owner` with given activations.
+    pub(crate) fn can_fill<D: Data>(&self, data: D, owner: usize, player: usize) -> bool {
+        (player != owner || self.self_fill)
+            && !data.is_protected()
+            && (data.is_active(player) || !self.fill_requires_reach)
+    }
+}
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
@@ -0,0 +1,1003 @@
+//! Search
+//!
+//! This module defines [`Searcher`], depth-limited minimax search with alpha-beta pruning
+//! over [`Game`]. Moves are made with [`Game::play`] and cancelled with [`Game::undo`],
+//! so the game doesn't have to be cloned. Every move is a ply, even if the player
+//! makes several moves in a turn. With more than two players search is "paranoid":
+//! the searching player maximizes the score and all other players minimize it.
+//!
+//! Positions at the depth limit are scored by [`Evaluator`], scores are from the point
+//! of view of the searching player. Won and lost games get [`WIN_SCORE`] minus the number
+//! of plies to the end, so faster wins are preferred.
+//!
+//! Moves are ordered to prune more: the best move from [`TranspositionTable`] goes first,
+//! then captures (fills of crosses of other players), then killer moves, that caused
+//! a cutoff at the same ply, and then other moves by history score: how often and how
+//! deep they caused cutoffs before.
+//!
+//! Searches return [`SearchReport`] with the best line, its score, the reached depth,
+//! number of visited positions and usage of the transposition table.
+//!
+//! Repetitions of positions aren't detected. They are possible only with
+//! [`RuleSet::remove_dead_chains`], which frees cells to be played again, there searches
+//! still end at the depth limit, but scores of repeated positions come from the table
+//! or the evaluator, not from the repetition itself.
+//!
+//! [`RuleSet::remove_dead_chains`]: crate::rules::RuleSet::remove_dead_chains
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::search::*;
+//! let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+//! let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+//! let initial = game.clone();
+//! let mut searcher = Searcher::new(Mobility);
+//! let outcome = searcher.search(&mut game, 3);
+//! assert_eq!(game, initial);
+//! assert_eq!(outcome.best(), Some(game.board().index(0, 0)));
+//! assert_eq!(outcome.pv().len(), 3);
+//! ```
+use core::cmp::Reverse;
+use core::ops::IndexMut;
+
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+
+use crate::base::CellKind;
+use crate::board::{Board, CellStorage, MAX_PLAYERS};
+use crate::counters::{Counter, PlayerCounters};
+use crate::engine::{ChangeLog, Data, Engine};
+use crate::game::Game;
+use crate::ibts::IbtsBoard;
+use crate::player_manager::{GameOver, GameState, LoseData};
+#[cfg(feature = "std")]
+use crate::transposition::SharedEntries;
+use crate::transposition::{Bound, Entry, EntryStorage, TranspositionTable};
+use crate::zobrist::Zobrist;
+
+/// Maximum depth of the search.
+pub const MAX_DEPTH: usize = 64;
+/// Score of the won game.
+pub const WIN_SCORE: i32 = 1_000_000;
+/// Number of positions between calls of the callback of [`Searcher::search_until`].
+const STOP_INTERVAL: u64 = 256;
+/// Number of entries of the history table, packed indices are taken modulo it.
+const HISTORY_LEN: usize = 1024;
+/// Maximum number of moves sorted at every position, the rest are searched in board order.
+const MAX_ORDERED: usize = 256;
+/// Maximum history score, higher bits of priority of the move hold its class.
+const HISTORY_MAX: u32 = (1 << 30) - 1;
+
+/// Evaluation of positions for the search. It's implemented for closures
+/// with the same signature as [`Evaluator::eval`].
+pub trait Evaluator<G> {
+    /// Returns the score of the position for the player, higher is better.
+    fn eval(&self, game: &G, player: usize) -> i32;
+}
+impl<G, F: Fn(&G, usize) -> i32> Evaluator<G> for F {
+    fn eval(&self, game: &G, player: usize) -> i32 {
+        self(game, player)
+    }
+}
+
+/// Simple evaluator: mobility of the player minus mobility of all other players.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Mobility;
+impl<C, B, S, L> Evaluator<Game<Board<C, B>, S, L>> for Mobility
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    fn eval(&self, game: &Game<Board<C, B>, S, L>, player: usize) -> i32 {
+        (0..game.player_manager().max_players)
+            .map(|other| {
+                let mobility = game.mobility(other) as i32;
+                if other == player {
+                    mobility
+                } else {
+                    -mobility
+                }
+            })
+            .sum()
+    }
+}
+
+/// Weights of [`DefaultEvaluator`]. Every feature is computed for all players,
+/// the score is the feature of the player minus features of other players.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct EvalWeights {
+    /// Weight of every cross of the player
+    pub crosses: i32,
+    /// Weight of every cell, where the player can make a move
+    pub mobility: i32,
+    /// Weight of every filled cell of the player in alive chains.
+    /// Alive chains keep the player's zone of reach, dead ones don't.
+    pub live_chains: i32,
+    /// Weight of every empty cell, that only the player can reach:
+    /// the frontier, that other players can't contest yet
+    pub frontier: i32,
+}
+impl Default for EvalWeights {
+    fn default() -> Self {
+        Self {
+            crosses: 2,
+            mobility: 3,
+            live_chains: 1,
+            frontier: 2,
+        }
+    }
+}
+
+/// Evaluator combining crosses, mobility, alive chains and frontier
+/// of players, see [`EvalWeights`].
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// # use crosses_utils::search::*;
+/// let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+/// let mut game = Game::new(board, PlayerManager::new(3, 2, [None; 2]));
+/// let evaluator = DefaultEvaluator::default();
+/// assert_eq!(evaluator.eval(&game, 0), 0);
+/// game.play(game.board().index(0, 0)).unwrap();
+/// assert!(evaluator.eval(&game, 0) > 0);
+/// assert_eq!(evaluator.eval(&game, 1), -evaluator.eval(&game, 0));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DefaultEvaluator {
+    weights: EvalWeights,
+}
+impl DefaultEvaluator {
+    /// Creates new evaluator with given weights.
+    pub fn new(weights: EvalWeights) -> Self {
+        Self { weights }
+    }
+    /// Returns weights of the evaluator.
+    pub fn weights(&self) -> EvalWeights {
+        self.weights
+    }
+}
+impl<C, B, S, L> Evaluator<Game<Board<C, B>, S, L>> for DefaultEvaluator
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    fn eval(&self, game: &Game<Board<C, B>, S, L>, player: usize) -> i32 {
+        let players = game.player_manager().max_players;
+        let board = game.board();
+        let mut features = [0; MAX_PLAYERS];
+        for packed in 0..board.width() * board.height() {
+            let index = board.unpack_index(packed);
+            let cell = board.get(index);
+            match cell.kind() {
+                CellKind::Filled if board.is_alive(index) => {
+                    features[cell.player()] += self.weights.live_chains
+                }
+                CellKind::Empty => {
+                    let mut reaching = (0..players).filter(|other| cell.is_active(*other));
+                    if let (Some(only), None) = (reaching.next(), reaching.next()) {
+                        features[only] += self.weights.frontier;
+                    }
+                }
+                _ => {}
+            }
+        }
+        (0..players)
+            .map(|other| {
+                let counters = game.counters();
+                let feature = features[other]
+                    + self.weights.crosses * counters.get(Counter::Crosses, other) as i32
+                    + self.weights.mobility * counters.get(Counter::Moves, other) as i32;
+                if other == player {
+                    feature
+                } else {
+                    -feature
+                }
+            })
+            .sum()
+    }
+}
+
+/// Sequence of moves, e.g. the principal variation.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Line {
+    moves: [usize; MAX_DEPTH],
+    len: usize,
+}
+impl Line {
+    /// Creates empty line.
+    pub const fn new() -> Self {
+        Self {
+            moves: [0; MAX_DEPTH],
+            len: 0,
+        }
+    }
+    /// Returns indices of the cells of the moves.
+    pub fn moves(&self) -> &[usize] {
+        &self.moves[..self.len]
+    }
+    /// Returns number of moves.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Checks whether the line has no moves.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Returns the first move.
+    pub fn first(&self) -> Option<usize> {
+        self.moves().first().copied()
+    }
+    /// Appends the move, if the line is shorter than [`MAX_DEPTH`].
+    pub(crate) fn push(&mut self, index: usize) {
+        if self.len < MAX_DEPTH {
+            self.moves[self.len] = index;
+            self.len += 1;
+        }
+    }
+    /// Removes the last move.
+    pub(crate) fn pop(&mut self) {
+        self.len = self.len.saturating_sub(1);
+    }
+    fn set(&mut self, first: usize, rest: &Line) {
+        let len = (rest.len + 1).min(MAX_DEPTH);
+        self.moves[0] = first;
+        self.moves[1..len].copy_from_slice(&rest.moves[..len - 1]);
+        self.len = len;
+    }
+}
+impl Default for Line {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Result of [`Searcher::search`], [`Searcher::search_until`] and other searches.
+///
+/// The score is from the point of view of the searching player in units of the evaluator,
+/// e.g. one cell of mobility for [`Mobility`]. Scores near [`WIN_SCORE`] mean forced end
+/// of the game, see [`SearchReport::win_in`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SearchReport {
+    score: i32,
+    pv: Line,
+    depth: usize,
+    nodes: u64,
+    hits: u64,
+    probes: u64,
+}
+impl SearchReport {
+    /// Returns index of the cell with the best move or `None` if there are no moves.
+    pub fn best(&self) -> Option<usize> {
+        self.pv.first()
+    }
+    /// Returns the score of the best move.
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+    /// Returns the principal variation: the best move and expected replies.
+    pub fn pv(&self) -> &Line {
+        &self.pv
+    }
+    /// Returns depth of the search.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+    /// Returns number of visited positions.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+    /// Returns number of probes of the transposition table, that found the position.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+    /// Returns number of probes of the transposition table.
+    pub fn probes(&self) -> u64 {
+        self.probes
+    }
+    /// Returns the share of probes of the transposition table, that found the position,
+    /// or zero if there were no probes.
+    pub fn hit_rate(&self) -> f64 {
+        if self.probes == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.probes as f64
+        }
+    }
+    /// Returns number of plies to the forced end of the game: positive, if the searching
+    /// player wins, and negative, if they lose. Returns `None` if the score is an evaluation.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::search::*;
+    /// let board = Board::new(2, 1, 2, [Cell::new(); 12], [0; 12]);
+    /// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+    /// let report = Searcher::new(Mobility).search(&mut game, 3);
+    /// assert_eq!(report.win_in(), Some(3));
+    /// assert_eq!(report.score(), WIN_SCORE - 3);
+    /// ```
+    pub fn win_in(&self) -> Option<i32> {
+        let limit = WIN_SCORE - MAX_DEPTH as i32;
+        match self.score {
+            score if score >= limit => Some(WIN_SCORE - score),
+            score if score <= -limit => Some(-WIN_SCORE - score),
+            _ => None,
+        }
+    }
+}
+
+/// Result of [`Searcher::ponder`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Ponder {
+    predicted: usize,
+    outcome: SearchReport,
+}
+impl Ponder {
+    /// Returns index of the cell of the predicted move.
+    pub fn predicted(&self) -> usize {
+        self.predicted
+    }
+    /// Returns the report of the search of the position after the predicted move.
+    pub fn report(&self) -> &SearchReport {
+        &self.outcome
+    }
+    /// Returns the result of the search, if the real move is the predicted one
+    /// (ponder hit), so it can be adopted or continued with [`Searcher::resume`].
+    pub fn hit(self, actual: usize) -> Option<SearchReport> {
+        (actual == self.predicted).then(|| self.outcome)
+    }
+}
+
+/// Alpha-beta search with given evaluator, see module docs.
+/// `T` - is type of storage of [`TranspositionTable`], by default the table is empty.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Searcher<E, T = [Entry; 0]> {
+    evaluator: E,
+    table: TranspositionTable<T>,
+    zobrist: Zobrist,
+    hash: u64,
+    root: usize,
+    depth: usize,
+    nodes: u64,
+    hits: u64,
+    probes: u64,
+    aborted: bool,
+    ordering: MoveOrdering,
+    split: (usize, usize),
+}
+impl<E> Searcher<E> {
+    /// Creates new searcher without transposition table.
+    pub fn new(evaluator: E) -> Self {
+        Self::with_table(evaluator, TranspositionTable::new([]))
+    }
+}
+impl<E, T: EntryStorage> Searcher<E, T> {
+    /// Creates new searcher with transposition table. Positions are hashed
+    /// with [`Zobrist::default`] keys, the current player and remaining moves.
+    pub fn with_table(evaluator: E, table: TranspositionTable<T>) -> Self {
+        Self {
+            evaluator,
+            table,
+            zobrist: Zobrist::default(),
+            hash: 0,
+            root: 0,
+            depth: 0,
+            nodes: 0,
+            hits: 0,
+            probes: 0,
+            aborted: false,
+            ordering: MoveOrdering::default(),
+            split: (0, 1),
+        }
+    }
+    /// Returns the evaluator.
+    pub fn evaluator(&self) -> &E {
+        &self.evaluator
+    }
+    /// Returns the transposition table.
+    pub fn table(&self) -> &TranspositionTable<T> {
+        &self.table
+    }
+    /// Returns the transposition table, e.g. to clear it.
+    pub fn table_mut(&mut self) -> &mut TranspositionTable<T> {
+        &mut self.table
+    }
+    /// Unwraps the evaluator.
+    pub fn into_inner(self) -> E {
+        self.evaluator
+    }
+
+    /// Searches the best move of the current player with given depth in plies.
+    /// The game is returned to its initial state.
+    /// # Panics
+    /// Panics if `depth` is greater than [`MAX_DEPTH`].
+    pub fn search<C, B, S, L>(
+        &mut self,
+        game: &mut Game<Board<C, B>, S, L>,
+        depth: usize,
+    ) -> SearchReport
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+        E: Evaluator<Game<Board<C, B>, S, L>>,
+    {
+        assert!(depth <= MAX_DEPTH, "depth is too large");
+        self.reset_stats();
+        self.ordering = MoveOrdering::default();
+        self.run(game, depth, &mut || false)
+    }
+    /// Searches the best move of the current player with iterative deepening: depth
+    /// is increased by one until `max_depth` is reached or `time_up` returns `true`.
+    /// `time_up` is called every few hundred positions, so it can read a clock
+    /// or check a flag. Returns the result of the last completed depth, depth `1`
+    /// is always completed. The game is returned to its initial state.
+    /// # Panics
+    /// Panics if `max_depth` is zero or greater than [`MAX_DEPTH`].
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::search::*;
+    /// let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+    /// let mut game = Game::new(board, PlayerManager::new(3, 2, [None; 2]));
+    /// let mut searcher = Searcher::new(Mobility);
+    /// let mut budget = 5000;
+    /// let outcome = searcher.search_until(&mut game, MAX_DEPTH, || {
+    ///     budget -= 1;
+    ///     budget == 0
+    /// });
+    /// assert!(outcome.depth() >= 1 && outcome.depth() < MAX_DEPTH);
+    /// assert_eq!(outcome.best(), Some(game.board().index(0, 0)));
+    /// ```
+    pub fn search_until<C, B, S, L>(
+        &mut self,
+        game: &mut Game<Board<C, B>, S, L>,
+        max_depth: usize,
+        time_up: impl FnMut() -> bool,
+    ) -> SearchReport
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+        E: Evaluator<Game<Board<C, B>, S, L>>,
+    {
+        assert!(
+            max_depth != 0 && max_depth <= MAX_DEPTH,
+            "depth is out of range"
+        );
+        self.reset_stats();
+        self.ordering = MoveOrdering::default();
+        let outcome = self.run(game, 1, &mut || false);
+        self.deepen(game, outcome, max_depth, time_up)
+    }
+    /// Continues iterative deepening of [`Searcher::search_until`] from the depth after
+    /// the depth of `outcome`, which should be a result of the search of the same position,
+    /// e.g. adopted with [`Ponder::hit`]. Returns `outcome` with the nodes of this search
+    /// added, if no depth is completed.
+    /// # Panics
+    /// Panics if `max_depth` is greater than [`MAX_DEPTH`].
+    pub fn resume<C, B, S, L>(
+        &mut self,
+        game: &mut Game<Board<C, B>, S, L>,
+        outcome: SearchReport,
+        max_depth: usize,
+        time_up: impl FnMut() -> bool,
+    ) -> SearchReport
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+        E: Evaluator<Game<Board<C, B>, S, L>>,
+    {
+        assert!(max_depth <= MAX_DEPTH, "depth is too large");
+        (self.nodes, self.hits, self.probes) = (outcome.nodes, outcome.hits, outcome.probes);
+        self.deepen(game, outcome, max_depth, time_up)
+    }
+    /// Searches the position after the predicted move while other player thinks. The move
+    /// at index `predicted` is made, the position is searched like with
+    /// [`Searcher::search_until`] and the move is cancelled. `stop` should return `true`
+    /// once the real move is known, then the search is aborted at the next check.
+    /// Returns `None` if the predicted move is illegal.
+    /// # Panics
+    /// Panics if `max_depth` is zero or greater than [`MAX_DEPTH`].
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::search::*;
+    /// let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+    /// let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+    /// let mut searcher = Searcher::new(Mobility);
+    /// let outcome = searcher.search(&mut game, 4);
+    /// game.play(outcome.best().unwrap()).unwrap();
+    /// let predicted = outcome.pv().moves()[1];
+    /// let mut budget = 300;
+    /// let ponder = searcher.ponder(&mut game, predicted, MAX_DEPTH, || {
+    ///     budget -= 1;
+    ///     budget == 0
+    /// });
+    /// let ponder = ponder.unwrap();
+    /// game.play(predicted).unwrap();
+    /// let adopted = ponder.hit(predicted).unwrap();
+    /// let outcome = searcher.resume(&mut game, adopted, adopted.depth() + 1, || false);
+    /// assert_eq!(outcome.depth(), adopted.depth() + 1);
+    /// ```
+    pub fn ponder<C, B, S, L>(
+        &mut self,
+        game: &mut Game<Board<C, B>, S, L>,
+        predicted: usize,
+        max_depth: usize,
+        stop: impl FnMut() -> bool,
+    ) -> Option<Ponder>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+        E: Evaluator<Game<Board<C, B>, S, L>>,
+    {
+        assert!(
+            max_depth != 0 && max_depth <= MAX_DEPTH,
+            "depth is out of range"
+        );
+        let record = game.play(predicted).ok()?;
+        let outcome = self.search_until(game, max_depth, stop);
+        game.undo(record).expect("the move was just made");
+        Some(Ponder { predicted, outcome })
+    }
+
+    /// Scores every legal move of the current player with the search of given depth,
+    /// which includes the move, and calls `f` with the position after the move,
+    /// index of the cell and the score, row by row. Unlike [`Searcher::search`],
+    /// scores of all moves are exact, not only of the best one, so it's slower.
+    /// The game is returned to its initial state.
+    /// # Panics
+    /// Panics if `depth` is zero or greater than [`MAX_DEPTH`].
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::search::*;
+    /// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// let mut game = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+    /// game.play(game.board().index(0, 0)).unwrap();
+    /// let mut scores = [0; 3];
+    /// let mut len = 0;
+    /// Searcher::new(Mobility).score_moves(&mut game, 1, |_, _, score| {
+    ///     scores[len] = score;
+    ///     len += 1;
+    /// });
+    /// assert_eq!(scores, [3, 3, 6]);
+    /// ```
+    pub fn score_moves<C, B, S, L>(
+        &mut self,
+        game: &mut Game<Board<C, B>, S, L>,
+        depth: usize,
+        mut f: impl FnMut(&Game<Board<C, B>, S, L>, usize, i32),
+    ) where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+        E: Evaluator<Game<Board<C, B>, S, L>>,
+    {
+        self.score_lines(game, depth, |game, index, score, _| f(game, index, score));
+    }
+    /// Like [`Searcher::score_moves`], but `f` also gets the principal variation after
+    /// the move and may change the game, if it returns it to the same state.
+    pub(crate) fn score_lines<C, B, S, L>(
+        &mut self,
+        game: &mut Game<Board<C, B>, S, L>,
+        depth: usize,
+        mut f: impl FnMut(&mut Game<Board<C, B>, S, L>, usize, i32, &Line),
+    ) where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+        E: Evaluator<Game<Board<C, B>, S, L>>,
+    {
+        assert!(depth != 0 && depth <= MAX_DEPTH, "depth is out of range");
+        if game.state() != GameState::Ongoing {
+            return;
+        }
+        self.reset_stats();
+        self.root = game.current_player();
+        self.depth = depth;
+        self.aborted = false;
+        let board = game.board();
+        let cells = board.width() * board.height();
+        self.hash = self
+            .zobrist
+            .hash(board, (0..cells).map(|packed| board.unpack_index(packed)));
+        let mut line = Line::new();
+        for packed in 0..cells {
+            let index = game.board().unpack_index(packed);
+            let old = game.board().get(index);
+            let record = match game.play(index) {
+                Ok(record) => record,
+                Err(_) => continue,
+            };
+            let (hash, board) = (self.hash, game.board());
+            let indices = (0..cells).map(|packed| board.unpack_index(packed));
+            self.hash = self.zobrist.after_move(hash, board, index, &old, indices);
+            let score =
+                self.alpha_beta(game, depth - 1, i32::MIN, i32::MAX, &mut line, &mut || {
+                    false
+                });
+            f(game, index, score, &line);
+            game.undo(record).expect("the move was just made");
+            self.hash = hash;
+        }
+    }
+
+    fn deepen<C, B, S, L>(
+        &mut self,
+        game: &mut Game<Board<C, B>, S, L>,
+        mut outcome: SearchReport,
+        max_depth: usize,
+        mut time_up: impl FnMut() -> bool,
+    ) -> SearchReport
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+        E: Evaluator<Game<Board<C, B>, S, L>>,
+    {
+        for depth in outcome.depth + 1..=max_depth {
+            if time_up() {
+                break;
+            }
+            let next = self.run(game, depth, &mut time_up);
+            if self.aborted {
+                break;
+            }
+            outcome = next;
+        }
+        (outcome.nodes, outcome.hits, outcome.probes) = (self.nodes, self.hits, self.probes);
+        outcome
+    }
+
+    fn run<C, B, S, L, F>(
+        &mut self,
+        game: &mut Game<Board<C, B>, S, L>,
+        depth: usize,
+        stop: &mut F,
+    ) -> SearchReport
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+        E: Evaluator<Game<Board<C, B>, S, L>>,
+        F: FnMut() -> bool,
+    {
+        self.root = game.current_player();
+        self.depth = depth;
+        self.aborted = false;
+        let board = game.board();
+        let indices = (0..board.width() * board.height()).map(|packed| board.unpack_index(packed));
+        self.hash = self.zobrist.hash(board, indices);
+        let mut pv = Line::new();
+        let score = self.alpha_beta(game, depth, i32::MIN, i32::MAX, &mut pv, stop);
+        SearchReport {
+            score,
+            pv,
+            depth,
+            nodes: self.nodes,
+            hits: self.hits,
+            probes: self.probes,
+        }
+    }
+    fn reset_stats(&mut self) {
+        self.nodes = 0;
+        self.hits = 0;
+        self.probes = 0;
+    }
+
+    fn alpha_beta<C, B, S, L, F>(
+        &mut self,
+        game: &mut Game<Board<C, B>, S, L>,
+        depth: usize,
+        mut alpha: i32,
+        mut beta: i32,
+        pv: &mut Line,
+        stop: &mut F,
+    ) -> i32
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+        E: Evaluator<Game<Board<C, B>, S, L>>,
+        F: FnMut() -> bool,
+    {
+        self.nodes += 1;
+        pv.len = 0;
+        if self.nodes % STOP_INTERVAL == 0 && stop() {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return 0;
+        }
+        let (root, ply) = (self.root, self.depth - depth);
+        match game.state() {
+            GameState::Ended(GameOver::Win(winner)) if winner == root => {
+                return WIN_SCORE - ply as i32
+            }
+            GameState::Ended(GameOver::Win(_)) => return ply as i32 - WIN_SCORE,
+            GameState::Ended(GameOver::Draw) => return 0,
+            GameState::Ongoing if depth == 0 => return self.evaluator.eval(game, root),
+            GameState::Ongoing => {}
+        }
+        let key = self.key(game);
+        let window = (alpha, beta);
+        let mut table_move = None;
+        let entry = self.table.probe(key);
+        if !self.table.is_empty() {
+            self.probes += 1;
+            self.hits += entry.is_some() as u64;
+        }
+        if let Some(entry) = entry {
+            table_move = entry.best();
+            if ply > 0 && entry.depth() >= depth {
+                let score = from_table(entry.score(), ply);
+                let cutoff = match entry.bound() {
+                    Bound::Exact => true,
+                    Bound::Lower => score >= beta,
+                    Bound::Upper => score <= alpha,
+                };
+                if cutoff {
+                    if let Some(packed) = table_move {
+                        pv.set(game.board().unpack_index(packed), &Line::new());
+                    }
+                    return score;
+                }
+            }
+        }
+        let maximizing = game.current_player() == root;
+        let mut best = None;
+        let mut best_move = None;
+        let mut line = Line::new();
+        let cells = game.board().width() * game.board().height();
+        let current = game.current_player();
+        let mut ordered = [(0, 0); MAX_ORDERED];
+        let (mut len, mut rest) = (0, cells);
+        for packed in 0..cells {
+            let cell = game.board().get(game.board().unpack_index(packed));
+            if game.board().rules().check_move(cell, current).is_err() {
+                continue;
+            }
+            if len == MAX_ORDERED {
+                rest = packed;
+                break;
+            }
+            let class = if Some(packed) == table_move {
+                3
+            } else if cell.kind() == CellKind::Cross {
+                2
+            } else if self.ordering.is_killer(ply, packed) {
+                1
+            } else {
+                0
+            };
+            let history = self.ordering.history[packed % HISTORY_LEN];
+            ordered[len] = (class << 30 | history.min(HISTORY_MAX), packed as u32);
+            len += 1;
+        }
+        let ordered = &mut ordered[..len];
+        ordered.sort_unstable_by_key(|(priority, packed)| (Reverse(*priority), *packed));
+        let moves = ordered
+            .iter()
+            .map(|(_, packed)| *packed as usize)
+            .chain(rest..cells);
+        for packed in moves {
+            if ply == 0 && packed % self.split.1 != self.split.0 {
+                continue;
+            }
+            let index = game.board().unpack_index(packed);
+            let old = game.board().get(index);
+            let record = match game.play(index) {
+                Ok(record) => record,
+                Err(_) => continue,
+            };
+            let (hash, board) = (self.hash, game.board());
+            let indices = (0..cells).map(|packed| board.unpack_index(packed));
+            self.hash = self.zobrist.after_move(hash, board, index, &old, indices);
+            let score = self.alpha_beta(game, depth - 1, alpha, beta, &mut line, stop);
+            game.undo(record).expect("the move was just made");
+            self.hash = hash;
+            if self.aborted {
+                return 0;
+            }
+            let better = match best {
+                None => true,
+                Some(best) if maximizing => score > best,
+                Some(best) => score < best,
+            };
+            if better {
+                best = Some(score);
+                best_move = Some(packed);
+                pv.set(index, &line);
+            }
+            if maximizing {
+                alpha = alpha.max(score);
+            } else {
+                beta = beta.min(score);
+            }
+            if alpha >= beta {
+                if old.kind() != CellKind::Cross {
+                    self.ordering.cutoff(ply, packed, depth);
+                }
+                break;
+            }
+        }
+        let best = match best {
+            Some(best) => best,
+            None => return self.evaluator.eval(game, root),
+        };
+        let bound = if best <= window.0 {
+            Bound::Upper
+        } else if best >= window.1 {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        // Results of the root with a part of moves are incomplete.
+        if ply > 0 || self.split.1 == 1 {
+            self.table
+                .store(key, depth, to_table(best, ply), bound, best_move);
+        }
+        best
+    }
+    fn key<C, B, S, L>(&self, game: &Game<Board<C, B>, S, L>) -> u64
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        let manager = game.player_manager();
+        let state = (self.root * MAX_PLAYERS + manager.current_player) * (manager.max_moves + 1)
+            + manager.remaining_moves;
+        self.hash ^ self.zobrist.player_key(state)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> Searcher<E, SharedEntries> {
+    /// Like [`Searcher::search`], but moves of the root are split between `threads` threads.
+    /// Every thread searches its moves with clones of the searcher and the game, and all
+    /// of them share entries of the transposition table. Numbers of nodes and hits of the
+    /// table are summed. Requires `std` feature.
+    /// # Panics
+    /// Panics if `threads` is zero or `depth` is greater than [`MAX_DEPTH`].
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::search::*;
+    /// # use crosses_utils::transposition::*;
+    /// let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+    /// let mut game = Game::new(board, PlayerManager::new(3, 2, [None; 2]));
+    /// game.play(game.board().index(0, 0)).unwrap();
+    /// let table = TranspositionTable::new(SharedEntries::new(4096));
+    /// let mut searcher = Searcher::with_table(Mobility, table);
+    /// let outcome = searcher.search_parallel(&game, 4, 4);
+    /// assert_eq!(outcome.score(), Searcher::new(Mobility).search(&mut game, 4).score());
+    /// ```
+    pub fn search_parallel<C, B, S, L>(
+        &mut self,
+        game: &Game<Board<C, B>, S, L>,
+        depth: usize,
+        threads: usize,
+    ) -> SearchReport
+    where
+        C: CellStorage + Clone + Send + 'static,
+        B: AsMut<[usize]> + Clone + Send + 'static,
+        S: IndexMut<usize, Output = Option<LoseData>> + Clone + Send + 'static,
+        L: ChangeLog<usize> + Clone + Send + 'static,
+        E: Evaluator<Game<Board<C, B>, S, L>> + Clone + Send + 'static,
+    {
+        assert_ne!(threads, 0, "there are no threads");
+        assert!(depth <= MAX_DEPTH, "depth is too large");
+        let handles: Vec<_> = (0..threads)
+            .map(|thread| {
+                let mut searcher = self.clone();
+                searcher.split = (thread, threads);
+                searcher.table.reset_stats();
+                let mut game = game.clone();
+                std::thread::spawn(move || {
+                    let outcome = searcher.search(&mut game, depth);
+                    (outcome, searcher.table)
+                })
+            })
+            .collect();
+        let mut best: Option<SearchReport> = None;
+        let (mut nodes, mut hits, mut probes) = (0, 0, 0);
+        for handle in handles {
+            let (outcome, table) = handle.join().expect("search thread has panicked");
+            nodes += outcome.nodes;
+            hits += outcome.hits;
+            probes += outcome.probes;
+            self.table.add_stats(&table);
+            // Threads without moves of the root just evaluate it.
+            let better = match &best {
+                None => true,
+                Some(best) if best.pv.is_empty() => !outcome.pv.is_empty(),
+                Some(best) => !outcome.pv.is_empty() && outcome.score > best.score,
+            };
+            if better {
+                best = Some(outcome);
+            }
+        }
+        let mut best = best.expect("there is at least one thread");
+        (best.nodes, best.hits, best.probes) = (nodes, hits, probes);
+        best
+    }
+}
+
+/// Tables of move ordering: two killer moves for every ply and history scores of cells.
+/// Moves are packed indices, `u32::MAX` is no move.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct MoveOrdering {
+    killers: [[u32; 2]; MAX_DEPTH],
+    history: [u32; HISTORY_LEN],
+}
+impl MoveOrdering {
+    fn is_killer(&self, ply: usize, packed: usize) -> bool {
+        self.killers[ply].contains(&(packed as u32))
+    }
+    /// Remembers the quiet move, that caused a cutoff.
+    fn cutoff(&mut self, ply: usize, packed: usize, depth: usize) {
+        let killers = &mut self.killers[ply];
+        if killers[0] != packed as u32 {
+            killers[1] = killers[0];
+            killers[0] = packed as u32;
+        }
+        let history = &mut self.history[packed % HISTORY_LEN];
+        *history = history.saturating_add((depth * depth) as u32);
+    }
+}
+impl Default for MoveOrdering {
+    fn default() -> Self {
+        Self {
+            killers: [[u32::MAX; 2]; MAX_DEPTH],
+            history: [0; HISTORY_LEN],
+        }
+    }
+}
+
+/// Converts scores of won games from plies since the root to plies since the position.
+fn to_table(score: i32, ply: usize) -> i32 {
+    match score {
+        score if score >= WIN_SCORE - MAX_DEPTH as i32 => score + ply as i32,
+        score if score <= MAX_DEPTH as i32 - WIN_SCORE => score - ply as i32,
+        score => score,
+    }
+}
+fn from_table(score: i32, ply: usize) -> i32 {
+    match score {
+        score if score >= WIN_SCORE - MAX_DEPTH as i32 => score - ply as i32,
+        score if score <= MAX_DEPTH as i32 - WIN_SCORE => score + ply as i32,
+        score => score,
+    }
+}
@@ -0,0 +1,302 @@
+//! Season statistics
+//!
+//! This module defines [`Season`], statistics of players accumulated over many game
+//! [`record`](crate::record)s: results by color, i.e. by the player of the game, average
+//! number of captures (filled cells at the end of the game), average length of games and
+//! favorite openings. Players are named by tags with symbols of their colors, e.g.
+//! `[x "Alice"]`, players without such tags aren't counted. The opening of a game
+//! is the position after first few moves, identified by its canonical hash (see
+//! [`canonicalize`]), so symmetric openings are the same.
+//!
+//! Seasons don't allocate: statistics are kept in a storage provided by the user
+//! and names of players are borrowed from the records.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::record::parse;
+//! # use crosses_utils::season::*;
+//! let position = "[Position \"2./2. 2 x 1/1 - 0\"]\n[Result \"x-wins\"]\n\n";
+//! let games = [
+//!     ("[x \"Alice\"]\n[o \"Bob\"]\n", "1. a1 2. b2 3. a2 4. b1 5. b2 6. a1 7. b1 x-wins"),
+//!     ("[x \"Bob\"]\n[o \"Alice\"]\n", "1. a1 2. b2 3. b1 4. a2 5. b2 6. a1 7. a2 x-wins"),
+//! ];
+//! let texts = games.map(|(players, moves)| format!("{}{}{}\n", players, position, moves));
+//! let mut season = Season::new(3, [None; 4]);
+//! for text in &texts {
+//!     let record = parse(text).unwrap();
+//!     season.add(&record, [Cell::new(); 16], [0; 16], [None; 2]).unwrap();
+//! }
+//! let alice = season.player("Alice").unwrap();
+//! assert_eq!((alice.games(), alice.wins()), (2, 1));
+//! assert_eq!((alice.win_rate(0), alice.win_rate(1)), (1.0, 0.0));
+//! assert_eq!(alice.average_length(), 7.0);
+//! assert_eq!(alice.average_captures(), 1.5);
+//! // Both games have the same opening up to symmetry.
+//! let (opening, count) = alice.favorite_opening().unwrap();
+//! assert_eq!(count, 2);
+//! assert_eq!(season.player("Bob").unwrap().favorite_opening(), Some((opening, 2)));
+//! assert_eq!(season.players().count(), 2);
+//! ```
+//!
+//! [`canonicalize`]: crate::symmetry::canonicalize
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::iter::Flatten;
+use core::ops::IndexMut;
+use core::slice::Iter;
+
+use crate::board::{CellStorage, MAX_PLAYERS};
+use crate::engine::MoveError;
+use crate::game::Game;
+use crate::player_manager::{GameOver, GameState, LoseData};
+use crate::record::{check_result, Record, RecordError};
+use crate::symmetry::canonicalize;
+use crate::text::PLAYER_SYMBOLS;
+
+/// Number of openings tracked for every player. When a player has more different
+/// openings, the least frequent one is replaced, so counts become approximate.
+pub const TRACKED_OPENINGS: usize = 8;
+
+/// Statistics of a player, see [`Season`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PlayerSeason<'a> {
+    name: &'a str,
+    games: [u32; MAX_PLAYERS],
+    wins: [u32; MAX_PLAYERS],
+    draws: u32,
+    captures: u64,
+    moves: u64,
+    openings: [(u64, u32); TRACKED_OPENINGS],
+}
+impl<'a> PlayerSeason<'a> {
+    fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            games: [0; MAX_PLAYERS],
+            wins: [0; MAX_PLAYERS],
+            draws: 0,
+            captures: 0,
+            moves: 0,
+            openings: [(0, 0); TRACKED_OPENINGS],
+        }
+    }
+    /// Returns the name of the player.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+    /// Returns number of games of the player.
+    pub fn games(&self) -> u32 {
+        self.games.iter().sum()
+    }
+    /// Returns number of games won by the player.
+    pub fn wins(&self) -> u32 {
+        self.wins.iter().sum()
+    }
+    /// Returns number of games ended with a draw.
+    pub fn draws(&self) -> u32 {
+        self.draws
+    }
+    /// Returns number of games, where the player played for given color.
+    pub fn games_as(&self, color: usize) -> u32 {
+        self.games[color]
+    }
+    /// Returns number of games won by the player with given color.
+    pub fn wins_as(&self, color: usize) -> u32 {
+        self.wins[color]
+    }
+    /// Returns share of won games with given color or zero if there are no such games.
+    pub fn win_rate(&self, color: usize) -> f64 {
+        ratio(self.wins[color] as u64, self.games[color])
+    }
+    /// Returns average number of filled cells of the player at the end of their games.
+    pub fn average_captures(&self) -> f64 {
+        ratio(self.captures, self.games())
+    }
+    /// Returns average number of moves of games of the player.
+    pub fn average_length(&self) -> f64 {
+        ratio(self.moves, self.games())
+    }
+    /// Returns tracked openings of the player as canonical hashes with numbers of games.
+    pub fn openings(&self) -> impl Iterator<Item = (u64, u32)> + '_ {
+        self.openings
+            .iter()
+            .copied()
+            .filter(|(_, count)| *count > 0)
+    }
+    /// Returns the most frequent opening of the player with number of games, if any.
+    /// The earliest tracked opening wins ties.
+    pub fn favorite_opening(&self) -> Option<(u64, u32)> {
+        self.openings().fold(None, |best, opening| match best {
+            Some((_, count)) if count >= opening.1 => best,
+            _ => Some(opening),
+        })
+    }
+    fn add_opening(&mut self, hash: u64) {
+        let slots = &mut self.openings;
+        let slot = match slots.iter().position(|(h, count)| *h == hash && *count > 0) {
+            Some(slot) => slot,
+            None => {
+                let (slot, _) = slots
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, (_, count))| *count)
+                    .unwrap();
+                slots[slot].0 = hash;
+                slot
+            }
+        };
+        slots[slot].1 += 1;
+    }
+}
+
+fn ratio(numerator: u64, denominator: u32) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// Error of [`Season::add`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SeasonError {
+    /// The record can't be replayed
+    Record(RecordError),
+    /// The storage has no place for a new player
+    Full,
+}
+impl Display for SeasonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            SeasonError::Record(error) => write!(f, "record is invalid: {}", error),
+            SeasonError::Full => write!(f, "storage of players is full"),
+        }
+    }
+}
+
+/// Statistics of players over many games, see module docs.
+/// `S` - is type of storage of statistics, one element per player.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Season<S> {
+    opening_moves: usize,
+    players: S,
+}
+impl<'a, S: AsRef<[Option<PlayerSeason<'a>>]> + AsMut<[Option<PlayerSeason<'a>>]>> Season<S> {
+    /// Creates new season. Openings are positions after `opening_moves` moves.
+    pub fn new(opening_moves: usize, mut players: S) -> Self {
+        players.as_mut().fill(None);
+        Self {
+            opening_moves,
+            players,
+        }
+    }
+    /// Returns statistics of all players in order of their first games.
+    pub fn players(&self) -> Flatten<Iter<'_, Option<PlayerSeason<'a>>>> {
+        self.players.as_ref().iter().flatten()
+    }
+    /// Returns statistics of the player with given name.
+    pub fn player(&self, name: &str) -> Option<&PlayerSeason<'a>> {
+        self.players().find(|player| player.name == name)
+    }
+    /// Returns the storage of statistics.
+    pub fn into_inner(self) -> S {
+        self.players
+    }
+
+    /// Replays the record in given storages (see [`Record::replay`])
+    /// and adds the game to statistics of its players.
+    /// # Errors
+    /// Returns [`SeasonError`] if the record can't be replayed or there is no place
+    /// for a new player. In this case statistics aren't changed.
+    pub fn add<C, B, L>(
+        &mut self,
+        record: &Record<'a>,
+        cells: C,
+        buffer: B,
+        losers: L,
+    ) -> Result<(), SeasonError>
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        L: IndexMut<usize, Output = Option<LoseData>>,
+    {
+        let error = |error| SeasonError::Record(error);
+        let mut game = Game::from_fen(record.position(), cells, buffer, losers)
+            .map_err(|e| error(RecordError::Position(e)))?;
+        let mut opening = None;
+        let mut moves = 0;
+        for (number, (x, y)) in record.moves().enumerate() {
+            if number == self.opening_moves {
+                opening = Some(canonicalize(game.board()).1);
+            }
+            let board = game.board();
+            if x >= board.width() || y >= board.height() {
+                return Err(error(RecordError::Move(number, MoveError::OutOfBounds)));
+            }
+            let index = board.index(x, y);
+            game.play(index)
+                .map_err(|e| error(RecordError::Move(number, e)))?;
+            moves += 1;
+        }
+        if moves == self.opening_moves {
+            opening = Some(canonicalize(game.board()).1);
+        }
+        check_result(game.state(), record.result()).map_err(error)?;
+        let players = game.player_manager().max_players.min(MAX_PLAYERS);
+        let mut slots = [None; MAX_PLAYERS];
+        for color in 0..players {
+            let name = match self.name(record, color) {
+                Some(name) => name,
+                None => continue,
+            };
+            let storage = self.players.as_ref();
+            let place = storage
+                .iter()
+                .position(|player| matches!(player, Some(player) if player.name == name))
+                .or_else(|| {
+                    // Several colors of the game may need new places.
+                    (0..storage.len())
+                        .find(|place| storage[*place].is_none() && !slots_contain(&slots, *place))
+                })
+                .ok_or(SeasonError::Full)?;
+            slots[color] = Some((place, name));
+        }
+        let summary = game.summary();
+        let storage = self.players.as_mut();
+        for (color, slot) in slots.iter().enumerate().take(players) {
+            let (place, name) = match slot {
+                Some(slot) => *slot,
+                None => continue,
+            };
+            let player = storage[place].get_or_insert_with(|| PlayerSeason::new(name));
+            player.games[color] += 1;
+            match record.result() {
+                GameState::Ended(GameOver::Win(winner)) if winner == color => {
+                    player.wins[color] += 1
+                }
+                GameState::Ended(GameOver::Draw) => player.draws += 1,
+                _ => {}
+            }
+            player.captures += summary.captures(color) as u64;
+            player.moves += moves as u64;
+            if let Some(hash) = opening {
+                player.add_opening(hash);
+            }
+        }
+        Ok(())
+    }
+    /// Returns the name of the player of given color in the record.
+    fn name(&self, record: &Record<'a>, color: usize) -> Option<&'a str> {
+        record
+            .tags()
+            .find(|(tag, _)| {
+                let mut chars = tag.chars();
+                chars.next() == Some(PLAYER_SYMBOLS[color]) && chars.next().is_none()
+            })
+            .map(|(_, value)| value)
+    }
+}
+
+/// Checks if the place of storage is already taken by other color of the game.
+fn slots_contain(slots: &[Option<(usize, &str)>], place: usize) -> bool {
+    slots.iter().flatten().any(|(taken, _)| *taken == place)
+}
@@ -0,0 +1,144 @@
+//! SGF export
+//!
+//! This module writes games in [SGF](https://www.red-bean.com/sgf/), so they can be opened
+//! by viewers and archival tools, that read only SGF. Crosses have no assigned game number,
+//! so [`GAME_NUMBER`] is used. Properties of the root node:
+//! - `FF[4]`, `GM[`[`GAME_NUMBER`]`]`, `CA[UTF-8]`
+//! - `SZ` - size of the board: `SZ[9]` for square and `SZ[10:8]` for rectangular boards
+//! - tags of the caller, e.g. `PB[Alice]`
+//! - `XP` - number of players, `XM` - maximum number of moves in a turn
+//! - `XF` - the initial position (see [`Game::to_fen`])
+//! - `RE` - the result, if the game has ended: `B+` or `W+` for two players,
+//!   property of the winner with `+` for more players, `0` for a draw
+//!
+//! Then every turn is a node with all moves of the turn, e.g. `;B[aa][ba]`. Moves of
+//! players are written with properties from [`MOVE_PROPERTIES`]: `B` and `W` for the first
+//! two, private ones for others. Cells are written like in SGF: column and row letters,
+//! `a` to `z` and `A` to `Z`, `aa` is the top-left cell.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::sgf::*;
+//! let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+//! let mut game = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+//! let initial = game.clone();
+//! let mut moves = [None; 3];
+//! for ((x, y), record) in [(0, 0), (1, 1), (2, 2)].into_iter().zip(&mut moves) {
+//!     *record = Some(game.play(game.board().index(x, y)).unwrap());
+//! }
+//! let mut text = String::new();
+//! write(&mut text, &[("PB", "Alice")], &initial, moves.iter().flatten(), game.state()).unwrap();
+//! assert_eq!(
+//!     text,
+//!     "(;FF[4]GM[101]CA[UTF-8]SZ[3]PB[Alice]XP[2]XM[2]XF[3./3./3. 2 x 2/2 - 0]\n\
+//!      ;B[aa][bb]\n\
+//!      ;W[cc])\n"
+//! );
+//! let tags = [("pb", "Alice")];
+//! assert!(write(&mut text, &tags, &initial, moves.iter().flatten(), game.state()).is_err());
+//! ```
+use core::fmt::{Display, Error, Result as FmtResult, Write};
+use core::ops::IndexMut;
+
+use crate::board::{Board, CellStorage, HistoryIndex};
+use crate::engine::ChangeLog;
+use crate::game::{Game, GameMove};
+use crate::player_manager::{GameOver, GameState, LoseData};
+
+/// Value of `GM` property. It isn't assigned to any game by SGF specification.
+pub const GAME_NUMBER: u32 = 101;
+/// Properties of moves of each player.
+pub const MOVE_PROPERTIES: [&str; 8] = ["B", "W", "YA", "YB", "YC", "YD", "YE", "YF"];
+/// Maximum width and height of the board, that can be written.
+pub const MAX_SIZE: usize = 52;
+
+/// Writes the game in SGF. `initial` is the position before the first move,
+/// `moves` are the moves made since then and `result` is the state of the game after them.
+/// Names of tags should be uppercase SGF properties, values are escaped. Moves are written
+/// in nodes by their turns, see [`GameMove::turn`], their indices can be stored
+/// as any [`HistoryIndex`].
+/// # Errors
+/// Returns [`Error`] if writing fails, the board is wider or higher than [`MAX_SIZE`]
+/// or a name of a tag isn't a property.
+pub fn write<'a, W, C, B, S, L, I>(
+    out: &mut W,
+    tags: &[(&str, &str)],
+    initial: &Game<Board<C, B>, S, L>,
+    moves: impl IntoIterator<Item = &'a GameMove<I>>,
+    result: GameState,
+) -> FmtResult
+where
+    W: Write,
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+    I: HistoryIndex + 'a,
+{
+    let board = initial.board();
+    let (width, height) = (board.width(), board.height());
+    let is_property = |name: &str| !name.is_empty() && name.chars().all(|c| c.is_ascii_uppercase());
+    if width > MAX_SIZE || height > MAX_SIZE || !tags.iter().all(|(name, _)| is_property(name)) {
+        return Err(Error);
+    }
+    write!(out, "(;FF[4]GM[{}]CA[UTF-8]", GAME_NUMBER)?;
+    if width == height {
+        write!(out, "SZ[{}]", width)?;
+    } else {
+        write!(out, "SZ[{}:{}]", width, height)?;
+    }
+    for (name, value) in tags {
+        write!(out, "{}", name)?;
+        write_text(out, value)?;
+    }
+    let manager = initial.player_manager();
+    write!(
+        out,
+        "XP[{}]XM[{}]XF",
+        manager.max_players, manager.max_moves
+    )?;
+    write_text(out, initial.to_fen())?;
+    match result {
+        GameState::Ongoing => {}
+        GameState::Ended(GameOver::Draw) => write!(out, "RE[0]")?,
+        GameState::Ended(GameOver::Win(player)) => write!(out, "RE[{}+]", MOVE_PROPERTIES[player])?,
+    }
+    let mut previous_turn = None;
+    for &GameMove { record, turn, .. } in moves {
+        if previous_turn != Some(turn) {
+            previous_turn = Some(turn);
+            write!(out, "\n;{}", MOVE_PROPERTIES[record.player])?;
+        }
+        let (x, y) = board.coords(record.index.to_index(board));
+        write!(out, "[{}{}]", letter(x), letter(y))?;
+    }
+    writeln!(out, ")")
+}
+
+fn letter(coordinate: usize) -> char {
+    match coordinate {
+        0..=25 => (b'a' + coordinate as u8) as char,
+        _ => (b'A' + (coordinate - 26) as u8) as char,
+    }
+}
+fn write_text(out: &mut impl Write, text: impl Display) -> FmtResult {
+    out.write_char('[')?;
+    write!(Escaped(out), "{}", text)?;
+    out.write_char(']')
+}
+
+/// Escapes `]` and `\` in SGF text.
+struct Escaped<'a, W>(&'a mut W);
+impl<W: Write> Write for Escaped<'_, W> {
+    fn write_str(&mut self, text: &str) -> FmtResult {
+        for c in text.chars() {
+            if matches!(c, ']' | '\\') {
+                self.0.write_char('\\')?;
+            }
+            self.0.write_char(c)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+//! Sharing games between threads
+//!
+//! Boards, games, views and transposition tables of this crate are `Send` and `Sync`,
+//! if their storages are. Reads of positions, e.g. [`Game::board`], [`Game::view`] and
+//! influence maps, take `&self`. Traversals, that only read the position, take `&self`
+//! and a buffer of the caller instead of the traversal buffer of the board:
+//! [`validate_board`], [`debug_validate`], [`connected`], [`captures`], [`dead_areas`],
+//! [`Game::adjudication`] and [`Game::limit_result`]. Searches take `&mut`, because they
+//! make and cancel moves, and so does [`Game::summary`], which uses the traversal buffer
+//! of the board, though all of them leave the game unchanged. So a game shared between
+//! threads shouldn't be searched in place, it would be locked for the whole search.
+//!
+//! [`SharedGame`] keeps the game behind a lock and counts its changes. The UI thread
+//! reads the game with [`SharedGame::read`] and makes moves, the search thread takes
+//! a [`SharedGame::snapshot`], searches its own copy and plays the result with
+//! [`SharedGame::play_if_unchanged`], which fails, if the game has changed meanwhile.
+//! Requires `std` feature.
+//!
+//! [`validate_board`]: crate::validate::validate_board
+//! [`debug_validate`]: crate::validate::debug_validate
+//! [`connected`]: crate::analysis::connected
+//! [`captures`]: crate::analysis::captures
+//! [`dead_areas`]: crate::analysis::dead_areas
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::search::*;
+//! # use crosses_utils::shared::*;
+//! let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+//! let shared = SharedGame::new(Game::new(board, PlayerManager::new(1, 2, [None; 2])));
+//! let bot = shared.clone();
+//! let search = std::thread::spawn(move || {
+//!     let (mut game, version) = bot.snapshot();
+//!     let best = Searcher::new(Mobility).search(&mut game, 3).best().unwrap();
+//!     bot.play_if_unchanged(version, best)
+//! });
+//! let played = search.join().unwrap();
+//! assert!(matches!(played, Some(Ok(_))));
+//! assert_eq!(shared.version(), 1);
+//! assert_eq!(shared.read(|game| game.current_player()), 1);
+//! assert_eq!(shared.play_if_unchanged(0, 0), None);
+//! ```
+use core::ops::IndexMut;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::board::{Board, Cell};
+use crate::engine::{Engine, MoveError};
+use crate::game::{Game, GameMove, GameView};
+use crate::player_manager::LoseData;
+use crate::transposition::{Entry, SharedEntries, TranspositionTable};
+use alloc::vec::Vec;
+
+/// Game shared between threads, see module docs. Clones of it refer to the same game.
+#[derive(Debug)]
+pub struct SharedGame<B: Engine, S: IndexMut<usize, Output = Option<LoseData>>> {
+    inner: Arc<RwLock<Versioned<B, S>>>,
+}
+#[derive(Debug)]
+struct Versioned<B: Engine, S: IndexMut<usize, Output = Option<LoseData>>> {
+    game: Game<B, S>,
+    version: u64,
+}
+impl<B, S> Clone for SharedGame<B, S>
+where
+    B: Engine,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+impl<B, S> SharedGame<B, S>
+where
+    B: Engine,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+{
+    /// Shares the game. Its version is `0`.
+    pub fn new(game: Game<B, S>) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(Versioned { game, version: 0 })),
+        }
+    }
+    /// Calls `f` with the game. Other threads can read the game at the same time,
+    /// but moves wait until `f` returns.
+    pub fn read<R>(&self, f: impl FnOnce(&Game<B, S>) -> R) -> R {
+        let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        f(&inner.game)
+    }
+    /// Returns number of moves and cancellations made through the shared game.
+    pub fn version(&self) -> u64 {
+        self.read_inner(|inner| inner.version)
+    }
+    /// Returns a copy of the game with its version.
+    pub fn snapshot(&self) -> (Game<B, S>, u64)
+    where
+        B: Clone,
+        S: Clone,
+    {
+        self.read_inner(|inner| (inner.game.clone(), inner.version))
+    }
+    /// Makes a move like [`Game::play`].
+    /// # Errors
+    /// Returns [`MoveError`] in the same cases as [`Game::play`].
+    pub fn play(&self, index: B::Index) -> Result<GameMove<B::Index>, MoveError> {
+        self.write(|inner| inner.game.play(index))
+    }
+    /// Makes a move like [`Game::play`], if the game has given version.
+    /// Returns `None` if the version has changed.
+    pub fn play_if_unchanged(
+        &self,
+        version: u64,
+        index: B::Index,
+    ) -> Option<Result<GameMove<B::Index>, MoveError>> {
+        self.write(|inner| (inner.version == version).then(|| inner.game.play(index)))
+    }
+    /// Cancels the move like [`Game::undo`].
+    /// # Errors
+    /// Returns [`MoveError`] in the same cases as [`Game::undo`].
+    pub fn undo(&self, record: GameMove<B::Index>) -> Result<(), MoveError> {
+        self.write(|inner| inner.game.undo(record))
+    }
+
+    fn read_inner<R>(&self, f: impl FnOnce(&Versioned<B, S>) -> R) -> R {
+        f(&self.inner.read().unwrap_or_else(PoisonError::into_inner))
+    }
+    /// Calls `f` with the game and increments the version, if `f` has succeeded.
+    fn write<R: Changed>(&self, f: impl FnOnce(&mut Versioned<B, S>) -> R) -> R {
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        let result = f(&mut inner);
+        if result.changed() {
+            inner.version += 1;
+        }
+        result
+    }
+}
+
+/// Results of changes of the game, that tell whether the game has changed.
+trait Changed {
+    fn changed(&self) -> bool;
+}
+impl<T> Changed for Result<T, MoveError> {
+    fn changed(&self) -> bool {
+        self.is_ok()
+    }
+}
+impl<T> Changed for Option<Result<T, MoveError>> {
+    fn changed(&self) -> bool {
+        matches!(self, Some(Ok(_)))
+    }
+}
+
+/// Fails to compile, if the types lose `Send` or `Sync`.
+#[allow(dead_code)]
+fn audit() {
+    fn send_sync<T: Send + Sync>() {}
+    type ArrayBoard = Board<[Cell; 25], [usize; 25]>;
+    type VecBoard = Board<Vec<Cell>, Vec<usize>>;
+    send_sync::<ArrayBoard>();
+    send_sync::<VecBoard>();
+    send_sync::<Game<ArrayBoard, [Option<LoseData>; 2]>>();
+    send_sync::<Game<VecBoard, Vec<Option<LoseData>>>>();
+    send_sync::<GameView<&VecBoard>>();
+    send_sync::<TranspositionTable<[Entry; 16]>>();
+    send_sync::<TranspositionTable<SharedEntries>>();
+    send_sync::<SharedGame<VecBoard, Vec<Option<LoseData>>>>();
+}
@@ -0,0 +1,211 @@
+//! Bot-vs-bot simulation
+//!
+//! This module plays series of games between [`Bot`]s, e.g. to compare evaluation weights.
+//! Bots change their players every game, so in game `k` bot `b` plays for player
+//! `(b + k) % n`, where `n` is the number of bots, and every bot moves first equally often.
+//!
+//! [`RandomGame`] plays a complete random game from a seed, e.g. for property tests.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::bots::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::simulate::*;
+//! let new_game = |_| {
+//!     let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+//!     Game::new(board, PlayerManager::new(3, 2, [None; 2]))
+//! };
+//! let (mut greedy, mut random) = (GreedyBot::default(), RandomBot::new(SplitMix64(1)));
+//! let stats = simulate(&mut [&mut greedy, &mut random], 10, new_game);
+//! assert_eq!(stats.games(), 10);
+//! assert_eq!(stats.wins(0) + stats.wins(1) + stats.draws(), 10);
+//! assert!(stats.wins(0) > stats.wins(1));
+//! assert!(stats.average_length() > 6.0);
+//! ```
+use core::ops::IndexMut;
+
+use crate::board::{Board, CellStorage, MAX_PLAYERS};
+use crate::bots::{Bot, RandomBot, SplitMix64};
+use crate::engine::ChangeLog;
+use crate::game::{Game, GameMove};
+use crate::player_manager::{GameOver, GameState, LoseData};
+
+/// Results of [`simulate`]. Wins are counted for bots, not players.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct SimulationStats {
+    games: u64,
+    wins: [u64; MAX_PLAYERS],
+    draws: u64,
+    unfinished: u64,
+    moves: u64,
+}
+impl SimulationStats {
+    /// Returns number of played games.
+    pub fn games(&self) -> u64 {
+        self.games
+    }
+    /// Returns number of games won by the bot.
+    pub fn wins(&self, bot: usize) -> u64 {
+        self.wins[bot]
+    }
+    /// Returns number of games ended with a draw.
+    pub fn draws(&self) -> u64 {
+        self.draws
+    }
+    /// Returns number of games, that were stopped, because a bot
+    /// didn't choose a move or chose an illegal one.
+    pub fn unfinished(&self) -> u64 {
+        self.unfinished
+    }
+    /// Returns number of moves of all games.
+    pub fn moves(&self) -> u64 {
+        self.moves
+    }
+    /// Returns average number of moves in a game or zero if no games were played.
+    pub fn average_length(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            self.moves as f64 / self.games as f64
+        }
+    }
+}
+
+/// Plays `games` games between the bots and collects statistics. `new_game` creates
+/// the initial position of the game with given number. Number of bots should be
+/// the same as number of players.
+/// # Panics
+/// Panics if number of bots and players differ.
+pub fn simulate<C, B, S, L>(
+    bots: &mut [&mut dyn Bot<Game<Board<C, B>, S, L>>],
+    games: usize,
+    mut new_game: impl FnMut(usize) -> Game<Board<C, B>, S, L>,
+) -> SimulationStats
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    let mut stats = SimulationStats::default();
+    let n = bots.len();
+    for number in 0..games {
+        let mut game = new_game(number);
+        assert_eq!(
+            game.player_manager().max_players,
+            n,
+            "number of bots and players differ"
+        );
+        let bot_of = |player: usize| (player + n - number % n) % n;
+        stats.games += 1;
+        play_out(bots, &mut game, bot_of, |_| {
+            stats.moves += 1;
+            true
+        });
+        match game.state() {
+            GameState::Ongoing => stats.unfinished += 1,
+            GameState::Ended(GameOver::Draw) => stats.draws += 1,
+            GameState::Ended(GameOver::Win(winner)) => stats.wins[bot_of(winner)] += 1,
+        }
+    }
+    stats
+}
+
+/// Plays the game, until it ends or a bot doesn't choose a legal move. `bot_of` returns
+/// the bot of the player, `on_move` is called after every move and stops the game,
+/// if it returns `false`.
+pub(crate) fn play_out<C, B, S, L>(
+    bots: &mut [&mut dyn Bot<Game<Board<C, B>, S, L>>],
+    game: &mut Game<Board<C, B>, S, L>,
+    bot_of: impl Fn(usize) -> usize,
+    mut on_move: impl FnMut(GameMove<usize>) -> bool,
+) where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    while game.state() == GameState::Ongoing {
+        let bot = &mut bots[bot_of(game.current_player())];
+        match bot.choose_move(game).map(|index| game.play(index)) {
+            Some(Ok(record)) if on_move(record) => {}
+            _ => break,
+        }
+    }
+}
+
+/// Random legal game, an iterator, that makes random legal moves of the current player
+/// until the game ends and yields index of every move with [`Game::consistency_hash`]
+/// after it. The same seed and the initial position give the same game.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// # use crosses_utils::simulate::*;
+/// let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+/// let initial = Game::new(board, PlayerManager::new(3, 2, [None; 2]));
+/// let mut random = RandomGame::new(initial.clone(), 7);
+/// let mut history = [(0, 0); 50];
+/// let mut len = 0;
+/// for (slot, item) in history.iter_mut().zip(&mut random) {
+///     *slot = item;
+///     len += 1;
+/// }
+/// assert_ne!(random.game().state(), GameState::Ongoing);
+/// let mut replay = initial;
+/// for (index, hash) in &history[..len] {
+///     replay.play(*index).unwrap();
+///     assert_eq!(replay.consistency_hash(), *hash);
+/// }
+/// assert!(RandomGame::new(replay, 7).next().is_none());
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RandomGame<C, B, S, L = ()>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    game: Game<Board<C, B>, S, L>,
+    bot: RandomBot<SplitMix64>,
+}
+impl<C, B, S, L> RandomGame<C, B, S, L>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    /// Creates the random game, that starts at the position of `game`.
+    pub fn new(game: Game<Board<C, B>, S, L>, seed: u64) -> Self {
+        Self {
+            game,
+            bot: RandomBot::new(SplitMix64(seed)),
+        }
+    }
+    /// Returns the game after the yielded moves.
+    pub fn game(&self) -> &Game<Board<C, B>, S, L> {
+        &self.game
+    }
+    /// Unwraps the game.
+    pub fn into_inner(self) -> Game<Board<C, B>, S, L> {
+        self.game
+    }
+}
+impl<C, B, S, L> Iterator for RandomGame<C, B, S, L>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    type Item = (usize, u64);
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.bot.choose_move(&self.game)?;
+        self.game.play(index).ok()?;
+        Some((index, self.game.consistency_hash()))
+    }
+}
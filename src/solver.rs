@@ -0,0 +1,293 @@
+//! Exhaustive solver
+//!
+//! This module defines [`Solver`], which solves two-player games completely: it plays
+//! every legal move to the end of the game and finds the game-theoretic value of the
+//! position. Winners win as fast as possible and losers resist as long as possible.
+//! Moves, that can't change the value, are pruned like in alpha-beta search, the best
+//! move of the previous visit and captures are tried first. Values of positions are
+//! memoized in [`TranspositionTable`] by Zobrist hash of the position, the current
+//! player and remaining moves, like in [`Searcher`].
+//!
+//! Number of positions grows very fast, so the solver is meant for tiny boards, up to
+//! about 4×4 with one or two moves per turn. There it's the reference for heuristics,
+//! search and the engine itself.
+//!
+//! Games with [`RuleSet::remove_dead_chains`] aren't solved: removed cells can be played
+//! again, so positions repeat and games may never end.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::search::*;
+//! # use crosses_utils::solver::*;
+//! # use crosses_utils::transposition::*;
+//! let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+//! let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+//! let initial = game.clone();
+//! let mut solver = Solver::new(TranspositionTable::new(vec![Entry::EMPTY; 1 << 16]));
+//! let solution = solver.solve(&mut game);
+//! assert_eq!(game, initial);
+//! assert_eq!(solution.outcome(), GameOver::Win(solution.winner().unwrap()));
+//! // Search deep enough finds the same value.
+//! let report = Searcher::new(Mobility).search(&mut game, solution.plies());
+//! let sign = if solution.winner() == Some(0) { 1 } else { -1 };
+//! assert_eq!(report.win_in(), Some(sign * solution.plies() as i32));
+//! ```
+//!
+//! [`Searcher`]: crate::search::Searcher
+//! [`RuleSet::remove_dead_chains`]: crate::rules::RuleSet::remove_dead_chains
+use core::ops::IndexMut;
+
+use crate::base::CellKind;
+use crate::board::{Board, CellStorage};
+use crate::engine::{ChangeLog, Data, Engine};
+use crate::game::Game;
+use crate::player_manager::{GameOver, GameState, LoseData};
+use crate::search::WIN_SCORE;
+use crate::transposition::{Bound, EntryStorage, TranspositionTable};
+use crate::zobrist::Zobrist;
+
+/// Game-theoretic value of a position found by [`Solver::solve`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Solution {
+    outcome: GameOver,
+    plies: usize,
+    best: Option<usize>,
+    nodes: u64,
+}
+impl Solution {
+    /// Returns the result of the game with perfect play.
+    pub fn outcome(&self) -> GameOver {
+        self.outcome
+    }
+    /// Returns the winner with perfect play, if any.
+    pub fn winner(&self) -> Option<usize> {
+        match self.outcome {
+            GameOver::Win(player) => Some(player),
+            GameOver::Draw => None,
+        }
+    }
+    /// Returns number of plies to the end of the won game with perfect play
+    /// or zero for a draw.
+    pub fn plies(&self) -> usize {
+        self.plies
+    }
+    /// Returns the best move of the current player or `None` if the game has ended.
+    pub fn best(&self) -> Option<usize> {
+        self.best
+    }
+    /// Returns number of solved positions, that weren't found in the table.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+}
+
+/// Exhaustive solver, see module docs.
+/// `T` - is type of storage of [`TranspositionTable`] with memoized values.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Solver<T> {
+    table: TranspositionTable<T>,
+    zobrist: Zobrist,
+    hash: u64,
+    nodes: u64,
+}
+impl<T: EntryStorage> Solver<T> {
+    /// Creates new solver with given table. Positions are hashed with
+    /// [`Zobrist::default`] keys. Values stay in the table between calls of
+    /// [`Solver::solve`], they are valid for all games with the same rules.
+    pub fn new(table: TranspositionTable<T>) -> Self {
+        Self {
+            table,
+            zobrist: Zobrist::default(),
+            hash: 0,
+            nodes: 0,
+        }
+    }
+    /// Returns the table.
+    pub fn table(&self) -> &TranspositionTable<T> {
+        &self.table
+    }
+    /// Returns the table, e.g. to clear it before solving games with other rules.
+    pub fn table_mut(&mut self) -> &mut TranspositionTable<T> {
+        &mut self.table
+    }
+    /// Unwraps the table.
+    pub fn into_inner(self) -> TranspositionTable<T> {
+        self.table
+    }
+
+    /// Solves the game. The game is returned to its initial state.
+    /// # Panics
+    /// Panics if the game doesn't have two players or its rules remove dead chains,
+    /// see module docs.
+    pub fn solve<C, B, S, L>(&mut self, game: &mut Game<Board<C, B>, S, L>) -> Solution
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        assert_eq!(
+            game.player_manager().max_players,
+            2,
+            "only two-player games can be solved"
+        );
+        assert!(
+            !game.board().rules().remove_dead_chains,
+            "games with removal of dead chains can't be solved"
+        );
+        self.nodes = 0;
+        let board = game.board();
+        let indices = (0..board.width() * board.height()).map(|packed| board.unpack_index(packed));
+        self.hash = self.zobrist.hash(board, indices);
+        let (score, best) = self.value(game, -WIN_SCORE - 1, WIN_SCORE + 1);
+        let outcome = match score {
+            0 => GameOver::Draw,
+            score if score > 0 => GameOver::Win(0),
+            _ => GameOver::Win(1),
+        };
+        let plies = if score == 0 {
+            0
+        } else {
+            WIN_SCORE - score.abs()
+        };
+        Solution {
+            outcome,
+            plies: plies as usize,
+            best: best.map(|packed| game.board().unpack_index(packed)),
+            nodes: self.nodes,
+        }
+    }
+
+    /// Returns the value of the position for the first player and packed index
+    /// of the best move. Won games have [`WIN_SCORE`] minus the number of plies
+    /// to the end, draws have zero. The value is exact only inside of the window
+    /// `(alpha, beta)`, otherwise it's a bound like in alpha-beta search.
+    fn value<C, B, S, L>(
+        &mut self,
+        game: &mut Game<Board<C, B>, S, L>,
+        mut alpha: i32,
+        mut beta: i32,
+    ) -> (i32, Option<usize>)
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        match game.state() {
+            GameState::Ended(GameOver::Win(0)) => return (WIN_SCORE, None),
+            GameState::Ended(GameOver::Win(_)) => return (-WIN_SCORE, None),
+            GameState::Ended(GameOver::Draw) => return (0, None),
+            GameState::Ongoing => {}
+        }
+        let key = self.key(game);
+        let entry = self.table.probe(key);
+        if let Some(entry) = entry {
+            let found = match entry.bound() {
+                Bound::Exact => true,
+                Bound::Lower => entry.score() >= beta,
+                Bound::Upper => entry.score() <= alpha,
+            };
+            if found {
+                return (entry.score(), entry.best());
+            }
+        }
+        self.nodes += 1;
+        let window = (alpha, beta);
+        let maximizing = game.current_player() == 0;
+        let mut best: Option<(i32, usize)> = None;
+        let cells = game.board().width() * game.board().height();
+        // The best move of the previous visit goes first, then captures.
+        let first = entry.and_then(|entry| entry.best());
+        let class = |game: &Game<Board<C, B>, S, L>, packed| {
+            if Some(packed) == first {
+                0
+            } else if game.board().get(game.board().unpack_index(packed)).kind() == CellKind::Cross
+            {
+                1
+            } else {
+                2
+            }
+        };
+        let moves = (0..3).flat_map(|pass| (0..cells).map(move |packed| (pass, packed)));
+        for (pass, packed) in moves {
+            if class(game, packed) != pass {
+                continue;
+            }
+            let index = game.board().unpack_index(packed);
+            let old = game.board().get(index);
+            let record = match game.play(index) {
+                Ok(record) => record,
+                Err(_) => continue,
+            };
+            let (hash, board) = (self.hash, game.board());
+            let indices = (0..cells).map(|packed| board.unpack_index(packed));
+            self.hash = self.zobrist.after_move(hash, board, index, &old, indices);
+            let (score, _) = self.value(game, earlier(alpha), earlier(beta));
+            game.undo(record).expect("the move was just made");
+            self.hash = hash;
+            let score = later(score);
+            let better = match best {
+                None => true,
+                Some((best, _)) if maximizing => score > best,
+                Some((best, _)) => score < best,
+            };
+            if better {
+                best = Some((score, packed));
+            }
+            if maximizing {
+                alpha = alpha.max(score);
+            } else {
+                beta = beta.min(score);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        let (score, best) = match best {
+            Some((score, best)) => (score, Some(best)),
+            None => (0, None),
+        };
+        let bound = if score <= window.0 {
+            Bound::Upper
+        } else if score >= window.1 {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.store(key, 0, score, bound, best);
+        (score, best)
+    }
+    fn key<C, B, S, L>(&self, game: &Game<Board<C, B>, S, L>) -> u64
+    where
+        C: CellStorage,
+        B: AsMut<[usize]>,
+        S: IndexMut<usize, Output = Option<LoseData>>,
+        L: ChangeLog<usize>,
+    {
+        let manager = game.player_manager();
+        let state = manager.current_player * (manager.max_moves + 1) + manager.remaining_moves;
+        self.hash ^ self.zobrist.player_key(state)
+    }
+}
+
+/// Returns the value of the position one ply before the position with given value.
+fn later(score: i32) -> i32 {
+    match score {
+        score if score > 0 => score - 1,
+        score if score < 0 => score + 1,
+        _ => 0,
+    }
+}
+
+/// Returns the value of the position one ply after the position with given value,
+/// the inverse of [`later`] for bounds of the window.
+fn earlier(score: i32) -> i32 {
+    match score {
+        score if score > 0 => score + 1,
+        score if score < 0 => score - 1,
+        _ => 0,
+    }
+}
@@ -0,0 +1,265 @@
+//! Game statistics
+//!
+//! This module defines [`GameStats`], statistics of moves of every player, that
+//! [`Game`] accumulates after [`Game::enable_stats`]. Cancelled moves are subtracted,
+//! so searches and other analysis, that make and cancel moves, don't change them.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::search::*;
+//! let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+//! let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+//! game.enable_stats();
+//! game.play(game.board().index(0, 0)).unwrap();
+//! Searcher::new(Mobility).search(&mut game, 4);
+//! let stats = game.stats().unwrap();
+//! assert_eq!((stats.moves(), stats.crosses(0), stats.crosses(1)), (1, 1, 0));
+//! assert_eq!(stats.mobility_gain(0), 2);
+//! ```
+use crate::base::{CellKind, GameBoard};
+use crate::board::MAX_PLAYERS;
+use crate::counters::{Counter, PlayerCounters};
+use crate::engine::{Data, Engine, MoveRecord};
+use crate::events::GameEvent;
+#[cfg(doc)]
+use crate::game::Game;
+use crate::ibts::{IbtsBoard, SearchResult};
+use crate::rules::RuleSet;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Statistics of moves of players, see [`Game::enable_stats`]. Moves of players with
+/// numbers greater or equal to [`MAX_PLAYERS`] are counted only in [`GameStats::moves`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct GameStats {
+    moves: u64,
+    crosses: [u64; MAX_PLAYERS],
+    fills: [u64; MAX_PLAYERS],
+    kills: [u64; MAX_PLAYERS],
+    revivals: [u64; MAX_PLAYERS],
+    mobility_before: [u64; MAX_PLAYERS],
+    mobility_after: [u64; MAX_PLAYERS],
+}
+impl GameStats {
+    /// Returns number of moves of all players.
+    pub fn moves(&self) -> u64 {
+        self.moves
+    }
+    /// Returns number of crosses placed into empty cells by the player.
+    pub fn crosses(&self, player: usize) -> u64 {
+        self.crosses[player]
+    }
+    /// Returns number of crosses of other players filled by the player.
+    pub fn fills(&self, player: usize) -> u64 {
+        self.fills[player]
+    }
+    /// Returns number of chains of other players killed by moves of the player.
+    pub fn kills(&self, player: usize) -> u64 {
+        self.kills[player]
+    }
+    /// Returns number of own chains revived by moves of the player.
+    pub fn revivals(&self, player: usize) -> u64 {
+        self.revivals[player]
+    }
+    /// Returns sum of mobility of the player before their moves.
+    pub fn mobility_before(&self, player: usize) -> u64 {
+        self.mobility_before[player]
+    }
+    /// Returns sum of mobility of the player after their moves.
+    pub fn mobility_after(&self, player: usize) -> u64 {
+        self.mobility_after[player]
+    }
+    /// Returns total change of mobility of the player by their moves.
+    pub fn mobility_gain(&self, player: usize) -> i64 {
+        self.mobility_after[player] as i64 - self.mobility_before[player] as i64
+    }
+
+    /// Adds the move or subtracts the cancelled one. Kills and revivals are the ones
+    /// of the move, so cancellation subtracts revivals for chains killed by it.
+    pub(crate) fn record<I>(&mut self, record: &MoveRecord<I>, move_: Move, cancelled: bool) {
+        let add = |value: &mut u64, delta: u64| {
+            *value = if cancelled {
+                value.saturating_sub(delta)
+            } else {
+                *value + delta
+            }
+        };
+        add(&mut self.moves, 1);
+        let player = record.player;
+        if player >= MAX_PLAYERS {
+            return;
+        }
+        let (kills, revivals) = if cancelled {
+            (move_.revivals, move_.kills)
+        } else {
+            (move_.kills, move_.revivals)
+        };
+        let (before, after) = if cancelled {
+            (move_.mobility_after, move_.mobility_before)
+        } else {
+            (move_.mobility_before, move_.mobility_after)
+        };
+        match record.prior_kind {
+            CellKind::Cross => add(&mut self.fills[player], 1),
+            _ => add(&mut self.crosses[player], 1),
+        }
+        add(&mut self.kills[player], kills as u64);
+        add(&mut self.revivals[player], revivals as u64);
+        add(&mut self.mobility_before[player], before as u64);
+        add(&mut self.mobility_after[player], after as u64);
+    }
+}
+
+/// What happened during [`make_move`] or [`cancel_move`], counted by [`Tally`].
+///
+/// [`make_move`]: crate::engine::make_move
+/// [`cancel_move`]: crate::engine::cancel_move
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub(crate) struct Move {
+    kills: usize,
+    revivals: usize,
+    mobility_before: usize,
+    mobility_after: usize,
+}
+
+/// Calls `f` with the engine wrapped in [`Tally`] and returns its result
+/// with what happened, including mobility of the player before and after.
+/// Changes of cells and chains are passed to `on_event`.
+pub(crate) fn tally<E, F, R>(
+    engine: &mut E,
+    player: usize,
+    on_event: F,
+    f: impl FnOnce(&mut Tally<'_, E, F>) -> R,
+) -> (R, Move)
+where
+    E: Engine,
+    F: FnMut(GameEvent<E::Index, E::Data>),
+{
+    let mobility_before = engine.counters().get(Counter::Moves, player);
+    let mut tally = Tally {
+        engine,
+        on_event,
+        kills: 0,
+        revivals: 0,
+    };
+    let result = f(&mut tally);
+    let (kills, revivals) = (tally.kills, tally.revivals);
+    let move_ = Move {
+        kills,
+        revivals,
+        mobility_before,
+        mobility_after: engine.counters().get(Counter::Moves, player),
+    };
+    (result, move_)
+}
+
+/// Engine wrapper, that counts kills and revivals of chains and reports them with
+/// changes of cells to `on_event`. Hooks of [`IbtsBoard`] other than
+/// [`IbtsBoard::on_split`] aren't forwarded, so that kills and revivals made by them
+/// go through the wrapper.
+pub(crate) struct Tally<'a, E, F> {
+    engine: &'a mut E,
+    on_event: F,
+    kills: usize,
+    revivals: usize,
+}
+impl<E: Engine, F: FnMut(GameEvent<E::Index, E::Data>)> Tally<'_, E, F> {
+    fn report(&mut self, index: E::Index, before: E::Data, after: E::Data) {
+        if before.kind() != after.kind() || before.player() != after.player() {
+            (self.on_event)(GameEvent::CellChanged {
+                index,
+                from: before,
+                to: after,
+            });
+        }
+    }
+}
+impl<E: Engine, F: FnMut(GameEvent<E::Index, E::Data>)> GameBoard for Tally<'_, E, F> {
+    type Index = E::Index;
+    type Adjacent = E::Adjacent;
+    type Player = E::Player;
+
+    fn adjacent(&self, index: Self::Index) -> Self::Adjacent {
+        self.engine.adjacent(index)
+    }
+    fn kind(&self, index: Self::Index) -> CellKind {
+        self.engine.kind(index)
+    }
+    fn player(&self, index: Self::Index) -> Self::Player {
+        self.engine.player(index)
+    }
+}
+impl<E: Engine, F: FnMut(GameEvent<E::Index, E::Data>)> IbtsBoard for Tally<'_, E, F> {
+    fn is_important(&self, index: Self::Index) -> bool {
+        self.engine.is_important(index)
+    }
+    fn set_important(&mut self, index: Self::Index, new: bool) {
+        self.engine.set_important(index, new)
+    }
+    fn is_alive(&self, index: Self::Index) -> bool {
+        self.engine.is_alive(index)
+    }
+    fn set_alive(&mut self, index: Self::Index, new: bool) {
+        self.engine.set_alive(index, new)
+    }
+
+    fn revive(&mut self, index: Self::Index) {
+        self.revivals += 1;
+        self.engine.revive(index);
+        (self.on_event)(GameEvent::ChainRevived { seed: index });
+    }
+    fn kill(&mut self, index: Self::Index) {
+        self.kills += 1;
+        self.engine.kill(index);
+        (self.on_event)(GameEvent::ChainKilled { seed: index });
+    }
+    fn search(&mut self, index: Self::Index) -> Option<SearchResult<Self::Index>> {
+        self.engine.search(index)
+    }
+    fn searches_activators(&self) -> bool {
+        self.engine.searches_activators()
+    }
+    fn on_split(&mut self, index: Self::Index) {
+        self.engine.on_split(index)
+    }
+}
+impl<E: Engine, F: FnMut(GameEvent<E::Index, E::Data>)> Engine for Tally<'_, E, F> {
+    type Data = E::Data;
+    type Counters = E::Counters;
+
+    fn get(&self, index: Self::Index) -> Self::Data {
+        self.engine.get(index)
+    }
+    fn set(&mut self, index: Self::Index, data: Self::Data) {
+        let before = self.engine.get(index);
+        self.engine.set(index, data);
+        self.report(index, before, data);
+    }
+    fn with_cell<R>(&mut self, index: Self::Index, f: impl FnOnce(&mut Self::Data) -> R) -> R {
+        let (before, after, result) = self.engine.with_cell(index, |data| {
+            let before = *data;
+            let result = f(data);
+            (before, *data, result)
+        });
+        self.report(index, before, after);
+        result
+    }
+    fn reach(&self, index: Self::Index) -> Self::Adjacent {
+        self.engine.reach(index)
+    }
+    fn for_each_dead(&self, f: impl FnMut(Self::Index)) {
+        self.engine.for_each_dead(f)
+    }
+    fn counters(&self) -> &Self::Counters {
+        self.engine.counters()
+    }
+    fn counters_mut(&mut self) -> &mut Self::Counters {
+        self.engine.counters_mut()
+    }
+    fn rules(&self) -> RuleSet {
+        self.engine.rules()
+    }
+}
@@ -0,0 +1,300 @@
+//! Post-game summary
+//!
+//! This module defines [`GameSummary`], the report of results of [`Game`] with [`Board`],
+//! returned by [`Game::summary`]: the result and its reason, the order and reasons of eliminations,
+//! number of moves and captures and chains of every player. It's displayed as several
+//! lines of text, one for the result, every elimination and every player.
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::summary::*;
+//! let board = Board::new(2, 1, 2, [Cell::new(); 12], [0; 12]);
+//! let mut game = Game::new(board, PlayerManager::new(1, 2, [None; 2]));
+//! for x in [0, 1, 1] {
+//!     game.play(game.board().index(x, 0)).unwrap();
+//! }
+//! let summary = game.summary();
+//! assert_eq!(summary.winner(), Some(0));
+//! assert_eq!((summary.captures(0), summary.largest_chain(0)), (1, 1));
+//! let elimination = summary.eliminations()[0];
+//! assert_eq!(elimination.reason(), EliminationReason::OutOfCrosses);
+//! assert_eq!(
+//!     summary.to_string(),
+//!     "game was won by player: 0 after 3 moves\n\
+//!      player 1 was eliminated at move 2: lost all crosses\n\
+//!      player 0: 1 captures, largest chain 1\n\
+//!      player 1: 0 captures, largest chain 0"
+//! );
+//! ```
+use core::fmt::Display;
+use core::ops::IndexMut;
+
+use crate::base::CellKind;
+use crate::board::{Board, CellStorage, Walk, MAX_PLAYERS};
+use crate::counters::{Counter, PlayerCounters};
+use crate::engine::{ChangeLog, Data, Engine};
+use crate::game::Game;
+use crate::player_manager::{GameOver, GameState, LoseData};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Reason of elimination of a player.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum EliminationReason {
+    /// The player couldn't make a move
+    OutOfMoves,
+    /// The player has lost all of their crosses
+    OutOfCrosses,
+    /// The game was adjudicated, see [`Game::adjudicate`]
+    Adjudicated,
+    /// The player has run out of time, see [`clock`](crate::clock)
+    Timeout,
+    /// The player has resigned
+    Resigned,
+}
+impl Display for EliminationReason {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EliminationReason::OutOfMoves => write!(f, "no moves left"),
+            EliminationReason::OutOfCrosses => write!(f, "lost all crosses"),
+            EliminationReason::Adjudicated => write!(f, "lost by adjudication"),
+            EliminationReason::Timeout => write!(f, "ran out of time"),
+            EliminationReason::Resigned => write!(f, "resigned"),
+        }
+    }
+}
+
+/// Reason of the end of the game, given to [`Game::end`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum EndReason {
+    /// The position has repeated, see [`DrawRules::repetitions`]
+    ///
+    /// [`DrawRules::repetitions`]: crate::draws::DrawRules::repetitions
+    Repetition,
+    /// Nobody has made progress for a long time, see [`DrawRules::quiet_turns`]
+    ///
+    /// [`DrawRules::quiet_turns`]: crate::draws::DrawRules::quiet_turns
+    NoProgress,
+    /// The game has reached the limit of moves, see [`Game::enforce_move_limit`]
+    MoveLimit,
+}
+impl Display for EndReason {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            EndReason::Repetition => write!(f, "repetition of position"),
+            EndReason::NoProgress => write!(f, "lack of progress"),
+            EndReason::MoveLimit => write!(f, "limit of moves"),
+        }
+    }
+}
+
+/// Elimination of a player, see [`GameSummary::eliminations`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Elimination {
+    move_index: usize,
+    player: usize,
+    reason: EliminationReason,
+}
+impl Elimination {
+    /// Returns the eliminated player.
+    pub fn player(&self) -> usize {
+        self.player
+    }
+    /// Returns index of the move, when the player was eliminated, starting from `0`.
+    pub fn move_index(&self) -> usize {
+        self.move_index
+    }
+    /// Returns reason of the elimination.
+    pub fn reason(&self) -> EliminationReason {
+        self.reason
+    }
+}
+impl Display for Elimination {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "player {} was eliminated at move {}: {}",
+            self.player, self.move_index, self.reason
+        )
+    }
+}
+
+/// Summary of the game, see module docs.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct GameSummary {
+    state: GameState,
+    end_reason: Option<EndReason>,
+    moves: usize,
+    players: usize,
+    eliminated: usize,
+    eliminations: [Elimination; MAX_PLAYERS],
+    captures: [usize; MAX_PLAYERS],
+    largest_chains: [usize; MAX_PLAYERS],
+}
+impl GameSummary {
+    /// Returns the state of the game.
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+    /// Returns the reason of the end of the game, see [`Game::end`].
+    pub fn end_reason(&self) -> Option<EndReason> {
+        self.end_reason
+    }
+    /// Returns the winner, if the game was won.
+    pub fn winner(&self) -> Option<usize> {
+        match self.state {
+            GameState::Ended(GameOver::Win(winner)) => Some(winner),
+            _ => None,
+        }
+    }
+    /// Returns total number of moves.
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+    /// Returns number of players.
+    pub fn players(&self) -> usize {
+        self.players
+    }
+    /// Returns eliminations of players in the order they happened.
+    pub fn eliminations(&self) -> &[Elimination] {
+        &self.eliminations[..self.eliminated]
+    }
+    /// Returns number of crosses of other players filled by the player.
+    pub fn captures(&self, player: usize) -> usize {
+        self.captures[player]
+    }
+    /// Returns number of cells of the largest chain of filled cells of the player.
+    pub fn largest_chain(&self, player: usize) -> usize {
+        self.largest_chains[player]
+    }
+}
+impl Display for GameSummary {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.state {
+            GameState::Ongoing => write!(f, "game is ongoing")?,
+            GameState::Ended(result) => write!(f, "{}", result)?,
+        }
+        if let Some(reason) = self.end_reason {
+            write!(f, " by {}", reason)?;
+        }
+        write!(f, " after {} moves", self.moves)?;
+        for elimination in self.eliminations() {
+            write!(f, "\n{}", elimination)?;
+        }
+        for player in 0..self.players {
+            write!(
+                f,
+                "\nplayer {}: {} captures, largest chain {}",
+                player, self.captures[player], self.largest_chains[player]
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns reason of elimination of the player, who has lost, by the position.
+pub(crate) fn elimination_reason(
+    counters: &impl PlayerCounters,
+    player: usize,
+) -> EliminationReason {
+    if counters.is_exhausted(Counter::Crosses, player)
+        && counters.is_exhausted(Counter::Moves, player)
+    {
+        EliminationReason::OutOfCrosses
+    } else {
+        EliminationReason::OutOfMoves
+    }
+}
+
+impl<C, B, S, L> Game<Board<C, B>, S, L>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    /// Returns the summary of the game, see module docs. Reasons of eliminations are
+    /// given to [`Game::mark_loser`] or determined by the final position: players without
+    /// crosses and moves have lost all crosses. Players, that haven't lost, when the game was won, were adjudicated.
+    /// Every filled cell is a capture of its player. The position isn't changed,
+    /// `&mut` is needed for the traversal buffer of the board.
+    pub fn summary(&mut self) -> GameSummary {
+        let state = self.state();
+        let manager = self.player_manager();
+        let counters = self.counters();
+        let players = manager.max_players.min(MAX_PLAYERS);
+        let moves = manager.current_move;
+        let mut summary = GameSummary {
+            state,
+            end_reason: self.end_reason(),
+            moves,
+            players,
+            eliminated: 0,
+            eliminations: [Elimination {
+                move_index: 0,
+                player: 0,
+                reason: EliminationReason::OutOfMoves,
+            }; MAX_PLAYERS],
+            captures: [0; MAX_PLAYERS],
+            largest_chains: [0; MAX_PLAYERS],
+        };
+        for player in 0..players {
+            let (move_index, reason) = match manager.losers[player] {
+                Some(lose_data) => (
+                    match lose_data.move_index {
+                        LoseData::BEFORE_START => 0,
+                        move_index => move_index,
+                    },
+                    self.marked_reason(player)
+                        .unwrap_or_else(|| elimination_reason(counters, player)),
+                ),
+                None => match state {
+                    GameState::Ended(GameOver::Win(winner))
+                        if winner != player && manager.neutral != Some(player) =>
+                    {
+                        (moves, EliminationReason::Adjudicated)
+                    }
+                    _ => continue,
+                },
+            };
+            summary.eliminations[summary.eliminated] = Elimination {
+                move_index,
+                player,
+                reason,
+            };
+            summary.eliminated += 1;
+        }
+        summary.eliminations[..summary.eliminated].sort_unstable();
+        let board = self.parts_mut().0;
+        for packed in 0..board.width() * board.height() {
+            let cell = board.get(board.unpack_index(packed));
+            if cell.kind() == CellKind::Filled && cell.player() < players {
+                summary.captures[cell.player()] += 1;
+            }
+        }
+        let mut chain = (usize::MAX, 0);
+        board.walk().components(
+            |board, seed, index| {
+                let cell = board.get(index);
+                cell.kind() == CellKind::Filled && cell.player() == board.get(seed).player()
+            },
+            |board, seed, _| {
+                if chain.0 != seed {
+                    chain = (seed, 0);
+                }
+                chain.1 += 1;
+                let player = board.get(seed).player();
+                if player < players {
+                    summary.largest_chains[player] = summary.largest_chains[player].max(chain.1);
+                }
+            },
+        );
+        summary
+    }
+}
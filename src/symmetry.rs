@@ -0,0 +1,178 @@
+//! Symmetries of boards
+//!
+//! This module defines [`SymmetryOp`], one of the symmetries of rectangular board,
+//! and [`canonicalize`], which picks the canonical form of the position among its
+//! symmetric forms. Symmetric positions have the same canonical hash, so opening books
+//! and transposition tables can store only one of them. Moves can be converted
+//! to and from the canonical form with [`SymmetryOp::apply`] and [`SymmetryOp::inverse`].
+//!
+//! Only the content of cells is taken into account, starting cells aren't.
+use core::cmp::Ordering;
+
+use crate::base::CellKind;
+use crate::board::{Board, CellStorage};
+use crate::engine::{Data, Engine};
+use crate::zobrist::Zobrist;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A symmetry of the board. Rotations are clockwise.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum SymmetryOp {
+    /// The board isn't changed
+    Identity,
+    /// Rotation by 90 degrees, only for square boards
+    Rotate90,
+    /// Rotation by 180 degrees
+    Rotate180,
+    /// Rotation by 270 degrees, only for square boards
+    Rotate270,
+    /// Reflection of columns: left-right
+    FlipHorizontal,
+    /// Reflection of rows: top-bottom
+    FlipVertical,
+    /// Reflection over the main diagonal, only for square boards
+    Transpose,
+    /// Reflection over the other diagonal, only for square boards
+    AntiTranspose,
+}
+impl SymmetryOp {
+    /// All symmetries of square boards.
+    pub const ALL: [SymmetryOp; 8] = [
+        SymmetryOp::Identity,
+        SymmetryOp::Rotate90,
+        SymmetryOp::Rotate180,
+        SymmetryOp::Rotate270,
+        SymmetryOp::FlipHorizontal,
+        SymmetryOp::FlipVertical,
+        SymmetryOp::Transpose,
+        SymmetryOp::AntiTranspose,
+    ];
+    /// Symmetries of non-square boards.
+    pub const RECTANGLE: [SymmetryOp; 4] = [
+        SymmetryOp::Identity,
+        SymmetryOp::Rotate180,
+        SymmetryOp::FlipHorizontal,
+        SymmetryOp::FlipVertical,
+    ];
+
+    /// Returns symmetries of the board with given size.
+    pub fn all(width: usize, height: usize) -> &'static [SymmetryOp] {
+        if width == height {
+            &Self::ALL
+        } else {
+            &Self::RECTANGLE
+        }
+    }
+    /// Returns the symmetry, that cancels this one.
+    pub fn inverse(self) -> Self {
+        match self {
+            SymmetryOp::Rotate90 => SymmetryOp::Rotate270,
+            SymmetryOp::Rotate270 => SymmetryOp::Rotate90,
+            op => op,
+        }
+    }
+    /// Returns size of the board with given size after the symmetry.
+    pub fn size(self, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            SymmetryOp::Rotate90
+            | SymmetryOp::Rotate270
+            | SymmetryOp::Transpose
+            | SymmetryOp::AntiTranspose => (height, width),
+            _ => (width, height),
+        }
+    }
+    /// Converts coordinates of the cell on the board with given size
+    /// to its coordinates after the symmetry.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::symmetry::*;
+    /// let op = SymmetryOp::Rotate90;
+    /// assert_eq!(op.apply(3, 3, 0, 0), (2, 0));
+    /// assert_eq!(op.inverse().apply(3, 3, 2, 0), (0, 0));
+    /// ```
+    pub fn apply(self, width: usize, height: usize, x: usize, y: usize) -> (usize, usize) {
+        let (right, bottom) = (width - 1 - x, height - 1 - y);
+        match self {
+            SymmetryOp::Identity => (x, y),
+            SymmetryOp::Rotate90 => (bottom, x),
+            SymmetryOp::Rotate180 => (right, bottom),
+            SymmetryOp::Rotate270 => (y, right),
+            SymmetryOp::FlipHorizontal => (right, y),
+            SymmetryOp::FlipVertical => (x, bottom),
+            SymmetryOp::Transpose => (y, x),
+            SymmetryOp::AntiTranspose => (bottom, right),
+        }
+    }
+}
+
+/// Returns the symmetry, that turns the position into its canonical form, and
+/// the hash of the canonical form. Canonical form is the lexicographically smallest
+/// sequence of cells (row by row) among all symmetric forms. The hash uses
+/// [`Zobrist::default`] keys with packed indices of the canonical form.
+/// # Example
+/// ```
+/// # use crosses_utils::symmetry::*;
+/// # use crosses_utils::text::*;
+/// let left = parse_into("x..\n...\n..o", [Default::default(); 25], [0; 25]).unwrap();
+/// let right = parse_into("..x\n...\no..", [Default::default(); 25], [0; 25]).unwrap();
+/// let (left_op, left_hash) = canonicalize(&left);
+/// let (right_op, right_hash) = canonicalize(&right);
+/// assert_eq!(left_hash, right_hash);
+/// assert_eq!(left_op, SymmetryOp::Rotate90);
+/// assert_eq!(right_op, SymmetryOp::Identity);
+/// ```
+pub fn canonicalize<C, B>(board: &Board<C, B>) -> (SymmetryOp, u64)
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let (width, height) = (board.width(), board.height());
+    let mut best = SymmetryOp::Identity;
+    for &op in &SymmetryOp::all(width, height)[1..] {
+        if compare(board, op, best) == Ordering::Less {
+            best = op;
+        }
+    }
+    let zobrist = Zobrist::default();
+    let (canonical_width, _) = best.size(width, height);
+    let mut hash = 0;
+    for y in 0..height {
+        for x in 0..width {
+            let cell = board.get(board.index(x, y));
+            let (x, y) = best.apply(width, height, x, y);
+            hash ^= zobrist.key(y * canonical_width + x, cell.kind(), cell.player());
+        }
+    }
+    (best, hash)
+}
+
+/// Compares forms of the position after two symmetries.
+fn compare<C, B>(board: &Board<C, B>, first: SymmetryOp, second: SymmetryOp) -> Ordering
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let (width, height) = (board.width(), board.height());
+    let (new_width, new_height) = first.size(width, height);
+    let code = |op: SymmetryOp, x, y| {
+        let (x, y) = op.inverse().apply(new_width, new_height, x, y);
+        let cell = board.get(board.index(x, y));
+        match cell.kind() {
+            CellKind::Empty => 0,
+            CellKind::Border => 1,
+            CellKind::Cross => 2 + 2 * cell.player(),
+            CellKind::Filled => 3 + 2 * cell.player(),
+        }
+    };
+    for y in 0..new_height {
+        for x in 0..new_width {
+            match code(first, x, y).cmp(&code(second, x, y)) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+    }
+    Ordering::Equal
+}
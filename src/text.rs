@@ -0,0 +1,531 @@
+//! Text form of boards
+//!
+//! This module defines canonical textual form of [`Board`], which is handy in tests,
+//! bug reports and docs. Every row of the board is written on its own line,
+//! every cell is written with one character:
+//! - `.` is empty cell,
+//! - `#` is border cell inside the board, i.e. an obstacle,
+//! - lowercase letter is a cross and uppercase letter is a filled cell of the player.
+//!   Letters of players are [`PLAYER_SYMBOLS`]: `x` is the first player, `o` is the second one and so on.
+//!
+//! [`render`] can also show IBTS flags of cells. Then every cell is followed by markers:
+//! `+` or `-` for alive or dead filled cell and `!` for important cell, and cells
+//! are separated by spaces. [`parse_into`] ignores spaces and markers, so rendered board
+//! can be parsed back. `parse` does the same, but allocates storages (requires `alloc` feature).
+//!
+//! Whole position of [`Game`] can be written in one line with [`Game::to_fen`]
+//! and read back with [`Game::from_fen`], like FEN in chess.
+//! # Example
+//! ```
+//! # use crosses_utils::text::*;
+//! let board = parse_into("x.\n.O", [Default::default(); 16], [0; 16]).unwrap();
+//! assert_eq!(render(&board).to_string(), "x.\n.O\n");
+//! assert_eq!(render(&board).aliveness().to_string(), "x  . \n.  O-\n");
+//! ```
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::ops::IndexMut;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::board::Cell;
+
+use crate::base::CellKind;
+use crate::board::{Board, CellStorage, MAX_PLAYERS};
+use crate::engine::{ChangeLog, Data, Engine};
+use crate::game::Game;
+use crate::player_manager::{GameOver, GameState, LoseData, PlayerManager};
+
+/// Letters of players. Crosses are lowercase, filled cells are uppercase.
+pub const PLAYER_SYMBOLS: [char; MAX_PLAYERS] = ['x', 'o', 'a', 'b', 'c', 'd', 'e', 'f'];
+
+/// Returns the character of the cell with given content.
+pub fn symbol(kind: CellKind, player: usize) -> char {
+    match kind {
+        CellKind::Empty => '.',
+        CellKind::Border => '#',
+        CellKind::Cross => PLAYER_SYMBOLS[player],
+        CellKind::Filled => PLAYER_SYMBOLS[player].to_ascii_uppercase(),
+    }
+}
+/// Returns the content of the cell, written with given character.
+/// Player of empty and border cells is `0`.
+/// # Example
+/// ```
+/// # use crosses_utils::base::CellKind;
+/// # use crosses_utils::text::*;
+/// assert_eq!(parse_symbol('O'), Some((CellKind::Filled, 1)));
+/// assert_eq!(parse_symbol('?'), None);
+/// ```
+pub fn parse_symbol(symbol: char) -> Option<(CellKind, usize)> {
+    match symbol {
+        '.' => Some((CellKind::Empty, 0)),
+        '#' => Some((CellKind::Border, 0)),
+        _ => {
+            let lower = symbol.to_ascii_lowercase();
+            let player = PLAYER_SYMBOLS.iter().position(|s| *s == lower)?;
+            if symbol == lower {
+                Some((CellKind::Cross, player))
+            } else {
+                Some((CellKind::Filled, player))
+            }
+        }
+    }
+}
+
+/// Returns displayable text form of the board.
+pub fn render<C, B>(board: &Board<C, B>) -> Render<'_, C, B> {
+    Render {
+        board,
+        aliveness: false,
+        importance: false,
+    }
+}
+
+/// Text form of [`Board`], returned by [`render`].
+#[derive(Clone, Copy, Debug)]
+pub struct Render<'a, C, B> {
+    board: &'a Board<C, B>,
+    aliveness: bool,
+    importance: bool,
+}
+impl<'a, C, B> Render<'a, C, B> {
+    /// Shows `+` after alive and `-` after dead filled cells.
+    pub fn aliveness(mut self) -> Self {
+        self.aliveness = true;
+        self
+    }
+    /// Shows `!` after important cells.
+    pub fn importance(mut self) -> Self {
+        self.importance = true;
+        self
+    }
+}
+impl<'a, C, B> Display for Render<'a, C, B>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let board = self.board;
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                let cell = board.get(board.index(x, y));
+                if x > 0 && (self.aliveness || self.importance) {
+                    write!(f, " ")?;
+                }
+                write!(f, "{}", symbol(cell.kind(), cell.player()))?;
+                if self.aliveness {
+                    let marker = match cell.kind() {
+                        CellKind::Filled if cell.flags().is_alive() => '+',
+                        CellKind::Filled => '-',
+                        _ => ' ',
+                    };
+                    write!(f, "{}", marker)?;
+                }
+                if self.importance {
+                    let marker = if cell.flags().is_important() {
+                        '!'
+                    } else {
+                        ' '
+                    };
+                    write!(f, "{}", marker)?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors of [`parse_into`] and [`Game::from_fen`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ParseError {
+    /// The text has no cells
+    Empty,
+    /// The character isn't a cell or a marker
+    UnknownSymbol(char),
+    /// The row has different number of cells than the first one
+    RaggedRows(usize),
+    /// Given storages are smaller than [`Board::storage_len`]
+    StorageTooSmall,
+    /// The position string has wrong fields
+    MalformedFen,
+}
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ParseError::Empty => write!(f, "text has no cells"),
+            ParseError::UnknownSymbol(symbol) => write!(f, "unknown symbol {:?}", symbol),
+            ParseError::RaggedRows(row) => write!(f, "row {} has wrong number of cells", row),
+            ParseError::StorageTooSmall => write!(f, "storage is too small"),
+            ParseError::MalformedFen => write!(f, "position string is malformed"),
+        }
+    }
+}
+
+/// Parses the board from its text form, allocating storages for it.
+/// See [`parse_into`] for details. Requires `alloc` feature.
+#[cfg(feature = "alloc")]
+pub fn parse(text: &str) -> Result<Board<Vec<Cell>, Vec<usize>>, ParseError> {
+    let (width, height, _) = dimensions(text)?;
+    let len = Board::<Vec<Cell>, Vec<usize>>::storage_len(width, height);
+    parse_into(text, alloc::vec![Cell::new(); len], alloc::vec![0; len])
+}
+/// Parses the board from its text form into given storages.
+/// Number of players is the number of the last player on the board, but at least `2`.
+/// Starting cells are the same as in [`Board::new`]. Text form doesn't store
+/// previous owners of the filled cells, they are set to the next players.
+/// Activations, IBTS flags and counters are computed with [`Board::init`].
+/// # Example
+/// ```
+/// # use crosses_utils::counters::*;
+/// # use crosses_utils::engine::*;
+/// # use crosses_utils::ibts::*;
+/// # use crosses_utils::text::*;
+/// let mut board = parse_into(
+///     "x X . \n\
+///      . . o!\n",
+///     [Default::default(); 20],
+///     [0; 20],
+/// )
+/// .unwrap();
+/// assert_eq!(render(&board).aliveness().importance().to_string(), "x ! X+! .  \n.   .   o  \n");
+/// assert_eq!(board.counters().get(Counter::Moves, 0), 4);
+/// let index = board.index(2, 1);
+/// assert!(make_move(&mut board, index, 0).is_ok());
+/// assert_eq!(parse_into("x\nxx", [Default::default(); 20], [0; 20]), Err(ParseError::RaggedRows(1)));
+/// ```
+pub fn parse_into<C, B>(text: &str, cells: C, mut buffer: B) -> Result<Board<C, B>, ParseError>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let (width, height, players) = dimensions(text)?;
+    let len = Board::<C, B>::storage_len(width, height);
+    if cells.len() < len || buffer.as_mut().len() < len {
+        return Err(ParseError::StorageTooSmall);
+    }
+    let mut board = Board::new(width, height, players, cells, buffer);
+    for (y, row) in rows(text).enumerate() {
+        for (x, symbol) in row.enumerate() {
+            set_symbol(&mut board, x, y, symbol);
+        }
+    }
+    board.init();
+    Ok(board)
+}
+
+/// FEN-like position string of [`Game`], returned by [`Game::to_fen`].
+#[derive(Clone, Copy, Debug)]
+pub struct Fen<'a, C, B, S, L = ()>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    game: &'a Game<Board<C, B>, S, L>,
+}
+impl<'a, C, B, S, L> Display for Fen<'a, C, B, S, L>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let board = self.game.board();
+        let content = |x, y| {
+            let cell = board.get(board.index(x, y));
+            symbol(cell.kind(), cell.player())
+        };
+        for y in 0..board.height() {
+            if y > 0 {
+                write!(f, "/")?;
+            }
+            let mut x = 0;
+            while x < board.width() {
+                let symbol = content(x, y);
+                let mut run = 1;
+                while x + run < board.width() && content(x + run, y) == symbol {
+                    run += 1;
+                }
+                if run > 1 {
+                    write!(f, "{}", run)?;
+                }
+                write!(f, "{}", symbol)?;
+                x += run;
+            }
+        }
+        let manager = self.game.player_manager();
+        write!(
+            f,
+            " {} {} {}/{} ",
+            manager.max_players,
+            PLAYER_SYMBOLS[manager.current_player],
+            manager.remaining_moves,
+            manager.max_moves,
+        )?;
+        let mut has_losers = false;
+        for (player, symbol) in PLAYER_SYMBOLS.iter().enumerate().take(manager.max_players) {
+            if let Some(data) = manager.losers[player] {
+                if has_losers {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}:{}:{}", symbol, data.move_index, data.remaining_moves)?;
+                has_losers = true;
+            }
+        }
+        if !has_losers {
+            write!(f, "-")?;
+        }
+        write!(f, " {}", manager.current_move)
+    }
+}
+
+impl<C, B, S, L> Game<Board<C, B>, S, L>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    /// Returns FEN-like position string of the game. It has six fields separated by spaces:
+    /// - rows of the board separated by `/`, where a number before the symbol of a cell
+    ///   repeats it, e.g. `3.x` is the same as `...x`,
+    /// - number of players,
+    /// - the current player,
+    /// - remaining and maximum number of moves in a turn separated by `/`,
+    /// - losers as `player:move_index:remaining_moves` separated by `,` or `-` if there are none,
+    /// - the number of the current move.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// let board = Board::new(3, 2, 2, [Cell::new(); 20], [0; 20]);
+    /// let mut game = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+    /// game.play(game.board().index(0, 0)).unwrap();
+    /// game.play(game.board().index(1, 0)).unwrap();
+    /// assert_eq!(game.to_fen().to_string(), "2x./3. 2 o 2/2 - 2");
+    /// ```
+    pub fn to_fen(&self) -> Fen<'_, C, B, S, L> {
+        Fen { game: self }
+    }
+}
+impl<C, B, S> Game<Board<C, B>, S>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+{
+    /// Parses the game from its FEN-like position string, see [`Game::to_fen`].
+    /// The board is set up the same way as in [`parse_into`]. State of the game
+    /// is determined by the losers. `losers` storage should have no losers initially.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::text::*;
+    /// let fen = "xX2./2.o1o 2 o 1/2 - 5";
+    /// let mut game = Game::from_fen(fen, [Cell::new(); 24], [0; 24], [None; 2]).unwrap();
+    /// assert_eq!(game.to_fen().to_string(), "xX2./2.2o 2 o 1/2 - 5");
+    /// game.play(game.board().index(1, 1)).unwrap();
+    /// assert_eq!(game.current_player(), 0);
+    /// let error = Game::from_fen("xx 2 x 1/1", [Cell::new(); 12], [0; 12], [None; 2]);
+    /// assert_eq!(error.unwrap_err(), ParseError::MalformedFen);
+    /// let error = Game::from_fen("0x 2 x 1/1 - 0", [Cell::new(); 12], [0; 12], [None; 2]);
+    /// assert_eq!(error.unwrap_err(), ParseError::MalformedFen);
+    /// let error = Game::from_fen("x. 2 x 0/1 - 0", [Cell::new(); 12], [0; 12], [None; 2]);
+    /// assert_eq!(error.unwrap_err(), ParseError::MalformedFen);
+    /// let error = Game::from_fen("x.o 3 x 1/1 0:0:1 1", [Cell::new(); 15], [0; 15], [None; 3]);
+    /// assert_eq!(error.unwrap_err(), ParseError::MalformedFen);
+    /// let error = Game::from_fen("x.o 3 o 1/1 2:4:1 1", [Cell::new(); 15], [0; 15], [None; 3]);
+    /// assert_eq!(error.unwrap_err(), ParseError::MalformedFen);
+    /// let fen = "10000000000. 2 x 1/1 - 0";
+    /// let error = Game::from_fen(fen, [Cell::new(); 12], [0; 12], [None; 2]);
+    /// assert_eq!(error.unwrap_err(), ParseError::StorageTooSmall);
+    /// ```
+    pub fn from_fen(fen: &str, cells: C, mut buffer: B, losers: S) -> Result<Self, ParseError> {
+        let mut fields = fen.split_whitespace();
+        let mut next = || fields.next().ok_or(ParseError::MalformedFen);
+        let rows = next()?;
+        let players = number(next()?)?;
+        let current_player = player(next()?, players)?;
+        let (remaining_moves, max_moves) =
+            next()?.split_once('/').ok_or(ParseError::MalformedFen)?;
+        let (remaining_moves, max_moves) = (number(remaining_moves)?, number(max_moves)?);
+        let losers_field = next()?;
+        let current_move = number(next()?)?;
+        if fields.next().is_some()
+            || !(2..=MAX_PLAYERS).contains(&players)
+            || max_moves == 0
+            || remaining_moves > max_moves
+        {
+            return Err(ParseError::MalformedFen);
+        }
+        let mut last_player = 0;
+        let limit = cells.len().min(buffer.as_mut().len());
+        let (width, height) = decode_rows(rows, limit, |_, _, symbol| {
+            if let Some((_, player)) = parse_symbol(symbol) {
+                last_player = last_player.max(player)
+            }
+        })?;
+        if last_player >= players {
+            return Err(ParseError::UnknownSymbol(PLAYER_SYMBOLS[last_player]));
+        }
+        let len = Board::<C, B>::storage_len(width, height);
+        if cells.len() < len || buffer.as_mut().len() < len {
+            return Err(ParseError::StorageTooSmall);
+        }
+        let mut board = Board::new(width, height, players, cells, buffer);
+        decode_rows(rows, limit, |x, y, symbol| {
+            set_symbol(&mut board, x, y, symbol)
+        })?;
+        board.init();
+        let mut manager = PlayerManager::new(max_moves, players, losers);
+        if losers_field != "-" {
+            for loser in losers_field.split(',') {
+                let mut parts = loser.split(':');
+                let mut next = || parts.next().ok_or(ParseError::MalformedFen);
+                let loser = player(next()?, players)?;
+                let data = LoseData {
+                    move_index: number(next()?)?,
+                    remaining_moves: number(next()?)?,
+                };
+                if parts.next().is_some() || manager.losers[loser].is_some() {
+                    return Err(ParseError::MalformedFen);
+                }
+                manager.losers[loser] = Some(data);
+            }
+        }
+        manager.remaining_moves = remaining_moves;
+        manager.current_player = current_player;
+        manager.current_move = current_move;
+        let mut not_losers = (0..players).filter(|p| manager.losers[*p].is_none());
+        manager.game_state = match (not_losers.next(), not_losers.next()) {
+            (None, _) => GameState::Ended(GameOver::Draw),
+            (Some(winner), None) => GameState::Ended(GameOver::Win(winner)),
+            _ => GameState::Ongoing,
+        };
+        let mut losses = (0..players).filter_map(|player| manager.losers[player]);
+        if losses.any(|data| data.is_after(current_move))
+            || matches!(manager.game_state, GameState::Ongoing)
+                && (remaining_moves == 0 || !manager.is_playing(current_player))
+        {
+            return Err(ParseError::MalformedFen);
+        }
+        Ok(Game::new(board, manager))
+    }
+}
+
+/// Sets the content of the cell from its symbol.
+fn set_symbol<C, B>(board: &mut Board<C, B>, x: usize, y: usize, symbol: char)
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    if let Some((kind, player)) = parse_symbol(symbol) {
+        let index = board.index(x, y);
+        board.set_up(index, kind, player);
+    }
+}
+/// Calls `f` with coordinates and symbol of every cell of run-length encoded rows.
+/// Returns width and height of the board. Rows are checked before `f` is called,
+/// so the board must fit `limit` cells with borders and counts of runs can't be `0`.
+fn decode_rows(
+    rows: &str,
+    limit: usize,
+    mut f: impl FnMut(usize, usize, char),
+) -> Result<(usize, usize), ParseError> {
+    let (mut width, mut height) = (0, 0);
+    for (y, row) in rows.split('/').enumerate() {
+        let (mut x, mut run) = (0usize, 0usize);
+        for symbol in row.chars() {
+            if let Some(digit) = symbol.to_digit(10) {
+                if digit == 0 && run == 0 {
+                    return Err(ParseError::MalformedFen);
+                }
+                run = run
+                    .checked_mul(10)
+                    .and_then(|run| run.checked_add(digit as usize))
+                    .ok_or(ParseError::MalformedFen)?;
+                continue;
+            }
+            parse_symbol(symbol).ok_or(ParseError::UnknownSymbol(symbol))?;
+            let end = x.checked_add(run.max(1)).ok_or(ParseError::MalformedFen)?;
+            if y > 0 && end > width {
+                return Err(ParseError::RaggedRows(y));
+            }
+            if !fits(end, y + 1, limit) {
+                return Err(ParseError::StorageTooSmall);
+            }
+            for _ in 0..run.max(1) {
+                f(x, y, symbol);
+                x += 1;
+            }
+            run = 0;
+        }
+        if run != 0 {
+            return Err(ParseError::MalformedFen);
+        }
+        if y == 0 {
+            width = x;
+        } else if x != width {
+            return Err(ParseError::RaggedRows(y));
+        }
+        height += 1;
+    }
+    if width == 0 {
+        return Err(ParseError::Empty);
+    }
+    Ok((width, height))
+}
+/// Checks if the board of given size with borders fits `limit` cells.
+fn fits(width: usize, height: usize, limit: usize) -> bool {
+    let len = (width.checked_add(2), height.checked_add(2));
+    matches!(len, (Some(w), Some(h)) if w.checked_mul(h).map_or(false, |len| len <= limit))
+}
+fn number(field: &str) -> Result<usize, ParseError> {
+    field.parse().map_err(|_| ParseError::MalformedFen)
+}
+fn player(field: &str, players: usize) -> Result<usize, ParseError> {
+    let mut chars = field.chars();
+    match (chars.next().and_then(parse_symbol), chars.next()) {
+        (Some((CellKind::Cross, player)), None) if player < players => Ok(player),
+        _ => Err(ParseError::MalformedFen),
+    }
+}
+
+/// Returns non-empty rows of the text without spaces and markers.
+fn rows(text: &str) -> impl Iterator<Item = impl Iterator<Item = char> + '_> + '_ {
+    text.lines()
+        .map(|line| {
+            line.chars()
+                .filter(|c| !matches!(c, '+' | '-' | '!') && !c.is_whitespace())
+        })
+        .filter(|row| row.clone().next().is_some())
+}
+fn dimensions(text: &str) -> Result<(usize, usize, usize), ParseError> {
+    let (mut width, mut height, mut players) = (0, 0, 2);
+    for (y, row) in rows(text).enumerate() {
+        let mut row_width = 0;
+        for symbol in row {
+            let (_, player) = parse_symbol(symbol).ok_or(ParseError::UnknownSymbol(symbol))?;
+            players = players.max(player + 1);
+            row_width += 1;
+        }
+        if y == 0 {
+            width = row_width;
+        } else if row_width != width {
+            return Err(ParseError::RaggedRows(y));
+        }
+        height += 1;
+    }
+    if height == 0 {
+        return Err(ParseError::Empty);
+    }
+    Ok((width, height, players))
+}
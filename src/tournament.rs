@@ -0,0 +1,349 @@
+//! Tournaments
+//!
+//! This module defines [`Tournament`], the table of results of games between participants,
+//! which produces pairings of rounds and standings. Round-robin pairings are made by the
+//! circle method, so every participant meets every other participant once. Swiss pairings
+//! pair participants with close scores, who haven't met yet: the first participant in the
+//! standings plays the highest placed possible opponent and so on. With odd number of
+//! participants one of them gets a bye, which gives a win, but never twice in Swiss rounds.
+//!
+//! Scores are counted in half points: a win gives `2`, a draw gives `1`. Standings are
+//! ordered by score, then by Buchholz score, i.e. the sum of scores of opponents, then by
+//! numbers of participants. Tournaments don't allocate: results are kept in a storage
+//! provided by the user.
+//! # Example
+//! ```
+//! # use crosses_utils::player_manager::GameOver;
+//! # use crosses_utils::tournament::*;
+//! let mut tournament = Tournament::new(3, [None; 9]);
+//! assert_eq!(tournament.round_robin_rounds(), 3);
+//! let mut pairings = [None; 2];
+//! for round in 0..3 {
+//!     let len = tournament.round_robin(round, &mut pairings);
+//!     for pairing in pairings[..len].iter().flatten() {
+//!         match *pairing {
+//!             Pairing::Game { first, second } => {
+//!                 // The participant with the lowest number wins.
+//!                 let winner = if first < second { 0 } else { 1 };
+//!                 tournament.record_game(first, second, GameOver::Win(winner).into());
+//!             }
+//!             Pairing::Bye(participant) => tournament.record_bye(participant),
+//!         }
+//!     }
+//! }
+//! let mut standings = [None; 3];
+//! tournament.standings(&mut standings);
+//! let leader = standings[0].unwrap();
+//! assert_eq!((leader.participant, leader.score, leader.buchholz), (0, 6, 6));
+//! assert_eq!(standings[2].unwrap().participant, 2);
+//! ```
+use core::cmp::Reverse;
+
+use crate::player_manager::GameOver;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of participants of a tournament.
+pub const MAX_PARTICIPANTS: usize = 64;
+
+/// Pairing of a round.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Pairing {
+    /// Game between participants, `first` plays for player `0`
+    Game {
+        /// The participant, who moves first
+        first: usize,
+        /// The participant, who moves second
+        second: usize,
+    },
+    /// The participant doesn't play in the round
+    Bye(usize),
+}
+
+/// Result of a game for one of participants.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Outcome {
+    /// The participant has won
+    Win,
+    /// The game ended with a draw
+    Draw,
+    /// The participant has lost
+    Loss,
+}
+impl Outcome {
+    /// Returns the outcome for the opponent.
+    pub fn reverse(self) -> Self {
+        match self {
+            Outcome::Win => Outcome::Loss,
+            Outcome::Draw => Outcome::Draw,
+            Outcome::Loss => Outcome::Win,
+        }
+    }
+    /// Returns score of the outcome in half points.
+    pub fn score(self) -> u32 {
+        match self {
+            Outcome::Win => 2,
+            Outcome::Draw => 1,
+            Outcome::Loss => 0,
+        }
+    }
+}
+/// Outcome for player `0` of a game of two players.
+impl From<GameOver> for Outcome {
+    fn from(result: GameOver) -> Self {
+        match result {
+            GameOver::Win(0) => Outcome::Win,
+            GameOver::Win(_) => Outcome::Loss,
+            GameOver::Draw => Outcome::Draw,
+        }
+    }
+}
+
+/// Place of a participant in standings, see [`Tournament::standings`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Standing {
+    /// The participant
+    pub participant: usize,
+    /// Score in half points
+    pub score: u32,
+    /// Sum of scores of opponents in half points
+    pub buchholz: u32,
+    /// Number of played games, byes aren't counted
+    pub games: usize,
+}
+
+/// Results of a tournament, see module docs. `S` - is type of storage of results,
+/// it should have `participants²` elements: element `a * participants + b` is outcome
+/// of the game of `a` against `b` for `a` and element `a * participants + a` is the bye
+/// of `a`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Tournament<S> {
+    participants: usize,
+    results: S,
+}
+impl<S: AsRef<[Option<Outcome>]> + AsMut<[Option<Outcome>]>> Tournament<S> {
+    /// Creates new tournament without results.
+    /// # Panics
+    /// Panics if there are more than [`MAX_PARTICIPANTS`] participants
+    /// or the storage has less than `participants²` elements.
+    pub fn new(participants: usize, mut results: S) -> Self {
+        assert!(participants <= MAX_PARTICIPANTS, "too many participants");
+        let results_slice = results.as_mut();
+        assert!(
+            results_slice.len() >= participants * participants,
+            "storage is too small"
+        );
+        results_slice.fill(None);
+        Self {
+            participants,
+            results,
+        }
+    }
+    /// Returns number of participants.
+    pub fn participants(&self) -> usize {
+        self.participants
+    }
+    /// Returns the storage of results.
+    pub fn into_inner(self) -> S {
+        self.results
+    }
+
+    /// Returns the outcome of the game of `a` against `b` for `a`, if they have met.
+    /// If `a` and `b` are the same, returns [`Outcome::Win`], if `a` has got a bye.
+    pub fn result(&self, a: usize, b: usize) -> Option<Outcome> {
+        self.results.as_ref()[a * self.participants + b]
+    }
+    /// Returns whether `a` and `b` have met.
+    pub fn have_met(&self, a: usize, b: usize) -> bool {
+        a != b && self.result(a, b).is_some()
+    }
+    /// Returns whether the participant has got a bye.
+    pub fn had_bye(&self, participant: usize) -> bool {
+        self.result(participant, participant).is_some()
+    }
+    /// Records the result of the game for the `first` participant.
+    /// The result of the previous game between them is replaced.
+    pub fn record_game(&mut self, first: usize, second: usize, outcome: Outcome) {
+        let n = self.participants;
+        let results = self.results.as_mut();
+        results[first * n + second] = Some(outcome);
+        results[second * n + first] = Some(outcome.reverse());
+    }
+    /// Records the bye of the participant.
+    pub fn record_bye(&mut self, participant: usize) {
+        let n = self.participants;
+        self.results.as_mut()[participant * n + participant] = Some(Outcome::Win);
+    }
+
+    /// Returns score of the participant in half points.
+    pub fn score(&self, participant: usize) -> u32 {
+        let n = self.participants;
+        self.results.as_ref()[participant * n..][..n]
+            .iter()
+            .flatten()
+            .map(|outcome| outcome.score())
+            .sum()
+    }
+    /// Returns Buchholz score of the participant in half points.
+    pub fn buchholz(&self, participant: usize) -> u32 {
+        (0..self.participants)
+            .filter(|opponent| self.have_met(participant, *opponent))
+            .map(|opponent| self.score(opponent))
+            .sum()
+    }
+    /// Writes standings of all participants into the beginning of `out`, from the first
+    /// place, and returns their number.
+    /// # Panics
+    /// Panics if `out` is shorter than number of participants.
+    pub fn standings(&self, out: &mut [Option<Standing>]) -> usize {
+        let n = self.participants;
+        for (participant, place) in out[..n].iter_mut().enumerate() {
+            *place = Some(Standing {
+                participant,
+                score: self.score(participant),
+                buchholz: self.buchholz(participant),
+                games: (0..n)
+                    .filter(|opponent| self.have_met(participant, *opponent))
+                    .count(),
+            });
+        }
+        out[..n].sort_unstable_by_key(|standing| {
+            standing.map(|s| (Reverse(s.score), Reverse(s.buchholz), s.participant))
+        });
+        n
+    }
+
+    /// Returns number of rounds of round-robin tournament.
+    pub fn round_robin_rounds(&self) -> usize {
+        (self.participants + self.participants % 2).saturating_sub(1)
+    }
+    /// Writes pairings of the round of round-robin tournament, starting with `0`,
+    /// into the beginning of `out` and returns their number. Participants move first
+    /// in about half of their games.
+    /// # Panics
+    /// Panics if the round isn't less than [`Tournament::round_robin_rounds`]
+    /// or `out` is too short.
+    pub fn round_robin(&self, round: usize, out: &mut [Option<Pairing>]) -> usize {
+        let rounds = self.round_robin_rounds();
+        assert!(round < rounds, "round is out of range");
+        // With odd number of participants, the missing participant means a bye.
+        let missing = self.participants;
+        let slot = |k: usize| match k {
+            0 => 0,
+            _ => (k - 1 + round) % rounds + 1,
+        };
+        for (i, pairing) in out[..(rounds + 1) / 2].iter_mut().enumerate() {
+            let (mut first, mut second) = (slot(i), slot(rounds - i));
+            if (i == 0 && round % 2 == 1) || (i != 0 && i % 2 == 0) {
+                core::mem::swap(&mut first, &mut second);
+            }
+            *pairing = Some(if first == missing {
+                Pairing::Bye(second)
+            } else if second == missing {
+                Pairing::Bye(first)
+            } else {
+                Pairing::Game { first, second }
+            });
+        }
+        (rounds + 1) / 2
+    }
+    /// Writes pairings of the next round of Swiss tournament into the beginning of `out`
+    /// and returns their number. If participants can't be paired without rematches,
+    /// neighbours in the standings are paired. Participants higher in the standings
+    /// move first.
+    /// # Panics
+    /// Panics if `out` is too short.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::tournament::*;
+    /// let mut tournament = Tournament::new(4, [None; 16]);
+    /// let mut pairings = [None; 2];
+    /// tournament.swiss(&mut pairings);
+    /// let game = |first, second| Some(Pairing::Game { first, second });
+    /// assert_eq!(pairings, [game(0, 1), game(2, 3)]);
+    /// tournament.record_game(0, 1, Outcome::Win);
+    /// tournament.record_game(2, 3, Outcome::Win);
+    /// tournament.swiss(&mut pairings);
+    /// assert_eq!(pairings, [game(0, 2), game(1, 3)]);
+    /// tournament.record_game(0, 2, Outcome::Draw);
+    /// tournament.record_game(1, 3, Outcome::Loss);
+    /// tournament.swiss(&mut pairings);
+    /// assert_eq!(pairings, [game(2, 1), game(0, 3)]);
+    /// ```
+    pub fn swiss(&self, out: &mut [Option<Pairing>]) -> usize {
+        let n = self.participants;
+        let mut standings = [None; MAX_PARTICIPANTS];
+        self.standings(&mut standings);
+        let mut order = [0; MAX_PARTICIPANTS];
+        for (place, standing) in order.iter_mut().zip(standings[..n].iter().flatten()) {
+            *place = standing.participant;
+        }
+        let order = &order[..n];
+        let mut used = [false; MAX_PARTICIPANTS];
+        let mut pairs = [(0, 0); MAX_PARTICIPANTS / 2];
+        let mut bye = None;
+        let paired = if n % 2 == 0 {
+            self.pair_rest(order, &mut used, &mut pairs, 0)
+        } else {
+            order.iter().rev().any(|&candidate| {
+                if self.had_bye(candidate) {
+                    return false;
+                }
+                used = [false; MAX_PARTICIPANTS];
+                used[candidate] = true;
+                bye = Some(candidate);
+                self.pair_rest(order, &mut used, &mut pairs, 0)
+            })
+        };
+        if !paired {
+            bye = (n % 2 == 1).then(|| {
+                let candidate = order.iter().rev().find(|p| !self.had_bye(**p));
+                *candidate.unwrap_or(&order[n - 1])
+            });
+            let mut rest = order.iter().filter(|p| Some(**p) != bye);
+            for pair in &mut pairs[..n / 2] {
+                *pair = (*rest.next().unwrap(), *rest.next().unwrap());
+            }
+        }
+        for (pairing, &(first, second)) in out.iter_mut().zip(&pairs[..n / 2]) {
+            *pairing = Some(Pairing::Game { first, second });
+        }
+        if let Some(participant) = bye {
+            out[n / 2] = Some(Pairing::Bye(participant));
+        }
+        (n + 1) / 2
+    }
+    /// Pairs participants, that aren't used yet, without rematches,
+    /// trying opponents in the order of standings.
+    fn pair_rest(
+        &self,
+        order: &[usize],
+        used: &mut [bool; MAX_PARTICIPANTS],
+        pairs: &mut [(usize, usize); MAX_PARTICIPANTS / 2],
+        len: usize,
+    ) -> bool {
+        let start = match order.iter().position(|p| !used[*p]) {
+            Some(start) => start,
+            None => return true,
+        };
+        let first = order[start];
+        used[first] = true;
+        for &second in &order[start + 1..] {
+            if used[second] || self.have_met(first, second) {
+                continue;
+            }
+            used[second] = true;
+            pairs[len] = (first, second);
+            if self.pair_rest(order, used, pairs, len + 1) {
+                return true;
+            }
+            used[second] = false;
+        }
+        used[first] = false;
+        false
+    }
+}
@@ -0,0 +1,286 @@
+//! Transposition table
+//!
+//! This module defines [`TranspositionTable`], fixed-size table of search results keyed
+//! by Zobrist hash of the position (see [`zobrist`](crate::zobrist)). [`Searcher`] with
+//! a table reuses results of positions reached with different move orders and searches
+//! the best move of the previous visit first.
+//!
+//! Entries are packed into 16 bytes and the table doesn't allocate: its storage is any
+//! slice of entries, e.g. an array or a Vec, filled with [`Entry::EMPTY`]. The entry at
+//! every slot is replaced only with the result of the same or deeper search.
+//!
+//! With `std` feature `SharedEntries` can be used to share entries between threads.
+//!
+//! [`Searcher`]: crate::search::Searcher
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::search::*;
+//! # use crosses_utils::transposition::*;
+//! let board = Board::new(4, 4, 2, [Cell::new(); 36], [0; 36]);
+//! let mut game = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+//! game.play(game.board().index(0, 0)).unwrap();
+//! let table = TranspositionTable::new([Entry::EMPTY; 1024]);
+//! let mut cached = Searcher::with_table(Mobility, table);
+//! let mut plain = Searcher::new(Mobility);
+//! let (outcome, expected) = (cached.search(&mut game, 5), plain.search(&mut game, 5));
+//! assert_eq!(outcome.score(), expected.score());
+//! assert!(outcome.nodes() < expected.nodes());
+//! assert!(cached.table().hits() > 0);
+//! assert!(outcome.hit_rate() > 0.0 && outcome.hits() < outcome.probes());
+//! assert_eq!((expected.probes(), expected.hit_rate()), (0, 0.0));
+//! ```
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+/// Kind of the score of the entry.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Bound {
+    /// The score is exact
+    Exact,
+    /// The real score is greater or equal
+    Lower,
+    /// The real score is less or equal
+    Upper,
+}
+
+/// Entry of [`TranspositionTable`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Entry {
+    check: u32,
+    score: i32,
+    best: u32,
+    depth: u8,
+    bound: u8,
+}
+impl Entry {
+    /// Empty entry.
+    pub const EMPTY: Entry = Entry {
+        check: 0,
+        score: 0,
+        best: u32::MAX,
+        depth: 0,
+        bound: u8::MAX,
+    };
+
+    /// Returns the score of the position.
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+    /// Returns kind of the score.
+    pub fn bound(&self) -> Bound {
+        match self.bound {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        }
+    }
+    /// Returns depth of the search.
+    pub fn depth(&self) -> usize {
+        self.depth as usize
+    }
+    /// Returns packed index of the best move, if any.
+    pub fn best(&self) -> Option<usize> {
+        (self.best != u32::MAX).then(|| self.best as usize)
+    }
+    fn is_empty(&self) -> bool {
+        self.bound == u8::MAX
+    }
+}
+impl Default for Entry {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+/// Storage of entries of [`TranspositionTable`]. It is implemented for everything that
+/// can be viewed as a slice of entries and for `SharedEntries` (requires `std` feature).
+pub trait EntryStorage {
+    /// Returns number of entries in the storage.
+    fn len(&self) -> usize;
+    /// Checks if the storage has no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Returns the entry at given slot.
+    fn load(&self, slot: usize) -> Entry;
+    /// Replaces the entry at given slot.
+    fn save(&mut self, slot: usize, entry: Entry);
+}
+impl<T: AsRef<[Entry]> + AsMut<[Entry]>> EntryStorage for T {
+    fn len(&self) -> usize {
+        self.as_ref().len()
+    }
+    fn load(&self, slot: usize) -> Entry {
+        self.as_ref()[slot]
+    }
+    fn save(&mut self, slot: usize, entry: Entry) {
+        self.as_mut()[slot] = entry
+    }
+}
+
+/// Entries shared between threads, e.g. by [`Searcher::search_parallel`]. Clones of it
+/// refer to the same entries. Entries are read and written without locks: every entry
+/// is stored as two atomic words, the first one is XORed with the second, so an entry
+/// torn by concurrent writes doesn't match the hash of the position. Requires `std` feature.
+///
+/// [`Searcher::search_parallel`]: crate::search::Searcher::search_parallel
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct SharedEntries {
+    entries: Arc<[[AtomicU64; 2]]>,
+}
+#[cfg(feature = "std")]
+impl SharedEntries {
+    /// Creates given number of empty entries.
+    pub fn new(len: usize) -> Self {
+        let (first, second) = split(Entry::EMPTY);
+        Self {
+            entries: (0..len)
+                .map(|_| [AtomicU64::new(first ^ second), AtomicU64::new(second)])
+                .collect(),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl EntryStorage for SharedEntries {
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+    fn load(&self, slot: usize) -> Entry {
+        let [first, second] = &self.entries[slot];
+        let second = second.load(Ordering::Relaxed);
+        join(first.load(Ordering::Relaxed) ^ second, second)
+    }
+    fn save(&mut self, slot: usize, entry: Entry) {
+        let (first, second) = split(entry);
+        let words = &self.entries[slot];
+        words[0].store(first ^ second, Ordering::Relaxed);
+        words[1].store(second, Ordering::Relaxed);
+    }
+}
+
+/// Table of search results, see module docs.
+/// `S` - is type of storage of entries.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct TranspositionTable<S> {
+    entries: S,
+    hits: u64,
+    misses: u64,
+}
+impl<S: EntryStorage> TranspositionTable<S> {
+    /// Creates new table with given storage, all entries should be empty.
+    pub fn new(entries: S) -> Self {
+        Self {
+            entries,
+            hits: 0,
+            misses: 0,
+        }
+    }
+    /// Returns number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    /// Checks if the table has no entries, so nothing is stored.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Returns the entry of the position with given hash, if any.
+    pub fn probe(&mut self, hash: u64) -> Option<Entry> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let entry = self.entries.load(slot(hash, self.entries.len()));
+        if !entry.is_empty() && entry.check == check(hash) {
+            self.hits += 1;
+            Some(entry)
+        } else {
+            self.misses += 1;
+            None
+        }
+    }
+    /// Stores the result of the search of the position with given hash, unless
+    /// its slot has the result of deeper search. `best` is packed index of the best move.
+    pub fn store(
+        &mut self,
+        hash: u64,
+        depth: usize,
+        score: i32,
+        bound: Bound,
+        best: Option<usize>,
+    ) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let slot = slot(hash, self.entries.len());
+        let entry = self.entries.load(slot);
+        let depth = depth.min(u8::MAX as usize) as u8;
+        if entry.is_empty() || depth >= entry.depth {
+            let entry = Entry {
+                check: check(hash),
+                score,
+                best: best.map_or(u32::MAX, |best| best as u32),
+                depth,
+                bound: bound as u8,
+            };
+            self.entries.save(slot, entry);
+        }
+    }
+    /// Empties all entries and resets statistics.
+    pub fn clear(&mut self) {
+        for slot in 0..self.entries.len() {
+            self.entries.save(slot, Entry::EMPTY);
+        }
+        self.reset_stats();
+    }
+    /// Returns number of probes, that found the position.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+    /// Returns number of probes, that didn't find the position.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+    /// Resets the numbers of hits and misses.
+    pub fn reset_stats(&mut self) {
+        self.hits = 0;
+        self.misses = 0;
+    }
+    /// Adds numbers of hits and misses of other table, e.g. a clone with shared entries.
+    #[cfg(feature = "std")]
+    pub(crate) fn add_stats(&mut self, other: &Self) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+    }
+    /// Unwraps the storage.
+    pub fn into_inner(self) -> S {
+        self.entries
+    }
+}
+
+fn slot(hash: u64, len: usize) -> usize {
+    (hash % len as u64) as usize
+}
+fn check(hash: u64) -> u32 {
+    (hash >> 32) as u32
+}
+#[cfg(feature = "std")]
+fn split(entry: Entry) -> (u64, u64) {
+    let first = (entry.check as u64) << 32 | entry.score as u32 as u64;
+    let second = (entry.best as u64) << 32 | (entry.depth as u64) << 8 | entry.bound as u64;
+    (first, second)
+}
+#[cfg(feature = "std")]
+fn join(first: u64, second: u64) -> Entry {
+    Entry {
+        check: (first >> 32) as u32,
+        score: first as u32 as i32,
+        best: (second >> 32) as u32,
+        depth: (second >> 8) as u8,
+        bound: second as u8,
+    }
+}
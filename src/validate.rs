@@ -0,0 +1,357 @@
+//! Validation of invariants
+//!
+//! This module checks, that the state of [`Board`] and [`Game`] is consistent, e.g. after
+//! every move in debug builds or tests. Counters, aliveness of chains and activations of
+//! cells are recomputed from scratch by their definitions and compared with the stored ones,
+//! and the state of [`PlayerManager`] is compared with counters of the board. The checks
+//! take `O(n²)` time in the worst case for a board of `n` cells, so they aren't meant
+//! for release builds.
+//!
+//! Found problems are collected in [`ValidationReport`], it keeps the first
+//! [`MAX_VIOLATIONS`] of them and counts the rest. Its [`Display`] lists them, e.g. for
+//! `assert!(report.is_valid(), "{}", report)`.
+//!
+//! [`PlayerManager`]: crate::player_manager::PlayerManager
+//! # Example
+//! ```
+//! # use crosses_utils::base::*;
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::counters::*;
+//! # use crosses_utils::engine::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::validate::*;
+//! let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+//! let mut game = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+//! for (x, y) in [(0, 0), (1, 1), (2, 2), (1, 1)] {
+//!     game.play(game.board().index(x, y)).unwrap();
+//!     assert!(debug_validate(&game, &mut [0; 25]).is_valid());
+//! }
+//! let mut board = game.board().clone();
+//! let (index, mut cell) = (board.index(2, 0), board.get(board.index(2, 0)));
+//! cell.set_content(CellKind::Cross, 1);
+//! board.set(index, cell);
+//! let report = validate_board(&board, &mut [0; 25]);
+//! assert_eq!(report.violations().next(), Some(Violation::Counter(Counter::Crosses, 1)));
+//! assert!(report.total() > 1);
+//! ```
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::ops::IndexMut;
+
+use crate::base::{CellKind, GameBoard};
+use crate::board::{Board, CellStorage, Walk};
+use crate::counters::{Counter, PlayerCounters};
+use crate::engine::{ChangeLog, Data, Engine};
+use crate::game::Game;
+use crate::ibts::IbtsBoard;
+use crate::player_manager::{GameState, LoseData, PlayerManager, PlayerStatusSource};
+
+/// Maximum number of violations stored in [`ValidationReport`].
+pub const MAX_VIOLATIONS: usize = 16;
+
+/// Broken invariant.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Violation {
+    /// The counter of the player differs from the number of their crosses or legal moves
+    Counter(Counter, usize),
+    /// The cell at given index has player, that isn't on the board
+    Player(usize),
+    /// Aliveness of the filled cell at given index differs from connection
+    /// of its chain to crosses of its player
+    Alive(usize),
+    /// The player has alive filled cells, but no crosses
+    Orphan(usize),
+    /// Activation of the cell at given index for the player differs from crosses
+    /// and alive filled cells of the player in reach of the cell
+    Active(usize, usize),
+    /// The game is ongoing, but the current player is out of range or has lost
+    CurrentPlayer(usize),
+    /// The game is ongoing, but remaining moves of the current player are out of
+    /// range from `1` to the budget of the turn
+    RemainingMoves(usize),
+    /// The game is ongoing, but the current player has no legal moves
+    Stalled(usize),
+    /// The player has lost at a move, that hasn't been made yet
+    Loser(usize),
+}
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Violation::Counter(counter, player) => {
+                write!(f, "counter {:?} of player {} is wrong", counter, player)
+            }
+            Violation::Player(index) => write!(f, "cell {} has unknown player", index),
+            Violation::Alive(index) => write!(f, "cell {} has wrong aliveness", index),
+            Violation::Orphan(player) => {
+                write!(f, "player {} has alive cells, but no crosses", player)
+            }
+            Violation::Active(index, player) => {
+                write!(
+                    f,
+                    "cell {} has wrong activation for player {}",
+                    index, player
+                )
+            }
+            Violation::CurrentPlayer(player) => write!(f, "player {} can't be current", player),
+            Violation::RemainingMoves(moves) => write!(f, "{} remaining moves are wrong", moves),
+            Violation::Stalled(player) => write!(f, "current player {} can't move", player),
+            Violation::Loser(player) => write!(f, "player {} has lost in the future", player),
+        }
+    }
+}
+
+/// Violations found by [`validate_board`] or [`debug_validate`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ValidationReport {
+    violations: [Option<Violation>; MAX_VIOLATIONS],
+    total: usize,
+}
+impl ValidationReport {
+    /// Checks if no violations were found.
+    pub fn is_valid(&self) -> bool {
+        self.total == 0
+    }
+    /// Returns stored violations in the order they were found.
+    pub fn violations(&self) -> impl Iterator<Item = Violation> + '_ {
+        self.violations.iter().flatten().copied()
+    }
+    /// Returns number of all found violations, including ones, that aren't stored.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    fn new() -> Self {
+        Self {
+            violations: [None; MAX_VIOLATIONS],
+            total: 0,
+        }
+    }
+    fn push(&mut self, violation: Violation) {
+        if let Some(slot) = self.violations.get_mut(self.total) {
+            *slot = Some(violation);
+        }
+        self.total += 1;
+    }
+}
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} violations", self.total)?;
+        for violation in self.violations() {
+            write!(f, "\n{}", violation)?;
+        }
+        if self.total > MAX_VIOLATIONS {
+            write!(f, "\nand {} more", self.total - MAX_VIOLATIONS)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the board: its counters, aliveness of filled cells and activations of cells.
+/// Chains are traversed with `buffer`, it should have at least [`Board::storage_len`]
+/// elements.
+/// # Panics
+/// Panics if `buffer` is too short.
+pub fn validate_board<C, B>(board: &Board<C, B>, buffer: &mut [usize]) -> ValidationReport
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let mut report = ValidationReport::new();
+    check_board(&mut board.walk_in(buffer), &mut report);
+    report
+}
+
+/// Checks the board like [`validate_board`] and the player manager of the game:
+/// the current player and remaining moves of an ongoing game, the player's ability
+/// to move and losses of players. Chains are traversed with `buffer`, it should have
+/// at least [`Board::storage_len`] elements.
+/// # Panics
+/// Panics if `buffer` is too short.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::game::*;
+/// # use crosses_utils::player_manager::*;
+/// # use crosses_utils::validate::*;
+/// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+/// let mut manager = PlayerManager::new(2, 2, [None; 2]);
+/// manager.remaining_moves = 3;
+/// manager.losers[1] = Some(LoseData { move_index: 5, remaining_moves: 0 });
+/// let report = debug_validate(&Game::new(board, manager), &mut [0; 25]);
+/// let expected = [Violation::RemainingMoves(3), Violation::Loser(1)];
+/// assert!(report.violations().eq(expected));
+/// let text = "2 violations\n3 remaining moves are wrong\nplayer 1 has lost in the future";
+/// assert_eq!(report.to_string(), text);
+/// ```
+pub fn debug_validate<C, B, S, L>(
+    game: &Game<Board<C, B>, S, L>,
+    buffer: &mut [usize],
+) -> ValidationReport
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    let mut report = ValidationReport::new();
+    check_board(&mut game.board().walk_in(buffer), &mut report);
+    check_manager(game.board(), game.player_manager(), &mut report);
+    report
+}
+
+/// Checks the game like [`debug_validate`], but traverses chains with the traversal
+/// buffer of the board, so the game is borrowed mutably, but isn't changed.
+#[cfg(feature = "testing")]
+pub(crate) fn validate_in_place<C, B, S, L>(game: &mut Game<Board<C, B>, S, L>) -> ValidationReport
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    let mut report = ValidationReport::new();
+    let (board, manager) = game.parts_mut();
+    check_board(&mut board.walk(), &mut report);
+    check_manager(board, manager, &mut report);
+    report
+}
+
+fn check_manager<C, B, S>(
+    board: &Board<C, B>,
+    manager: &PlayerManager<S>,
+    report: &mut ValidationReport,
+) where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+{
+    let counters = board.counters();
+    if manager.game_state == GameState::Ongoing {
+        let current = manager.current_player;
+        if current >= manager.max_players || !manager.is_playing(current) {
+            report.push(Violation::CurrentPlayer(current));
+        } else if counters.out_of_moves(current) {
+            report.push(Violation::Stalled(current));
+        }
+        let remaining = manager.remaining_moves;
+        if remaining == 0 || remaining > manager.budget(manager.current_turn) {
+            report.push(Violation::RemainingMoves(remaining));
+        }
+    }
+    for player in 0..manager.max_players {
+        if let Some(data) = manager.losers[player] {
+            if data.is_after(manager.current_move) {
+                report.push(Violation::Loser(player));
+            }
+        }
+    }
+}
+
+fn check_board<C, B>(walk: &mut impl Walk<C, B>, report: &mut ValidationReport)
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let board = walk.board();
+    let players = board.counters().players();
+    let len = board.width() * board.height();
+    for index in (0..len).map(|packed| board.unpack_index(packed)) {
+        let cell = board.get(index);
+        if cell.kind() != CellKind::Empty && cell.player() >= players {
+            report.push(Violation::Player(index));
+        }
+    }
+    for player in 0..players {
+        let crosses = (0..len)
+            .map(|packed| board.unpack_index(packed))
+            .filter(|i| board.kind(*i) == CellKind::Cross && board.player(*i) == player)
+            .count();
+        if board.counters().get(Counter::Crosses, player) != crosses {
+            report.push(Violation::Counter(Counter::Crosses, player));
+        }
+        let moves = board.legal_moves(player).count();
+        if board.counters().get(Counter::Moves, player) != moves {
+            report.push(Violation::Counter(Counter::Moves, player));
+        }
+    }
+    for player in 0..players {
+        check_chains(walk, player, report);
+    }
+    let board = walk.board();
+    for index in (0..len).map(|packed| board.unpack_index(packed)) {
+        let cell = board.get(index);
+        for player in 0..players {
+            let reached = board
+                .reach(index)
+                .into_iter()
+                .any(|i| is_activator(board, i, player));
+            if cell.is_active(player) != (reached || board.start(player) == Some(index)) {
+                report.push(Violation::Active(index, player));
+            }
+        }
+    }
+}
+
+/// Checks, that filled cells of the player are alive if and only if they are connected
+/// to crosses of the player.
+fn check_chains<C, B>(walk: &mut impl Walk<C, B>, player: usize, report: &mut ValidationReport)
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    let of_player = |board: &Board<C, B>, i| {
+        board.player(i) == player && matches!(board.kind(i), CellKind::Cross | CellKind::Filled)
+    };
+    let is_alive = |board: &Board<C, B>, i| {
+        board.kind(i) == CellKind::Filled && board.player(i) == player && board.is_alive(i)
+    };
+    let mut connected = 0;
+    walk.traverse(
+        |board, i| board.kind(i) == CellKind::Cross && board.player(i) == player,
+        of_player,
+        |board, i| {
+            if board.kind(i) == CellKind::Filled {
+                connected += 1;
+                if !board.is_alive(i) {
+                    report.push(Violation::Alive(i));
+                }
+            }
+            false
+        },
+    );
+    let board = walk.board();
+    let len = board.width() * board.height();
+    let alive = (0..len)
+        .filter(|packed| is_alive(board, board.unpack_index(*packed)))
+        .count();
+    if alive > connected {
+        // Some alive cells aren't connected, each of them is searched for a cross alone.
+        for packed in 0..len {
+            let index = walk.board().unpack_index(packed);
+            let is_cross = |board: &Board<C, B>, i| board.kind(i) == CellKind::Cross;
+            if is_alive(walk.board(), index)
+                && !walk.traverse(|_, i| i == index, of_player, is_cross)
+            {
+                report.push(Violation::Alive(index));
+            }
+        }
+    }
+    let board = walk.board();
+    if alive > 0 && board.counters().get(Counter::Crosses, player) == 0 {
+        report.push(Violation::Orphan(player));
+    }
+}
+
+/// Checks if the cell activates cells in its reach for the player.
+fn is_activator<C, B>(board: &Board<C, B>, index: usize, player: usize) -> bool
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+{
+    board.player(index) == player
+        && match board.kind(index) {
+            CellKind::Cross => true,
+            CellKind::Filled => board.is_alive(index),
+            _ => false,
+        }
+}
@@ -0,0 +1,426 @@
+//! Zobrist hashing
+//!
+//! This module defines [`Zobrist`] keys of cells and [`Hashed`], a wrapper of [`Engine`],
+//! which keeps the hash of the position up to date as [`make_move`] and [`cancel_move`]
+//! change cells. Hash of the position is XOR of the keys of all cells, so positions
+//! reached with different move orders have the same hash. It's needed for transposition
+//! tables and detection of repetitions.
+//!
+//! Keys aren't stored in a table, they are derived from the seed, index, kind and player
+//! of the cell with a hash function, so they don't need memory and work for any board.
+//! Empty and border cells have zero keys, damage of crosses is mixed into their keys.
+//!
+//! [`Game::consistency_hash`] hashes the whole state of the game with the same hash function.
+//!
+//! Results of moves are deterministic: they depend only on the position and the move,
+//! not on the platform, storages of the board or wrappers like [`Anchored`] and [`Hashed`].
+//! Orders of adjacent cells and of searches only decide, which cells are marked important,
+//! and importance isn't a part of the position: it isn't hashed and [`Anchored`] can mark
+//! other cells than the wrapped board. So peers of a lockstep game can use different
+//! implementations and compare hashes.
+//!
+//! [`make_move`]: crate::engine::make_move
+//! [`cancel_move`]: crate::engine::cancel_move
+//! [`Anchored`]: crate::ibts::Anchored
+//! # Example
+//! ```
+//! # use crosses_utils::board::*;
+//! # use crosses_utils::game::*;
+//! # use crosses_utils::ibts::*;
+//! # use crosses_utils::player_manager::*;
+//! # use crosses_utils::simulate::*;
+//! # use crosses_utils::zobrist::*;
+//! let board = Board::new(5, 5, 2, [Cell::new(); 49], [0; 49]);
+//! let manager = PlayerManager::new(2, 2, [None; 2]);
+//! let zobrist = Zobrist::default();
+//! let vectors = Board::new(5, 5, 2, vec![Cell::new(); 49], vec![0; 49]);
+//! let mut vectors = Game::new(vectors, manager.clone());
+//! let mut anchored = Game::new(Anchored::new(board.clone(), [None; 49]), manager.clone());
+//! let mut hashed = Game::new(Hashed::new(board.clone(), zobrist, 0..49), manager.clone());
+//! for (index, hash) in RandomGame::new(Game::new(board, manager), 5) {
+//!     vectors.play(index).unwrap();
+//!     anchored.play(index).unwrap();
+//!     hashed.play(index).unwrap();
+//!     assert_eq!(vectors.consistency_hash(), hash);
+//!     let position = zobrist.hash(vectors.board(), 0..49);
+//!     assert_eq!(zobrist.hash(anchored.board(), 0..49), position);
+//!     assert_eq!(hashed.board().hash(), position);
+//! }
+//! assert_ne!(vectors.state(), GameState::Ongoing);
+//! assert_eq!(anchored.state(), vectors.state());
+//! ```
+use core::hash::{Hash, Hasher};
+use core::ops::IndexMut;
+
+use crate::base::{CellKind, GameBoard};
+use crate::board::{Board, CellStorage};
+use crate::counters::{Counter, PlayerCounters};
+use crate::engine::{ChangeLog, Data, Engine};
+use crate::game::Game;
+use crate::ibts::{IbtsBoard, SearchResult};
+use crate::player_manager::{GameOver, GameState, LoseData};
+use crate::rules::RuleSet;
+
+/// Seed of [`Zobrist::default`].
+pub const DEFAULT_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Zobrist keys, derived from a seed.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Zobrist {
+    seed: u64,
+}
+impl Zobrist {
+    /// Creates keys derived from given seed.
+    pub const fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+    /// Returns the key of the cell with given content.
+    pub fn key<I: Hash>(&self, index: I, kind: CellKind, player: usize) -> u64 {
+        if matches!(kind, CellKind::Empty | CellKind::Border) {
+            return 0;
+        }
+        let mut hasher = KeyHasher(self.seed);
+        index.hash(&mut hasher);
+        hasher.write_u8(kind as u8);
+        hasher.write_usize(player);
+        hasher.finish()
+    }
+    /// Returns the key of the cell with given data: the key of its content mixed
+    /// with damage of the cross, see [`Data::damage`].
+    pub fn data_key<I: Hash, D: Data>(&self, index: I, data: &D) -> u64 {
+        let key = self.key(&index, data.kind(), data.player());
+        if data.kind() != CellKind::Cross || data.damage() == 0 {
+            return key;
+        }
+        let mut hasher = KeyHasher(self.seed.rotate_left(32));
+        index.hash(&mut hasher);
+        hasher.write_u8(data.damage());
+        key ^ hasher.finish()
+    }
+    /// Returns the key of the player, e.g. to mix the current player into the hash.
+    pub fn player_key(&self, player: usize) -> u64 {
+        let mut hasher = KeyHasher(!self.seed);
+        hasher.write_usize(player);
+        hasher.finish()
+    }
+    /// Computes the hash of the cells at given indices from scratch.
+    pub fn hash<E: Engine>(&self, engine: &E, indices: impl IntoIterator<Item = E::Index>) -> u64
+    where
+        E::Index: Hash,
+    {
+        indices.into_iter().fold(0, |hash, index| {
+            hash ^ self.data_key(index, &engine.get(index))
+        })
+    }
+    /// Returns the hash of the position after the move at `index` from the `hash`
+    /// before it and `old` data of the cell. If the rules change other cells after moves,
+    /// see [`RuleSet::needs_log`], the hash of the cells at `indices` is computed from scratch.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::engine::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// # use crosses_utils::rules::*;
+    /// # use crosses_utils::zobrist::*;
+    /// let mut board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// board.set_rules(RuleSet { convert_chains: true, ..RuleSet::STANDARD });
+    /// let log = ChangeStack::new([Change::default(); 16]);
+    /// let mut game = Game::with_log(board, PlayerManager::new(1, 2, [None; 2]), log);
+    /// let zobrist = Zobrist::default();
+    /// let mut hash = zobrist.hash(game.board(), 0..25);
+    /// for (x, y) in [(0, 0), (2, 2), (1, 1), (2, 1), (2, 1), (1, 1)] {
+    ///     let index = game.board().index(x, y);
+    ///     let old = game.board().get(index);
+    ///     game.play(index).unwrap();
+    ///     hash = zobrist.after_move(hash, game.board(), index, &old, 0..25);
+    ///     assert_eq!(hash, zobrist.hash(game.board(), 0..25));
+    /// }
+    /// assert_eq!(game.log().len(), 1);
+    /// ```
+    pub fn after_move<E: Engine>(
+        &self,
+        hash: u64,
+        engine: &E,
+        index: E::Index,
+        old: &E::Data,
+        indices: impl IntoIterator<Item = E::Index>,
+    ) -> u64
+    where
+        E::Index: Hash,
+    {
+        if engine.rules().needs_log() {
+            return self.hash(engine, indices);
+        }
+        hash ^ self.data_key(index, old) ^ self.data_key(index, &engine.get(index))
+    }
+}
+impl Default for Zobrist {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEED)
+    }
+}
+
+/// [`Engine`] with the hash of its position. Every change of the content
+/// of a cell through [`Engine::set`] or [`Engine::with_cell`] updates the hash.
+/// # Example
+/// ```
+/// # use crosses_utils::board::*;
+/// # use crosses_utils::engine::*;
+/// # use crosses_utils::zobrist::*;
+/// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+/// let mut first = Hashed::new(board.clone(), Zobrist::default(), 0..25);
+/// let mut second = Hashed::new(board, Zobrist::default(), 0..25);
+/// let board = first.engine();
+/// let (a, b, c) = (board.index(0, 0), board.index(1, 1), board.index(0, 1));
+/// make_move(&mut first, a, 0).unwrap();
+/// make_move(&mut first, b, 0).unwrap();
+/// make_move(&mut first, c, 0).unwrap();
+/// make_move(&mut second, a, 0).unwrap();
+/// make_move(&mut second, c, 0).unwrap();
+/// make_move(&mut second, b, 0).unwrap();
+/// assert_eq!(first.hash(), second.hash());
+/// assert_eq!(first.hash(), Zobrist::default().hash(&first, 0..25));
+/// assert_ne!(first.hash(), 0);
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Hashed<E> {
+    engine: E,
+    zobrist: Zobrist,
+    hash: u64,
+}
+impl<E: Engine> Hashed<E>
+where
+    E::Index: Hash,
+{
+    /// Wraps the engine. `indices` should contain all cells of the engine,
+    /// they are used to compute initial hash.
+    pub fn new(engine: E, zobrist: Zobrist, indices: impl IntoIterator<Item = E::Index>) -> Self {
+        let hash = zobrist.hash(&engine, indices);
+        Self {
+            engine,
+            zobrist,
+            hash,
+        }
+    }
+    /// Returns the hash of the position.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+    /// Returns Zobrist keys of the engine.
+    pub fn zobrist(&self) -> Zobrist {
+        self.zobrist
+    }
+    /// Returns the wrapped engine.
+    pub fn engine(&self) -> &E {
+        &self.engine
+    }
+    /// Unwraps the engine.
+    pub fn into_inner(self) -> E {
+        self.engine
+    }
+    fn key(&self, index: E::Index, data: &E::Data) -> u64 {
+        self.zobrist.data_key(index, data)
+    }
+}
+impl<E: Engine> GameBoard for Hashed<E> {
+    type Index = E::Index;
+    type Adjacent = E::Adjacent;
+    type Player = E::Player;
+
+    fn adjacent(&self, index: Self::Index) -> Self::Adjacent {
+        self.engine.adjacent(index)
+    }
+    fn kind(&self, index: Self::Index) -> CellKind {
+        self.engine.kind(index)
+    }
+    fn player(&self, index: Self::Index) -> Self::Player {
+        self.engine.player(index)
+    }
+}
+impl<E: Engine> IbtsBoard for Hashed<E> {
+    fn is_important(&self, index: Self::Index) -> bool {
+        self.engine.is_important(index)
+    }
+    fn set_important(&mut self, index: Self::Index, new: bool) {
+        self.engine.set_important(index, new)
+    }
+    fn is_alive(&self, index: Self::Index) -> bool {
+        self.engine.is_alive(index)
+    }
+    fn set_alive(&mut self, index: Self::Index, new: bool) {
+        self.engine.set_alive(index, new)
+    }
+
+    fn revive(&mut self, index: Self::Index) {
+        self.engine.revive(index)
+    }
+    fn kill(&mut self, index: Self::Index) {
+        self.engine.kill(index)
+    }
+    fn search(&mut self, index: Self::Index) -> Option<SearchResult<Self::Index>> {
+        self.engine.search(index)
+    }
+    fn searches_activators(&self) -> bool {
+        self.engine.searches_activators()
+    }
+
+    fn on_place_cross(&mut self, index: Self::Index) {
+        self.engine.on_place_cross(index)
+    }
+    fn on_place_filled(&mut self, index: Self::Index, previous_player: Self::Player) {
+        self.engine.on_place_filled(index, previous_player)
+    }
+    fn on_remove_filled(&mut self, index: Self::Index, previous_player: Self::Player) {
+        self.engine.on_remove_filled(index, previous_player)
+    }
+    fn on_release_filled(&mut self, index: Self::Index, previous_player: Self::Player) {
+        self.engine.on_release_filled(index, previous_player)
+    }
+    fn on_split(&mut self, index: Self::Index) {
+        self.engine.on_split(index)
+    }
+    fn on_remove_cross(&mut self, index: Self::Index, previous_player: Self::Player) {
+        self.engine.on_remove_cross(index, previous_player)
+    }
+}
+impl<E: Engine> Engine for Hashed<E>
+where
+    E::Index: Hash,
+{
+    type Data = E::Data;
+    type Counters = E::Counters;
+
+    fn get(&self, index: Self::Index) -> Self::Data {
+        self.engine.get(index)
+    }
+    fn set(&mut self, index: Self::Index, data: Self::Data) {
+        let old = self.engine.get(index);
+        self.hash ^= self.key(index, &old) ^ self.key(index, &data);
+        self.engine.set(index, data)
+    }
+    fn with_cell<R>(&mut self, index: Self::Index, f: impl FnOnce(&mut Self::Data) -> R) -> R {
+        let old = self.engine.get(index);
+        let (result, new) = self.engine.with_cell(index, |data| (f(data), *data));
+        self.hash ^= self.key(index, &old) ^ self.key(index, &new);
+        result
+    }
+    fn reach(&self, index: Self::Index) -> Self::Adjacent {
+        self.engine.reach(index)
+    }
+    fn for_each_dead(&self, f: impl FnMut(Self::Index)) {
+        self.engine.for_each_dead(f)
+    }
+    fn counters(&self) -> &Self::Counters {
+        self.engine.counters()
+    }
+    fn counters_mut(&mut self) -> &mut Self::Counters {
+        self.engine.counters_mut()
+    }
+    fn rules(&self) -> RuleSet {
+        self.engine.rules()
+    }
+}
+
+impl<C, B, S, L> Game<Board<C, B>, S, L>
+where
+    C: CellStorage,
+    B: AsMut<[usize]>,
+    S: IndexMut<usize, Output = Option<LoseData>>,
+    L: ChangeLog<usize>,
+{
+    /// Returns the hash of the whole state of the game: content of cells, counters
+    /// and the player manager. Peers of lockstep game can exchange it after every move
+    /// to detect divergence. All values are hashed as `u64`, so the hash is the same
+    /// on all platforms.
+    /// # Example
+    /// ```
+    /// # use crosses_utils::board::*;
+    /// # use crosses_utils::game::*;
+    /// # use crosses_utils::player_manager::*;
+    /// let board = Board::new(3, 3, 2, [Cell::new(); 25], [0; 25]);
+    /// let mut first = Game::new(board, PlayerManager::new(2, 2, [None; 2]));
+    /// let mut second = first.clone();
+    /// first.play(first.board().index(0, 0)).unwrap();
+    /// assert_ne!(first.consistency_hash(), second.consistency_hash());
+    /// second.play(second.board().index(0, 0)).unwrap();
+    /// assert_eq!(first.consistency_hash(), second.consistency_hash());
+    /// ```
+    pub fn consistency_hash(&self) -> u64 {
+        let mut hasher = KeyHasher(DEFAULT_SEED);
+        let board = self.board();
+        hasher.write_usize(board.width());
+        hasher.write_usize(board.height());
+        for packed in 0..board.width() * board.height() {
+            let cell = board.get(board.unpack_index(packed));
+            let (code, previous) = match cell.kind() {
+                CellKind::Empty => (0, 0),
+                CellKind::Border => (1, 0),
+                CellKind::Cross => (2 + 2 * cell.player(), 0),
+                CellKind::Filled => (3 + 2 * cell.player(), cell.previous_player()),
+            };
+            hasher.write_usize(code);
+            hasher.write_usize(previous);
+        }
+        let manager = self.player_manager();
+        for player in 0..manager.max_players {
+            hasher.write_usize(self.counters().get(Counter::Moves, player));
+            hasher.write_usize(self.counters().get(Counter::Crosses, player));
+            match manager.losers[player] {
+                Some(data) => {
+                    hasher.write_usize(data.move_index.wrapping_add(1));
+                    hasher.write_usize(data.remaining_moves);
+                }
+                None => hasher.write_usize(0),
+            }
+        }
+        hasher.write_usize(manager.remaining_moves);
+        hasher.write_usize(manager.max_moves);
+        hasher.write_usize(manager.current_player);
+        hasher.write_usize(manager.max_players);
+        hasher.write_usize(manager.current_move);
+        hasher.write_usize(manager.current_turn);
+        hasher.write_usize(manager.schedule.period);
+        hasher.write_usize(manager.schedule.min_moves);
+        hasher.write_u8(manager.stalemate as u8);
+        hasher.write_usize(manager.neutral.map_or(0, |player| player + 1));
+        hasher.write_usize(match manager.game_state {
+            GameState::Ongoing => 0,
+            GameState::Ended(GameOver::Draw) => 1,
+            GameState::Ended(GameOver::Win(player)) => 2 + player,
+        });
+        hasher.finish()
+    }
+}
+
+/// FNV-1a over little-endian `u64` words followed by a SplitMix64 finalizer,
+/// so keys don't depend on the platform.
+struct KeyHasher(u64);
+impl Hasher for KeyHasher {
+    fn finish(&self) -> u64 {
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    fn write_u8(&mut self, value: u8) {
+        self.write_u64(value as u64)
+    }
+    fn write_u16(&mut self, value: u16) {
+        self.write_u64(value as u64)
+    }
+    fn write_u32(&mut self, value: u32) {
+        self.write_u64(value as u64)
+    }
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes())
+    }
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64)
+    }
+}